@@ -0,0 +1,85 @@
+//! Benchmarks of the ACMI parsing and the track extraction, run with `cargo bench`.
+
+use std::io::Cursor;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use lso::extract_recoveries;
+use lso::synthetic::Pass;
+use lso::track::Track;
+
+const RECORDINGS: &[(&str, &[u8])] = &[
+    (
+        "wire_1_01_FA18C",
+        include_bytes!("../tests/recordings/wire_1_01_FA18C.zip.acmi"),
+    ),
+    (
+        "wire_2_01_FA18C",
+        include_bytes!("../tests/recordings/wire_2_01_FA18C.zip.acmi"),
+    ),
+    (
+        "wire_3_01_T45",
+        include_bytes!("../tests/recordings/wire_3_01_T45.zip.acmi"),
+    ),
+    (
+        "wire_4_01_FA18C",
+        include_bytes!("../tests/recordings/wire_4_01_FA18C.zip.acmi"),
+    ),
+    (
+        "wire_4_02_F14A",
+        include_bytes!("../tests/recordings/wire_4_02_F14A.zip.acmi"),
+    ),
+];
+
+fn parse_acmi(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_acmi");
+    for (name, acmi) in RECORDINGS {
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                lso::acmi::parse(Cursor::new(acmi))
+                    .unwrap()
+                    .map(Result::unwrap)
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn extract_tracks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("extract_tracks");
+    for (name, acmi) in RECORDINGS {
+        group.bench_function(*name, |b| {
+            b.iter(|| extract_recoveries(&mut Cursor::new(acmi)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// `Track::next` on its own (without the parsing), fed with the frames of synthesized passes.
+fn track_next(c: &mut Criterion) {
+    let mut group = c.benchmark_group("track_next");
+    let passes = [
+        ("ideal", Pass::ideal()),
+        ("overshooting_start", Pass::overshooting_start()),
+        ("settle_at_ramp", Pass::settle_at_ramp()),
+        ("bolter", Pass::bolter()),
+    ];
+    for (name, pass) in passes {
+        let (frames, touchdown) = pass.frames();
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut track = Track::new("Synthetic", pass.carrier, pass.plane);
+                for (carrier, plane) in &frames[..touchdown] {
+                    if !track.next(black_box(carrier), black_box(plane)) {
+                        break;
+                    }
+                }
+                track
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse_acmi, extract_tracks, track_next);
+criterion_main!(benches);
@@ -0,0 +1,131 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use tacview::record::{Event, EventKind, GlobalProperty, Record};
+use tacview::ParseError;
+
+use crate::config::AcmiFormat;
+use crate::realtime::LiveRecording;
+use crate::track::TrackResult;
+
+/// The object ID of the plane in the recordings of the LSO (the carrier is `1`).
+pub const PLANE_ID: u64 = 2;
+
+/// The prefix of the data source (see [GlobalProperty::DataSource]) the LSO records the name of
+/// the DCS server with (e.g. `DCS server: Training`).
+pub const SERVER_PREFIX: &str = "DCS server: ";
+
+/// Parse an ACMI recording, either zip-compressed or plain text (detected by its content).
+pub fn parse<'a>(
+    rd: impl Read + 'a,
+) -> Result<Box<dyn Iterator<Item = Result<Record, ParseError>> + 'a>, ParseError> {
+    let mut rd = BufReader::new(rd);
+    // zip archives start with `PK`
+    if rd.fill_buf()?.starts_with(b"PK") {
+        Ok(Box::new(tacview::Parser::new_compressed(rd)?))
+    } else {
+        Ok(Box::new(tacview::Parser::new(rd)?))
+    }
+}
+
+/// The file an ACMI recording is written to.
+pub enum AcmiFile {
+    Zip(Box<zip::ZipWriter<BufWriter<File>>>),
+    Text(BufWriter<File>),
+}
+
+impl AcmiFile {
+    /// Create the file at `path` and start an ACMI recording in it.
+    pub fn create(
+        path: &Path,
+        format: AcmiFormat,
+    ) -> Result<tacview::Writer<Self>, std::io::Error> {
+        let file = BufWriter::new(File::create(path)?);
+        let file = match format {
+            AcmiFormat::Zip => {
+                let mut zip = zip::ZipWriter::new(file);
+                zip.start_file("track.txt.acmi", zip::write::SimpleFileOptions::default())
+                    .map_err(std::io::Error::from)?;
+                AcmiFile::Zip(Box::new(zip))
+            }
+            AcmiFormat::Text => AcmiFile::Text(file),
+        };
+        tacview::Writer::new(file)
+    }
+
+    /// Complete the file (for zip archives, this writes their central directory).
+    pub fn finish(self) -> Result<(), std::io::Error> {
+        match self {
+            AcmiFile::Zip(zip) => zip.finish().map_err(std::io::Error::from)?.flush(),
+            AcmiFile::Text(mut file) => file.flush(),
+        }
+    }
+}
+
+impl Write for AcmiFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            AcmiFile::Zip(zip) => zip.write(buf),
+            AcmiFile::Text(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            AcmiFile::Zip(zip) => zip.flush(),
+            AcmiFile::Text(file) => file.flush(),
+        }
+    }
+}
+
+/// An ACMI recording, which is optionally also streamed live to Tacview clients.
+pub struct Recording<W: Write> {
+    writer: tacview::Writer<W>,
+    live: Option<LiveRecording>,
+}
+
+impl<W: Write> Recording<W> {
+    pub fn new(writer: tacview::Writer<W>, live: Option<LiveRecording>) -> Self {
+        Self { writer, live }
+    }
+
+    pub fn write(&mut self, record: impl Into<Record>) -> Result<(), std::io::Error> {
+        let record = record.into();
+        if let Some(live) = &mut self.live {
+            live.write(&record);
+        }
+        self.writer.write(record)
+    }
+
+    /// Stop streaming the recording and return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+}
+
+/// Write the results of the passes (each with the object ID of its plane) into the recording at
+/// its current time: a bookmark per pass, which Tacview lists with its events, and the
+/// debriefing of the recording.
+pub fn write_debriefing<W: Write>(
+    recording: &mut Recording<W>,
+    passes: &[(u64, &TrackResult)],
+) -> Result<(), std::io::Error> {
+    let mut debriefings = Vec::with_capacity(passes.len());
+    for (i, (plane_id, track)) in passes.iter().enumerate() {
+        let debriefing = if passes.len() > 1 {
+            format!("Pass {}: {}", i + 1, track.debriefing())
+        } else {
+            track.debriefing()
+        };
+        recording.write(Event {
+            kind: EventKind::Bookmark,
+            params: vec![plane_id.to_string()],
+            text: Some(debriefing.clone()),
+        })?;
+        debriefings.push(debriefing);
+    }
+    recording.write(GlobalProperty::Debriefing(debriefings.join("; ")))?;
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use stubs::atmosphere;
+use stubs::atmosphere::v0::atmosphere_service_client::AtmosphereServiceClient;
+use stubs::common::v0::InputPosition;
+use tonic::{transport::Channel, Status};
+use ultraviolet::DVec3;
+
+pub struct AtmosphereClient {
+    svc: AtmosphereServiceClient<Channel>,
+}
+
+impl AtmosphereClient {
+    pub fn new(ch: Channel) -> Self {
+        Self {
+            svc: AtmosphereServiceClient::new(ch),
+        }
+    }
+
+    /// The velocity (in m/s) of the wind at the given position, in the same coordinate system as
+    /// [crate::transform::Transform::position] (+x east, +z north).
+    pub async fn get_wind(&mut self, lat: f64, lon: f64, alt: f64) -> Result<DVec3, Status> {
+        let res = self
+            .svc
+            .get_wind(atmosphere::v0::GetWindRequest {
+                position: Some(InputPosition { lat, lon, alt }),
+            })
+            .await?
+            .into_inner();
+
+        // the heading is the direction the wind is coming from
+        let heading = f64::from(res.heading).to_radians();
+        let strength = f64::from(res.strength);
+        Ok(DVec3::new(
+            -heading.sin() * strength,
+            0.0,
+            -heading.cos() * strength,
+        ))
+    }
+}
@@ -0,0 +1,30 @@
+use stubs::coalition;
+use stubs::coalition::v0::coalition_service_client::CoalitionServiceClient;
+use stubs::common::v0::{Coalition, Static};
+use tonic::{transport::Channel, Status};
+
+pub struct CoalitionClient {
+    svc: CoalitionServiceClient<Channel>,
+}
+
+impl CoalitionClient {
+    pub fn new(ch: Channel) -> Self {
+        Self {
+            svc: CoalitionServiceClient::new(ch),
+        }
+    }
+
+    pub async fn get_static_objects(
+        &mut self,
+        coalition: Coalition,
+    ) -> Result<Vec<Static>, Status> {
+        let res = self
+            .svc
+            .get_static_objects(coalition::v0::GetStaticObjectsRequest {
+                coalition: coalition.into(),
+            })
+            .await?
+            .into_inner();
+        Ok(res.statics)
+    }
+}
@@ -0,0 +1,49 @@
+use serde::Deserialize;
+use stubs::custom;
+use stubs::custom::v0::custom_service_client::CustomServiceClient;
+use tonic::{transport::Channel, Status};
+
+use crate::track::Weight;
+use crate::utils::kg_to_lb;
+
+pub struct CustomClient {
+    svc: CustomServiceClient<Channel>,
+}
+
+impl CustomClient {
+    pub fn new(ch: Channel) -> Self {
+        Self {
+            svc: CustomServiceClient::new(ch),
+        }
+    }
+
+    /// The internal fuel and the gross weight (computed from the empty weight and the internal
+    /// fuel, so without the pilot, stores and external tanks) of the given unit.
+    pub async fn get_weight(&mut self, unit_name: &str) -> Result<Weight, Status> {
+        #[derive(Deserialize)]
+        struct Mass {
+            fuel: f64,
+            empty: f64,
+        }
+
+        let lua = format!(
+            r#"local unit = Unit.getByName({})
+local desc = unit:getDesc()
+return {{ fuel = math.min(unit:getFuel(), 1) * desc.fuelMassMax, empty = desc.massEmpty }}"#,
+            serde_json::to_string(unit_name).unwrap_or_default()
+        );
+        let res = self
+            .svc
+            .eval(custom::v0::EvalRequest { lua })
+            .await?
+            .into_inner();
+        let mass: Mass = serde_json::from_str(&res.json).map_err(|err| {
+            Status::internal(format!("invalid unit mass `{}`: {}", res.json, err))
+        })?;
+
+        Ok(Weight {
+            fuel: kg_to_lb(mass.fuel),
+            gross: kg_to_lb(mass.empty + mass.fuel),
+        })
+    }
+}
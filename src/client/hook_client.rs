@@ -21,4 +21,29 @@ impl HookClient {
             .into_inner();
         Ok(res.name)
     }
+
+    /// The name of the DCS server (from its `serverSettings.lua`), if set. Requires the Lua
+    /// evaluation of DCS-gRPC to be enabled (`evalEnabled = true`).
+    pub async fn get_server_name(&mut self) -> Result<Option<String>, Status> {
+        let res = self
+            .svc
+            .eval(hook::v0::EvalRequest {
+                lua: SERVER_NAME_LUA.to_string(),
+            })
+            .await?
+            .into_inner();
+        Ok(serde_json::from_str::<String>(&res.json)
+            .ok()
+            .filter(|name| !name.is_empty()))
+    }
 }
+
+/// Reads the server name from the server settings (the hook environment doesn't expose it).
+const SERVER_NAME_LUA: &str = r#"
+local settings = loadfile(lfs.writedir() .. "Config/serverSettings.lua")
+if not settings then return "" end
+local env = {}
+setfenv(settings, env)
+settings()
+return env.cfg and env.cfg.name or ""
+"#;
@@ -0,0 +1,73 @@
+use stubs::metadata;
+use stubs::metadata::v0::metadata_service_client::MetadataServiceClient;
+use tonic::{transport::Channel, Code, Status};
+
+/// The oldest DCS-gRPC version the LSO works with.
+pub const MIN_DCS_GRPC_VERSION: (u32, u32, u32) = (0, 8, 1);
+
+pub struct MetadataClient {
+    svc: MetadataServiceClient<Channel>,
+}
+
+impl MetadataClient {
+    pub fn new(ch: Channel) -> Self {
+        Self {
+            svc: MetadataServiceClient::new(ch),
+        }
+    }
+
+    pub async fn get_version(&mut self) -> Result<String, Status> {
+        let res = self
+            .svc
+            .get_version(metadata::v0::GetVersionRequest {})
+            .await?
+            .into_inner();
+        Ok(res.version)
+    }
+}
+
+/// Parse a version like `0.8.1` (ignoring a leading `v` and anything after the patch version,
+/// e.g. `-beta`).
+pub fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version.trim().trim_start_matches('v').splitn(3, '.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    let patch = parts.next().map_or(Some(0), |patch| {
+        let digits = patch
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(patch.len());
+        patch[..digits].parse().ok()
+    })?;
+    Some((major, minor, patch))
+}
+
+/// Check the version DCS-gRPC reported (see [MetadataClient::get_version]) against
+/// [MIN_DCS_GRPC_VERSION]. Returns the version if it is recent enough (or of an unknown format).
+pub fn check_version(version: Result<String, Status>) -> Result<String, crate::error::Error> {
+    let (major, minor, patch) = MIN_DCS_GRPC_VERSION;
+    let required = format!("{}.{}.{}", major, minor, patch);
+
+    let version = match version {
+        Ok(version) => version,
+        Err(status) if status.code() == Code::Unimplemented => {
+            return Err(crate::error::Error::IncompatibleVersion(format!(
+                "the server runs a version older than {} (which doesn't report its version), \
+                 update DCS-gRPC to {} or newer",
+                required, required
+            )))
+        }
+        Err(status) => return Err(status.into()),
+    };
+    match parse_version(&version) {
+        Some(parsed) if parsed < MIN_DCS_GRPC_VERSION => {
+            return Err(crate::error::Error::IncompatibleVersion(format!(
+                "the server runs {}, update DCS-gRPC to {} or newer",
+                version, required
+            )))
+        }
+        Some(_) => {}
+        None => tracing::warn!(version, "unknown DCS-gRPC version format"),
+    }
+
+    Ok(version)
+}
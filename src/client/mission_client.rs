@@ -1,9 +1,11 @@
 use std::future::ready;
 
 use futures_util::{Stream, StreamExt};
+use stubs::common::v0::GroupCategory;
 use stubs::mission;
 use stubs::mission::v0::mission_service_client::MissionServiceClient;
 use stubs::mission::v0::stream_events_response::Event;
+use stubs::mission::v0::stream_units_response::Update;
 use tonic::{transport::Channel, Status};
 
 pub struct MissionClient {
@@ -47,4 +49,34 @@ impl MissionClient {
             });
         Ok(events)
     }
+
+    /// Stream all units of the given category. Initially yields all current units, afterwards
+    /// only units that changed (polled by DCS-gRPC once every `poll_rate` seconds) and units that
+    /// are gone.
+    pub async fn stream_units(
+        &mut self,
+        category: GroupCategory,
+        poll_rate: u32,
+    ) -> Result<impl Stream<Item = Result<(f64, Update), Status>>, Status> {
+        let units = self
+            .svc
+            .stream_units(mission::v0::StreamUnitsRequest {
+                poll_rate: Some(poll_rate),
+                max_backoff: None,
+                category: category.into(),
+            })
+            .await?
+            .into_inner()
+            .filter_map(|update| {
+                ready(match update {
+                    Ok(stubs::mission::v0::StreamUnitsResponse {
+                        time,
+                        update: Some(update),
+                    }) => Some(Ok((time, update))),
+                    Err(err) => Some(Err(err)),
+                    Ok(_) => None,
+                })
+            });
+        Ok(units)
+    }
 }
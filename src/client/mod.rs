@@ -1,7 +1,17 @@
+mod atmosphere_client;
+mod coalition_client;
+mod custom_client;
 mod hook_client;
+mod metadata_client;
 mod mission_client;
+mod net_client;
 mod unit_client;
 
+pub use atmosphere_client::*;
+pub use coalition_client::*;
+pub use custom_client::*;
 pub use hook_client::*;
+pub use metadata_client::*;
 pub use mission_client::*;
+pub use net_client::*;
 pub use unit_client::*;
@@ -0,0 +1,48 @@
+use stubs::net;
+use stubs::net::v0::get_players_response::GetPlayerInfo;
+use stubs::net::v0::net_service_client::NetServiceClient;
+use tonic::{transport::Channel, Status};
+
+pub struct NetClient {
+    svc: NetServiceClient<Channel>,
+}
+
+impl NetClient {
+    pub fn new(ch: Channel) -> Self {
+        Self {
+            svc: NetServiceClient::new(ch),
+        }
+    }
+
+    pub async fn get_players(&mut self) -> Result<Vec<GetPlayerInfo>, Status> {
+        let res = self
+            .svc
+            .get_players(net::v0::GetPlayersRequest {})
+            .await?
+            .into_inner();
+        Ok(res.players)
+    }
+
+    /// The UCID (the unique ID of the player's DCS account) of the connected player with the given
+    /// name.
+    pub async fn get_ucid(&mut self, player_name: &str) -> Result<Option<String>, Status> {
+        Ok(self
+            .get_players()
+            .await?
+            .into_iter()
+            .find(|player| player.name == player_name)
+            .map(|player| player.ucid)
+            .filter(|ucid| !ucid.is_empty()))
+    }
+
+    /// Send a chat message only the given player sees.
+    pub async fn send_chat_to(&mut self, player_id: u32, message: String) -> Result<(), Status> {
+        self.svc
+            .send_chat_to(net::v0::SendChatToRequest {
+                message,
+                target_player_id: player_id,
+            })
+            .await?;
+        Ok(())
+    }
+}
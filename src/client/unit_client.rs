@@ -2,6 +2,7 @@ use stubs::common::v0::Unit;
 use stubs::unit;
 use stubs::unit::v0::unit_service_client::UnitServiceClient;
 use tonic::{transport::Channel, Status};
+use ultraviolet::DVec3;
 
 use crate::transform::Transform;
 
@@ -19,6 +20,16 @@ impl UnitClient {
     pub async fn get_transform(
         &mut self,
         unit_name: impl Into<String>,
+    ) -> Result<Transform, Status> {
+        self.get_transform_in_wind(unit_name, DVec3::zero()).await
+    }
+
+    /// Get the transform of the unit with its AoA corrected for the given `wind` (see
+    /// [Transform::in_wind]).
+    pub async fn get_transform_in_wind(
+        &mut self,
+        unit_name: impl Into<String>,
+        wind: DVec3,
     ) -> Result<Transform, Status> {
         let res = self
             .svc
@@ -28,13 +39,13 @@ impl UnitClient {
             .await?
             .into_inner();
 
-        Ok((
+        Ok(Transform::in_wind(
             res.time,
             res.position.unwrap_or_default(),
             res.orientation.unwrap_or_default(),
             res.velocity.unwrap_or_default(),
-        )
-            .into())
+            wind,
+        ))
     }
 
     pub async fn get_unit(&mut self, unit_name: &str) -> Result<Unit, Status> {
@@ -63,4 +74,21 @@ impl UnitClient {
             .attributes;
         Ok(descriptor)
     }
+
+    pub async fn get_draw_argument_value(
+        &mut self,
+        unit_name: &str,
+        argument: u32,
+    ) -> Result<f64, Status> {
+        let value = self
+            .svc
+            .get_draw_argument_value(unit::v0::GetDrawArgumentValueRequest {
+                name: unit_name.to_string(),
+                argument,
+            })
+            .await?
+            .into_inner()
+            .value;
+        Ok(value)
+    }
 }
@@ -0,0 +1,54 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Parser)]
+enum Command {
+    /// Check a config file for errors (e.g. misspelled settings) and for settings that don't make
+    /// sense.
+    Validate {
+        /// The config file (defaults to the one given with `--config`).
+        path: Option<PathBuf>,
+    },
+}
+
+pub fn execute(opts: Opts, config_path: Option<&Path>) -> Result<(), crate::error::Error> {
+    match opts.command {
+        Command::Validate { path } => {
+            let Some(path) = path.as_deref().or(config_path) else {
+                return Err(crate::error::Error::InvalidConfig(
+                    "no config file given".to_string(),
+                ));
+            };
+            let problems = validate(path);
+            if problems.is_empty() {
+                println!("{} is valid", path.display());
+                return Ok(());
+            }
+
+            for problem in &problems {
+                println!("{}", problem);
+            }
+            Err(crate::error::Error::InvalidConfig(format!(
+                "{} has {} problem(s)",
+                path.display(),
+                problems.len()
+            )))
+        }
+    }
+}
+
+/// All problems of the config file at `path`, including why it can't be loaded at all (e.g. a
+/// syntax error or an unknown setting, with the line and column).
+pub fn validate(path: &Path) -> Vec<String> {
+    match Config::load(path) {
+        Ok(config) => config.problems(),
+        Err(err) => vec![crate::error::report(&err)],
+    }
+}
@@ -0,0 +1,268 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tonic::transport::Endpoint;
+use tonic::Code;
+
+use crate::client::{parse_version, HookClient, MetadataClient, MIN_DCS_GRPC_VERSION};
+use crate::config::{Config, NotifierConfig};
+use crate::store;
+
+use super::run::Server;
+
+/// How long to wait for DCS-gRPC to respond.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(clap::Parser)]
+pub struct Opts {
+    /// The directory the recordings are saved to (as given to `run`).
+    #[clap(short = 'o', long, default_value = ".")]
+    out_dir: PathBuf,
+
+    /// The URI of DCS-gRPC (as given to `run`). Can be specified multiple times.
+    #[clap(long = "uri", default_value = "http://127.0.0.1:50051")]
+    servers: Vec<Server>,
+
+    /// The Discord webhook recovery recordings are posted to (as given to `run`).
+    #[clap(long)]
+    discord_webhook: Option<String>,
+
+    /// The Discord webhook errors of the LSO are posted to (as given to `run`).
+    #[clap(long)]
+    admin_webhook: Option<String>,
+}
+
+/// Prints the result of each check, with a hint on how to fix it if it failed.
+#[derive(Default)]
+struct Checks {
+    failed: usize,
+}
+
+impl Checks {
+    fn ok(&mut self, msg: impl Display) {
+        println!("[ OK ] {}", msg);
+    }
+
+    fn warn(&mut self, msg: impl Display, hint: impl Display) {
+        println!("[WARN] {}", msg);
+        println!("       {}", hint);
+    }
+
+    fn fail(&mut self, msg: impl Display, hint: impl Display) {
+        self.failed += 1;
+        println!("[FAIL] {}", msg);
+        println!("       {}", hint);
+    }
+}
+
+pub async fn execute(opts: Opts, config_path: Option<&Path>) -> Result<(), crate::error::Error> {
+    let mut checks = Checks::default();
+
+    let config = check_config(&mut checks, config_path);
+    check_out_dir(&mut checks, &opts.out_dir);
+    for server in &opts.servers {
+        check_server(&mut checks, server).await;
+    }
+
+    let webhooks = opts
+        .discord_webhook
+        .iter()
+        .map(|url| ("--discord-webhook", url))
+        .chain(
+            opts.admin_webhook
+                .iter()
+                .map(|url| ("--admin-webhook", url)),
+        )
+        .chain(
+            config
+                .notifiers
+                .iter()
+                .filter_map(|notifier| match notifier {
+                    NotifierConfig::Discord { url } => Some(("notifiers", url)),
+                    _ => None,
+                }),
+        )
+        .chain(
+            config
+                .admin_notifiers
+                .iter()
+                .filter_map(|notifier| match notifier {
+                    NotifierConfig::Discord { url } => Some(("admin_notifiers", url)),
+                    _ => None,
+                }),
+        );
+    for (source, url) in webhooks {
+        check_discord_webhook(&mut checks, source, url).await;
+    }
+
+    if checks.failed > 0 {
+        return Err(crate::error::Error::Doctor(checks.failed));
+    }
+    Ok(())
+}
+
+fn check_config(checks: &mut Checks, path: Option<&Path>) -> Config {
+    let Some(path) = path else {
+        checks.ok("no config file given, using the defaults");
+        return Config::default();
+    };
+
+    match Config::load(path) {
+        Ok(config) => {
+            let problems = config.problems();
+            if problems.is_empty() {
+                checks.ok(format_args!("config file `{}` is valid", path.display()));
+            } else {
+                checks.fail(
+                    format_args!("config file `{}` has problems", path.display()),
+                    problems.join("\n       "),
+                );
+            }
+            config
+        }
+        Err(err) => {
+            checks.fail(
+                format_args!("can't load config file `{}`", path.display()),
+                crate::error::report(&err),
+            );
+            Config::default()
+        }
+    }
+}
+
+fn check_out_dir(checks: &mut Checks, out_dir: &Path) {
+    let probe = out_dir.join(".lso-doctor");
+    let result = std::fs::create_dir_all(out_dir)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .and_then(|_| std::fs::remove_file(&probe));
+    match result {
+        Ok(()) => checks.ok(format_args!(
+            "can write to the output directory `{}`",
+            out_dir.display()
+        )),
+        Err(err) => checks.fail(
+            format_args!(
+                "can't write to the output directory `{}`: {}",
+                out_dir.display(),
+                err
+            ),
+            "Run the LSO as a user that may write to it, or choose another directory with `-o`.",
+        ),
+    }
+
+    let results_path = out_dir.join(store::RESULTS_FILENAME);
+    if results_path.exists() {
+        match store::load(&results_path) {
+            Ok(records) => checks.ok(format_args!(
+                "the results file contains {} passes",
+                records.len()
+            )),
+            Err(err) => checks.fail(
+                format_args!("can't read the results file `{}`", results_path.display()),
+                crate::error::report(&err),
+            ),
+        }
+    }
+}
+
+async fn check_server(checks: &mut Checks, server: &Server) {
+    let channel = match Endpoint::from(server.uri.clone())
+        .connect_timeout(TIMEOUT)
+        .connect()
+        .await
+    {
+        Ok(channel) => channel,
+        Err(err) => {
+            checks.fail(
+                format_args!(
+                    "{}: can't connect to {}: {}",
+                    server,
+                    server.uri,
+                    crate::error::report(&err)
+                ),
+                "Is DCS running with DCS-gRPC installed and a mission loaded? Check that the URI \
+                 matches the host and port DCS-gRPC is configured to listen on.",
+            );
+            return;
+        }
+    };
+
+    let min_version = format!(
+        "{}.{}.{}",
+        MIN_DCS_GRPC_VERSION.0, MIN_DCS_GRPC_VERSION.1, MIN_DCS_GRPC_VERSION.2
+    );
+    match tokio::time::timeout(TIMEOUT, MetadataClient::new(channel.clone()).get_version()).await {
+        Ok(Ok(version)) => match parse_version(&version) {
+            Some(parsed) if parsed >= MIN_DCS_GRPC_VERSION => {
+                checks.ok(format_args!("{}: DCS-gRPC {}", server, version))
+            }
+            Some(_) => checks.fail(
+                format_args!("{}: DCS-gRPC {} is too old", server, version),
+                format_args!("Update DCS-gRPC to version {} or newer.", min_version),
+            ),
+            None => checks.warn(
+                format_args!("{}: unknown DCS-gRPC version `{}`", server, version),
+                format_args!("The LSO requires DCS-gRPC {} or newer.", min_version),
+            ),
+        },
+        Ok(Err(status)) if status.code() == Code::Unimplemented => checks.fail(
+            format_args!("{}: DCS-gRPC is too old to report its version", server),
+            format_args!("Update DCS-gRPC to version {} or newer.", min_version),
+        ),
+        Ok(Err(status)) => checks.fail(
+            format_args!("{}: failed to get the DCS-gRPC version: {}", server, status),
+            "Check the logs of DCS-gRPC (Saved Games/DCS/Logs/grpc.log).",
+        ),
+        Err(_) => {
+            checks.fail(
+                format_args!("{}: DCS-gRPC didn't respond within {:?}", server, TIMEOUT),
+                "Is the mission running (and not paused on the briefing screen)?",
+            );
+            return;
+        }
+    }
+
+    match tokio::time::timeout(TIMEOUT, HookClient::new(channel).get_mission_name()).await {
+        Ok(Ok(name)) => checks.ok(format_args!("{}: mission `{}` is running", server, name)),
+        Ok(Err(status)) => checks.fail(
+            format_args!("{}: failed to get the mission name: {}", server, status),
+            "Make sure DCS-gRPC's hook script is installed (Saved Games/DCS/Scripts/Hooks/\
+             DCS-gRPC.lua), as the LSO needs it.",
+        ),
+        Err(_) => checks.fail(
+            format_args!("{}: DCS-gRPC didn't respond within {:?}", server, TIMEOUT),
+            "Is the mission running (and not paused on the briefing screen)?",
+        ),
+    }
+}
+
+#[cfg(feature = "discord")]
+async fn check_discord_webhook(checks: &mut Checks, source: &str, url: &str) {
+    match serenity::http::Http::new("token")
+        .get_webhook_from_url(url)
+        .await
+    {
+        Ok(webhook) => checks.ok(format_args!(
+            "Discord webhook `{}` ({})",
+            webhook.name.unwrap_or_default(),
+            source
+        )),
+        Err(err) => checks.fail(
+            format_args!("the Discord webhook of {} is invalid: {}", source, err),
+            "Copy its URL again from the settings of the Discord channel (Integrations > \
+             Webhooks).",
+        ),
+    }
+}
+
+#[cfg(not(feature = "discord"))]
+async fn check_discord_webhook(checks: &mut Checks, source: &str, _url: &str) {
+    checks.fail(
+        format_args!(
+            "the Discord webhook of {} is ignored, as the LSO is built without Discord",
+            source
+        ),
+        "Build the LSO with the `discord` feature (enabled by default).",
+    );
+}
@@ -1,383 +1,464 @@
-use std::collections::HashMap;
 use std::fs::File;
-use std::io::Read;
-use std::ops::Neg;
-use std::path::PathBuf;
-use std::str::FromStr;
+use std::io::BufWriter;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
-use crate::data::{AirplaneInfo, CarrierInfo};
-use crate::draw::DrawError;
-use crate::tasks::detect_recovery_attempt::is_recovery_attempt;
-use crate::tasks::record_recovery::FILENAME_DATETIME_FORMAT;
-use crate::track::{Track, TrackResult};
-use crate::transform::Transform;
-use tacview::record::{Event, EventKind, GlobalProperty, Property, Record, Tag, Update};
-use time::format_description::well_known::Rfc3339;
-use time::{Duration, OffsetDateTime, UtcOffset};
-use ultraviolet::{DRotor3, DVec3};
+use crate::acmi::{AcmiFile, Recording};
+use crate::config::{AcmiFormat, Config};
+use crate::extract::{extract_recoveries_with_config, extract_tracks, CarrierPlanePair};
+use crate::filename::FilenameParts;
+use crate::store::PassRecord;
+use crate::track::TrackResult;
+use rayon::prelude::*;
+use tacview::record::{GlobalProperty, Property, Record};
+use time::OffsetDateTime;
+use ulid::Ulid;
 
 #[derive(clap::Parser)]
 pub struct Opts {
-    /// The path to the ACMI recording recoveries should be extracted from (must be recordings
-    /// created by the LSO; recordings directly from TacView will not work).
+    /// The path to the ACMI recording recoveries should be extracted from, zip-compressed or plain
+    /// text (created by the LSO or recorded by Tacview itself). Can also be a directory or a glob
+    /// pattern (e.g. `recordings/**/*.acmi`) to process all recordings in it.
     input: PathBuf,
-}
-
-pub fn execute(opts: Opts) -> Result<(), crate::error::Error> {
-    let start = Instant::now();
-
-    let mut file = File::open(opts.input)?;
-    let mut tracks = extract_tracks(&mut file)?;
-    for track in &mut tracks {
-        track.draw()?;
-    }
 
-    println!("Took: {:.4}s", start.elapsed().as_secs_f64());
-
-    Ok(())
+    /// Also process the recordings in subdirectories when the input is a directory.
+    #[clap(short, long)]
+    recursive: bool,
+
+    /// The directory the charts and other outputs are saved to.
+    #[clap(short = 'o', long, default_value = ".")]
+    out_dir: PathBuf,
+
+    /// Which outputs to create: a chart (`png`), an interactive debrief (`html`) and/or the result
+    /// (`json`) per pass, and/or a summary of all passes (`csv`, saved as `lso-results.csv`). Can
+    /// be specified multiple times.
+    #[clap(long = "format", value_enum, default_value = "png")]
+    formats: Vec<Format>,
+
+    /// Don't draw charts (e.g. to only create the other outputs of `--format all`).
+    #[clap(long)]
+    no_chart: bool,
+
+    /// Only process the passes of this pilot (case-insensitive).
+    #[clap(long)]
+    pilot: Option<String>,
+
+    /// Only process the passes that ended at or after this time (RFC 3339, e.g.
+    /// `2024-05-01T18:00:00Z`, or relative like `2h`).
+    #[clap(long, value_parser = super::parse_time)]
+    from: Option<OffsetDateTime>,
+
+    /// Only process the passes that ended at or before this time (RFC 3339 or relative).
+    #[clap(long, value_parser = super::parse_time)]
+    to: Option<OffsetDateTime>,
+
+    /// How many recordings are processed in parallel when processing multiple recordings
+    /// (defaults to the number of CPUs). Each one is held in memory while it is processed.
+    #[clap(short, long)]
+    jobs: Option<usize>,
+
+    /// An ACMI recording (created by the LSO) of a previous pass to overlay onto the charts for
+    /// comparison. Uses the pass of the same pilot if there is one, or the first pass otherwise.
+    #[clap(long)]
+    compare: Option<PathBuf>,
+
+    /// The width of the charts in px (overrides `chart.width` of the config).
+    #[clap(long)]
+    width: Option<u32>,
+
+    /// The factor the size of the charts is multiplied with, e.g. `2` for high-DPI displays
+    /// (overrides `chart.scale` of the config).
+    #[clap(long)]
+    scale: Option<f64>,
+
+    /// Write a copy of the recording with the (re-)graded passes embedded as events and
+    /// debriefing to this path (as plain text if it ends with `.txt.acmi`, zip-compressed
+    /// otherwise). When processing multiple recordings, this is the directory the copies are
+    /// written to (named like the recordings).
+    #[clap(long)]
+    acmi: Option<PathBuf>,
+
+    /// The name of the DCS server the recordings were made on, added to the filenames, charts and
+    /// results (overrides the server name recorded by `lso run`).
+    #[clap(long)]
+    server_name: Option<String>,
 }
 
-#[allow(unused)] // used in integration tests
-pub fn extract_recoveries(rd: &mut impl Read) -> Result<Vec<TrackResult>, crate::error::Error> {
-    let mut tracks = extract_tracks(rd)?;
-    Ok(tracks
-        .into_iter()
-        .filter(|t| t.is_recovery_attempt)
-        .map(|t| t.datums.finish())
-        .collect())
+/// An output of the `file` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Format {
+    Png,
+    Html,
+    Json,
+    Csv,
+    All,
 }
 
-fn extract_tracks(rd: &mut impl Read) -> Result<Vec<CarrierPlanePair>, crate::error::Error> {
-    let parser = tacview::Parser::new_compressed(rd)?;
-
-    let mut recording_time =
-        OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
-    let mut carriers: HashMap<u64, &'static CarrierInfo> = HashMap::new();
-    let mut planes: HashMap<u64, (String, &'static AirplaneInfo)> = HashMap::new();
-    let mut tracks: Vec<CarrierPlanePair> = Vec::new();
-
-    let mut time = 0.0;
-    for record in parser {
-        match record? {
-            Record::GlobalProperty(GlobalProperty::RecordingTime(time)) => {
-                if let Ok(time) = OffsetDateTime::parse(&time, &Rfc3339) {
-                    recording_time = if let Ok(offset) = UtcOffset::current_local_offset() {
-                        time.to_offset(offset)
-                    } else {
-                        time
-                    };
-                }
-            }
-
-            Record::Frame(secs) => {
-                for track in &mut tracks {
-                    track.process_frame()?;
-                }
+/// Where and which outputs are saved for each pass.
+pub struct Outputs {
+    pub dir: PathBuf,
+    pub chart: bool,
+    pub debrief: bool,
+    pub json: bool,
+    /// The name of the DCS server the passes are labeled with (instead of the one in the
+    /// recording).
+    pub server_name: Option<String>,
+}
 
-                time = secs;
-            }
+/// Which passes are processed.
+#[derive(Default)]
+pub struct Filter {
+    pub pilot: Option<String>,
+    pub from: Option<OffsetDateTime>,
+    pub to: Option<OffsetDateTime>,
+}
 
-            Record::Update(update) => {
-                if !carriers.contains_key(&update.id) && !planes.contains_key(&update.id) {
-                    let pilot_name = update
-                        .props
-                        .iter()
-                        .find_map(|p| {
-                            if let Property::Pilot(pilot_name) = p {
-                                Some(pilot_name.as_str())
-                            } else {
-                                None
-                            }
-                        })
-                        .unwrap_or("KI");
-                    let name = update.props.iter().find_map(|p| {
-                        if let Property::Name(name) = p {
-                            Some(name)
-                        } else {
-                            None
-                        }
-                    });
-                    let tags = update.props.iter().find_map(|p| {
-                        if let Property::Type(tags) = p {
-                            Some(tags)
-                        } else {
-                            None
-                        }
-                    });
-
-                    if let Some((name, tags)) = name.zip(tags) {
-                        if tags.contains(&Tag::AircraftCarrier) {
-                            match CarrierInfo::by_type(name) {
-                                Some(carrier_info) => {
-                                    for (plane_id, (pilot_name, plane_info)) in &planes {
-                                        tracks.push(CarrierPlanePair::new(
-                                            recording_time + Duration::seconds_f64(time),
-                                            update.id,
-                                            carrier_info,
-                                            *plane_id,
-                                            pilot_name,
-                                            plane_info,
-                                        ));
-                                    }
-
-                                    carriers.insert(update.id, carrier_info);
-                                }
-                                None => tracing::trace!(name, "unsupported aircraft carrier"),
-                            }
-                        } else if tags.contains(&Tag::FixedWing) {
-                            match AirplaneInfo::by_type(name) {
-                                Some(plane_info) => {
-                                    for (carrier_id, carrier_info) in &carriers {
-                                        tracks.push(CarrierPlanePair::new(
-                                            recording_time + Duration::seconds_f64(time),
-                                            *carrier_id,
-                                            carrier_info,
-                                            update.id,
-                                            pilot_name,
-                                            plane_info,
-                                        ));
-                                    }
-
-                                    planes.insert(update.id, (pilot_name.to_string(), plane_info));
-                                }
-                                None => tracing::trace!(name, "unsupported fixed wing aircraft"),
-                            }
-                        }
-                    }
-                }
+impl Filter {
+    fn matches(&self, pair: &CarrierPlanePair) -> bool {
+        let ended = pair.ended();
+        self.pilot
+            .as_deref()
+            .is_none_or(|pilot| pair.pilot_name.eq_ignore_ascii_case(pilot))
+            && self.from.is_none_or(|from| ended >= from)
+            && self.to.is_none_or(|to| ended <= to)
+    }
+}
 
-                for track in &mut tracks {
-                    track.update(time, &update);
-                }
-            }
+/// The name of the summary of all passes (in the output directory).
+const CSV_FILENAME: &str = "lso-results.csv";
 
-            Record::Event(Event {
-                kind: EventKind::Landed,
-                mut params,
-                ..
-            }) => {
-                tracing::trace!(?params, "landed event");
-                if let Some((carrier_id, plane_id)) = params
-                    .pop()
-                    .and_then(|id| u64::from_str(&id).ok())
-                    .zip(params.pop().and_then(|id| u64::from_str(&id).ok()))
-                {
-                    tracing::trace!(carrier_id, plane_id, "landed event");
-                    for track in &mut tracks {
-                        track.landed(carrier_id, plane_id);
-                    }
-                }
-            }
+pub fn execute(opts: Opts, mut config: Config) -> Result<(), crate::error::Error> {
+    let start = Instant::now();
 
-            Record::Event(Event {
-                kind: EventKind::Message,
-                mut params,
-                text: Some(dcs_grading),
-            }) => {
-                if let Some((carrier_id, plane_id)) = params
-                    .pop()
-                    .and_then(|id| u64::from_str(&id).ok())
-                    .zip(params.pop().and_then(|id| u64::from_str(&id).ok()))
-                {
-                    tracing::trace!(carrier_id, plane_id, dcs_grading, "dcs lso grading");
-                    for track in &mut tracks {
-                        track.dcs_grading(carrier_id, plane_id, &dcs_grading);
-                    }
-                }
-            }
+    if let Some(width) = opts.width {
+        config.chart.width = width;
+    }
+    if let Some(scale) = opts.scale {
+        config.chart.scale = scale;
+    }
 
-            _ => {}
+    let references = if let Some(path) = opts.compare {
+        extract_recoveries_with_config(&mut File::open(path)?, &config)?
+    } else {
+        Vec::new()
+    };
+
+    let has_format = |format| opts.formats.contains(&format) || opts.formats.contains(&Format::All);
+    let outputs = Outputs {
+        dir: opts.out_dir.clone(),
+        chart: has_format(Format::Png) && !opts.no_chart,
+        debrief: has_format(Format::Html),
+        json: has_format(Format::Json),
+        server_name: opts.server_name.clone(),
+    };
+    let csv = has_format(Format::Csv);
+    let filter = Filter {
+        pilot: opts.pilot.clone(),
+        from: opts.from,
+        to: opts.to,
+    };
+    std::fs::create_dir_all(&outputs.dir)?;
+
+    let Some(inputs) = find_recordings(&opts.input, opts.recursive)? else {
+        let records = process_recording(
+            &opts.input,
+            &config,
+            &outputs,
+            &filter,
+            &references,
+            opts.acmi.as_deref(),
+        )?;
+        if csv {
+            write_csv(&outputs.dir, &records)?;
         }
+        println!("Took: {:.4}s", start.elapsed().as_secs_f64());
+        return Ok(());
+    };
+
+    if let Some(dir) = &opts.acmi {
+        std::fs::create_dir_all(dir)?;
+    }
+    let results = process_batch(&inputs, opts.jobs, |input| {
+        println!("Processing {}", input.display());
+        let acmi = opts
+            .acmi
+            .as_ref()
+            .zip(input.file_name())
+            .map(|(dir, name)| dir.join(name));
+        process_recording(
+            input,
+            &config,
+            &outputs,
+            &filter,
+            &references,
+            acmi.as_deref(),
+        )
+    })?;
+
+    let records = results
+        .iter()
+        .flatten()
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>();
+    if csv {
+        write_csv(&outputs.dir, &records)?;
     }
 
-    for track in &mut tracks {
-        track.process_frame()?;
+    let failed = inputs
+        .iter()
+        .zip(&results)
+        .filter(|(_, result)| result.is_none())
+        .map(|(input, _)| input)
+        .collect::<Vec<_>>();
+
+    println!(
+        "Processed {} recordings with {} passes ({} failed)",
+        inputs.len(),
+        records.len(),
+        failed.len()
+    );
+    for input in failed {
+        println!("  failed: {}", input.display());
     }
+    println!("Took: {:.4}s", start.elapsed().as_secs_f64());
 
-    Ok(tracks)
+    Ok(())
 }
 
-struct CarrierPlanePair {
-    recording_time: OffsetDateTime,
-    pilot_name: String,
-    carrier_id: u64,
-    carrier: Transform,
-    carrier_info: &'static CarrierInfo,
-    plane_id: u64,
-    plane: Transform,
-    plane_info: &'static AirplaneInfo,
-    is_recovery_attempt: bool,
-    is_dirty: bool,
-    is_done: bool,
-    datums: Track,
-    landed: bool,
+/// The recordings to process if `input` is a directory or a glob pattern (sorted by path), or
+/// `None` if it is a single recording.
+fn find_recordings(
+    input: &Path,
+    recursive: bool,
+) -> Result<Option<Vec<PathBuf>>, crate::error::Error> {
+    let mut recordings = if input.is_dir() {
+        let mut recordings = Vec::new();
+        collect_recordings(input, recursive, &mut recordings)?;
+        recordings
+    } else if !input.exists()
+        && input
+            .to_str()
+            .is_some_and(|input| input.contains(['*', '?', '[']))
+    {
+        glob::glob(&input.to_string_lossy())?
+            .filter_map(|entry| entry.ok())
+            .filter(|path| path.is_file())
+            .collect()
+    } else {
+        return Ok(None);
+    };
+    recordings.sort();
+    Ok(Some(recordings))
 }
 
-impl CarrierPlanePair {
-    fn new(
-        recording_time: OffsetDateTime,
-        carrier_id: u64,
-        carrier_info: &'static CarrierInfo,
-        plane_id: u64,
-        pilot_name: &str,
-        plane_info: &'static AirplaneInfo,
-    ) -> Self {
-        Self {
-            recording_time,
-            pilot_name: pilot_name.to_string(),
-            carrier_id,
-            carrier: Default::default(),
-            carrier_info,
-            plane_id,
-            plane: Default::default(),
-            plane_info,
-            is_recovery_attempt: false,
-            is_dirty: false,
-            is_done: false,
-            datums: Track::new(pilot_name, carrier_info, plane_info),
-            landed: false,
-        }
-    }
-
-    fn update(&mut self, time: f64, update: &Update) {
-        let (transform, is_plane) = if update.id == self.carrier_id {
-            (&mut self.carrier, false)
-        } else if update.id == self.plane_id {
-            (&mut self.plane, true)
-        } else {
-            return;
-        };
-
-        for p in &update.props {
-            match p {
-                Property::T(coords) => {
-                    let mut orientation_changed = false;
-
-                    if let Some(roll) = coords.roll {
-                        transform.roll = roll;
-                        orientation_changed = true;
-                    }
-                    if let Some(pitch) = coords.pitch {
-                        transform.pitch = pitch;
-                        orientation_changed = true;
-                    }
-                    if let Some(yaw) = coords.yaw {
-                        transform.yaw = yaw;
-                        orientation_changed = true;
-                    }
-                    if let Some(heading) = coords.heading {
-                        transform.heading = heading;
-                        orientation_changed = true;
-                    }
-
-                    if orientation_changed {
-                        transform.forward = DVec3::new(
-                            transform.yaw.to_radians().sin() * transform.pitch.to_radians().cos(),
-                            transform.pitch.to_radians().sin(),
-                            transform.yaw.to_radians().cos() * transform.pitch.to_radians().cos(),
-                        );
-                        transform.rotation = DRotor3::from_euler_angles(
-                            transform.roll.neg().to_radians(),
-                            transform.pitch.neg().to_radians(),
-                            transform.heading.neg().to_radians(),
-                        );
-                    }
-
-                    let mut new_pos = transform.position;
-
-                    if let Some(altitude) = coords.altitude {
-                        new_pos.y = altitude;
-                        transform.alt = altitude;
+/// Add the ACMI recordings in `dir` (and, if `recursive`, its subdirectories) to `recordings`.
+/// Process all `inputs` with `process` on `jobs` threads (all cores by default) and return the
+/// records of each, or `None` for the inputs that failed (which are reported right away).
+pub fn process_batch(
+    inputs: &[PathBuf],
+    jobs: Option<usize>,
+    process: impl Fn(&Path) -> Result<Vec<PassRecord>, crate::error::Error> + Sync,
+) -> Result<Vec<Option<Vec<PassRecord>>>, crate::error::Error> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.unwrap_or_default())
+        .build()
+        .map_err(std::io::Error::other)?;
+    Ok(pool.install(|| {
+        inputs
+            .par_iter()
+            .map(|input| {
+                // a corrupt recording must not abort the whole batch, not even by panicking
+                let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    process(input.as_path()).map_err(|err| crate::error::report(&err))
+                }));
+                match result {
+                    Ok(Ok(records)) => Some(records),
+                    Ok(Err(err)) => {
+                        eprintln!("Failed to process {}: {}", input.display(), err);
+                        None
                     }
-                    if let Some(u) = coords.u {
-                        new_pos.x = u;
+                    Err(_) => {
+                        eprintln!("Failed to process {}: panicked", input.display());
+                        None
                     }
-                    if let Some(v) = coords.v {
-                        new_pos.z = v;
-                    }
-
-                    transform.position = new_pos;
-                    transform.time = time;
-
-                    if is_plane {
-                        self.is_dirty = true;
-                    }
-                }
-                Property::Pilot(pilot_name) => {
-                    self.pilot_name = pilot_name.to_string();
                 }
-                Property::AOA(aoa) => {
-                    transform.aoa = *aoa;
-                }
-                _ => {}
+            })
+            .collect()
+    }))
+}
+
+pub fn collect_recordings(
+    dir: &Path,
+    recursive: bool,
+    recordings: &mut Vec<PathBuf>,
+) -> Result<(), std::io::Error> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if recursive {
+                collect_recordings(&path, recursive, recordings)?;
             }
+        } else if path.extension().is_some_and(|ext| ext == "acmi") {
+            recordings.push(path);
         }
     }
+    Ok(())
+}
 
-    fn landed(&mut self, carrier_id: u64, plane_id: u64) {
-        if self.carrier_id == carrier_id && self.plane_id == plane_id && !self.landed {
-            self.landed = true;
-            self.is_dirty = true;
+/// Save the outputs of all passes in the recording at `input` (with the charts optionally compared
+/// to one of the `references`), and return their results.
+pub fn process_recording(
+    input: &Path,
+    config: &Config,
+    outputs: &Outputs,
+    filter: &Filter,
+    references: &[TrackResult],
+    acmi: Option<&Path>,
+) -> Result<Vec<PassRecord>, crate::error::Error> {
+    let mut tracks = extract_tracks(&mut File::open(input)?, config)?;
+
+    let mut results = Vec::new();
+    let mut records = Vec::new();
+    for track in tracks.iter_mut().filter(|track| filter.matches(track)) {
+        let reference = references
+            .iter()
+            .find(|r| r.pilot_name == track.pilot_name)
+            .or(references.first());
+        match save_pass(track, config, outputs, reference) {
+            Ok(Some((result, record))) => {
+                results.push((track.plane_id, result));
+                records.push(record);
+            }
+            Ok(None) => {}
+            // continue with the other passes of the recording
+            Err(err) => eprintln!(
+                "Failed to process the pass of {}: {}",
+                track,
+                crate::error::report(&err)
+            ),
         }
     }
 
-    fn dcs_grading(&mut self, carrier_id: u64, plane_id: u64, dcs_grading: &str) {
-        if self.carrier_id == carrier_id && self.plane_id == plane_id {
-            self.datums.set_dcs_grading(dcs_grading.to_string());
-        }
+    if let Some(path) = acmi {
+        write_graded_copy(input, path, &results)?;
+        println!("Saved graded recording to {}", path.display());
     }
 
-    fn process_frame(&mut self) -> Result<(), DrawError> {
-        if !self.is_dirty || self.is_done {
-            return Ok(());
-        }
+    Ok(records)
+}
 
-        self.is_dirty = false;
+fn write_csv(dir: &Path, records: &[PassRecord]) -> Result<(), crate::error::Error> {
+    let path = dir.join(CSV_FILENAME);
+    crate::store::write_csv(BufWriter::new(File::create(&path)?), records)?;
+    println!("Saved results to {}", path.display());
+    Ok(())
+}
 
-        if self.carrier.time == 0.0 || self.plane.time == 0.0 {
-            return Ok(());
+/// Copy the recording at `input` to `output` and append the results of the passes (see
+/// [crate::acmi::write_debriefing]).
+fn write_graded_copy(
+    input: &Path,
+    output: &Path,
+    results: &[(u64, TrackResult)],
+) -> Result<(), crate::error::Error> {
+    let parser = crate::acmi::parse(File::open(input)?)?;
+    let mut recording = Recording::new(
+        AcmiFile::create(output, AcmiFormat::from_path(output))?,
+        None,
+    );
+    for record in parser {
+        match record? {
+            // replaced by the new debriefing
+            Record::GlobalProperty(GlobalProperty::Debriefing(_)) => {}
+            Record::Update(mut update) => {
+                for prop in &mut update.props {
+                    if let Property::Pilot(pilot_name) = prop {
+                        *pilot_name = crate::privacy::recorded_pilot_name(pilot_name);
+                    }
+                }
+                recording.write(update)?
+            }
+            record => recording.write(record)?,
         }
+    }
+    crate::acmi::write_debriefing(
+        &mut recording,
+        &results
+            .iter()
+            .map(|(plane_id, track)| (*plane_id, track))
+            .collect::<Vec<_>>(),
+    )?;
+    recording.into_inner().finish()?;
+
+    Ok(())
+}
 
-        if self.is_recovery_attempt {
-            let mut should_continue = self.datums.next(&self.carrier, &self.plane);
-            if self.landed {
-                self.datums.landed(&self.carrier, &self.plane);
-                should_continue = false;
+/// Save the outputs of the pass of the pair (if any) and return its result.
+#[cfg_attr(not(feature = "charts"), allow(unused_variables))]
+fn save_pass(
+    pair: &mut CarrierPlanePair,
+    config: &Config,
+    outputs: &Outputs,
+    reference: Option<&TrackResult>,
+) -> Result<Option<(TrackResult, PassRecord)>, crate::error::Error> {
+    if let Some(mut track) = pair.finish_pass() {
+        let server_name = outputs
+            .server_name
+            .as_deref()
+            .or(pair.server_name.as_deref());
+        track.server_name = server_name.map(String::from);
+        let ended = pair.ended();
+        let id = Ulid::from_datetime(ended.into()).to_string();
+        let mut record = PassRecord::new(
+            ended,
+            server_name,
+            &pair.carrier_name,
+            &pair.plane_type,
+            &pair.carrier_type,
+            &track,
+        );
+        record.id = Some(id.clone());
+
+        // don't create the output directory if nothing is saved to it
+        if outputs.chart || outputs.debrief || outputs.json {
+            let (dir, filename) = crate::filename::output_location(
+                &outputs.dir,
+                &config.output,
+                &FilenameParts {
+                    datetime: Some(pair.recording_time),
+                    server: server_name,
+                    pilot: Some(&pair.pilot_name),
+                    plane: Some(&pair.plane_type),
+                    mission: pair.mission_name.as_deref(),
+                    id: Some(&id),
+                    track: Some(&track),
+                    ..Default::default()
+                },
+            )?;
+            #[cfg(feature = "charts")]
+            if outputs.chart {
+                crate::draw::draw_chart(&dir, &filename, &track, &config.chart, reference)?;
             }
-            if !should_continue {
-                self.is_done = true;
+            if outputs.debrief {
+                crate::debrief::write_html(
+                    &dir.join(format!("{}.html", filename)),
+                    &track,
+                    &record,
+                    None,
+                )?;
+            }
+            if outputs.json {
+                std::fs::write(
+                    dir.join(format!("{}.json", filename)),
+                    serde_json::to_vec_pretty(&record)?,
+                )?;
             }
-        } else if is_recovery_attempt(&self.carrier, &self.plane) {
-            self.is_recovery_attempt = true;
         }
 
-        Ok(())
+        return Ok(Some((track, record)));
     }
 
-    fn draw(&mut self) -> Result<(), DrawError> {
-        if self.is_recovery_attempt {
-            let out_dir = PathBuf::from(".");
-            let filename = format!(
-                "LSO-{}-{}",
-                self.recording_time
-                    .format(&FILENAME_DATETIME_FORMAT)
-                    .unwrap_or_default(),
-                self.pilot_name
-                    .chars()
-                    .filter(|c| c.is_ascii_alphanumeric())
-                    .collect::<String>()
-            );
-            let track = std::mem::replace(
-                &mut self.datums,
-                Track::new(&self.pilot_name, self.carrier_info, self.plane_info),
-            )
-            .finish();
-            crate::draw::draw_chart(&out_dir, &filename, &track)?;
-            self.is_recovery_attempt = false;
-            self.landed = false;
-        }
-
-        Ok(())
-    }
+    Ok(None)
 }
@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+
+use time::OffsetDateTime;
+
+use crate::leaderboard;
+#[cfg(feature = "discord")]
+use crate::notify::{DiscordNotifier, Notifier};
+use crate::store;
+
+use super::parse_time;
+
+#[derive(clap::Parser)]
+pub struct Opts {
+    /// The results file written by `lso run` (located in its output directory).
+    #[clap(long, default_value = store::RESULTS_FILENAME)]
+    results: PathBuf,
+
+    /// Only include passes recorded on this server.
+    #[clap(long)]
+    server: Option<String>,
+
+    /// Only include passes recorded since this time (RFC 3339, or relative like `30d`).
+    #[clap(long, value_parser = parse_time)]
+    since: Option<OffsetDateTime>,
+
+    /// Only rank pilots with at least this many passes.
+    #[clap(long, default_value = "3")]
+    min_passes: usize,
+
+    /// The number of pilots to list.
+    #[clap(long, default_value = "10")]
+    limit: usize,
+
+    /// A Discord webhook the leaderboard should be posted to.
+    #[cfg(feature = "discord")]
+    #[clap(long)]
+    discord_webhook: Option<String>,
+}
+
+pub async fn execute(opts: Opts) -> Result<(), crate::error::Error> {
+    let records = store::load(&opts.results)?
+        .into_iter()
+        .filter(|r| {
+            opts.server
+                .as_deref()
+                .is_none_or(|s| r.server.as_deref() == Some(s))
+        })
+        .filter(|r| {
+            opts.since
+                .is_none_or(|since| r.time().is_some_and(|time| time >= since))
+        })
+        .collect::<Vec<_>>();
+
+    let mut standings = leaderboard::standings(&records, opts.min_passes);
+    standings.truncate(opts.limit);
+    print!("{}", leaderboard::format(&standings));
+
+    #[cfg(feature = "discord")]
+    if let Some(discord_webhook) = &opts.discord_webhook {
+        DiscordNotifier::new(discord_webhook, Default::default())
+            .notify(&leaderboard::notification("Leaderboard", &standings))
+            .await?;
+    }
+
+    Ok(())
+}
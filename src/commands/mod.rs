@@ -1,2 +1,67 @@
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+
+pub mod config;
+#[cfg(feature = "grpc")]
+pub mod doctor;
 pub mod file;
+pub mod leaderboard;
+pub mod recoveries;
+pub mod regrade;
+pub mod report;
+#[cfg(feature = "grpc")]
 pub mod run;
+#[cfg(all(windows, feature = "grpc"))]
+pub mod service;
+pub mod site;
+#[cfg(feature = "charts")]
+pub mod spotting;
+#[cfg(feature = "charts")]
+pub mod stats;
+pub mod watch;
+
+/// Parse a time given on the command line, either as RFC 3339 or relative to now (e.g. `30d` for
+/// 30 days ago, see [parse_duration]).
+pub(crate) fn parse_time(s: &str) -> Result<OffsetDateTime, String> {
+    match OffsetDateTime::parse(s, &Rfc3339) {
+        Ok(time) => Ok(time),
+        Err(err) => {
+            let duration = parse_duration(s)
+                .map_err(|_| format!("{} (expected RFC 3339 or a duration like `30d`)", err))?;
+            OffsetDateTime::now_utc()
+                .checked_sub(duration)
+                .ok_or_else(|| format!("`{}` is too far in the past", s))
+        }
+    }
+}
+
+/// Parse a (positive) duration given as a number with a unit (`s`, `m`, `h`, `d` or `w`), e.g.
+/// `7d`.
+pub(crate) fn parse_duration(s: &str) -> Result<Duration, String> {
+    let unit = s
+        .char_indices()
+        .last()
+        .map(|(i, _)| i)
+        .ok_or_else(|| "empty duration".to_string())?;
+    let (value, unit) = s.split_at(unit);
+    let value = value
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite() && *value >= 0.0)
+        .ok_or_else(|| format!("invalid duration `{}`", s))?;
+    let seconds = match unit {
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3_600.0,
+        "d" => 86_400.0,
+        "w" => 604_800.0,
+        _ => {
+            return Err(format!(
+                "invalid duration unit in `{}` (expected s, m, h, d or w)",
+                s
+            ))
+        }
+    };
+    Duration::checked_seconds_f64(value * seconds)
+        .ok_or_else(|| format!("duration `{}` is too long", s))
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use time::{Duration, OffsetDateTime};
+
+use crate::config::Config;
+use crate::filename::{DATETIME_FORMAT, TIME_FORMAT};
+use crate::recovery::recovery_windows;
+use crate::store;
+
+use super::parse_time;
+
+#[derive(clap::Parser)]
+pub struct Opts {
+    /// The results file written by `lso run` (located in its output directory).
+    #[clap(long, default_value = store::RESULTS_FILENAME)]
+    results: PathBuf,
+
+    /// Only include passes recorded on this server.
+    #[clap(long)]
+    server: Option<String>,
+
+    /// Only include passes recorded since this time (RFC 3339, e.g. `2024-05-01T18:00:00Z`, or
+    /// relative like `30d`).
+    #[clap(long, value_parser = parse_time)]
+    since: Option<OffsetDateTime>,
+}
+
+pub fn execute(opts: Opts, config: Config) -> Result<(), crate::error::Error> {
+    let records = store::load(&opts.results)?
+        .into_iter()
+        .filter(|r| {
+            opts.server
+                .as_deref()
+                .is_none_or(|s| r.server.as_deref() == Some(s))
+        })
+        .filter(|r| {
+            opts.since
+                .is_none_or(|since| r.time().is_some_and(|time| time >= since))
+        })
+        .collect::<Vec<_>>();
+
+    let target = Duration::seconds_f64(config.recovery.target_interval_s);
+    for window in recovery_windows(&records, Duration::seconds_f64(config.recovery.max_gap_s)) {
+        let intervals = window.trap_intervals();
+        let ragged = intervals.iter().filter(|i| **i > target).count();
+        println!(
+            "{}{} {} - {}: {} passes, {} traps, average interval {}, longest {}, {} ragged (> {})",
+            window
+                .server
+                .map(|s| format!("[{}] ", s))
+                .unwrap_or_default(),
+            window.carrier,
+            window.start().format(DATETIME_FORMAT).unwrap_or_default(),
+            window.end().format(TIME_FORMAT).unwrap_or_default(),
+            window.passes.len(),
+            window.traps().count(),
+            format_interval(window.average_trap_interval()),
+            format_interval(intervals.iter().max().copied()),
+            ragged,
+            format_interval(Some(target)),
+        );
+        for (number, time, record) in window.numbered_passes() {
+            println!(
+                "  {:>2}. {} {} ({})",
+                number,
+                time.format(TIME_FORMAT).unwrap_or_default(),
+                record.pilot,
+                record.grading.summary(record.hook_up),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Format the interval as `m:ss` (or `-` if there is none).
+fn format_interval(interval: Option<Duration>) -> String {
+    match interval {
+        Some(interval) => {
+            let seconds = interval.whole_seconds();
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        }
+        None => "-".to_string(),
+    }
+}
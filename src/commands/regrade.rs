@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+use time::{Duration, OffsetDateTime};
+
+use super::file::{Filter, Outputs};
+use crate::config::Config;
+use crate::filename::DATETIME_SECONDS_FORMAT;
+use crate::store::{self, PassRecord};
+use crate::track::Grading;
+
+/// How far apart the end of a regraded pass and the time of a stored result may be to still be
+/// considered the same pass (the time of a pass graded live is when its recording was finished,
+/// which is a little after the end of the pass extracted from that recording).
+const MATCH_WINDOW: Duration = Duration::minutes(2);
+
+#[derive(clap::Parser)]
+pub struct Opts {
+    /// The directory with the ACMI recordings to regrade (e.g. the output directory of `lso run`),
+    /// including its subdirectories.
+    dir: PathBuf,
+
+    /// The results to compare against (defaults to the results file in the directory).
+    #[clap(long)]
+    results: Option<PathBuf>,
+
+    /// How many recordings are processed in parallel (defaults to the number of CPUs).
+    #[clap(short, long)]
+    jobs: Option<usize>,
+
+    /// Also list the passes whose results didn't change.
+    #[clap(long)]
+    all: bool,
+}
+
+/// The parts of a result that are compared.
+#[derive(Debug, PartialEq, Eq)]
+struct Outcome {
+    grade: String,
+    wire: Option<u8>,
+}
+
+impl Outcome {
+    fn new(record: &PassRecord) -> Self {
+        let (grade, wire) = match &record.grading {
+            Grading::Unknown => ("unknown".to_string(), None),
+            Grading::TouchAndGo => ("touch-and-go".to_string(), None),
+            Grading::Bolter if record.hook_up => ("touch-and-go".to_string(), None),
+            Grading::Bolter => ("bolter".to_string(), None),
+            Grading::Recovered {
+                cable,
+                cable_estimated,
+            } => ("trap".to_string(), cable.or(*cable_estimated)),
+            Grading::Incident(incident) => (incident.as_str().to_lowercase(), None),
+            Grading::Spin => ("spin".to_string(), None),
+            Grading::OwnWaveOff => ("own wave-off".to_string(), None),
+        };
+        Outcome { grade, wire }
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.wire {
+            Some(wire) => write!(f, "{} #{}", self.grade, wire),
+            None => f.write_str(&self.grade),
+        }
+    }
+}
+
+pub fn execute(opts: Opts, config: Config) -> Result<(), crate::error::Error> {
+    let results_path = opts
+        .results
+        .clone()
+        .unwrap_or_else(|| opts.dir.join(store::RESULTS_FILENAME));
+    // the recordings contain the names the passes were recorded with
+    let stored = store::load_as_recorded(&results_path)?;
+
+    let mut inputs = Vec::new();
+    super::file::collect_recordings(&opts.dir, true, &mut inputs)?;
+    inputs.sort();
+
+    // only grade, don't save any charts
+    let outputs = Outputs {
+        dir: opts.dir.clone(),
+        chart: false,
+        debrief: false,
+        json: false,
+        server_name: None,
+    };
+    let results = super::file::process_batch(&inputs, opts.jobs, |input| {
+        super::file::process_recording(input, &config, &outputs, &Filter::default(), &[], None)
+    })?;
+    let mut regraded = results.into_iter().flatten().flatten().collect::<Vec<_>>();
+    regraded.sort_by_key(PassRecord::time);
+
+    // Only the stored results of the time span covered by the recordings can be matched (older
+    // recordings might have been deleted already).
+    let times = regraded.iter().filter_map(PassRecord::time);
+    let span = times
+        .clone()
+        .min()
+        .zip(times.max())
+        .map(|(from, to)| (from - MATCH_WINDOW, to + MATCH_WINDOW));
+    let mut stored = stored
+        .into_iter()
+        .filter(|record| {
+            span.zip(record.time())
+                .is_some_and(|((from, to), time)| time >= from && time <= to)
+        })
+        .map(Some)
+        .collect::<Vec<_>>();
+
+    let mut unchanged = 0;
+    let mut changed = 0;
+    let mut new = 0;
+    for record in &regraded {
+        let outcome = Outcome::new(record);
+        let Some(previous) = take_match(&mut stored, record) else {
+            new += 1;
+            println!("{}  new: {}", describe(record), outcome);
+            continue;
+        };
+        let previous = Outcome::new(&previous);
+        if previous == outcome {
+            unchanged += 1;
+            if opts.all {
+                println!("{}  {}", describe(record), outcome);
+            }
+        } else {
+            changed += 1;
+            println!("{}  {} -> {}", describe(record), previous, outcome);
+        }
+    }
+
+    let mut missing = 0;
+    for record in stored.into_iter().flatten() {
+        missing += 1;
+        println!(
+            "{}  not found: {}",
+            describe(&record),
+            Outcome::new(&record)
+        );
+    }
+
+    println!(
+        "Regraded {} passes of {} recordings: {} unchanged, {} changed, {} new, {} not found",
+        regraded.len(),
+        inputs.len(),
+        unchanged,
+        changed,
+        new,
+        missing
+    );
+
+    Ok(())
+}
+
+/// Remove and return the stored result of the same pilot closest in time to `record` (within
+/// [MATCH_WINDOW]).
+fn take_match(stored: &mut [Option<PassRecord>], record: &PassRecord) -> Option<PassRecord> {
+    let time = record.time()?;
+    let distance = |other: &PassRecord| other.time().map(|other| (other - time).abs());
+    stored
+        .iter_mut()
+        .filter(|other| {
+            other.as_ref().is_some_and(|other| {
+                other.pilot == record.pilot
+                    && distance(other).is_some_and(|distance| distance <= MATCH_WINDOW)
+            })
+        })
+        .min_by_key(|other| other.as_ref().and_then(distance))?
+        .take()
+}
+
+fn describe(record: &PassRecord) -> String {
+    let time = record
+        .time()
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+        .format(DATETIME_SECONDS_FORMAT)
+        .unwrap_or_default();
+    format!("{} {} ({})", time, record.pilot, record.plane_type)
+}
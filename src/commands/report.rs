@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use time::{Duration, OffsetDateTime};
+
+use crate::config::Config;
+#[cfg(feature = "discord")]
+use crate::notify::{DiscordNotifier, Notification, Notifier};
+use crate::store;
+
+use super::parse_time;
+
+#[derive(clap::Parser)]
+pub struct Opts {
+    /// The results file written by `lso run` (located in its output directory).
+    #[clap(long, default_value = store::RESULTS_FILENAME)]
+    results: PathBuf,
+
+    /// Only include passes recorded on this server.
+    #[clap(long)]
+    server: Option<String>,
+
+    /// Only include passes recorded since this time (RFC 3339, e.g. `2024-05-01T18:00:00Z`, or
+    /// relative like `30d`).
+    #[clap(long, value_parser = parse_time)]
+    since: Option<OffsetDateTime>,
+
+    /// Only include passes recorded until this time (RFC 3339).
+    #[clap(long, value_parser = parse_time)]
+    until: Option<OffsetDateTime>,
+
+    /// The title of the report.
+    #[clap(long, default_value = "Carrier recoveries")]
+    title: String,
+
+    /// The path the report is saved to.
+    #[clap(short = 'o', long, default_value = "report.html")]
+    out: PathBuf,
+
+    /// A Discord webhook the report should be posted to.
+    #[cfg(feature = "discord")]
+    #[clap(long)]
+    discord_webhook: Option<String>,
+}
+
+pub async fn execute(opts: Opts, config: Config) -> Result<(), crate::error::Error> {
+    let records = store::load(&opts.results)?
+        .into_iter()
+        .filter(|r| {
+            opts.server
+                .as_deref()
+                .is_none_or(|s| r.server.as_deref() == Some(s))
+        })
+        .filter(|r| {
+            opts.since
+                .is_none_or(|since| r.time().is_some_and(|time| time >= since))
+        })
+        .filter(|r| {
+            opts.until
+                .is_none_or(|until| r.time().is_some_and(|time| time <= until))
+        })
+        .collect::<Vec<_>>();
+
+    crate::report::write_html(
+        &opts.out,
+        &opts.title,
+        &records,
+        Duration::seconds_f64(config.recovery.max_gap_s),
+    )?;
+    println!("Saved {} passes to {}", records.len(), opts.out.display());
+
+    #[cfg(feature = "discord")]
+    if let Some(discord_webhook) = &opts.discord_webhook {
+        DiscordNotifier::new(discord_webhook, Default::default())
+            .notify(&Notification {
+                title: Some(opts.title.clone()),
+                files: vec![opts.out.clone().into()],
+                ..Default::default()
+            })
+            .await?;
+    }
+
+    Ok(())
+}
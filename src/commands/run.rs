@@ -1,24 +1,28 @@
-use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::data::{AirplaneInfo, CarrierInfo};
-use crate::tasks::TaskParams;
+use crate::client::{check_version, HookClient, MetadataClient, UnitClient, MIN_DCS_GRPC_VERSION};
+use crate::config::Config;
+use crate::event_log::{EventLog, LifecycleEvent};
+use crate::leaderboard;
+use crate::notify::{AdminAlerts, DiscordUsers, Notifiers};
+use crate::realtime::RealtimeTelemetry;
+use crate::recovery::RecoveryWindows;
+use crate::status::{ServerStatus, Status};
+use crate::store;
+use crate::tasks::supervisor::Supervisor;
+use crate::tasks::{AcmiRetention, DetectionParams, DiscordPosts};
 use crate::utils::shutdown::ShutdownHandle;
 use backoff::ExponentialBackoff;
-use futures_util::future::select;
-use futures_util::{StreamExt, TryFutureExt};
-use stubs::coalition::v0::coalition_service_client::CoalitionServiceClient;
-use stubs::common::v0::{Coalition, GroupCategory};
-use stubs::group::v0::group_service_client::GroupServiceClient;
-use stubs::mission::v0::mission_service_client::MissionServiceClient;
-use stubs::mission::v0::stream_events_response::Event;
-use stubs::unit::v0::unit_service_client::UnitServiceClient;
-use stubs::{coalition, common, group, mission, unit};
-use tokio::sync::mpsc;
+use futures_util::future::{join_all, select};
+use time::OffsetDateTime;
 use tonic::transport::{Channel, Endpoint, Uri};
-use tonic::Status;
+use tonic::Code;
+use tracing::Instrument;
 
 #[derive(clap::Parser)]
 pub struct Opts {
@@ -26,39 +30,293 @@ pub struct Opts {
     #[clap(short = 'o', long, default_value = ".")]
     out_dir: PathBuf,
 
-    /// The URI of DCS-gRPC.
-    #[clap(long, default_value = "http://127.0.0.1:50051")]
-    uri: Uri,
+    /// The URI of DCS-gRPC. Can be specified multiple times to monitor multiple DCS servers at
+    /// once. The URI can optionally be prefixed with a server name (e.g.
+    /// `Training=http://127.0.0.1:50051`), which is added to filenames and Discord posts.
+    #[clap(long = "uri", default_value = "http://127.0.0.1:50051")]
+    servers: Vec<Server>,
+
+    /// The name of the (single) DCS server, which is added to filenames, charts, Discord posts and
+    /// results, so that the passes of multiple LSO instances can be told apart. Use the `NAME=URI`
+    /// form of `--uri` when monitoring multiple servers.
+    #[clap(long)]
+    server_name: Option<String>,
+
+    /// Use the name the DCS server is configured with (in its `serverSettings.lua`) for servers
+    /// that aren't named via `--server-name` or `--uri NAME=URI`. Requires the Lua evaluation of
+    /// DCS-gRPC to be enabled (`evalEnabled = true` in its config).
+    #[clap(long)]
+    server_name_from_dcs: bool,
 
     /// A Discord webhook recovery recordings should be posted to.
     #[clap(long)]
     discord_webhook: Option<String>,
 
+    /// Which posts to send to the Discord webhook (and the chat services in the config): every
+    /// pass, only a summary of all passes when the mission ends, or both.
+    #[clap(long, value_enum, default_value_t = DiscordPosts::Passes)]
+    discord_posts: DiscordPosts,
+
+    /// Write a summary of all passes (as HTML) to the output directory when the mission ends
+    /// (always enabled when posting it to Discord).
+    #[clap(long)]
+    session_report: bool,
+
+    /// Post the leaderboard of the passes since the previous post to Discord (and the other
+    /// configured chat services) every this often (e.g. `7d` for weekly).
+    #[clap(long, value_parser = super::parse_duration)]
+    leaderboard_every: Option<time::Duration>,
+
+    /// The Discord webhook the leaderboard is posted to (defaults to where the passes are posted).
+    #[clap(long)]
+    leaderboard_webhook: Option<String>,
+
+    /// Append lifecycle events (detection and recording started, pass completed, Discord post
+    /// sent, errors) as JSON lines to this file.
+    #[clap(long)]
+    event_log: Option<PathBuf>,
+
+    /// Stream the recordings live via Tacview's real-time telemetry protocol on this address
+    /// (e.g. `127.0.0.1:42674`), so the approaches can be watched in Tacview while they happen.
+    #[clap(long)]
+    tacview_realtime: Option<SocketAddr>,
+
+    /// A Discord webhook errors of the LSO itself (e.g. a lost connection to DCS-gRPC or failed
+    /// posts) are posted to, so that server operators notice when passes stop being recorded.
+    #[clap(long)]
+    admin_webhook: Option<String>,
+
+    /// Serve the state of the LSO via HTTP on this address (e.g. `127.0.0.1:8080`): `/healthz`
+    /// responds with 503 if a server is disconnected or stopped sending units, `/status` with the
+    /// details as JSON (connection state, tracked units and active recordings per server).
+    #[clap(long)]
+    status_addr: Option<SocketAddr>,
+
+    /// Also accept requests to control the LSO on the status address (pause and resume the
+    /// detection, finish a recording, reconnect). Anyone who can reach the address can control
+    /// the LSO, so only use it with a local address.
+    #[clap(long, requires = "status_addr")]
+    control: bool,
+
     /// A JSON file that maps player names to Discord user IDs.
     #[clap(long)]
     discord_users: Option<PathBuf>,
 
+    /// The token of a Discord bot that lets pilots link themselves: its `/lso-link` command gives
+    /// them a code to type in the DCS chat, after which their player is added to the
+    /// `--discord-users` file.
+    #[clap(long, requires = "discord_users")]
+    discord_bot_token: Option<String>,
+
     /// Whether to also record carrier recoveries of KI units (mostly useful for testing/debugging).
     #[clap(long = "ki")]
     include_ki: bool,
+
+    /// Which ACMI recordings to keep (charts are always kept).
+    #[clap(long, value_enum, default_value_t = AcmiRetention::All)]
+    keep_acmi: AcmiRetention,
+
+    /// The maximum number of recoveries that are recorded concurrently (per server).
+    #[clap(long, default_value = "20")]
+    max_recordings: usize,
+
+    /// How long (in seconds) to wait for active recordings to finish on shutdown.
+    #[clap(long, default_value = "60")]
+    drain_timeout: u64,
 }
 
 pub async fn execute(
     opts: Opts,
+    config: Config,
     shutdown_handle: ShutdownHandle,
 ) -> Result<(), crate::error::Error> {
-    if opts.discord_webhook.is_some() {
-        tracing::info!("Discord integration enabled.");
+    if opts.server_name.is_some() && opts.servers.len() > 1 {
+        return Err(crate::error::Error::InvalidConfig(
+            "`--server-name` can only be used with a single server, name multiple servers via \
+             `--uri NAME=URI` instead"
+                .to_string(),
+        ));
     }
+    let users = DiscordUsers::load(opts.discord_users.as_deref())?;
+    let admin = AdminAlerts::new(Notifiers::new(
+        opts.admin_webhook.as_deref(),
+        Default::default(),
+        &config.admin_notifiers,
+    ));
+    let notifiers = Notifiers::new(
+        opts.discord_webhook.as_deref(),
+        users.clone(),
+        &config.notifiers,
+    )
+    .with_admin_alerts(admin.clone());
+    if !notifiers.is_empty() {
+        tracing::info!("Chat integration enabled.");
+    }
+    let config = Arc::new(config);
+    let event_log = EventLog::new(opts.event_log.as_deref(), config.mqtt.as_ref())?;
 
-    tracing::info!(uri = %opts.uri, "Connecting to gRPC server");
-
-    let users: Arc<HashMap<String, u64>> =
-        Arc::new(if let Some(path) = opts.discord_users.as_deref() {
-            serde_json::from_slice(&tokio::fs::read(path).await?)?
+    if let Some(every) = opts.leaderboard_every {
+        let leaderboard_notifiers = match opts.leaderboard_webhook.as_deref() {
+            Some(webhook) => {
+                Notifiers::new(Some(webhook), users.clone(), &[]).with_admin_alerts(admin.clone())
+            }
+            None => notifiers.clone(),
+        };
+        if leaderboard_notifiers.is_empty() {
+            tracing::warn!("not posting leaderboards as no Discord webhook or notifier is set");
         } else {
-            Default::default()
+            tokio::spawn(post_leaderboards(
+                opts.out_dir.join(store::RESULTS_FILENAME),
+                leaderboard_notifiers,
+                every,
+                shutdown_handle.clone(),
+            ));
+        }
+    }
+
+    if let Err(err) = crate::tasks::record_recovery::remove_stale_recordings(&opts.out_dir).await {
+        tracing::warn!(%err, "failed to remove partial recordings left over from a crash");
+    }
+
+    if config.retention.is_enabled() {
+        tokio::spawn(crate::retention::cleanup_periodically(
+            opts.out_dir.clone(),
+            config.retention,
+            shutdown_handle.clone(),
+        ));
+    }
+
+    let realtime = match opts.tacview_realtime {
+        Some(addr) => Some(
+            RealtimeTelemetry::spawn(
+                addr,
+                format!("dcs-grpc-lso v{}", env!("CARGO_PKG_VERSION")),
+                shutdown_handle.clone(),
+            )
+            .await?,
+        ),
+        None => None,
+    };
+
+    #[cfg(not(feature = "discord"))]
+    if opts.discord_bot_token.is_some() {
+        tracing::warn!("ignoring the Discord bot token, as the LSO is built without Discord");
+    }
+    #[cfg(feature = "discord")]
+    if let Some(token) = opts.discord_bot_token.clone() {
+        let users = users.clone();
+        let shutdown_handle = shutdown_handle.clone();
+        tokio::spawn(async move {
+            if let Err(err) = crate::notify::discord_bot::run(&token, users, shutdown_handle).await
+            {
+                tracing::error!(%err, "Discord bot failed");
+            }
         });
+    }
+
+    let shared = Shared {
+        notifiers,
+        config,
+        event_log,
+        realtime,
+        admin: admin.clone(),
+        link_users: (cfg!(feature = "discord") && opts.discord_bot_token.is_some())
+            .then_some(users),
+    };
+
+    // When monitoring multiple servers, always name them to be able to tell their recordings apart.
+    let name_servers = opts.servers.len() > 1;
+    let server_names = opts
+        .servers
+        .iter()
+        .map(|server| {
+            server
+                .name
+                .clone()
+                .or_else(|| opts.server_name.clone())
+                .or_else(|| name_servers.then(|| server.to_string()))
+        })
+        .collect::<Vec<_>>();
+    let supervisors = server_names
+        .iter()
+        .map(|name| Supervisor::new(opts.max_recordings, admin.for_server(name.as_deref())))
+        .collect::<Vec<_>>();
+
+    let status = Status::default();
+    let statuses = server_names
+        .iter()
+        .zip(&supervisors)
+        .map(|(name, supervisor)| status.server(name.as_deref(), supervisor.clone()))
+        .collect::<Vec<_>>();
+    if let Some(addr) = opts.status_addr {
+        status.spawn(addr, opts.control, shutdown_handle.clone())?;
+    }
+
+    select(
+        Box::pin(join_all(
+            opts.servers
+                .iter()
+                .zip(&supervisors)
+                .zip(statuses)
+                .zip(server_names)
+                .map(|(((server, supervisor), status), server_name)| {
+                    let span = tracing::info_span!("server", name = server_name.as_deref());
+                    monitor(
+                        &opts,
+                        server,
+                        server_name,
+                        shared.clone(),
+                        supervisor.clone(),
+                        status,
+                        shutdown_handle.clone(),
+                    )
+                    .instrument(span)
+                }),
+        )),
+        shutdown_handle.signal(),
+    )
+    .await;
+
+    // Give active recordings the chance to complete the pass and save it.
+    tracing::info!("Waiting for active recordings to finish");
+    let drain_timeout = Duration::from_secs(opts.drain_timeout);
+    if tokio::time::timeout(
+        // leave some time to save the recording after the drain timeout
+        drain_timeout + Duration::from_secs(10),
+        join_all(supervisors.iter().map(|s| s.recordings_done())),
+    )
+    .await
+    .is_err()
+    {
+        tracing::warn!("Aborting recordings that didn't finish in time");
+    }
+
+    Ok(())
+}
+
+/// The state shared by the connections to all servers.
+#[derive(Clone)]
+struct Shared {
+    notifiers: Notifiers,
+    config: Arc<Config>,
+    event_log: EventLog,
+    realtime: Option<RealtimeTelemetry>,
+    admin: AdminAlerts,
+    /// The Discord users pilots link themselves to via the chat (if the Discord bot is enabled).
+    link_users: Option<DiscordUsers>,
+}
+
+async fn monitor(
+    opts: &Opts,
+    server: &Server,
+    server_name: Option<String>,
+    shared: Shared,
+    supervisor: Supervisor,
+    status: ServerStatus,
+    shutdown_handle: ShutdownHandle,
+) {
+    tracing::info!(uri = %server.uri, "Connecting to gRPC server");
+    let admin = shared.admin.for_server(server_name.as_deref());
 
     let backoff = ExponentialBackoff {
         // never wait longer than 30s for a retry
@@ -68,270 +326,296 @@ pub async fn execute(
         ..Default::default()
     };
 
-    select(
-        Box::pin(backoff::future::retry_notify(
-            backoff,
-            // on each try, run the program and consider every error as transient (ie. worth
-            // retrying)
-            || async {
-                run(&opts, users.clone(), shutdown_handle.clone())
-                    .await
-                    .map_err(backoff::Error::transient)
-            },
-            // error hook:
-            |err, backoff: Duration| {
-                tracing::debug!(
-                    %err,
-                    backoff = %format!("{:.2}s", backoff.as_secs_f64()),
-                    "retrying after error"
+    let result = backoff::future::retry_notify(
+        backoff,
+        // on each try, run the program and consider every error as transient (ie. worth
+        // retrying), except for an incompatible DCS-gRPC version, which a retry won't fix
+        || async {
+            run(
+                opts,
+                server,
+                server_name.clone(),
+                shared.clone(),
+                supervisor.clone(),
+                status.clone(),
+                shutdown_handle.clone(),
+            )
+            .await
+            .map_err(|err| match err {
+                crate::error::Error::IncompatibleVersion(_) => backoff::Error::permanent(err),
+                err => backoff::Error::transient(err),
+            })
+        },
+        // error hook:
+        |err: crate::error::Error, backoff: Duration| {
+            status.disconnected(crate::error::report(&err));
+            if !matches!(err, crate::error::Error::Reconnect) {
+                admin.connection_lost(crate::error::report(&err));
+                shared.event_log.log(
+                    server_name.as_deref(),
+                    LifecycleEvent::Error {
+                        message: err.to_string(),
+                    },
                 );
-            },
-        )),
-        shutdown_handle.signal(),
+            }
+            tracing::debug!(
+                %err,
+                backoff = %format!("{:.2}s", backoff.as_secs_f64()),
+                "retrying after error"
+            );
+        },
     )
     .await;
 
-    Ok(())
+    if let Err(err) = result {
+        let message = crate::error::report(&err);
+        tracing::error!(uri = %server.uri, "{}, giving up on this server", message);
+        status.disconnected(message.clone());
+        admin.alert(format!("Not recording passes anymore: {}", message));
+        shared.event_log.log(
+            server_name.as_deref(),
+            LifecycleEvent::Error {
+                message: err.to_string(),
+            },
+        );
+    }
 }
 
 async fn run(
     opts: &Opts,
-    users: Arc<HashMap<String, u64>>,
+    server: &Server,
+    server_name: Option<String>,
+    shared: Shared,
+    supervisor: Supervisor,
+    status: ServerStatus,
     shutdown_handle: ShutdownHandle,
 ) -> Result<(), crate::error::Error> {
-    let out_dir = opts.out_dir.clone();
-    let channel = Endpoint::from(opts.uri.clone())
+    let channel = Endpoint::from(server.uri.clone())
         .keep_alive_while_idle(true)
         .connect()
         .await?;
-    tracing::info!("Connected");
-    let mut coalition_svc = CoalitionServiceClient::new(channel.clone());
-    let group_svc = GroupServiceClient::new(channel.clone());
-    let mut unit_svc = UnitServiceClient::new(channel.clone());
-    let mut mission_svc = MissionServiceClient::new(channel.clone());
-
-    // initial full-sync of all current units inside of the mission
-    let groups = coalition_svc
-        .get_groups(coalition::v0::GetGroupsRequest {
-            coalition: Coalition::All.into(),
-            category: 0,
-        })
-        .map_ok(|res| res.into_inner().groups)
-        .await?;
+    let version = check_compatibility(channel.clone()).await?;
+    tracing::info!(version, "Connected");
+    shared.admin.for_server(server_name.as_deref()).connected();
+    status.connected();
 
-    let group_units = futures_util::future::try_join_all(
-        groups
-            .into_iter()
-            .filter(|group| {
-                if let Ok(category) = GroupCategory::try_from(group.category) {
-                    matches!(category, GroupCategory::Airplane | GroupCategory::Ship)
-                } else {
-                    false
-                }
-            })
-            .map(|group| {
-                let mut group_svc = group_svc.clone();
-                async move {
-                    group_svc
-                        .get_units(group::v0::GetUnitsRequest {
-                            group_name: group.name,
-                            active: Some(true),
-                        })
-                        .map_ok(|res| res.into_inner().units)
-                        .await
-                }
-            }),
-    )
-    .await?;
-
-    let mut planes: HashMap<String, (u32, String, &'static AirplaneInfo)> = HashMap::new();
-    let mut carriers: HashMap<String, (u32, &'static CarrierInfo)> = HashMap::new();
-
-    for units in group_units {
-        for unit in units {
-            match check_candidate(&mut unit_svc, &unit, opts.include_ki).await? {
-                Some(Candidate::Plane(plane_info)) => {
-                    planes.insert(
-                        unit.name,
-                        (
-                            unit.id,
-                            unit.player_name.unwrap_or_else(|| String::from("KI")),
-                            plane_info,
-                        ),
-                    );
-                }
-                Some(Candidate::Carrier(carrier_info)) => {
-                    carriers.insert(unit.name, (unit.id, carrier_info));
+    // servers named on the command line keep that name
+    let server_name =
+        if opts.server_name_from_dcs && server.name.is_none() && opts.server_name.is_none() {
+            match HookClient::new(channel.clone()).get_server_name().await {
+                Ok(Some(dcs_name)) => Some(dcs_name),
+                Ok(None) => server_name,
+                Err(err) => {
+                    tracing::warn!(%err, "failed to read the server name from DCS");
+                    server_name
                 }
-                None => {}
             }
+        } else {
+            server_name
+        };
+
+    // continue the recovery windows of the passes recorded before (e.g. prior to a restart), but
+    // don't fail to (re)connect just because the results can't be read (e.g. while the file is
+    // locked by another program)
+    let results_path = opts.out_dir.join(store::RESULTS_FILENAME);
+    let loaded = tokio::task::spawn_blocking(move || {
+        if results_path.exists() {
+            store::load(&results_path).map_err(|err| crate::error::report(&err))
+        } else {
+            Ok(Vec::new())
+        }
+    })
+    .await;
+    let records = match loaded {
+        Ok(Ok(records)) => records,
+        Ok(Err(err)) => {
+            tracing::warn!(%err, "failed to load results for the recovery windows");
+            Vec::new()
+        }
+        Err(err) => {
+            tracing::warn!(%err, "failed to load results for the recovery windows");
+            Vec::new()
+        }
+    };
+    let recovery_windows = RecoveryWindows::new(
+        time::Duration::seconds_f64(shared.config.recovery.max_gap_s),
+        server_name.as_deref(),
+        &records,
+    );
+
+    let link_users = shared.link_users;
+    let params = DetectionParams {
+        out_dir: opts.out_dir.clone(),
+        server_name,
+        session_report: opts.session_report
+            || (!shared.notifiers.is_empty() && opts.discord_posts.session()),
+        notifiers: shared.notifiers,
+        discord_posts: opts.discord_posts,
+        acmi_retention: opts.keep_acmi,
+        ch: channel,
+        include_ki: opts.include_ki,
+        config: shared.config,
+        recovery_windows,
+        event_log: shared.event_log,
+        realtime: shared.realtime,
+        status: status.clone(),
+        supervisor: supervisor.clone(),
+        shutdown: shutdown_handle,
+        drain_timeout: Duration::from_secs(opts.drain_timeout),
+    };
+    let detection = supervisor.supervise("detect_recovery_attempts", || {
+        crate::tasks::detect_recovery_attempt::detect_recovery_attempts(params.clone())
+    });
+    let reports = async {
+        if params.session_report {
+            supervisor
+                .supervise("report_sessions", || {
+                    crate::tasks::report_session::report_sessions(params.clone())
+                })
+                .await
+        } else {
+            Ok(())
+        }
+    };
+    let links = async {
+        if let Some(users) = &link_users {
+            supervisor
+                .supervise("link_players", || {
+                    crate::tasks::link_players::link_players(params.clone(), users.clone())
+                })
+                .await
+        } else {
+            Ok(())
+        }
+    };
+    let tasks = async {
+        futures_util::future::try_join3(detection, reports, links).await?;
+        Ok(())
+    };
+
+    // dropping the tasks aborts them (active recordings are continued)
+    tokio::select! {
+        result = tasks => result,
+        _ = status.reconnect_requested() => {
+            tracing::info!("Reconnecting as requested");
+            Err(crate::error::Error::Reconnect)
         }
     }
+}
 
-    let (tx, mut rx) = mpsc::channel(1);
-
-    let discord_webhook = opts.discord_webhook.clone();
-    let tx2 = tx.clone();
-    let spawn_detect_recovery_attempt =
-        move |carrier_id: u32,
-              carrier_name: String,
-              carrier_info: &'static CarrierInfo,
-              plane_id: u32,
-              plane_name: String,
-              plane_info: &'static AirplaneInfo,
-              pilot_name: String| {
-            let out_dir = out_dir.clone();
-            let discord_webhook = discord_webhook.clone();
-            let users = users.clone();
-            let channel = channel.clone();
-            let tx = tx2.clone();
-            let shutdown_handle = shutdown_handle.clone();
-            tokio::spawn(async move {
-                if let Err(err) =
-                    crate::tasks::detect_recovery_attempt::detect_recovery_attempt(TaskParams {
-                        out_dir: &out_dir,
-                        discord_webhook,
-                        users,
-                        ch: channel,
-                        carrier_id,
-                        carrier_name: &carrier_name,
-                        plane_id,
-                        plane_name: &plane_name,
-                        pilot_name: &pilot_name,
-                        carrier_info,
-                        plane_info,
-                        shutdown: shutdown_handle,
-                    })
-                    .await
-                {
-                    tx.send(err).await.ok();
-                }
-            });
-        };
+/// Check that the server runs a DCS-gRPC version that supports everything the LSO needs, so that
+/// an outdated (or broken) installation is reported right away instead of recordings silently
+/// never happening. Returns the version of DCS-gRPC.
+async fn check_compatibility(channel: Channel) -> Result<String, crate::error::Error> {
+    let (major, minor, patch) = MIN_DCS_GRPC_VERSION;
+    let required = format!("{}.{}.{}", major, minor, patch);
 
-    for (carrier_name, (carrier_id, carrier_info)) in &carriers {
-        for (plane_name, (plane_id, pilot_name, plane_info)) in &planes {
-            spawn_detect_recovery_attempt(
-                *carrier_id,
-                carrier_name.clone(),
-                carrier_info,
-                *plane_id,
-                plane_name.clone(),
-                plane_info,
-                pilot_name.clone(),
-            );
+    let version = check_version(MetadataClient::new(channel.clone()).get_version().await)?;
+
+    // Probe the RPCs the LSO relies on (a missing unit is fine, an unimplemented RPC is not).
+    let probes = [
+        (
+            "UnitService.GetTransform",
+            UnitClient::new(channel.clone())
+                .get_transform("")
+                .await
+                .err(),
+        ),
+        (
+            "HookService.GetMissionName",
+            HookClient::new(channel).get_mission_name().await.err(),
+        ),
+    ];
+    for (rpc, err) in probes {
+        if err.is_some_and(|status| status.code() == Code::Unimplemented) {
+            return Err(crate::error::Error::IncompatibleVersion(format!(
+                "the server ({}) doesn't support {}, update DCS-gRPC to {} or newer",
+                version, rpc, required
+            )));
         }
     }
 
-    // listen for birth events to track carriers and planes spawned at a later point in time
-    let mut events = mission_svc
-        .stream_events(mission::v0::StreamEventsRequest {})
-        .await?
-        .into_inner();
-    let tx = tx.clone();
-    let include_ki = opts.include_ki;
-    tokio::spawn(async move {
-        while let Some(event) = events.next().await {
-            let event = match event {
-                Ok(stubs::mission::v0::StreamEventsResponse {
-                    event: Some(event), ..
-                }) => event,
-                Ok(_) => continue,
-                Err(err) => {
-                    tx.send(err.into()).await.ok();
-                    return;
-                }
-            };
-
-            if let Event::Birth(mission::v0::stream_events_response::BirthEvent {
-                initiator:
-                    Some(common::v0::Initiator {
-                        initiator: Some(common::v0::initiator::Initiator::Unit(unit)),
-                    }),
-                ..
-            }) = event
-            {
-                match check_candidate(&mut unit_svc, &unit, include_ki).await {
-                    Ok(Some(Candidate::Plane(plane_info))) => {
-                        for (carrier_name, (carrier_id, carrier_info)) in &carriers {
-                            spawn_detect_recovery_attempt(
-                                *carrier_id,
-                                carrier_name.clone(),
-                                carrier_info,
-                                unit.id,
-                                unit.name.clone(),
-                                plane_info,
-                                unit.player_name
-                                    .clone()
-                                    .unwrap_or_else(|| String::from("KI")),
-                            );
-                        }
-                    }
-                    Ok(Some(Candidate::Carrier(carrier_info))) => {
-                        for (plane_name, (plane_id, pilot_name, plane_info)) in &planes {
-                            spawn_detect_recovery_attempt(
-                                unit.id,
-                                unit.name.clone(),
-                                carrier_info,
-                                *plane_id,
-                                plane_name.clone(),
-                                plane_info,
-                                pilot_name.clone(),
-                            );
-                        }
-                    }
-                    Ok(None) => {}
-                    Err(err) => {
-                        tracing::error!(
-                            unit_name = %unit.name,
-                            %err,
-                            "ignoring unit due to an error while checking its eligibility",
-                        );
-                    }
-                }
-            }
+    Ok(version)
+}
+
+/// Post the leaderboard of the passes since the previous post to the chat services every `every`
+/// until shutdown.
+async fn post_leaderboards(
+    results_path: PathBuf,
+    notifiers: Notifiers,
+    every: time::Duration,
+    shutdown_handle: ShutdownHandle,
+) {
+    let Ok(interval) = Duration::try_from(every) else {
+        tracing::warn!("invalid leaderboard interval");
+        return;
+    };
+    loop {
+        let since = OffsetDateTime::now_utc();
+        let sleep = Box::pin(tokio::time::sleep(interval));
+        if let futures_util::future::Either::Right(_) =
+            select(sleep, Box::pin(shutdown_handle.signal())).await
+        {
+            return;
         }
-    });
 
-    match rx.recv().await {
-        Some(err) => Err(err),
-        None => Ok(()),
+        let loaded = {
+            let results_path = results_path.clone();
+            tokio::task::spawn_blocking(move || {
+                store::load(&results_path).map_err(|err| crate::error::report(&err))
+            })
+            .await
+        };
+        let records = match loaded {
+            Ok(Ok(records)) => records
+                .into_iter()
+                .filter(|r| r.time().is_some_and(|time| time >= since))
+                .collect::<Vec<_>>(),
+            Ok(Err(err)) => {
+                tracing::warn!(%err, "failed to load results for the leaderboard");
+                continue;
+            }
+            Err(err) => {
+                tracing::warn!(%err, "failed to load results for the leaderboard");
+                continue;
+            }
+        };
+        let mut standings = leaderboard::standings(&records, 1);
+        standings.truncate(10);
+        let title = format!("Leaderboard since {}", since.date());
+        notifiers
+            .notify(&leaderboard::notification(&title, &standings))
+            .await;
     }
 }
 
-#[derive(Debug)]
-enum Candidate {
-    Carrier(&'static CarrierInfo),
-    Plane(&'static AirplaneInfo),
+/// A DCS-gRPC server to connect to, optionally with a name (`NAME=URI`).
+#[derive(Clone)]
+pub struct Server {
+    pub name: Option<String>,
+    pub uri: Uri,
 }
 
-async fn check_candidate(
-    svc: &mut UnitServiceClient<Channel>,
-    unit: &common::v0::Unit,
-    include_ki: bool,
-) -> Result<Option<Candidate>, Status> {
-    match GroupCategory::try_from(unit.group.as_ref().map(|g| g.category).unwrap_or(-1)) {
-        Ok(GroupCategory::Airplane) if unit.player_name.is_some() || include_ki => {
-            return Ok(AirplaneInfo::by_type(&unit.r#type).map(Candidate::Plane))
-        }
-        Ok(GroupCategory::Ship) => {
-            let attrs = svc
-                .get_descriptor(unit::v0::GetDescriptorRequest {
-                    name: unit.name.clone(),
-                })
-                .await?
-                .into_inner()
-                .attributes;
+impl FromStr for Server {
+    type Err = String;
 
-            if attrs
-                .iter()
-                .any(|a| a.as_str() == "AircraftCarrier With Arresting Gear")
-            {
-                return Ok(CarrierInfo::by_type(&unit.r#type).map(Candidate::Carrier));
-            }
-        }
-        _ => {}
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, uri) = match s.split_once('=') {
+            Some((name, uri)) if !name.contains("://") => (Some(name.to_string()), uri),
+            _ => (None, s),
+        };
+        Ok(Server {
+            name,
+            uri: uri.parse().map_err(|err| format!("invalid URI: {}", err))?,
+        })
     }
+}
 
-    Ok(None)
+impl fmt::Display for Server {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.uri.authority().map_or("", |a| a.as_str())),
+        }
+    }
 }
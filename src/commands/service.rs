@@ -0,0 +1,356 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::ptr::{null, null_mut};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use std::{fmt, io};
+
+use clap::Parser;
+use tokio::runtime::Handle;
+use tokio::sync::Notify;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+use windows_service::service::{
+    ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+    ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+};
+use windows_service::service_control_handler::{
+    self, ServiceControlHandlerResult, ServiceStatusHandle,
+};
+use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+use windows_service::{define_windows_service, service_dispatcher};
+use windows_sys::Win32::Foundation::HANDLE;
+use windows_sys::Win32::System::EventLog::{
+    DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE,
+    EVENTLOG_WARNING_TYPE,
+};
+
+use crate::config::Config;
+use crate::error::Error;
+use crate::utils::shutdown::ShutdownHandle;
+
+const DEFAULT_NAME: &str = "LSO";
+const DESCRIPTION: &str = "Grades carrier recoveries on a DCS server (via DCS-gRPC).";
+
+/// How long the service control manager should wait for the service to stop (slightly longer
+/// than the default `--drain-timeout`, as active recordings are completed first).
+const STOP_WAIT_HINT: Duration = Duration::from_secs(90);
+
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Parser)]
+enum Command {
+    /// Register the LSO as a Windows service that is started automatically with Windows.
+    Install(InstallOpts),
+
+    /// Stop and remove the Windows service.
+    Uninstall {
+        /// The name of the service.
+        #[clap(long, default_value = DEFAULT_NAME)]
+        name: String,
+    },
+
+    /// Run as the Windows service (started by the service control manager, not meant to be run
+    /// manually).
+    Run(Box<RunOpts>),
+}
+
+#[derive(clap::Parser)]
+struct InstallOpts {
+    /// The name of the service (install multiple services with different names to e.g. use
+    /// different configs per DCS server).
+    #[clap(long, default_value = DEFAULT_NAME)]
+    name: String,
+
+    /// The arguments the service passes to `lso run` (including global options like `--config`
+    /// or `--log-file`), given after a `--`, e.g. `-- --uri http://127.0.0.1:50051`.
+    #[clap(last = true)]
+    args: Vec<String>,
+}
+
+#[derive(clap::Parser)]
+struct RunOpts {
+    /// The name of the service.
+    #[clap(long, default_value = DEFAULT_NAME)]
+    name: String,
+
+    /// The directory the service was installed from (services are started in the system
+    /// directory, so relative paths would resolve to there otherwise).
+    #[clap(long)]
+    working_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    run: super::run::Opts,
+}
+
+impl Opts {
+    /// The directory to change into before any (relative) paths are opened.
+    pub fn working_dir(&self) -> Option<&Path> {
+        match &self.command {
+            Command::Run(opts) => opts.working_dir.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// A layer that writes warnings and errors to the Windows event log while running as a
+    /// service.
+    pub fn event_log_layer(&self) -> Option<EventLogLayer> {
+        match &self.command {
+            Command::Run(opts) => EventLogLayer::new(&opts.name),
+            _ => None,
+        }
+    }
+}
+
+pub async fn execute(
+    opts: Opts,
+    config: Config,
+    shutdown_handle: ShutdownHandle,
+    stop: Arc<Notify>,
+) -> Result<(), Error> {
+    match opts.command {
+        Command::Install(opts) => install(opts),
+        Command::Uninstall { name } => uninstall(&name),
+        Command::Run(opts) => {
+            let name = opts.name.clone();
+            *SERVICE.lock().unwrap() = Some(Service {
+                name: opts.name,
+                opts: opts.run,
+                config,
+                shutdown_handle,
+                stop,
+                runtime: Handle::current(),
+            });
+
+            // blocks until the service is stopped
+            tokio::task::spawn_blocking(move || service_dispatcher::start(name, ffi_service_main))
+                .await
+                .map_err(|err| Error::Service(io::Error::other(err)))?
+                .map_err(service_error)
+        }
+    }
+}
+
+fn install(opts: InstallOpts) -> Result<(), Error> {
+    // fail now instead of when the service is started
+    let args = ["lso", "service", "run"].map(String::from).into_iter();
+    if let Err(err) = crate::Opts::try_parse_from(args.chain(opts.args.iter().cloned())) {
+        err.exit();
+    }
+
+    let mut launch_arguments = ["service", "run", "--name", &opts.name]
+        .map(OsString::from)
+        .to_vec();
+    launch_arguments.push("--working-dir".into());
+    launch_arguments.push(std::env::current_dir()?.into_os_string());
+    launch_arguments.extend(opts.args.into_iter().map(OsString::from));
+
+    let manager = ServiceManager::local_computer(
+        None::<&str>,
+        ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE,
+    )
+    .map_err(service_error)?;
+    // the arguments are quoted by `windows-service`
+    let info = ServiceInfo {
+        name: OsString::from(&opts.name),
+        display_name: OsString::from(&opts.name),
+        service_type: ServiceType::OWN_PROCESS,
+        start_type: ServiceStartType::AutoStart,
+        error_control: ServiceErrorControl::Normal,
+        executable_path: std::env::current_exe()?,
+        launch_arguments,
+        dependencies: Vec::new(),
+        account_name: None,
+        account_password: None,
+    };
+    let service = manager
+        .create_service(&info, ServiceAccess::CHANGE_CONFIG)
+        .map_err(service_error)?;
+    if let Err(err) = service.set_description(DESCRIPTION) {
+        tracing::warn!(%err, "failed to set the service description");
+    }
+
+    println!(
+        "Installed service `{}` (start it with `sc start {}`)",
+        opts.name, opts.name
+    );
+    Ok(())
+}
+
+fn uninstall(name: &str) -> Result<(), Error> {
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)
+        .map_err(service_error)?;
+    let service = manager
+        .open_service(
+            name,
+            ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE,
+        )
+        .map_err(service_error)?;
+
+    // fails if the service isn't running, which is fine
+    if service.query_status().map_err(service_error)?.current_state != ServiceState::Stopped {
+        let _ = service.stop();
+    }
+    service.delete().map_err(service_error)?;
+
+    println!("Uninstalled service `{}`", name);
+    Ok(())
+}
+
+/// Everything [service_main] needs to run the LSO, handed over from [execute] (the service main
+/// function is called by Windows on a separate thread and without any context).
+struct Service {
+    name: String,
+    opts: super::run::Opts,
+    config: Config,
+    shutdown_handle: ShutdownHandle,
+    stop: Arc<Notify>,
+    runtime: Handle,
+}
+
+static SERVICE: Mutex<Option<Service>> = Mutex::new(None);
+
+define_windows_service!(ffi_service_main, service_main);
+
+fn service_main(_arguments: Vec<OsString>) {
+    let Some(service) = SERVICE.lock().unwrap().take() else {
+        return;
+    };
+    if let Err(err) = run_service(service) {
+        tracing::error!(%err, "failed to run the service");
+    }
+}
+
+fn run_service(service: Service) -> Result<(), windows_service::Error> {
+    // the handler is registered before its status handle is known
+    let status_handle = Arc::new(OnceLock::<ServiceStatusHandle>::new());
+    let handler = {
+        let status_handle = status_handle.clone();
+        let stop = service.stop.clone();
+        move |control| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                if let Some(handle) = status_handle.get() {
+                    let _ = handle.set_service_status(status(
+                        ServiceState::StopPending,
+                        STOP_WAIT_HINT,
+                        0,
+                    ));
+                }
+                // the same as CTRL+C when run from the console
+                stop.notify_one();
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        }
+    };
+    let handle = service_control_handler::register(&service.name, handler)?;
+    let _ = status_handle.set(handle);
+    handle.set_service_status(status(ServiceState::Running, Duration::ZERO, 0))?;
+
+    let result = service.runtime.block_on(super::run::execute(
+        service.opts,
+        service.config,
+        service.shutdown_handle,
+    ));
+    let exit_code = match result {
+        Ok(()) => 0,
+        Err(err) => {
+            tracing::error!("{}", crate::error::report(&err));
+            1
+        }
+    };
+    handle.set_service_status(status(ServiceState::Stopped, Duration::ZERO, exit_code))
+}
+
+fn status(state: ServiceState, wait_hint: Duration, exit_code: u32) -> ServiceStatus {
+    ServiceStatus {
+        service_type: ServiceType::OWN_PROCESS,
+        current_state: state,
+        controls_accepted: if state == ServiceState::Running {
+            ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN
+        } else {
+            ServiceControlAccept::empty()
+        },
+        exit_code: ServiceExitCode::Win32(exit_code),
+        checkpoint: 0,
+        wait_hint,
+        process_id: None,
+    }
+}
+
+fn service_error(err: windows_service::Error) -> Error {
+    Error::Service(io::Error::other(err))
+}
+
+/// Writes warnings and errors to the Application log of the Windows event log (the service
+/// control manager already records when the service is started and stopped).
+pub struct EventLogLayer {
+    handle: HANDLE,
+}
+
+impl EventLogLayer {
+    fn new(source: &str) -> Option<Self> {
+        let source = wide(source);
+        let handle = unsafe { RegisterEventSourceW(null(), source.as_ptr()) };
+        (handle != 0).then_some(EventLogLayer { handle })
+    }
+}
+
+impl<S: Subscriber> Layer<S> for EventLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let kind = match *event.metadata().level() {
+            Level::ERROR => EVENTLOG_ERROR_TYPE,
+            Level::WARN => EVENTLOG_WARNING_TYPE,
+            _ => return,
+        };
+        let mut message = Message::default();
+        event.record(&mut message);
+        let message = wide(&message.0);
+        let strings = [message.as_ptr()];
+        unsafe {
+            ReportEventW(
+                self.handle,
+                kind,
+                0,
+                0,
+                null_mut(),
+                1,
+                0,
+                strings.as_ptr(),
+                null(),
+            );
+        }
+    }
+}
+
+impl Drop for EventLogLayer {
+    fn drop(&mut self) {
+        unsafe { DeregisterEventSource(self.handle) };
+    }
+}
+
+/// The message of an event followed by its fields (as `key=value`).
+#[derive(Default)]
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{:?}", value));
+        } else {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+}
+
+/// A null-terminated UTF-16 string for the Windows API.
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
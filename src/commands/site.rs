@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use crate::store;
+
+#[derive(clap::Parser)]
+pub struct Opts {
+    /// The output directory of `lso run` (containing the results file and the charts, debriefs and
+    /// recordings). The index is saved to it as `index.html`.
+    out_dir: PathBuf,
+
+    /// Only include passes recorded on this server.
+    #[clap(long)]
+    server: Option<String>,
+
+    /// The title of the index.
+    #[clap(long, default_value = "Carrier recoveries")]
+    title: String,
+}
+
+pub fn execute(opts: Opts) -> Result<(), crate::error::Error> {
+    let records = store::load(&opts.out_dir.join(store::RESULTS_FILENAME))?
+        .into_iter()
+        .filter(|r| {
+            opts.server
+                .as_deref()
+                .is_none_or(|s| r.server.as_deref() == Some(s))
+        })
+        .collect::<Vec<_>>();
+
+    let path = crate::site::write_index(&opts.out_dir, &opts.title, &records)?;
+    println!("Saved {} passes to {}", records.len(), path.display());
+
+    Ok(())
+}
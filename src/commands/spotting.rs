@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::data::CarrierInfo;
+use crate::draw::{draw_spotting_chart, Layout, Theme};
+use crate::store::{self, PassRecord};
+use crate::track::Grading;
+use time::OffsetDateTime;
+
+use super::parse_time;
+
+#[derive(clap::Parser)]
+pub struct Opts {
+    /// The results file written by `lso run` (located in its output directory).
+    #[clap(long, default_value = store::RESULTS_FILENAME)]
+    results: PathBuf,
+
+    /// Only include pilots whose name contains this text (e.g. a squadron tag). Can be specified
+    /// multiple times.
+    #[clap(long)]
+    pilot: Vec<String>,
+
+    /// Only include passes recorded on this server.
+    #[clap(long)]
+    server: Option<String>,
+
+    /// Only include passes recorded since this time (RFC 3339, e.g. `2024-05-01T18:00:00Z`, or
+    /// relative like `30d`).
+    #[clap(long, value_parser = parse_time)]
+    since: Option<OffsetDateTime>,
+
+    /// The path the chart is saved to.
+    #[clap(short = 'o', long, default_value = "spotting.png")]
+    out: PathBuf,
+}
+
+pub fn execute(opts: Opts, config: Config) -> Result<(), crate::error::Error> {
+    let records = store::load(&opts.results)?
+        .into_iter()
+        .filter(|r| opts.pilot.is_empty() || opts.pilot.iter().any(|p| r.pilot.contains(p)))
+        .filter(|r| {
+            opts.server
+                .as_deref()
+                .is_none_or(|s| r.server.as_deref() == Some(s))
+        })
+        .filter(|r| {
+            opts.since
+                .is_none_or(|since| r.time().is_some_and(|time| time >= since))
+        })
+        .collect::<Vec<_>>();
+
+    let touchdowns = records
+        .iter()
+        .filter_map(|r| {
+            let trapped = matches!(r.grading, Grading::Recovered { .. });
+            r.touchdown.map(|touchdown| (touchdown, trapped))
+        })
+        .collect::<Vec<_>>();
+
+    let title = format!(
+        "{} touchdowns{}",
+        touchdowns.len(),
+        if opts.pilot.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", opts.pilot.join(", "))
+        }
+    );
+    draw_spotting_chart(
+        &opts.out,
+        &title,
+        carrier_info(&records),
+        &touchdowns,
+        &Theme::from_config(&config.chart),
+        &Layout::from_config(&config.chart),
+    )?;
+
+    println!(
+        "Saved {} touchdowns to {}",
+        touchdowns.len(),
+        opts.out.display()
+    );
+
+    Ok(())
+}
+
+/// The landing area to draw, which is the one of the carrier most of the passes were flown to.
+fn carrier_info(records: &[PassRecord]) -> &'static CarrierInfo {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for record in records {
+        *counts.entry(record.carrier_type.as_str()).or_default() += 1;
+    }
+
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts
+        .into_iter()
+        .find_map(|(carrier_type, _)| CarrierInfo::by_type(carrier_type))
+        .or_else(|| CarrierInfo::by_type("CVN_71"))
+        .unwrap()
+}
@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+use time::OffsetDateTime;
+
+use crate::config::Config;
+use crate::cq::CqStatus;
+use crate::draw::{draw_pilot_stats, Layout, Theme};
+use crate::stats::PilotStats;
+use crate::store;
+
+use super::parse_time;
+
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Parser)]
+enum Command {
+    /// The GPA and boarding rate over time, the wire distribution and the most common deviations
+    /// of a single pilot.
+    Pilot(PilotOpts),
+}
+
+#[derive(clap::Parser)]
+pub struct PilotOpts {
+    /// The name of the pilot.
+    name: String,
+
+    /// The results file written by `lso run` (located in its output directory).
+    #[clap(long, default_value = store::RESULTS_FILENAME)]
+    results: PathBuf,
+
+    /// Only include passes recorded since this time (RFC 3339, e.g. `2024-05-01T18:00:00Z`, or
+    /// relative like `30d`).
+    #[clap(long, value_parser = parse_time)]
+    since: Option<OffsetDateTime>,
+
+    /// The path the chart is saved to.
+    #[clap(short = 'o', long, default_value = "pilot-stats.png")]
+    out: PathBuf,
+
+    /// Save the statistics as JSON to this path (instead of printing them).
+    #[clap(long)]
+    json: Option<PathBuf>,
+}
+
+pub fn execute(opts: Opts, config: Config) -> Result<(), crate::error::Error> {
+    match opts.command {
+        Command::Pilot(opts) => pilot(opts, config),
+    }
+}
+
+fn pilot(opts: PilotOpts, config: Config) -> Result<(), crate::error::Error> {
+    let records = store::load(&opts.results)?
+        .into_iter()
+        .filter(|r| {
+            opts.since
+                .is_none_or(|since| r.time().is_some_and(|time| time >= since))
+        })
+        .collect::<Vec<_>>();
+
+    let Some(mut stats) = PilotStats::new(&opts.name, &records) else {
+        println!("No passes of {} found", opts.name);
+        return Ok(());
+    };
+    stats.cq = config
+        .cq
+        .map(|cq| CqStatus::new(cq, &opts.name, &records, OffsetDateTime::now_utc()));
+
+    draw_pilot_stats(
+        &opts.out,
+        &stats,
+        &Theme::from_config(&config.chart),
+        &Layout::from_config(&config.chart),
+    )?;
+
+    match &opts.json {
+        Some(path) => {
+            std::fs::write(path, serde_json::to_vec_pretty(&stats)?)?;
+            println!(
+                "Saved statistics to {} and {}",
+                path.display(),
+                opts.out.display()
+            );
+        }
+        None => println!("{}", serde_json::to_string_pretty(&stats)?),
+    }
+
+    Ok(())
+}
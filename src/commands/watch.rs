@@ -0,0 +1,168 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use super::file::{Filter, Outputs};
+use crate::config::Config;
+use crate::store;
+use crate::utils::shutdown::ShutdownHandle;
+
+#[derive(clap::Parser)]
+pub struct Opts {
+    /// The directory to watch for new ACMI recordings.
+    dir: PathBuf,
+
+    /// Also watch the subdirectories.
+    #[clap(short, long)]
+    recursive: bool,
+
+    /// The directory the charts and the results file are saved to.
+    #[clap(short = 'o', long, default_value = ".")]
+    out_dir: PathBuf,
+
+    /// Also process the recordings that already exist when starting.
+    #[clap(long)]
+    existing: bool,
+
+    /// How often (in seconds) to look for new recordings. A recording is processed once it
+    /// didn't change for this long.
+    #[clap(long, default_value = "10")]
+    interval: u64,
+
+    /// The name of the DCS server the recordings are made on, added to the filenames, charts and
+    /// results (overrides the server name recorded by `lso run`).
+    #[clap(long)]
+    server_name: Option<String>,
+}
+
+/// The size and modification time of a recording, to tell whether it is still being written.
+type FileState = (u64, Option<SystemTime>);
+
+/// How often processing a recording is attempted before it is skipped.
+const MAX_ATTEMPTS: u32 = 3;
+
+pub async fn execute(
+    opts: Opts,
+    config: Config,
+    shutdown_handle: ShutdownHandle,
+) -> Result<(), crate::error::Error> {
+    std::fs::create_dir_all(&opts.out_dir)?;
+    let config = Arc::new(config);
+    let results_path = opts.out_dir.join(store::RESULTS_FILENAME);
+
+    let mut done = HashSet::new();
+    if !opts.existing {
+        done.extend(find(&opts).await?.into_keys());
+    }
+    let mut pending: HashMap<PathBuf, FileState> = HashMap::new();
+    // the number of failed attempts to process a recording
+    let mut failures: HashMap<PathBuf, u32> = HashMap::new();
+    tracing::info!(dir = %opts.dir.display(), "Watching for new recordings");
+
+    let mut interval = tokio::time::interval(Duration::from_secs(opts.interval.max(1)));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown_handle.signal() => return Ok(()),
+        }
+
+        let found = match find(&opts).await {
+            Ok(found) => found,
+            Err(err) => {
+                tracing::warn!(%err, "failed to look for new recordings");
+                continue;
+            }
+        };
+        for (path, state) in found {
+            if done.contains(&path) {
+                continue;
+            }
+            // only process recordings once they are completely written
+            if pending.insert(path.clone(), state) != Some(state) {
+                continue;
+            }
+            pending.remove(&path);
+
+            tracing::info!(path = %path.display(), "Processing recording");
+            let config = config.clone();
+            let outputs = Outputs {
+                dir: opts.out_dir.clone(),
+                chart: true,
+                debrief: config.output.debrief,
+                json: false,
+                server_name: opts.server_name.clone(),
+            };
+            let (input, results_path) = (path.clone(), results_path.clone());
+            let result = tokio::task::spawn_blocking(move || -> Result<(), String> {
+                let records = super::file::process_recording(
+                    &input,
+                    &config,
+                    &outputs,
+                    &Filter::default(),
+                    &[],
+                    None,
+                )
+                .map_err(|err| crate::error::report(&err))?;
+                tracing::info!(passes = records.len(), "Processed recording");
+                if !records.is_empty() {
+                    store::append(&results_path, &records)
+                        .map_err(|err| crate::error::report(&err))?;
+                }
+                Ok(())
+            })
+            .await;
+            let result = match result {
+                Ok(result) => result,
+                Err(err) => Err(err.to_string()),
+            };
+
+            match result {
+                Ok(()) => {
+                    failures.remove(&path);
+                    done.insert(path);
+                }
+                Err(err) => {
+                    let attempts = failures.entry(path.clone()).or_default();
+                    *attempts += 1;
+                    if *attempts < MAX_ATTEMPTS {
+                        // retried once it is found unchanged again
+                        tracing::warn!(
+                            path = %path.display(),
+                            %err,
+                            attempts,
+                            "failed to process recording, retrying"
+                        );
+                    } else {
+                        tracing::warn!(
+                            path = %path.display(),
+                            %err,
+                            attempts,
+                            "failed to process recording, skipping it"
+                        );
+                        failures.remove(&path);
+                        done.insert(path);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// All recordings in the watched directory with their current state (walked off the runtime).
+async fn find(opts: &Opts) -> Result<HashMap<PathBuf, FileState>, std::io::Error> {
+    let (dir, recursive) = (opts.dir.clone(), opts.recursive);
+    tokio::task::spawn_blocking(move || -> Result<_, std::io::Error> {
+        let mut recordings = Vec::new();
+        super::file::collect_recordings(&dir, recursive, &mut recordings)?;
+        Ok(recordings
+            .into_iter()
+            .filter_map(|path| {
+                let metadata = std::fs::metadata(&path).ok()?;
+                Some((path, (metadata.len(), metadata.modified().ok())))
+            })
+            .collect())
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
@@ -0,0 +1,746 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use ultraviolet::DVec3;
+
+use crate::data::{AirplaneInfo, AoaBrackets, DataRegistry};
+use crate::privacy::PilotNames;
+
+/// Settings that can be provided via a JSON config file (`--config`). Every setting is optional
+/// and falls back to its default if omitted.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub detection: DetectionEnvelope,
+    /// Which wires are rigged for certain missions and/or carriers (the first matching rule
+    /// applies). All wires are considered rigged if no rule matches.
+    pub rigged_wires: Vec<RiggedWires>,
+    /// Only record KI units of these groups (by group name) when recording KI units (`--ki`); all
+    /// of them if empty.
+    pub ki_groups: Vec<String>,
+    /// Missions and/or carriers during which touching down and flying on is a touch-and-go
+    /// instead of a bolter (the hook being up at touchdown always is).
+    pub touch_and_go: Vec<TouchAndGo>,
+    pub chart: ChartConfig,
+    pub recovery: RecoveryConfig,
+    pub output: OutputConfig,
+    /// When to remove old charts, recordings and reports from the output directory.
+    pub retention: RetentionConfig,
+    /// Chat services the passes are posted to (in addition to the `--discord-webhook`).
+    pub notifiers: Vec<NotifierConfig>,
+    /// Chat services errors of the LSO itself are posted to (in addition to the
+    /// `--admin-webhook`), see [crate::notify::AdminAlerts].
+    pub admin_notifiers: Vec<NotifierConfig>,
+    /// Publish the lifecycle events (see [crate::event_log]) to an MQTT broker.
+    pub mqtt: Option<MqttConfig>,
+    /// Smooth the AoA and the position of the datums of each pass before it is graded and drawn
+    /// (not smoothed if omitted).
+    pub smoothing: Option<Smoothing>,
+    /// Resample the datums of each pass to a fixed grid (after smoothing them), so that passes
+    /// recorded live and extracted from recordings (with a varying frame rate) are comparable
+    /// (kept as sampled if omitted).
+    pub resampling: Option<Resampling>,
+    /// Where the AoA of the plane is taken from.
+    pub aoa_source: AoaSource,
+    /// How helicopters are handled (they are never graded like planes).
+    pub helicopters: HelicopterMode,
+    /// Overrides of the built-in airplane data, by the exact DCS airplane type (e.g. `F-14B`).
+    pub airplanes: BTreeMap<String, AirplaneOverrides>,
+    /// Track the pilots' progress towards their carrier qualification (see [crate::cq]; not
+    /// tracked if omitted).
+    pub cq: Option<CqConfig>,
+    /// Anonymize the names of the pilots in all outputs.
+    pub privacy: PrivacyConfig,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, crate::error::Error> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Whether the KI units of the given group are recorded (with `--ki`).
+    pub fn records_ki_group(&self, group_name: &str) -> bool {
+        self.ki_groups.is_empty() || self.ki_groups.iter().any(|name| name == group_name)
+    }
+
+    /// Register the overrides of the built-in airplane data (see [DataRegistry]), once at startup.
+    pub fn register_data(&self) {
+        for (plane_type, overrides) in &self.airplanes {
+            if let Some(info) = AirplaneInfo::by_type(plane_type) {
+                DataRegistry::register_airplane(&[plane_type], overrides.apply(info));
+            }
+        }
+    }
+
+    /// Settings that are syntactically valid, but don't make sense (e.g. a negative distance), or
+    /// that are likely mistakes (e.g. a misspelled placeholder).
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut positive = |name: &str, value: f64| {
+            if value <= 0.0 || !value.is_finite() {
+                problems.push(format!("`{}` must be greater than 0 (is {})", name, value));
+            }
+        };
+
+        let detection = &self.detection;
+        positive("detection.max_altitude_ft", detection.max_altitude_ft);
+        positive("detection.max_distance_nm", detection.max_distance_nm);
+        positive("detection.max_turn_rate", detection.max_turn_rate);
+        positive("chart.scale", self.chart.scale);
+        positive("chart.range_nm", self.chart.range_nm);
+        if let Some(tick_interval_s) = self.chart.tick_interval_s {
+            positive("chart.tick_interval_s", tick_interval_s);
+        }
+        positive("recovery.max_gap_s", self.recovery.max_gap_s);
+        positive(
+            "recovery.target_interval_s",
+            self.recovery.target_interval_s,
+        );
+        if let Some(traffic_range_nm) = self.output.traffic_range_nm {
+            positive("output.traffic_range_nm", traffic_range_nm);
+        }
+        if let Some(max_age_days) = self.retention.max_age_days {
+            positive("retention.max_age_days", max_age_days);
+        }
+        if let Some(max_size_mb) = self.retention.max_size_mb {
+            positive("retention.max_size_mb", max_size_mb);
+        }
+        if let Some(Smoothing::Ema { time_constant_s }) = self.smoothing {
+            positive("smoothing.time_constant_s", time_constant_s);
+        }
+        match self.resampling {
+            Some(Resampling::Time { interval_s }) => positive("resampling.interval_s", interval_s),
+            Some(Resampling::Distance { interval_m }) => {
+                positive("resampling.interval_m", interval_m)
+            }
+            None => {}
+        }
+        if let Some(cq) = self.cq {
+            positive("cq.window_days", cq.window_days);
+        }
+
+        if detection.min_distance_m >= crate::utils::nm_to_m(detection.max_distance_nm) {
+            problems.push(
+                "`detection.min_distance_m` must be less than `detection.max_distance_nm`, or no \
+                 recovery attempt is ever detected"
+                    .to_string(),
+            );
+        }
+        // the start is measured from the touchdown point, which is aft of the carrier's origin
+        let min_distance_nm = crate::track::START_DISTANCE + 0.1;
+        if detection.max_distance_nm > 0.0 && detection.max_distance_nm < min_distance_nm {
+            problems.push(format!(
+                "`detection.max_distance_nm` should be at least {} to assess the start of the \
+                 passes (is {})",
+                min_distance_nm, detection.max_distance_nm
+            ));
+        }
+        if !(-1.0..=1.0).contains(&detection.min_nose_alignment) {
+            problems.push(format!(
+                "`detection.min_nose_alignment` must be between -1 and 1 (is {})",
+                detection.min_nose_alignment
+            ));
+        }
+        if let Some(cq) = self
+            .cq
+            .filter(|cq| !(0.0..=1.0).contains(&cq.min_boarding_rate))
+        {
+            problems.push(format!(
+                "`cq.min_boarding_rate` must be between 0 and 1 (is {})",
+                cq.min_boarding_rate
+            ));
+        }
+        if self
+            .mqtt
+            .as_ref()
+            .is_some_and(|mqtt| mqtt.password.is_some() && mqtt.username.is_none())
+        {
+            problems.push(
+                "`mqtt.password` is ignored without `mqtt.username` (MQTT doesn't allow a password \
+                 without a username)"
+                    .to_string(),
+            );
+        }
+        if self.privacy.pilot_names == PilotNames::Hash && self.privacy.salt.is_empty() {
+            problems.push(
+                "`privacy.salt` should be set when hashing pilot names, as the hashes of known \
+                 names can be looked up otherwise"
+                    .to_string(),
+            );
+        }
+        if self.chart.width < 100 {
+            problems.push(format!(
+                "`chart.width` must be at least 100 (is {})",
+                self.chart.width
+            ));
+        }
+
+        if let Some(Smoothing::SavitzkyGolay { window }) = self.smoothing {
+            if window < 3 {
+                problems.push(format!(
+                    "`smoothing.window` must be at least 3 (is {})",
+                    window
+                ));
+            }
+        }
+
+        for (plane_type, overrides) in &self.airplanes {
+            if AirplaneInfo::by_type(plane_type).is_none() {
+                problems.push(format!(
+                    "`airplanes` contains the unsupported airplane type `{}`",
+                    plane_type
+                ));
+            }
+            if let Some(hook_to_eye) = overrides.hook_to_eye.filter(|h| *h <= 0.0) {
+                problems.push(format!(
+                    "`airplanes.{}.hook_to_eye` must be greater than 0 (is {})",
+                    plane_type, hook_to_eye
+                ));
+            }
+            if overrides
+                .aoa_brackets
+                .is_some_and(|aoa_brackets| !aoa_brackets.is_ascending())
+            {
+                problems.push(format!(
+                    "`airplanes.{}.aoa_brackets` must be ascending (fast < slightly_fast < \
+                     on_speed < slightly_slow)",
+                    plane_type
+                ));
+            }
+        }
+
+        for (i, rule) in self.rigged_wires.iter().enumerate() {
+            if rule.wires.is_empty() {
+                problems.push(format!("`rigged_wires[{}].wires` is empty", i));
+            }
+            if let Some(wire) = rule.wires.iter().find(|wire| !(1..=4).contains(*wire)) {
+                problems.push(format!(
+                    "`rigged_wires[{}].wires` contains wire {}, but only wires 1-4 exist",
+                    i, wire
+                ));
+            }
+        }
+
+        let templates = std::iter::once(("output.filename", &self.output.filename)).chain(
+            self.output
+                .subdirectories
+                .iter()
+                .map(|template| ("output.subdirectories", template)),
+        );
+        for (name, template) in templates {
+            for placeholder in crate::filename::unknown_placeholders(template) {
+                problems.push(format!(
+                    "`{}` contains the unknown placeholder `{{{}}}`",
+                    name, placeholder
+                ));
+            }
+        }
+        if !self.output.filename.contains("{id}") && !self.output.filename.contains("{datetime}") {
+            problems.push(
+                "`output.filename` contains neither `{id}` nor `{datetime}`, so passes will \
+                 overwrite each other"
+                    .to_string(),
+            );
+        }
+        // the outputs are only recognized by the ID of their pass
+        if self.retention.is_enabled() && !self.output.filename.contains("{id}") {
+            problems.push(
+                "`output.filename` doesn't contain `{id}`, so `retention` never removes any \
+                 outputs"
+                    .to_string(),
+            );
+        }
+
+        #[cfg(feature = "notify")]
+        problems.extend(self.notifier_problems());
+
+        problems
+    }
+
+    /// The invalid URLs of the notifiers.
+    #[cfg(feature = "notify")]
+    fn notifier_problems(&self) -> Vec<String> {
+        let notifiers = self
+            .notifiers
+            .iter()
+            .map(|notifier| ("notifiers", notifier))
+            .chain(
+                self.admin_notifiers
+                    .iter()
+                    .map(|notifier| ("admin_notifiers", notifier)),
+            );
+        let mut problems = Vec::new();
+        for (name, notifier) in notifiers {
+            let url = match notifier {
+                NotifierConfig::Discord { url } | NotifierConfig::Webhook { url } => url,
+                NotifierConfig::Matrix { homeserver, .. } => homeserver,
+                NotifierConfig::Slack { .. } | NotifierConfig::Telegram { .. } => continue,
+            };
+            if let Err(err) = reqwest::Url::parse(url) {
+                problems.push(format!(
+                    "`{}` contains the invalid URL `{}` ({})",
+                    name, url, err
+                ));
+            }
+        }
+        problems
+    }
+
+    /// The wires rigged on the given carrier (matched by its unit name or type) during the given
+    /// mission, if configured.
+    pub fn rigged_wires(
+        &self,
+        mission_name: Option<&str>,
+        carrier_name: &str,
+        carrier_type: &str,
+    ) -> Option<&[u8]> {
+        self.rigged_wires
+            .iter()
+            .find(|rule| {
+                rule.mission
+                    .as_deref()
+                    .is_none_or(|m| Some(m) == mission_name)
+                    && rule
+                        .carrier
+                        .as_deref()
+                        .is_none_or(|c| c == carrier_name || c == carrier_type)
+            })
+            .map(|rule| rule.wires.as_slice())
+    }
+
+    /// Whether touch-and-goes are declared for the given carrier (matched by its unit name or type)
+    /// during the given mission.
+    pub fn touch_and_go(
+        &self,
+        mission_name: Option<&str>,
+        carrier_name: &str,
+        carrier_type: &str,
+    ) -> bool {
+        self.touch_and_go.iter().any(|rule| {
+            rule.mission
+                .as_deref()
+                .is_none_or(|m| Some(m) == mission_name)
+                && rule
+                    .carrier
+                    .as_deref()
+                    .is_none_or(|c| c == carrier_name || c == carrier_type)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RiggedWires {
+    /// Only apply to the mission with this name.
+    pub mission: Option<String>,
+    /// Only apply to the carrier with this unit name or type.
+    pub carrier: Option<String>,
+    /// The rigged wires (1-4).
+    pub wires: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TouchAndGo {
+    /// Only apply to the mission with this name.
+    pub mission: Option<String>,
+    /// Only apply to the carrier with this unit name or type.
+    pub carrier: Option<String>,
+}
+
+/// Overrides of the built-in data of an airplane type (see [AirplaneInfo]). Omitted values keep
+/// the built-in ones.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AirplaneOverrides {
+    /// Hook position (in m, as `[right, up, forward]`) relative to the object's origin.
+    pub hook: Option<[f64; 3]>,
+    /// The vertical distance (in m) from the hook to the pilot's eye on the glide slope.
+    pub hook_to_eye: Option<f64>,
+    /// The upper bounds of the AoA ratings (in degrees).
+    pub aoa_brackets: Option<AoaBrackets>,
+}
+
+impl AirplaneOverrides {
+    /// The given airplane data with these overrides applied.
+    pub fn apply(&self, info: &AirplaneInfo) -> AirplaneInfo {
+        AirplaneInfo {
+            hook: self.hook.map_or(info.hook, DVec3::from),
+            hook_to_eye: self.hook_to_eye.unwrap_or(info.hook_to_eye),
+            aoa_brackets: self.aoa_brackets.unwrap_or(info.aoa_brackets),
+            ..info.clone()
+        }
+    }
+}
+
+/// Settings for the charts drawn for every pass.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChartConfig {
+    /// Add panels showing the sink rate and the closure speed.
+    pub speeds: bool,
+    /// The built-in color theme the charts are drawn with.
+    pub theme: ThemeName,
+    /// Colors overriding the ones of the theme.
+    pub colors: ThemeColors,
+    /// The width of the charts in px (the heights follow).
+    pub width: u32,
+    /// The factor the size of the charts (including lines and text) is multiplied with, e.g. `2`
+    /// for sharp charts on high-DPI displays.
+    pub scale: f64,
+    /// The distance (in nm) from the carrier the approach is shown for.
+    pub range_nm: f64,
+    /// Mark the approach every this many seconds (counted back from the end of the pass) instead
+    /// of at the labeled distances and the ramp.
+    pub tick_interval_s: Option<f64>,
+}
+
+impl Default for ChartConfig {
+    fn default() -> Self {
+        Self {
+            speeds: false,
+            theme: ThemeName::default(),
+            colors: ThemeColors::default(),
+            width: 1000,
+            scale: 1.0,
+            range_nm: 0.78,
+            tick_interval_s: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    /// The dark theme without red/green color coding.
+    Colorblind,
+}
+
+/// Colors (as `#RRGGBB`) overriding the ones of the theme. See [crate::draw::Theme] for where
+/// they are used.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeColors {
+    pub bg: Option<HexColor>,
+    pub fg: Option<HexColor>,
+    pub guide_red: Option<HexColor>,
+    pub guide_yellow: Option<HexColor>,
+    pub guide_green: Option<HexColor>,
+    pub guide_gray: Option<HexColor>,
+    pub aoa_fast: Option<HexColor>,
+    pub aoa_slightly_fast: Option<HexColor>,
+    pub aoa_on_speed: Option<HexColor>,
+    pub aoa_slightly_slow: Option<HexColor>,
+    pub aoa_slow: Option<HexColor>,
+}
+
+/// A color given as `#RRGGBB`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct HexColor(pub u8, pub u8, pub u8);
+
+impl TryFrom<String> for HexColor {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let hex = s
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.is_ascii())
+            .ok_or_else(|| format!("invalid color `{}`, expected `#RRGGBB`", s))?;
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| format!("invalid color `{}`, expected `#RRGGBB`", s))
+        };
+
+        Ok(HexColor(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+/// A chat service passes are posted to (see [crate::notify]).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum NotifierConfig {
+    /// A Discord webhook.
+    Discord { url: String },
+    /// Any URL the passes are posted to as `multipart/form-data`.
+    Webhook { url: String },
+    /// A Slack channel (by its ID), posted to with a bot token.
+    Slack { token: String, channel: String },
+    /// A Telegram chat, posted to with a bot token.
+    Telegram { token: String, chat_id: String },
+    /// A Matrix room (by its ID), posted to with the access token of a user in the room.
+    Matrix {
+        homeserver: String,
+        access_token: String,
+        room_id: String,
+    },
+}
+
+/// The MQTT broker events are published to.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MqttConfig {
+    pub host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// The topic events are published to. `{server}` is replaced with the name of the server (or
+    /// `default`) and `{event}` with the type of the event (e.g. `pass_completed`).
+    #[serde(default = "default_mqtt_topic")]
+    pub topic: String,
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_client_id() -> String {
+    "dcs-grpc-lso".to_string()
+}
+
+fn default_mqtt_topic() -> String {
+    "lso/{server}/{event}".to_string()
+}
+
+/// Settings for grouping passes into recovery windows and rating the intervals between traps.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecoveryConfig {
+    /// A break of more than this (in seconds) between two passes to a carrier ends a recovery
+    /// window.
+    pub max_gap_s: f64,
+    /// Intervals between two traps longer than this (in seconds) are considered ragged.
+    pub target_interval_s: f64,
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self {
+            max_gap_s: 600.0,
+            target_interval_s: 60.0,
+        }
+    }
+}
+
+/// The requirements of the carrier qualification.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CqConfig {
+    /// The number of day traps required.
+    pub day_traps: usize,
+    /// The number of night traps required.
+    pub night_traps: usize,
+    /// The minimum boarding rate (traps per pass, from 0 to 1).
+    pub min_boarding_rate: f64,
+    /// Only the passes of this many days (up to now) count.
+    pub window_days: f64,
+}
+
+impl Default for CqConfig {
+    fn default() -> Self {
+        Self {
+            day_traps: 10,
+            night_traps: 6,
+            min_boarding_rate: 0.8,
+            window_days: 30.0,
+        }
+    }
+}
+
+/// How the names of the pilots are anonymized (see [crate::privacy]).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PrivacyConfig {
+    pub pilot_names: PilotNames,
+    /// A secret mixed into the hashes (with `"pilot_names": "hash"`), so that they can't be
+    /// reversed by hashing known names.
+    pub salt: String,
+    /// The file the real name of each anonymized pilot is saved to, for the admins. Keep it out of
+    /// published directories.
+    pub mapping_file: PathBuf,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            pilot_names: PilotNames::Keep,
+            salt: String::new(),
+            mapping_file: PathBuf::from("lso-pilot-names.json"),
+        }
+    }
+}
+
+/// Settings for the files the passes are saved to.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    /// The template for the filenames of the charts and recordings (without extension), see
+    /// [crate::filename::render] for the placeholders.
+    pub filename: String,
+    /// Templates for the subdirectories of the output directory the charts and recordings are
+    /// saved to, one per level (e.g. `["{mission}", "{pilot}"]`). Uses the same placeholders as
+    /// the filename.
+    pub subdirectories: Vec<String>,
+    /// Also record the other airplanes and helicopters within this distance (in nm) of the
+    /// carrier (e.g. `5`).
+    pub traffic_range_nm: Option<f64>,
+    /// The format the ACMI recordings are saved in.
+    pub acmi_format: AcmiFormat,
+    /// Save the charts to the output directory. When disabled, the charts are only rendered in
+    /// memory and attached to the notifications (e.g. when the passes are only posted to a
+    /// webhook).
+    pub save_charts: bool,
+    /// Also save an interactive HTML debrief of each pass next to its chart (see
+    /// [crate::debrief::write_html]).
+    pub debrief: bool,
+}
+
+/// The format of ACMI recordings.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AcmiFormat {
+    /// Zip-compressed (`.zip.acmi`).
+    #[default]
+    Zip,
+    /// Uncompressed text (`.txt.acmi`).
+    Text,
+}
+
+impl AcmiFormat {
+    /// The format of the recording at `path`, based on its extension (recordings are compressed
+    /// unless they end with `.txt.acmi`).
+    pub fn from_path(path: &Path) -> Self {
+        if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.to_ascii_lowercase().ends_with(".txt.acmi"))
+        {
+            AcmiFormat::Text
+        } else {
+            AcmiFormat::Zip
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            AcmiFormat::Zip => "zip.acmi",
+            AcmiFormat::Text => "txt.acmi",
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            filename: crate::filename::DEFAULT_TEMPLATE.to_string(),
+            subdirectories: Vec::new(),
+            traffic_range_nm: None,
+            acmi_format: AcmiFormat::Zip,
+            save_charts: true,
+            debrief: false,
+        }
+    }
+}
+
+/// Limits for the charts, recordings and reports kept in the output directory (`run` removes the
+/// oldest ones once an hour). Nothing is removed by default.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionConfig {
+    /// Remove outputs older than this (in days).
+    pub max_age_days: Option<f64>,
+    /// Remove the oldest outputs while all of them together are larger than this (in MB).
+    pub max_size_mb: Option<f64>,
+}
+
+impl RetentionConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_days.is_some() || self.max_size_mb.is_some()
+    }
+}
+
+/// A low-pass filter for the noisy AoA and position of the datums (see [crate::utils::smoothing]).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "filter", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Smoothing {
+    /// An exponential moving average with the given time constant (in seconds), applied forwards
+    /// and backwards.
+    Ema { time_constant_s: f64 },
+    /// A quadratic fit over a window of the given number of datums, which keeps short but real
+    /// deviations better than the moving average.
+    SavitzkyGolay { window: usize },
+}
+
+/// A fixed grid the datums are resampled to (by interpolating between the sampled ones).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "grid", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Resampling {
+    /// A datum every `interval_s` seconds.
+    Time { interval_s: f64 },
+    /// A datum every `interval_m` meters along the landing area's centerline.
+    Distance { interval_m: f64 },
+}
+
+/// How helicopters landing on a carrier are handled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HelicopterMode {
+    /// Ignore helicopters.
+    #[default]
+    Skip,
+    /// Report where helicopters set down on the landing area (spot accuracy, no wires). Only
+    /// detected when recording live.
+    DeckLandings,
+}
+
+/// Where the AoA of a plane is taken from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AoaSource {
+    /// The AoA recorded from the plane's own telemetry (e.g. by Tacview for the player's plane)
+    /// if it is part of the recording, otherwise it is derived from the plane's movement (relative
+    /// to the air when recording live).
+    #[default]
+    Telemetry,
+    /// Always derive the AoA from the plane's movement relative to the ground (which ignores the
+    /// wind), so that passes are comparable regardless of who recorded them.
+    Geometric,
+}
+
+/// The envelope a plane has to be in relative to a carrier to be considered to be in a recovery
+/// attempt.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DetectionEnvelope {
+    /// Planes above this altitude (in ft) are ignored.
+    pub max_altitude_ft: f64,
+    /// Planes farther away from the carrier than this distance (in nm) are ignored.
+    pub max_distance_nm: f64,
+    /// Planes closer to the carrier than this distance (in m) are ignored (to exclude takeoffs).
+    pub min_distance_m: f64,
+    /// The minimum dot product between the plane's nose and the direction to the carrier (1.0
+    /// being pointed directly at the carrier).
+    pub min_nose_alignment: f64,
+    /// Recordings are not started while the carrier turns faster than this (in degrees per
+    /// second), and passes during which it did are marked.
+    pub max_turn_rate: f64,
+}
+
+impl Default for DetectionEnvelope {
+    fn default() -> Self {
+        Self {
+            max_altitude_ft: 500.0,
+            max_distance_nm: 1.5,
+            min_distance_m: 200.0,
+            min_nose_alignment: 0.65,
+            max_turn_rate: 0.5,
+        }
+    }
+}
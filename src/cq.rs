@@ -0,0 +1,81 @@
+//! Carrier qualification (CQ): the progress of a pilot towards the qualification requirements
+//! configured in [crate::config::CqConfig].
+
+use std::fmt;
+
+use serde::Serialize;
+use time::{Duration, OffsetDateTime};
+
+use crate::config::CqConfig;
+use crate::store::PassRecord;
+use crate::track::Grading;
+
+/// The progress of a single pilot towards the qualification.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CqStatus {
+    /// The number of day traps within the window.
+    pub day_traps: usize,
+    /// The number of night traps within the window.
+    pub night_traps: usize,
+    /// The number of passes within the window (touch-and-goes don't count).
+    pub passes: usize,
+    /// Traps per pass within the window.
+    pub boarding_rate: Option<f64>,
+    /// Whether all requirements are met.
+    pub qualified: bool,
+    #[serde(skip)]
+    config: CqConfig,
+}
+
+impl CqStatus {
+    /// The status of the given pilot from their passes in `records` within the configured window
+    /// up to `now`.
+    pub fn new(config: CqConfig, pilot: &str, records: &[PassRecord], now: OffsetDateTime) -> Self {
+        let since = now - Duration::seconds_f64(config.window_days * 86_400.0);
+        let records = records
+            .iter()
+            .filter(|r| r.pilot == pilot && !r.is_touch_and_go())
+            .filter(|r| r.time().is_some_and(|time| time >= since && time <= now))
+            .collect::<Vec<_>>();
+        let traps = records
+            .iter()
+            .filter(|r| matches!(r.grading, Grading::Recovered { .. }))
+            .collect::<Vec<_>>();
+        let night_traps = traps.iter().filter(|r| r.night).count();
+        let day_traps = traps.len() - night_traps;
+        let boarding_rate =
+            (!records.is_empty()).then(|| traps.len() as f64 / records.len() as f64);
+
+        CqStatus {
+            day_traps,
+            night_traps,
+            passes: records.len(),
+            boarding_rate,
+            qualified: day_traps >= config.day_traps
+                && night_traps >= config.night_traps
+                && boarding_rate.is_some_and(|rate| rate >= config.min_boarding_rate),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for CqStatus {
+    /// E.g. `6/10 day, 2/6 night traps, 86% boarding rate (min. 80%), in progress`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} day, {}/{} night traps, {:.0}% boarding rate (min. {:.0}%), {}",
+            self.day_traps,
+            self.config.day_traps,
+            self.night_traps,
+            self.config.night_traps,
+            self.boarding_rate.unwrap_or_default() * 100.0,
+            self.config.min_boarding_rate * 100.0,
+            if self.qualified {
+                "qualified"
+            } else {
+                "in progress"
+            }
+        )
+    }
+}
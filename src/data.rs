@@ -1,7 +1,11 @@
 #![allow(unused)]
 
+use std::collections::HashMap;
 use std::ops::Neg;
+use std::sync::RwLock;
 
+use once_cell::sync::Lazy;
+use serde::Deserialize;
 use ultraviolet::{DRotor3, DVec3};
 
 // Connector positions (hook, cable, ...) extracted via ModelViewer2.
@@ -14,6 +18,21 @@ const NIMITZ: CarrierInfo = CarrierInfo {
     // CoreMods\tech\USS_Nimitz\scripts\USS_Nimitz_RunwaysAndRoutes.lua
     deck_angle: 9.1359,
     deck_altitude: 20.1494,
+    // approximated from the deck layout (not extracted from the model)
+    lens: DVec3 {
+        x: -30.5,
+        y: 21.0,
+        z: -41.0,
+    },
+    lens_basic_angle: 3.5,
+    // approximated from the deck layout (not extracted from the model)
+    ramp: DVec3 {
+        x: 8.3,
+        y: 20.15,
+        z: -158.0,
+    },
+    landing_area_length: 230.0,
+    landing_area_width: 32.0,
     cable1: (
         // POINT_TROS_01_01
         DVec3 {
@@ -76,6 +95,21 @@ const FORRESTAL: CarrierInfo = CarrierInfo {
     // CoreMods\tech\USS_Nimitz\scripts\USS_Nimitz_RunwaysAndRoutes.lua
     deck_angle: 9.42,
     deck_altitude: 18.46,
+    // approximated from the deck layout (not extracted from the model)
+    lens: DVec3 {
+        x: -31.0,
+        y: 19.3,
+        z: -29.2,
+    },
+    lens_basic_angle: 3.5,
+    // approximated from the deck layout (not extracted from the model)
+    ramp: DVec3 {
+        x: 7.7,
+        y: 18.46,
+        z: -142.0,
+    },
+    landing_area_length: 210.0,
+    landing_area_width: 30.0,
     cable1: (
         // POINT_TROS_01_01
         DVec3 {
@@ -140,20 +174,17 @@ static FA18C: AirplaneInfo = AirplaneInfo {
         y: -2.240897,
         z: -7.237348,
     },
-    glide_slope: 3.5,
-    aoa_rating: |aoa: f64| -> Aoa {
-        // https://forums.vrsimulations.com/support/index.php/Navigation_Tutorial_Flight#Angle_of_Attack_Bracket
-        if aoa <= 6.9 {
-            Aoa::Fast
-        } else if aoa <= 7.4 {
-            Aoa::SlightlyFast
-        } else if aoa < 8.8 {
-            Aoa::OnSpeed
-        } else if aoa < 9.3 {
-            Aoa::SlightlySlow
-        } else {
-            Aoa::Slow
-        }
+    hook_to_eye: 4.2,
+    gear_draw_arg: 0,
+    hook_draw_arg: 25,
+    aoa_scale: AoaScale::Degrees,
+    max_trap_weight: 34_000.0,
+    // https://forums.vrsimulations.com/support/index.php/Navigation_Tutorial_Flight#Angle_of_Attack_Bracket
+    aoa_brackets: AoaBrackets {
+        fast: 6.9,
+        slightly_fast: 7.4,
+        on_speed: 8.8,
+        slightly_slow: 9.3,
     },
 };
 
@@ -163,22 +194,23 @@ static F14: AirplaneInfo = AirplaneInfo {
         y: -1.978941,
         z: -6.563727,
     },
-    glide_slope: 3.5,
-    aoa_rating: |aoa: f64| -> Aoa {
-        // https://www.heatblur.se/F-14Manual/cockpit.html?highlight=aoa#approach-indexer
-        // aoa degrees for tomcat calculated by degrees=((units/1.0989) - 3.01) from units in manual based off conversation found here:
-        // https://forum.dcs.world/topic/228893-aoa-units-to-degrees-conversion/#:~:text=Which%20makes%20around%201%20unit%3D1%2C67%20degrees.
-        if aoa <= 9.7 {
-            Aoa::Fast
-        } else if aoa <= 10.2 {
-            Aoa::SlightlyFast
-        } else if aoa < 11.1 {
-            Aoa::OnSpeed
-        } else if aoa < 11.6 {
-            Aoa::SlightlySlow
-        } else {
-            Aoa::Slow
-        }
+    hook_to_eye: 4.8,
+    gear_draw_arg: 0,
+    hook_draw_arg: 25,
+    // see the comment of the AoA rating below
+    aoa_scale: AoaScale::Units {
+        factor: 1.0989,
+        offset: 3.01,
+    },
+    max_trap_weight: 54_000.0,
+    // https://www.heatblur.se/F-14Manual/cockpit.html?highlight=aoa#approach-indexer
+    // aoa degrees for tomcat calculated by degrees=((units/1.0989) - 3.01) from units in manual based off conversation found here:
+    // https://forum.dcs.world/topic/228893-aoa-units-to-degrees-conversion/#:~:text=Which%20makes%20around%201%20unit%3D1%2C67%20degrees.
+    aoa_brackets: AoaBrackets {
+        fast: 9.7,
+        slightly_fast: 10.2,
+        on_speed: 11.1,
+        slightly_slow: 11.6,
     },
 };
 
@@ -188,29 +220,77 @@ static T45: AirplaneInfo = AirplaneInfo {
         y: -1.778766,
         z: -4.782536,
     },
-    glide_slope: 3.5,
-    aoa_rating: |aoa: f64| -> Aoa {
-        // same as FA18C, so potentially wrong
-        if aoa <= 6.9 {
-            Aoa::Fast
-        } else if aoa <= 7.4 {
-            Aoa::SlightlyFast
-        } else if aoa < 8.8 {
-            Aoa::OnSpeed
-        } else if aoa < 9.3 {
-            Aoa::SlightlySlow
-        } else {
-            Aoa::Slow
-        }
+    hook_to_eye: 3.4,
+    gear_draw_arg: 0,
+    hook_draw_arg: 25,
+    aoa_scale: AoaScale::Degrees,
+    max_trap_weight: 14_500.0,
+    // same as FA18C, so potentially wrong (can be corrected via the `airplanes` setting)
+    aoa_brackets: AoaBrackets {
+        fast: 6.9,
+        slightly_fast: 7.4,
+        on_speed: 8.8,
+        slightly_slow: 9.3,
     },
 };
 
-#[derive(Debug)]
+/// The wires of a carrier, if all of them are rigged.
+pub const ALL_WIRES: [u8; 4] = [1, 2, 3, 4];
+
+static REGISTRY: Lazy<RwLock<DataRegistry>> = Lazy::new(Default::default);
+
+/// Carriers and airplanes registered at runtime, e.g. for modded units or to correct the built-in
+/// data (like a hook offset) without a new release. Registered types take precedence over the
+/// built-in ones in [CarrierInfo::by_type] and [AirplaneInfo::by_type].
+///
+/// Meant to be filled once at startup: the registered data lives for the rest of the program
+/// (it is leaked, as the lookups hand out `'static` references).
+#[derive(Default)]
+pub struct DataRegistry {
+    carriers: HashMap<String, &'static CarrierInfo>,
+    airplanes: HashMap<String, &'static AirplaneInfo>,
+}
+
+impl DataRegistry {
+    /// Register the carrier for the given DCS unit types.
+    pub fn register_carrier(types: &[&str], info: CarrierInfo) -> &'static CarrierInfo {
+        let info = &*Box::leak(Box::new(info));
+        let mut registry = REGISTRY.write().unwrap();
+        for t in types {
+            registry.carriers.insert(t.to_string(), info);
+        }
+        info
+    }
+
+    /// Register the airplane for the given DCS unit types.
+    pub fn register_airplane(types: &[&str], info: AirplaneInfo) -> &'static AirplaneInfo {
+        let info = &*Box::leak(Box::new(info));
+        let mut registry = REGISTRY.write().unwrap();
+        for t in types {
+            registry.airplanes.insert(t.to_string(), info);
+        }
+        info
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct CarrierInfo {
     /// Counter-clockwise offset from BRC to FB in degrees.
     pub deck_angle: f64,
     // in meter
     pub deck_altitude: f64,
+    /// Position of the IFLOLS (the lens) relative to the object's origin.
+    pub lens: DVec3,
+    /// The glide slope (in degrees) the lens is set to, which is the optimal glide slope for all
+    /// planes.
+    pub lens_basic_angle: f64,
+    /// Position of the ramp (the aft edge of the deck) on the landing area's centerline relative
+    /// to the object's origin.
+    pub ramp: DVec3,
+    /// Length (from the ramp) and width of the landing area in meters (approximated from the deck
+    /// layout).
+    pub landing_area_length: f64,
+    pub landing_area_width: f64,
     /// Cable pendant positions (left, right) relative to the object' origin.
     pub cable1: (DVec3, DVec3),
     pub cable2: (DVec3, DVec3),
@@ -219,21 +299,88 @@ pub struct CarrierInfo {
 }
 
 impl CarrierInfo {
+    /// The pendant positions of the given cable (1-4).
+    pub fn cable(&self, nr: u8) -> Option<&(DVec3, DVec3)> {
+        match nr {
+            1 => Some(&self.cable1),
+            2 => Some(&self.cable2),
+            3 => Some(&self.cable3),
+            4 => Some(&self.cable4),
+            _ => None,
+        }
+    }
+
     /// Calculate the offset from the origin where the optimal glide path hits the deck.
-    pub fn optimal_landing_offset(&self, plane: &AirplaneInfo) -> DVec3 {
-        // optimal hook touchdown point is halfway between the second and third cable
-        // (according to NAVAIR 00-80T-104 4.2.8)
-        let touchdown_at = (self.cable2.0 - self.cable3.1) / 2.0;
-        let touchdown_at = self.cable3.1 + touchdown_at;
+    pub fn optimal_landing_offset(&self, plane: &AirplaneInfo, rigged_wires: &[u8]) -> DVec3 {
+        let touchdown_at = self.target_touchdown(rigged_wires);
 
         let hook_offset = plane.hook.rotated_by(DRotor3::from_rotation_yz(
-            plane.glide_slope.to_radians().neg(),
+            self.lens_basic_angle.to_radians().neg(),
         ));
 
         touchdown_at - hook_offset
     }
 
+    /// The optimal hook touchdown point relative to the origin.
+    pub fn target_touchdown(&self, rigged_wires: &[u8]) -> DVec3 {
+        // optimal hook touchdown point is in the middle of the rigged cables, so halfway between
+        // the second and third cable for a four-wire rig (according to NAVAIR 00-80T-104 4.2.8)
+        let mut cables = rigged_wires
+            .iter()
+            .filter_map(|nr| self.cable(*nr))
+            .collect::<Vec<_>>();
+        if cables.is_empty() {
+            cables = ALL_WIRES.iter().filter_map(|nr| self.cable(*nr)).collect();
+        }
+        let first = cables[(cables.len() - 1) / 2];
+        let second = cables[cables.len() / 2];
+        let touchdown_at = (first.0 - second.1) / 2.0;
+        second.1 + touchdown_at
+    }
+
+    /// The distance (in m) along the landing area's centerline from the ramp to the optimal hook
+    /// touchdown point.
+    pub fn ramp_distance(&self, rigged_wires: &[u8]) -> f64 {
+        (self.target_touchdown(rigged_wires) - self.ramp).dot(self.landing_direction())
+    }
+
+    /// Whether the given position (relative to the object's origin) is on (or just above) the
+    /// landing area.
+    pub fn in_landing_area(&self, position: DVec3) -> bool {
+        let (along, across) = self.landing_area_offset(position);
+        (0.0..=self.landing_area_length).contains(&along)
+            && across.abs() <= self.landing_area_width / 2.0
+            && position.y - self.ramp.y < 10.0
+    }
+
+    /// The distance (in m) of the given position (relative to the object's origin) along the
+    /// landing area's centerline from the ramp, and its distance to the right of the centerline
+    /// (negative if left of it).
+    pub fn landing_area_offset(&self, position: DVec3) -> (f64, f64) {
+        let offset = position - self.ramp;
+        (
+            offset.dot(self.landing_direction()),
+            offset.dot(self.landing_right()),
+        )
+    }
+
+    /// The direction (relative to the carrier) a plane lands along the angled deck.
+    pub fn landing_direction(&self) -> DVec3 {
+        let angle = self.deck_angle.to_radians();
+        DVec3::new(angle.sin().neg(), 0.0, angle.cos())
+    }
+
+    /// The direction (relative to the carrier) to the right of the landing area's centerline.
+    pub fn landing_right(&self) -> DVec3 {
+        let angle = self.deck_angle.to_radians();
+        DVec3::new(angle.cos(), 0.0, angle.sin())
+    }
+
+    /// The carrier of the given DCS unit type (registered or built-in).
     pub fn by_type(t: &str) -> Option<&'static Self> {
+        if let Some(info) = REGISTRY.read().unwrap().carriers.get(t) {
+            return Some(info);
+        }
         match t {
             "CVN_71" | "CVN_72" | "CVN_73" | "CVN_75" | "Stennis" => Some(&NIMITZ),
             "Forrestal" => Some(&FORRESTAL),
@@ -242,7 +389,7 @@ impl CarrierInfo {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Aoa {
     Fast,
     SlightlyFast,
@@ -251,20 +398,102 @@ pub enum Aoa {
     Slow,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AirplaneInfo {
     /// Hook position relative to the object's origin.
     pub hook: DVec3,
-    /// The optimal glide slope in degrees.
-    pub glide_slope: f64,
-    /// A function that returns its current AOA rating.
-    pub aoa_rating: fn(aoa: f64) -> Aoa,
+    /// The vertical distance (in m) from the hook to the pilot's eye when on the glide slope
+    /// (approximated from the hook and the cockpit position and the plane's on-speed pitch). The
+    /// lens is corrected for it, so that a centered ball leads the hook to the target wire.
+    pub hook_to_eye: f64,
+    /// Draw argument of the (nose) gear (0 = retracted, 1 = extended).
+    pub gear_draw_arg: u32,
+    /// Draw argument of the tailhook (0 = retracted, 1 = extended).
+    pub hook_draw_arg: u32,
+    /// The scale the AoA is flown in (and displayed in, in addition to degrees).
+    pub aoa_scale: AoaScale,
+    /// The maximum gross weight (in lb) the plane may be trapped with.
+    pub max_trap_weight: f64,
+    /// The AoA brackets the AoA is rated by.
+    pub aoa_brackets: AoaBrackets,
+}
+
+/// The upper bounds (in degrees, also for planes flown in units) of the AoA ratings. An AoA of
+/// `fast` or less is fast, and one of `slightly_slow` or more is slow.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AoaBrackets {
+    pub fast: f64,
+    pub slightly_fast: f64,
+    pub on_speed: f64,
+    pub slightly_slow: f64,
+}
+
+impl AoaBrackets {
+    pub fn rating(&self, aoa: f64) -> Aoa {
+        if aoa <= self.fast {
+            Aoa::Fast
+        } else if aoa <= self.slightly_fast {
+            Aoa::SlightlyFast
+        } else if aoa < self.on_speed {
+            Aoa::OnSpeed
+        } else if aoa < self.slightly_slow {
+            Aoa::SlightlySlow
+        } else {
+            Aoa::Slow
+        }
+    }
+
+    /// Whether each bound is greater than the previous one.
+    pub fn is_ascending(&self) -> bool {
+        self.fast < self.slightly_fast
+            && self.slightly_fast < self.on_speed
+            && self.on_speed < self.slightly_slow
+    }
+}
+
+/// The scale of a plane's AoA gauge and indexer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AoaScale {
+    Degrees,
+    /// Units, converted from degrees by `units = (degrees + offset) * factor`.
+    Units {
+        factor: f64,
+        offset: f64,
+    },
 }
 
 impl AirplaneInfo {
+    /// The rating of the given AoA (in degrees).
+    pub fn aoa_rating(&self, aoa: f64) -> Aoa {
+        self.aoa_brackets.rating(aoa)
+    }
+
+    /// The AoA in units (if the plane's AoA is flown in units) for the given AoA in degrees.
+    pub fn aoa_units(&self, aoa: f64) -> Option<f64> {
+        match self.aoa_scale {
+            AoaScale::Degrees => None,
+            AoaScale::Units { factor, offset } => Some((aoa + offset) * factor),
+        }
+    }
+
+    /// Format the given AoA (in degrees) for display, e.g. `10.6° (15.0 units)`.
+    pub fn format_aoa(&self, aoa: f64) -> String {
+        match self.aoa_units(aoa) {
+            Some(units) => format!("{:.1}° ({:.1} units)", aoa, units),
+            None => format!("{:.1}°", aoa),
+        }
+    }
+
+    /// The airplane of the given DCS unit type (registered or built-in).
     pub fn by_type(t: &str) -> Option<&'static Self> {
+        if let Some(info) = REGISTRY.read().unwrap().airplanes.get(t) {
+            return Some(info);
+        }
         match t {
             "FA-18C_hornet" => Some(&FA18C),
+            // the B hasn't been measured separately, so both share the data of the A (each can be
+            // corrected via the `airplanes` setting)
             "F-14A-135-GR" | "F-14B" => Some(&F14),
             "T-45" => Some(&T45),
             t => None,
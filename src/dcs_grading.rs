@@ -0,0 +1,122 @@
+use std::str::FromStr;
+
+/// The grading of a pass by the DCS LSO, parsed from the comment of a `LandingQualityMark` event.
+/// The comments look like `LSO: GRADE:--- : _TMRDAR_  (EGTL)  WIRE# 1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DcsGrading {
+    /// The comment as received from DCS.
+    pub comment: String,
+    pub grade: LsoGrade,
+    /// The deviation codes (e.g. `_TMRDAR_`, `(EGTL)`, `LUL`).
+    pub deviations: Vec<String>,
+    pub wire: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LsoGrade {
+    /// `_OK_`
+    Perfect,
+    /// `OK`
+    Ok,
+    /// `(OK)`
+    Fair,
+    /// `---`
+    NoGrade,
+    /// `C`
+    Cut,
+    /// `B`
+    Bolter,
+    /// `WO`
+    WaveOff,
+    /// `WOP`
+    PatternWaveOff,
+    /// `OWO`
+    OwnWaveOff,
+    /// Any grade not known (yet).
+    Other(String),
+}
+
+impl DcsGrading {
+    pub fn parse(comment: &str) -> Self {
+        let mut grade = LsoGrade::Other(String::new());
+        let mut deviations = Vec::new();
+        let mut wire = None;
+
+        if let Some((_, rest)) = comment.split_once("GRADE:") {
+            let mut tokens = rest.split_whitespace();
+            if let Some(g) = tokens.next() {
+                grade = LsoGrade::from(g);
+            }
+
+            while let Some(token) = tokens.next() {
+                match token {
+                    ":" => {}
+                    "WIRE#" => wire = tokens.next().and_then(parse_wire),
+                    t if t.starts_with("WIRE#") => wire = parse_wire(&t["WIRE#".len()..]),
+                    t => deviations.push(t.to_string()),
+                }
+            }
+        }
+
+        DcsGrading {
+            comment: comment.to_string(),
+            grade,
+            deviations,
+            wire,
+        }
+    }
+}
+
+impl LsoGrade {
+    pub fn as_str(&self) -> &str {
+        match self {
+            LsoGrade::Perfect => "_OK_",
+            LsoGrade::Ok => "OK",
+            LsoGrade::Fair => "(OK)",
+            LsoGrade::NoGrade => "---",
+            LsoGrade::Cut => "C",
+            LsoGrade::Bolter => "B",
+            LsoGrade::WaveOff => "WO",
+            LsoGrade::PatternWaveOff => "WOP",
+            LsoGrade::OwnWaveOff => "OWO",
+            LsoGrade::Other(s) => s,
+        }
+    }
+
+    /// The (NATOPS) points the grade counts towards the grade point average (GPA), or `None` if
+    /// the pass doesn't count (e.g. pattern wave-offs).
+    pub fn points(&self) -> Option<f64> {
+        match self {
+            LsoGrade::Perfect => Some(5.0),
+            LsoGrade::Ok => Some(4.0),
+            LsoGrade::Fair => Some(3.0),
+            LsoGrade::Bolter => Some(2.5),
+            LsoGrade::NoGrade => Some(2.0),
+            LsoGrade::OwnWaveOff => Some(2.0),
+            LsoGrade::WaveOff => Some(1.0),
+            LsoGrade::Cut => Some(0.0),
+            LsoGrade::PatternWaveOff | LsoGrade::Other(_) => None,
+        }
+    }
+}
+
+impl From<&str> for LsoGrade {
+    fn from(s: &str) -> Self {
+        match s {
+            "_OK_" => LsoGrade::Perfect,
+            "OK" => LsoGrade::Ok,
+            "(OK)" => LsoGrade::Fair,
+            "---" => LsoGrade::NoGrade,
+            "C" => LsoGrade::Cut,
+            "B" => LsoGrade::Bolter,
+            "WO" => LsoGrade::WaveOff,
+            "WOP" => LsoGrade::PatternWaveOff,
+            "OWO" => LsoGrade::OwnWaveOff,
+            s => LsoGrade::Other(s.to_string()),
+        }
+    }
+}
+
+fn parse_wire(s: &str) -> Option<u8> {
+    s.get(0..1).and_then(|w| u8::from_str(w).ok())
+}
@@ -0,0 +1,294 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::report::{escape, html_head, notes};
+use crate::stats;
+use crate::store::PassRecord;
+use crate::track::TrackResult;
+use crate::utils::{m_to_ft, m_to_nm};
+
+/// Write the debrief of a single pass as a standalone HTML file: an interactive chart of the
+/// approach (zoomable, with the values under the cursor), the grade and its breakdown by segment,
+/// and the details of the pass. `acmi` is the link to its recording (relative to the file).
+pub fn write_html(
+    path: &Path,
+    track: &TrackResult,
+    record: &PassRecord,
+    acmi: Option<&str>,
+) -> Result<(), crate::error::Error> {
+    let title = format!("{}: {}", record.pilot, track.grading.summary(track.hook_up));
+
+    let mut html = String::new();
+    writeln!(html, "{}", html_head(&title, STYLE))?;
+    writeln!(html, "<h1>{}</h1>", escape(&title))?;
+    writeln!(html, "<p>{}</p>", escape(&track.debriefing()))?;
+    if let Some(acmi) = acmi {
+        writeln!(
+            html,
+            "<p><a href=\"{}\">Recording (ACMI)</a></p>",
+            escape(acmi)
+        )?;
+    }
+
+    writeln!(
+        html,
+        "<div id=\"readout\">&nbsp;</div>\n<div id=\"charts\"></div>\n\
+         <p class=\"hint\">Scroll to zoom, drag to pan, double-click to reset.</p>"
+    )?;
+
+    writeln!(html, "<h2>Grade</h2>\n<table>")?;
+    row(
+        &mut html,
+        "Result",
+        track.grading.summary(track.hook_up).into_owned(),
+    )?;
+    if let Some(dcs_grading) = &track.dcs_grading {
+        let mut grade = dcs_grading.grade.as_str().to_string();
+        for deviation in &dcs_grading.deviations {
+            grade.push(' ');
+            grade.push_str(deviation);
+        }
+        row(&mut html, "DCS LSO", grade)?;
+    }
+    if let Some(points) = stats::points(record) {
+        row(&mut html, "Points", format!("{:.1}", points))?;
+    }
+    if let Some(start) = track.start() {
+        row(&mut html, "X", start.to_string())?;
+    }
+    for (segment, trends) in track.trends() {
+        row(
+            &mut html,
+            segment.as_str(),
+            if trends.is_empty() {
+                "steady".to_string()
+            } else {
+                trends
+                    .iter()
+                    .map(|t| t.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            },
+        )?;
+    }
+    if let Some((cable_estimated, cable)) = track.cable_mismatch() {
+        row(
+            &mut html,
+            "Wire mismatch",
+            format!("estimated #{}, DCS reported #{}", cable_estimated, cable),
+        )?;
+    }
+    writeln!(html, "</table>")?;
+
+    writeln!(html, "<h2>Pass</h2>\n<table>")?;
+    if let Some(id) = &record.id {
+        row(&mut html, "ID", id.clone())?;
+    }
+    row(&mut html, "Time", record.time.clone())?;
+    if let Some(server) = &record.server {
+        row(&mut html, "Server", server.clone())?;
+    }
+    row(
+        &mut html,
+        "Carrier",
+        match &record.carrier {
+            Some(carrier) => format!("{} ({})", carrier, record.carrier_type),
+            None => record.carrier_type.clone(),
+        },
+    )?;
+    row(&mut html, "Aircraft", record.plane_type.clone())?;
+    if let Some(groove_time) = track.groove_time() {
+        row(&mut html, "Groove", format!("{:.1}s", groove_time))?;
+    }
+    if let Some(groove_aoa) = track.groove_aoa() {
+        row(
+            &mut html,
+            "AoA in the groove",
+            format!("{:.1}°", groove_aoa),
+        )?;
+    }
+    if let Some(ramp_clearance) = track.ramp_clearance {
+        row(
+            &mut html,
+            "Ramp clearance",
+            format!("{:.0}ft", ramp_clearance),
+        )?;
+    }
+    if let Some(touchdown) = &track.touchdown {
+        row(&mut html, "Touchdown", touchdown.to_string())?;
+    }
+    if let Some(touchdown_g) = track.touchdown_g {
+        row(&mut html, "Touchdown G", format!("{:.1}", touchdown_g))?;
+    }
+    if let Some(weight) = &track.weight {
+        row(&mut html, "Weight", weight.to_string())?;
+    }
+    if track.is_night() {
+        row(&mut html, "Night", "yes".to_string())?;
+    }
+    let notes = notes(record);
+    if !notes.is_empty() {
+        row(&mut html, "Notes", notes.join(", "))?;
+    }
+    writeln!(html, "</table>")?;
+
+    // the datums are embedded as-is and drawn by the script, which keeps the file self-contained
+    let brackets = &track.plane_info.aoa_brackets;
+    let data = json!({
+        "glide_slope": track.carrier_info.lens_basic_angle,
+        "aoa": [brackets.fast, brackets.slightly_fast, brackets.on_speed, brackets.slightly_slow],
+        "datums": track
+            .datums
+            .iter()
+            .map(|d| [m_to_nm(d.x), m_to_ft(d.alt), m_to_ft(d.y), d.aoa, d.ball])
+            .collect::<Vec<_>>(),
+    });
+    writeln!(
+        html,
+        "<script id=\"data\" type=\"application/json\">{}</script>\n<script>{}</script>",
+        // `</` must not appear within a script element
+        data.to_string().replace("</", "<\\/"),
+        SCRIPT
+    )?;
+    writeln!(html, "</body>\n</html>")?;
+
+    std::fs::write(path, html)?;
+
+    Ok(())
+}
+
+/// The link to `path` from a file in `dir` (e.g. from the debrief to the recording, which can be
+/// in a different subdirectory of the output directory).
+pub fn relative_link(dir: &Path, path: &Path) -> String {
+    let from = dir.components().collect::<Vec<_>>();
+    let to = path.components().collect::<Vec<_>>();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    std::iter::repeat_n("..".into(), from.len() - common)
+        .chain(to[common..].iter().map(|c| c.as_os_str().to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn row(html: &mut String, label: &str, value: String) -> std::fmt::Result {
+    writeln!(
+        html,
+        "<tr><th>{}</th><td>{}</td></tr>",
+        label,
+        escape(&value)
+    )
+}
+
+/// The style of the debrief on top of the shared one (see [html_head]).
+const STYLE: &str = "body { max-width: 960px; } \
+svg { display: block; background: #1a1a1a; margin-bottom: 4px; cursor: grab; user-select: none; } \
+svg text { fill: #aaa; font-size: 11px; } \
+#readout { font-family: monospace; margin: 1em 0 0.5em; } \
+.hint { color: #777; font-size: 0.8em; }";
+
+/// Draws the side view, the lineup, the AoA and the ball over the distance (in nm) from the
+/// carrier into SVGs, with the line colored by the AoA like in the charts.
+const SCRIPT: &str = r#"
+const data = JSON.parse(document.getElementById('data').textContent);
+const datums = data.datums;
+const W = 900, H = 180, L = 56, R = 8, T = 8, B = 20;
+const colors = ['#dc2626', '#ea580c', '#ca8a04', '#65a30d', '#16a34a'];
+const aoaColor = aoa => colors[aoa <= data.aoa[0] ? 0 : aoa <= data.aoa[1] ? 1
+    : aoa < data.aoa[2] ? 2 : aoa < data.aoa[3] ? 3 : 4];
+const gs = Math.tan(data.glide_slope * Math.PI / 180) * 6076.12;
+const panels = [
+  { label: 'Altitude (ft)', value: d => d[1], guide: x => gs * x },
+  { label: 'Lineup (ft right)', value: d => d[2], guide: () => 0 },
+  { label: 'AoA (°)', value: d => d[3], band: [data.aoa[1], data.aoa[2]] },
+  { label: 'Ball (cells)', value: d => d[4], guide: () => 0 },
+];
+const full = [Math.min(0, ...datums.map(d => d[0])), Math.max(...datums.map(d => d[0]))];
+let domain = full.slice();
+let cursor = null;
+const ns = 'http://www.w3.org/2000/svg';
+const px = x => L + (x - domain[0]) / (domain[1] - domain[0]) * (W - L - R);
+const fromPx = p => domain[0] + (p - L) / (W - L - R) * (domain[1] - domain[0]);
+const el = (name, attrs) => {
+  const e = document.createElementNS(ns, name);
+  for (const k in attrs) e.setAttribute(k, attrs[k]);
+  return e;
+};
+
+function draw() {
+  const container = document.getElementById('charts');
+  container.innerHTML = '';
+  const visible = datums.filter(d => d[0] >= domain[0] && d[0] <= domain[1]);
+  for (const panel of panels) {
+    const svg = el('svg', { width: W, height: H, viewBox: `0 0 ${W} ${H}` });
+    const values = visible.map(panel.value);
+    if (panel.guide) values.push(panel.guide(domain[0]), panel.guide(domain[1]));
+    if (panel.band) values.push(...panel.band);
+    let [lo, hi] = [Math.min(...values), Math.max(...values)];
+    const pad = (hi - lo) * 0.1 || 1;
+    [lo, hi] = [lo - pad, hi + pad];
+    const py = v => T + (hi - v) / (hi - lo) * (H - T - B);
+    if (panel.band) {
+      svg.append(el('rect', { x: L, width: W - L - R, y: py(panel.band[1]),
+        height: py(panel.band[0]) - py(panel.band[1]), fill: '#ca8a04', 'fill-opacity': 0.15 }));
+    }
+    if (panel.guide) {
+      svg.append(el('line', { x1: px(domain[0]), y1: py(panel.guide(domain[0])),
+        x2: px(domain[1]), y2: py(panel.guide(domain[1])), stroke: '#94a3b8', 'stroke-opacity': 0.5 }));
+    }
+    for (let i = 1; i < visible.length; i++) {
+      const [a, b] = [visible[i - 1], visible[i]];
+      svg.append(el('line', { x1: px(a[0]), y1: py(panel.value(a)), x2: px(b[0]),
+        y2: py(panel.value(b)), stroke: aoaColor(b[3]), 'stroke-width': 2 }));
+    }
+    for (const v of [lo + pad, (lo + hi) / 2, hi - pad]) {
+      const t = el('text', { x: 4, y: py(v) + 4 });
+      t.textContent = v.toFixed(Math.abs(hi - lo) < 10 ? 1 : 0);
+      svg.append(t);
+    }
+    const label = el('text', { x: L + 4, y: T + 12 });
+    label.textContent = panel.label;
+    svg.append(label);
+    const axis = el('text', { x: W - R - 60, y: H - 6 });
+    axis.textContent = `${domain[0].toFixed(2)}–${domain[1].toFixed(2)} nm`;
+    svg.append(axis);
+    if (cursor) {
+      svg.append(el('line', { x1: px(cursor[0]), y1: T, x2: px(cursor[0]), y2: H - B, stroke: '#eee',
+        'stroke-opacity': 0.4 }));
+      svg.append(el('circle', { cx: px(cursor[0]), cy: py(panel.value(cursor)), r: 3, fill: '#eee' }));
+    }
+    listen(svg);
+    container.append(svg);
+  }
+  document.getElementById('readout').textContent = cursor
+    ? `${cursor[0].toFixed(2)} nm | alt ${cursor[1].toFixed(0)} ft | lineup ${cursor[2].toFixed(0)} ft `
+      + `| AoA ${cursor[3].toFixed(1)}° | ball ${cursor[4].toFixed(1)}`
+    : ' ';
+}
+
+let drag = null;
+function listen(svg) {
+  svg.addEventListener('wheel', e => {
+    e.preventDefault();
+    const x = fromPx(e.offsetX), f = e.deltaY > 0 ? 1.25 : 0.8;
+    domain = [Math.max(full[0], x - (x - domain[0]) * f), Math.min(full[1], x + (domain[1] - x) * f)];
+    draw();
+  });
+  svg.addEventListener('mousedown', e => { drag = { x: e.offsetX, domain: domain.slice() }; });
+  svg.addEventListener('mousemove', e => {
+    if (drag) {
+      const shift = (drag.x - e.offsetX) / (W - L - R) * (drag.domain[1] - drag.domain[0]);
+      const clamped = Math.max(full[0] - drag.domain[0], Math.min(full[1] - drag.domain[1], shift));
+      domain = [drag.domain[0] + clamped, drag.domain[1] + clamped];
+    } else {
+      const x = fromPx(e.offsetX);
+      cursor = datums.reduce((best, d) => Math.abs(d[0] - x) < Math.abs(best[0] - x) ? d : best,
+        datums[0]);
+    }
+    draw();
+  });
+  svg.addEventListener('dblclick', () => { domain = full.slice(); draw(); });
+}
+window.addEventListener('mouseup', () => { drag = null; });
+if (datums.length > 0) draw();
+"#;
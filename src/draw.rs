@@ -13,96 +13,427 @@ use plotters::style::{Color, IntoFont, RGBColor, TextStyle};
 use plotters_bitmap::bitmap_pixel::RGBPixel;
 use plotters_bitmap::BitMapBackend;
 
-use crate::data::{AirplaneInfo, Aoa};
-use crate::track::{Datum, Grading, TrackResult};
-use crate::utils::{ft_to_nm, m_to_ft, m_to_nm, nm_to_ft, nm_to_m};
+use crate::config::{ChartConfig, HexColor, ThemeName};
+use crate::data::{AirplaneInfo, Aoa, CarrierInfo, ALL_WIRES};
+use crate::stats::PilotStats;
+use crate::track::{Datum, DeckPosition, Grading, Segment, TrackResult, Trend};
+use crate::utils::{ft_to_nm, m_to_ft, m_to_nm, ms_to_kts, nm_to_ft, nm_to_m};
 
-const THEME_BG: RGBColor = RGBColor(31, 41, 55); // 1F2937
-const THEME_FG: RGBColor = RGBColor(156, 163, 175); // 9CA3AF
+/// The colors the charts are drawn with.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub bg: RGBColor,
+    pub fg: RGBColor,
 
-const THEME_GUIDE_RED: RGBColor = RGBColor(239, 68, 68); // EF4444
-const THEME_GUIDE_YELLOW: RGBColor = RGBColor(254, 240, 138); // FEF08A
-const THEME_GUIDE_GREEN: RGBColor = RGBColor(34, 197, 94); // 22C55E
-const THEME_GUIDE_GRAY: RGBColor = RGBColor(100, 116, 139); // 64748B
+    pub guide_red: RGBColor,
+    pub guide_yellow: RGBColor,
+    pub guide_green: RGBColor,
+    pub guide_gray: RGBColor,
 
-const THEME_AOA_FAST: RGBColor = RGBColor(239, 68, 68); // EF4444
-const THEME_AOA_SLIGHTLY_FAST: RGBColor = RGBColor(239, 165, 68); // EFA544
-const THEME_AOA_ON_SPEED: RGBColor = RGBColor(254, 240, 138); // FEF08A
-const THEME_AOA_SLIGHTLY_SLOW: RGBColor = RGBColor(170, 197, 34); // AAC522
-const THEME_AOA_SLOW: RGBColor = RGBColor(34, 197, 94); // 22C55E
+    pub aoa_fast: RGBColor,
+    pub aoa_slightly_fast: RGBColor,
+    pub aoa_on_speed: RGBColor,
+    pub aoa_slightly_slow: RGBColor,
+    pub aoa_slow: RGBColor,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        bg: RGBColor(31, 41, 55),    // 1F2937
+        fg: RGBColor(156, 163, 175), // 9CA3AF
+
+        guide_red: RGBColor(239, 68, 68),      // EF4444
+        guide_yellow: RGBColor(254, 240, 138), // FEF08A
+        guide_green: RGBColor(34, 197, 94),    // 22C55E
+        guide_gray: RGBColor(100, 116, 139),   // 64748B
+
+        aoa_fast: RGBColor(239, 68, 68),           // EF4444
+        aoa_slightly_fast: RGBColor(239, 165, 68), // EFA544
+        aoa_on_speed: RGBColor(254, 240, 138),     // FEF08A
+        aoa_slightly_slow: RGBColor(170, 197, 34), // AAC522
+        aoa_slow: RGBColor(34, 197, 94),           // 22C55E
+    };
+
+    pub const LIGHT: Theme = Theme {
+        bg: RGBColor(249, 250, 251), // F9FAFB
+        fg: RGBColor(55, 65, 81),    // 374151
+
+        guide_red: RGBColor(220, 38, 38),    // DC2626
+        guide_yellow: RGBColor(202, 138, 4), // CA8A04
+        guide_green: RGBColor(22, 163, 74),  // 16A34A
+        guide_gray: RGBColor(148, 163, 184), // 94A3B8
+
+        aoa_fast: RGBColor(220, 38, 38),           // DC2626
+        aoa_slightly_fast: RGBColor(234, 88, 12),  // EA580C
+        aoa_on_speed: RGBColor(202, 138, 4),       // CA8A04
+        aoa_slightly_slow: RGBColor(101, 163, 13), // 65A30D
+        aoa_slow: RGBColor(22, 163, 74),           // 16A34A
+    };
+
+    /// The dark theme with red and green replaced by colors that can be told apart with the
+    /// common forms of color blindness (orange to purple for the AoA, based on ColorBrewer's PuOr,
+    /// and the Okabe-Ito sky blue, yellow and vermillion for the guides).
+    pub const COLORBLIND: Theme = Theme {
+        guide_red: RGBColor(213, 94, 0),      // D55E00
+        guide_yellow: RGBColor(240, 228, 66), // F0E442
+        guide_green: RGBColor(86, 180, 233),  // 56B4E9
+
+        aoa_fast: RGBColor(230, 97, 1),             // E66101
+        aoa_slightly_fast: RGBColor(253, 184, 99),  // FDB863
+        aoa_on_speed: RGBColor(247, 247, 247),      // F7F7F7
+        aoa_slightly_slow: RGBColor(178, 171, 210), // B2ABD2
+        aoa_slow: RGBColor(128, 100, 190),          // 8064BE
+
+        ..Theme::DARK
+    };
+
+    /// The configured base theme with the configured colors overridden.
+    pub fn from_config(config: &ChartConfig) -> Self {
+        let theme = match config.theme {
+            ThemeName::Dark => Theme::DARK,
+            ThemeName::Light => Theme::LIGHT,
+            ThemeName::Colorblind => Theme::COLORBLIND,
+        };
+        let colors = &config.colors;
+        let color = |c: Option<HexColor>, default: RGBColor| {
+            c.map(|HexColor(r, g, b)| RGBColor(r, g, b))
+                .unwrap_or(default)
+        };
+
+        Theme {
+            bg: color(colors.bg, theme.bg),
+            fg: color(colors.fg, theme.fg),
+            guide_red: color(colors.guide_red, theme.guide_red),
+            guide_yellow: color(colors.guide_yellow, theme.guide_yellow),
+            guide_green: color(colors.guide_green, theme.guide_green),
+            guide_gray: color(colors.guide_gray, theme.guide_gray),
+            aoa_fast: color(colors.aoa_fast, theme.aoa_fast),
+            aoa_slightly_fast: color(colors.aoa_slightly_fast, theme.aoa_slightly_fast),
+            aoa_on_speed: color(colors.aoa_on_speed, theme.aoa_on_speed),
+            aoa_slightly_slow: color(colors.aoa_slightly_slow, theme.aoa_slightly_slow),
+            aoa_slow: color(colors.aoa_slow, theme.aoa_slow),
+        }
+    }
+}
+
+/// The dimensions of the charts.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    /// The width of the charts (in px, before scaling).
+    pub width: u32,
+    /// The factor all sizes (in px) are multiplied with, e.g. `2.0` for high-DPI displays.
+    pub scale: f64,
+    /// The visible distance (in nm) from the carrier of the approach views and the strips below.
+    pub range_x: Range<f64>,
+    /// The visible lateral offset (in nm) of the top view.
+    pub top_range_y: Range<f64>,
+    /// The visible altitude (in ft) of the side view.
+    pub side_range_y: Range<f64>,
+}
+
+impl Layout {
+    pub fn from_config(config: &ChartConfig) -> Self {
+        // keep the angles covered by the approach views (and thus their heights) when showing a
+        // longer or shorter distance
+        let range_nm = config.range_nm.max(0.25);
+        let factor =
+            (range_nm - DEFAULT_RANGE_X.start) / (DEFAULT_RANGE_X.end - DEFAULT_RANGE_X.start);
+
+        Layout {
+            width: config.width.max(MIN_WIDTH),
+            scale: config.scale.clamp(0.5, 4.0),
+            range_x: DEFAULT_RANGE_X.start..range_nm,
+            top_range_y: (DEFAULT_TOP_RANGE_Y.start * factor)..(DEFAULT_TOP_RANGE_Y.end * factor),
+            side_range_y: (DEFAULT_SIDE_RANGE_Y.start * factor)
+                ..(DEFAULT_SIDE_RANGE_Y.end * factor),
+        }
+    }
+
+    /// Scale the given size (in px).
+    fn px(&self, px: u32) -> u32 {
+        (f64::from(px) * self.scale).round() as u32
+    }
+
+    /// Scale the given position (in px).
+    fn point(&self, x: i32, y: i32) -> (i32, i32) {
+        (
+            (f64::from(x) * self.scale).round() as i32,
+            (f64::from(y) * self.scale).round() as i32,
+        )
+    }
 
-const WIDTH: u32 = 1000;
+    /// The distances labeled on the x-axis (every quarter nm).
+    fn key_points(&self) -> Vec<f64> {
+        (1..)
+            .map(|i| f64::from(i) * 0.25)
+            .take_while(|x| *x <= self.range_x.end)
+            .collect()
+    }
+}
+
+const MIN_WIDTH: u32 = 400;
 const X_LABEL_AREA_SIZE: u32 = 30;
-const RANGE_X: Range<f64> = -0.02..0.78;
-const TOP_RANGE_Y: Range<f64> = -0.15..0.15;
-const SIDE_RANGE_Y: Range<f64> = 0.0..350.0;
+const DEFAULT_RANGE_X: Range<f64> = -0.02..0.78;
+const DEFAULT_TOP_RANGE_Y: Range<f64> = -0.15..0.15;
+const DEFAULT_SIDE_RANGE_Y: Range<f64> = 0.0..350.0;
 const OVERLAP_OFFSET: u32 = 130;
+/// The visible part of the landing area in ft along (reversed, so that the plane moves from right
+/// to left like in the other views) and right of its centerline, relative to the target touchdown
+/// point.
+const DECK_RANGE_X: Range<f64> = -400.0..150.0;
+const DECK_RANGE_Y: Range<f64> = -75.0..75.0;
+const SPOTTING_TITLE_HEIGHT: u32 = 56;
+const STATS_PANEL_HEIGHT: u32 = 200;
+const AOA_HEIGHT: u32 = 160;
+const SPEED_HEIGHT: u32 = 120;
+const BALL_HEIGHT: u32 = 120;
+const BALL_RANGE_Y: Range<f64> = -6.5..6.5;
+/// The time span (in seconds) rates are derived over (to smooth out the rounding of the recorded
+/// positions).
+const RATE_WINDOW: f64 = 0.5;
+const SINK_RATE_RANGE_Y: Range<f64> = -250.0..2000.0;
+/// The visible AoA range (in degrees) around the middle of the on-speed bracket.
+const AOA_SPAN: f64 = 4.0;
 
 #[tracing::instrument(skip_all)]
 pub fn draw_chart(
     out_dir: &std::path::Path,
     filename: &str,
     track: &TrackResult,
+    chart_config: &ChartConfig,
+    reference: Option<&TrackResult>,
 ) -> Result<PathBuf, DrawError> {
-    let side_height = ((ft_to_nm(SIDE_RANGE_Y.end - SIDE_RANGE_Y.start) * 5.0
-        / (RANGE_X.end - RANGE_X.start))
-        * (WIDTH as f64))
-        .floor() as u32;
-
-    let top_height = (((TOP_RANGE_Y.end - TOP_RANGE_Y.start) / (RANGE_X.end - RANGE_X.start))
-        * (WIDTH as f64))
-        .floor() as u32
+    let path = out_dir.join(format!("{}.png", filename));
+    render_chart(track, chart_config, reference)?.save_with_format(&path, ImageFormat::Png)?;
+    Ok(path)
+}
+
+/// Draws the chart like [draw_chart], but returns the encoded PNG instead of saving it.
+#[tracing::instrument(skip_all)]
+pub fn draw_chart_png(
+    track: &TrackResult,
+    chart_config: &ChartConfig,
+    reference: Option<&TrackResult>,
+) -> Result<Vec<u8>, DrawError> {
+    let mut png = Vec::new();
+    render_chart(track, chart_config, reference)?
+        .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+fn render_chart(
+    track: &TrackResult,
+    chart_config: &ChartConfig,
+    reference: Option<&TrackResult>,
+) -> Result<image::RgbImage, DrawError> {
+    let theme = &Theme::from_config(chart_config);
+    let layout = &Layout::from_config(chart_config);
+    let side_height = ((ft_to_nm(layout.side_range_y.end - layout.side_range_y.start) * 5.0
+        / (layout.range_x.end - layout.range_x.start))
+        * f64::from(layout.width))
+    .floor() as u32;
+
+    let top_height = (((layout.top_range_y.end - layout.top_range_y.start)
+        / (layout.range_x.end - layout.range_x.start))
+        * f64::from(layout.width))
+    .floor() as u32
         - OVERLAP_OFFSET;
 
-    let path = out_dir.join(filename).with_extension("png");
-    let root_drawing_area =
-        BitMapBackend::new(&path, (WIDTH, top_height + side_height + X_LABEL_AREA_SIZE))
-            .into_drawing_area();
-    root_drawing_area.fill(&THEME_BG)?;
+    let deck_height = (((DECK_RANGE_Y.end - DECK_RANGE_Y.start)
+        / (DECK_RANGE_X.end - DECK_RANGE_X.start))
+        * f64::from(layout.width))
+    .floor() as u32;
 
-    let (side, _) = root_drawing_area.split_vertically(side_height);
-    let (_, top) = root_drawing_area.split_vertically(side_height - OVERLAP_OFFSET);
+    let speeds_height = if chart_config.speeds {
+        2 * SPEED_HEIGHT
+    } else {
+        0
+    };
 
-    draw_side_view(track, side)?;
-    draw_top_view(track, top)?;
+    // everything below is in (scaled) pixels
+    let (side_height, top_height, deck_height, speeds_height) = (
+        layout.px(side_height),
+        layout.px(top_height),
+        layout.px(deck_height),
+        layout.px(speeds_height),
+    );
 
-    let text_style = TextStyle::from(("sans-serif", 24).into_font()).color(&THEME_FG);
+    let size = (
+        layout.px(layout.width),
+        top_height
+            + side_height
+            + layout.px(X_LABEL_AREA_SIZE)
+            + layout.px(AOA_HEIGHT)
+            + layout.px(BALL_HEIGHT)
+            + speeds_height
+            + deck_height,
+    );
+    let mut image = image::RgbImage::new(size.0, size.1);
+    let root_drawing_area = BitMapBackend::with_buffer(&mut image, size).into_drawing_area();
+    root_drawing_area.fill(&theme.bg)?;
 
-    root_drawing_area.draw_text(
-        &format!("Pilot: {}", track.pilot_name),
-        &text_style,
-        (16, 16),
-    )?;
+    {
+        let (approach, strips) = root_drawing_area
+            .split_vertically(top_height + side_height + layout.px(X_LABEL_AREA_SIZE));
+        let (side, _) = approach.split_vertically(side_height);
+        let (_, top) = approach.split_vertically(side_height - layout.px(OVERLAP_OFFSET));
+        let (aoa, rest) = strips.split_vertically(layout.px(AOA_HEIGHT));
+        let (ball, rest) = rest.split_vertically(layout.px(BALL_HEIGHT));
+        let (speeds, deck) = rest.split_vertically(speeds_height);
 
-    root_drawing_area.draw_text(
-        &match track.grading {
+        let ticks = tick_marks(track, layout, chart_config.tick_interval_s);
+        draw_side_view(track, reference, &ticks, theme, layout, side)?;
+        draw_top_view(track, reference, &ticks, theme, layout, top)?;
+        draw_aoa_view(track, theme, layout, aoa)?;
+        draw_ball_view(track, theme, layout, ball)?;
+        if chart_config.speeds {
+            let (sink_rate, closure) = speeds.split_vertically(layout.px(SPEED_HEIGHT));
+            draw_speeds_view(track, theme, layout, sink_rate, closure)?;
+        }
+        draw_deck_view(track, theme, layout, deck)?;
+    }
+
+    let line_style = TextStyle::from(("sans-serif", layout.px(24)).into_font()).color(&theme.fg);
+
+    let mut lines = vec![
+        Cow::Owned(format!("Pilot: {}", track.pilot_name)),
+        track
+            .server_name
+            .as_deref()
+            .map(|server_name| Cow::Owned(format!("Server: {}", server_name)))
+            .unwrap_or_default(),
+        match track.grading {
             Grading::Unknown => Cow::Borrowed(""),
-            Grading::Bolter => Cow::Borrowed("Bolter"),
+            Grading::Bolter | Grading::TouchAndGo => track.grading.summary(track.hook_up),
+            Grading::Incident(incident) => Cow::Borrowed(incident.as_str()),
+            Grading::Spin => Cow::Borrowed("Spin"),
+            Grading::OwnWaveOff => Cow::Borrowed("Own wave-off"),
             Grading::Recovered { cable, .. } => cable
                 .map(|c| Cow::Owned(format!("Cable {}", c)))
                 .unwrap_or(Cow::Borrowed("(failed to detect cable)")),
         },
-        &text_style,
-        (16, 48),
-    )?;
+    ];
+    if track.is_night() {
+        lines.push(Cow::Borrowed("Night"));
+    }
+    if let Some(dcs_grading) = &track.dcs_grading {
+        let mut grade = format!("LSO: {}", dcs_grading.grade.as_str());
+        for deviation in &dcs_grading.deviations {
+            grade.push(' ');
+            grade.push_str(deviation);
+        }
+        lines.push(Cow::Owned(grade));
+    }
+    if let Some(groove_time) = track.groove_time() {
+        lines.push(Cow::Owned(format!("Groove: {:.0}s", groove_time)));
+    }
+    if let Some(ramp_clearance) = track.ramp_clearance {
+        lines.push(Cow::Owned(format!("Ramp: {:.0}ft", ramp_clearance)));
+    }
+    if let Some(touchdown_g) = track.touchdown_g {
+        lines.push(Cow::Owned(if track.is_overstress() {
+            format!("Touchdown: {:.1}G (potential overstress)", touchdown_g)
+        } else {
+            format!("Touchdown: {:.1}G", touchdown_g)
+        }));
+    }
+    if let Some(start) = track.start() {
+        let deviations = start.deviations();
+        lines.push(Cow::Owned(if deviations.is_empty() {
+            "Start: good".to_string()
+        } else {
+            format!("Start: {}", deviations.join(", "))
+        }));
+    }
+    if let Some((fb, change)) = track.fb() {
+        lines.push(Cow::Owned(if change.abs() >= 1.0 {
+            format!("FB: {:03.0}° (turned {:+.0}° during pass)", fb, change)
+        } else {
+            format!("FB: {:03.0}°", fb)
+        }));
+    }
+    if let Some(touchdown) = &track.touchdown {
+        lines.push(Cow::Owned(format!("Touchdown: {}", touchdown)));
+    }
+    if let Some(deck_motion) = track.deck_motion.filter(|m| !m.is_steady()) {
+        lines.push(Cow::Owned(format!("Deck: {}", deck_motion)));
+    }
+    if track.interrupted {
+        lines.push(Cow::Borrowed("(recording interrupted)"));
+    }
+    if let Some(unit_name) = &track.foul_deck {
+        lines.push(Cow::Owned(format!("(foul deck: {})", unit_name)));
+    }
+    if track.carrier_turning {
+        lines.push(Cow::Borrowed("(carrier turning during pass)"));
+    }
+    lines.retain(|line| !line.is_empty());
+
+    // stats box
+    let mut box_width = 0;
+    for line in &lines {
+        let (w, _) = root_drawing_area.estimate_text_size(line, &line_style)?;
+        box_width = box_width.max(w as i32);
+    }
+    let box_height = 32 * lines.len() as i32;
+    let box_corners = [
+        layout.point(8, 8),
+        (
+            box_width + layout.point(24, 0).0,
+            layout.point(0, box_height + 16).1,
+        ),
+    ];
+    root_drawing_area.draw(&Rectangle::new(box_corners, theme.bg.mix(0.8).filled()))?;
+    root_drawing_area.draw(&Rectangle::new(box_corners, theme.guide_gray.mix(0.6)))?;
+    for (i, line) in lines.iter().enumerate() {
+        root_drawing_area.draw_text(line, &line_style, layout.point(16, 16 + 32 * i as i32))?;
+    }
+
+    // AoA legend
+    let (mut x, y) = layout.point(16, box_height + 32);
+    for (label, color) in [
+        ("Fast", theme.aoa_fast),
+        ("Slightly fast", theme.aoa_slightly_fast),
+        ("On speed", theme.aoa_on_speed),
+        ("Slightly slow", theme.aoa_slightly_slow),
+        ("Slow", theme.aoa_slow),
+    ] {
+        let (swatch_offset, swatch_size) = layout.point(4, 14);
+        root_drawing_area.draw(&Rectangle::new(
+            [
+                (x, y + swatch_offset),
+                (x + swatch_size, y + swatch_offset + swatch_size),
+            ],
+            color.filled(),
+        ))?;
+        let label_offset = layout.point(20, 0).0;
+        root_drawing_area.draw_text(label, &text_style(theme, layout), (x + label_offset, y))?;
+        let (w, _) = root_drawing_area.estimate_text_size(label, &text_style(theme, layout))?;
+        x += w as i32 + layout.point(40, 0).0;
+    }
 
+    root_drawing_area.present()?;
     std::mem::drop(root_drawing_area);
 
-    Ok(path)
+    Ok(image)
 }
 
 #[tracing::instrument(skip_all)]
 pub fn draw_top_view(
     track: &TrackResult,
+    reference: Option<&TrackResult>,
+    ticks: &[Datum],
+    theme: &Theme,
+    layout: &Layout,
     canvas: DrawingArea<BitMapBackend<'_, RGBPixel>, Shift>,
 ) -> Result<(), DrawError> {
     let mut chart = ChartBuilder::on(&canvas)
         .margin(0u32)
-        .x_label_area_size(X_LABEL_AREA_SIZE)
+        .x_label_area_size(layout.px(X_LABEL_AREA_SIZE))
         .y_label_area_size(0u32)
         .build_cartesian_2d(
-            CustomRange(RANGE_X.with_key_points(vec![0.25f64, 0.5, 0.75, 1.0])),
-            TOP_RANGE_Y,
+            CustomRange(layout.range_x.clone().with_key_points(layout.key_points())),
+            layout.top_range_y.clone(),
         )?;
 
     // Then we can draw a mesh
@@ -110,13 +441,13 @@ pub fn draw_top_view(
         .configure_mesh()
         .disable_mesh()
         .disable_y_axis()
-        .axis_style(THEME_FG)
-        .x_label_style(text_style())
+        .axis_style(theme.fg)
+        .x_label_style(text_style(theme, layout))
         .draw()?;
 
     // carrier top image is 300x300px which corresponds to 115x115m
     let (w, _h) = canvas.dim_in_pixel();
-    let a = nm_to_m(RANGE_X.end - RANGE_X.start);
+    let a = nm_to_m(layout.range_x.end - layout.range_x.start);
     let m2px = f64::from(w) / a;
     let img_size = ((115.0 * m2px) as u32, (115.0 * m2px) as u32);
     let img_carrier_top = image::load_from_memory_with_format(
@@ -136,63 +467,74 @@ pub fn draw_top_view(
     // Procedures, Figure 5
     let lines = [
         // 0.25degree on center line
-        (0.25f64, THEME_GUIDE_GRAY),
+        (0.25f64, theme.guide_gray),
         // orange
-        (0.75, THEME_GUIDE_GREEN),
+        (0.75, theme.guide_green),
         // red
-        (3.0, THEME_GUIDE_YELLOW),
+        (3.0, theme.guide_yellow),
         // red
-        (6.0, THEME_GUIDE_RED),
+        (6.0, theme.guide_red),
     ];
 
     for (deg, color) in lines {
-        let y = deg.to_radians().tan() * RANGE_X.end;
+        let y = deg.to_radians().tan() * layout.range_x.end;
         chart.draw_series(LineSeries::new(
-            [(0.0, 0.0), (RANGE_X.end, y)],
+            [(0.0, 0.0), (layout.range_x.end, y)],
             color.mix(0.4),
         ))?;
         chart.draw_series(LineSeries::new(
-            [(0.0, 0.0), (RANGE_X.end, y.neg())],
+            [(0.0, 0.0), (layout.range_x.end, y.neg())],
             color.mix(0.4),
         ))?;
     }
 
-    let mut track_in_nm = track
+    // draw ideal pass (on centerline)
+    chart.draw_series(LineSeries::new(
+        [(0.0, 0.0), (layout.range_x.end, 0.0)],
+        theme.aoa_on_speed.mix(0.2).stroke_width(layout.px(6)),
+    ))?;
+
+    // draw the pass to compare with
+    if let Some(reference) = reference {
+        chart.draw_series(LineSeries::new(
+            decreasing_x(
+                reference
+                    .datums
+                    .iter()
+                    .map(|d| Datum {
+                        x: m_to_nm(d.x),
+                        y: m_to_nm(d.y),
+                        ..*d
+                    })
+                    .filter(|d| layout.range_x.contains(&d.x) && layout.top_range_y.contains(&d.y)),
+            )
+            .map(|d| (d.x, d.y)),
+            theme.fg.mix(0.5).stroke_width(layout.px(2)),
+        ))?;
+    }
+
+    let track_in_nm = track
         .datums
         .iter()
         .map(|d| Datum {
             x: m_to_nm(d.x),
             y: m_to_nm(d.y),
-            aoa: d.aoa,
-            alt: d.alt,
+            ..*d
         })
-        .filter(|d| RANGE_X.contains(&d.x) && TOP_RANGE_Y.contains(&d.y));
-
-    // filter out datums with an x that is not continuously getting smaller (as drawing the series
-    // will explode otherwise)
-    let mut x_before = f64::MAX;
-    let track_in_nm = std::iter::from_fn(move || {
-        for datum in &mut track_in_nm {
-            if datum.x < x_before {
-                x_before = datum.x;
-                return Some(datum);
-            }
-        }
-
-        None
-    });
+        .filter(|d| layout.range_x.contains(&d.x) && layout.top_range_y.contains(&d.y));
+    let track_in_nm = decreasing_x(track_in_nm);
 
     // draw approach shadow
     chart.draw_series(LineSeries::new(
         track_in_nm.clone().map(|d| (d.x, d.y)),
-        THEME_BG.stroke_width(4),
+        theme.bg.stroke_width(layout.px(4)),
     ))?;
 
     // draw approach
     let mut points = Vec::new();
-    let mut color = THEME_AOA_ON_SPEED;
+    let mut color = theme.aoa_on_speed;
     for datum in track_in_nm {
-        let next_color = aoa_color(datum.aoa, track.plane_info);
+        let next_color = aoa_color(datum.aoa, track.plane_info, theme);
         let point = (datum.x, datum.y);
 
         if points.is_empty() {
@@ -204,7 +546,7 @@ pub fn draw_top_view(
 
             chart.draw_series(LineSeries::new(
                 points.iter().cloned(),
-                color.stroke_width(2),
+                color.stroke_width(layout.px(2)),
             ))?;
 
             points.clear();
@@ -217,15 +559,64 @@ pub fn draw_top_view(
     if !points.is_empty() {
         chart.draw_series(LineSeries::new(
             points.iter().cloned(),
-            color.stroke_width(2),
+            color.stroke_width(layout.px(2)),
         ))?;
     }
+
+    // draw tick marks
+    chart.draw_series(
+        ticks
+            .iter()
+            .map(|d| (m_to_nm(d.x), m_to_nm(d.y)))
+            .filter(|(_, y)| layout.top_range_y.contains(y))
+            .map(|point| Circle::new(point, layout.px(4), theme.fg.stroke_width(layout.px(2)))),
+    )?;
+
+    // mark the segments of the groove, with an arrow for the lateral drift within each of them
+    // (pointing up for drifting right)
+    let trends = track.trends();
+    let label_y = layout.top_range_y.start * 0.8;
+    for segment in Segment::ALL {
+        let (from, to) = segment.range();
+        chart.draw_series(LineSeries::new(
+            [
+                (from, layout.top_range_y.start),
+                (from, layout.top_range_y.end),
+            ],
+            theme.fg.mix(0.15),
+        ))?;
+        let label = ((from + to) / 2.0, label_y);
+        chart.draw_series(std::iter::once(Text::new(
+            segment.as_str(),
+            label,
+            text_style(theme, layout),
+        )))?;
+        let drift = segment_trends(&trends, segment).find_map(|trend| match trend {
+            Trend::DriftingLeft => Some(false),
+            Trend::DriftingRight => Some(true),
+            _ => None,
+        });
+        if let Some(up) = drift {
+            chart.draw_series(std::iter::once(
+                EmptyElement::at(label)
+                    + PathElement::new(
+                        trend_arrow(up, layout),
+                        theme.fg.stroke_width(layout.px(2)),
+                    ),
+            ))?;
+        }
+    }
+
     Ok(())
 }
 
 #[tracing::instrument(skip_all)]
 pub fn draw_side_view(
     track: &TrackResult,
+    reference: Option<&TrackResult>,
+    ticks: &[Datum],
+    theme: &Theme,
+    layout: &Layout,
     canvas: DrawingArea<BitMapBackend<'_, RGBPixel>, Shift>,
 ) -> Result<(), DrawError> {
     let mut chart = ChartBuilder::on(&canvas)
@@ -233,8 +624,8 @@ pub fn draw_side_view(
         .x_label_area_size(0u32)
         .y_label_area_size(0u32)
         .build_cartesian_2d(
-            CustomRange(RANGE_X.with_key_points(vec![0.25f64, 0.5, 0.75, 1.0])),
-            SIDE_RANGE_Y,
+            CustomRange(layout.range_x.clone().with_key_points(layout.key_points())),
+            layout.side_range_y.clone(),
         )?;
 
     // Then we can draw a mesh
@@ -243,13 +634,13 @@ pub fn draw_side_view(
         .disable_mesh()
         .disable_x_axis()
         .disable_y_axis()
-        .axis_style(THEME_FG)
-        .x_label_style(text_style())
+        .axis_style(theme.fg)
+        .x_label_style(text_style(theme, layout))
         .draw()?;
 
     // carrier side image is 300x150px which corresponds to 115x57.5m
     let (w, _h) = canvas.dim_in_pixel();
-    let a = nm_to_m(RANGE_X.end - RANGE_X.start);
+    let a = nm_to_m(layout.range_x.end - layout.range_x.start);
     let m2px = f64::from(w) / a;
     let img_size = ((115.0 * m2px) as u32, (57.5 * m2px) as u32);
     let img_carrier_side = image::load_from_memory_with_format(
@@ -261,62 +652,78 @@ pub fn draw_side_view(
     chart.draw_series(std::iter::once(elem))?;
 
     // draw centerline
+    let glide_slope = track.carrier_info.lens_basic_angle;
     let lines = [
-        (track.plane_info.glide_slope - 0.9, THEME_GUIDE_RED),
-        (track.plane_info.glide_slope - 0.6, THEME_GUIDE_YELLOW),
-        (track.plane_info.glide_slope - 0.25, THEME_GUIDE_GREEN),
-        (track.plane_info.glide_slope, THEME_GUIDE_GRAY),
-        (track.plane_info.glide_slope + 0.25, THEME_GUIDE_GREEN),
-        (track.plane_info.glide_slope + 0.7, THEME_GUIDE_YELLOW),
-        (track.plane_info.glide_slope + 1.5, THEME_GUIDE_RED),
+        (glide_slope - 0.9, theme.guide_red),
+        (glide_slope - 0.6, theme.guide_yellow),
+        (glide_slope - 0.25, theme.guide_green),
+        (glide_slope, theme.guide_gray),
+        (glide_slope + 0.25, theme.guide_green),
+        (glide_slope + 0.7, theme.guide_yellow),
+        (glide_slope + 1.5, theme.guide_red),
     ];
 
     for (deg, color) in lines {
-        let mut x = RANGE_X.end;
-        let mut y = nm_to_ft(deg.to_radians().tan() * RANGE_X.end);
-        if y > SIDE_RANGE_Y.end {
-            x = ft_to_nm(SIDE_RANGE_Y.end) / deg.to_radians().tan();
-            y = SIDE_RANGE_Y.end;
+        let mut x = layout.range_x.end;
+        let mut y = nm_to_ft(deg.to_radians().tan() * layout.range_x.end);
+        if y > layout.side_range_y.end {
+            x = ft_to_nm(layout.side_range_y.end) / deg.to_radians().tan();
+            y = layout.side_range_y.end;
         }
         chart.draw_series(LineSeries::new([(0.0, 0.0), (x, y)], color.mix(0.4)))?;
+
+        // draw ideal pass (on glide slope)
+        if deg == glide_slope {
+            chart.draw_series(LineSeries::new(
+                [(0.0, 0.0), (x, y)],
+                theme.aoa_on_speed.mix(0.2).stroke_width(layout.px(6)),
+            ))?;
+        }
     }
 
-    let mut track_descent = track
+    // draw the pass to compare with
+    if let Some(reference) = reference {
+        chart.draw_series(LineSeries::new(
+            decreasing_x(
+                reference
+                    .datums
+                    .iter()
+                    .map(|d| Datum {
+                        x: m_to_nm(d.x),
+                        alt: m_to_ft(d.alt),
+                        ..*d
+                    })
+                    .filter(|d| {
+                        layout.range_x.contains(&d.x) && layout.side_range_y.contains(&d.alt)
+                    }),
+            )
+            .map(|d| (d.x, d.alt)),
+            theme.fg.mix(0.5).stroke_width(layout.px(2)),
+        ))?;
+    }
+
+    let track_descent = track
         .datums
         .iter()
         .map(|d| Datum {
             x: m_to_nm(d.x),
-            y: d.y,
-            aoa: d.aoa,
             alt: m_to_ft(d.alt),
+            ..*d
         })
-        .filter(|d| RANGE_X.contains(&d.x) && SIDE_RANGE_Y.contains(&d.alt));
-
-    // filter out datums with an x that is not continuously getting smaller (as drawing the series
-    // will explode otherwise)
-    let mut x_before = f64::MAX;
-    let track_descent = std::iter::from_fn(move || {
-        for datum in &mut track_descent {
-            if datum.x < x_before {
-                x_before = datum.x;
-                return Some(datum);
-            }
-        }
-
-        None
-    });
+        .filter(|d| layout.range_x.contains(&d.x) && layout.side_range_y.contains(&d.alt));
+    let track_descent = decreasing_x(track_descent);
 
     // draw approach shadow
     chart.draw_series(LineSeries::new(
         track_descent.clone().map(|d| (d.x, d.alt)),
-        THEME_BG.stroke_width(4),
+        theme.bg.stroke_width(layout.px(4)),
     ))?;
 
     // draw approach
     let mut points = Vec::new();
-    let mut color = THEME_AOA_ON_SPEED;
+    let mut color = theme.aoa_on_speed;
     for datum in track_descent {
-        let next_color = aoa_color(datum.aoa, track.plane_info);
+        let next_color = aoa_color(datum.aoa, track.plane_info, theme);
 
         let point = (datum.x, datum.alt);
 
@@ -329,7 +736,7 @@ pub fn draw_side_view(
 
             chart.draw_series(LineSeries::new(
                 points.iter().cloned(),
-                color.stroke_width(2),
+                color.stroke_width(layout.px(2)),
             ))?;
 
             points.clear();
@@ -342,24 +749,696 @@ pub fn draw_side_view(
     if !points.is_empty() {
         chart.draw_series(LineSeries::new(
             points.iter().cloned(),
-            color.stroke_width(2),
+            color.stroke_width(layout.px(2)),
         ))?;
     }
 
+    // mark the hook's clearance over the ramp
+    if let Some(ramp_clearance) = track.ramp_clearance {
+        let x = m_to_nm(track.carrier_info.ramp_distance(&track.rigged_wires));
+        if layout.side_range_y.contains(&ramp_clearance) {
+            chart.draw_series(std::iter::once(Cross::new(
+                (x, ramp_clearance),
+                layout.px(6),
+                ramp_color(ramp_clearance, theme).stroke_width(layout.px(2)),
+            )))?;
+        }
+    }
+
+    // draw tick marks
+    chart.draw_series(
+        ticks
+            .iter()
+            .map(|d| (m_to_nm(d.x), m_to_ft(d.alt)))
+            .filter(|(_, alt)| layout.side_range_y.contains(alt))
+            .map(|point| Circle::new(point, layout.px(4), theme.fg.stroke_width(layout.px(2)))),
+    )?;
+
     Ok(())
 }
 
-fn text_style() -> TextStyle<'static> {
-    TextStyle::from(("sans-serif", 20).into_font()).color(&THEME_FG)
+/// Draw the AoA over the distance, with the plane's AoA brackets shaded in the background.
+#[tracing::instrument(skip_all)]
+pub fn draw_aoa_view(
+    track: &TrackResult,
+    theme: &Theme,
+    layout: &Layout,
+    canvas: DrawingArea<BitMapBackend<'_, RGBPixel>, Shift>,
+) -> Result<(), DrawError> {
+    // Center the chart on the on-speed bracket, which is determined by sampling the plane's AoA
+    // rating.
+    let on_speed = (0..400)
+        .map(|i| f64::from(i) * 0.05)
+        .filter(|aoa| matches!(track.plane_info.aoa_rating(*aoa), Aoa::OnSpeed))
+        .collect::<Vec<_>>();
+    let center = match (on_speed.first(), on_speed.last()) {
+        (Some(first), Some(last)) => (first + last) / 2.0,
+        _ => 8.0,
+    };
+    let range_y = (center - AOA_SPAN)..(center + AOA_SPAN);
+
+    let mut chart = ChartBuilder::on(&canvas)
+        .margin(0u32)
+        .x_label_area_size(0u32)
+        .y_label_area_size(0u32)
+        .build_cartesian_2d(layout.range_x.clone(), range_y.clone())?;
+
+    // shade the brackets
+    let step = 0.05;
+    chart.draw_series((0..((AOA_SPAN * 2.0 / step) as usize)).map(|i| {
+        let aoa = range_y.start + i as f64 * step;
+        Rectangle::new(
+            [
+                (layout.range_x.start, aoa),
+                (layout.range_x.end, aoa + step),
+            ],
+            aoa_color(aoa + step / 2.0, track.plane_info, theme)
+                .mix(0.15)
+                .filled(),
+        )
+    }))?;
+
+    let datums = decreasing_x(
+        track
+            .datums
+            .iter()
+            .map(|d| Datum {
+                x: m_to_nm(d.x),
+                ..*d
+            })
+            .filter(|d| layout.range_x.contains(&d.x)),
+    );
+    chart.draw_series(LineSeries::new(
+        datums.map(|d| (d.x, d.aoa.clamp(range_y.start, range_y.end))),
+        theme.fg.stroke_width(layout.px(2)),
+    ))?;
+
+    let label = match track.groove_aoa() {
+        Some(aoa) => format!("AoA, groove avg. {}", track.plane_info.format_aoa(aoa)),
+        None => "AoA".to_string(),
+    };
+    canvas.draw_text(&label, &text_style(theme, layout), layout.point(16, 8))?;
+
+    Ok(())
+}
+
+/// Draw the history of the (simulated) meatball over the distance.
+#[tracing::instrument(skip_all)]
+pub fn draw_ball_view(
+    track: &TrackResult,
+    theme: &Theme,
+    layout: &Layout,
+    canvas: DrawingArea<BitMapBackend<'_, RGBPixel>, Shift>,
+) -> Result<(), DrawError> {
+    let mut chart = ChartBuilder::on(&canvas)
+        .margin(0u32)
+        .x_label_area_size(0u32)
+        .y_label_area_size(0u32)
+        .build_cartesian_2d(layout.range_x.clone(), BALL_RANGE_Y)?;
+
+    // draw the datum lights and the edges of the lens
+    for (cells, color) in [
+        (0.0, theme.guide_green),
+        (6.0, theme.guide_gray),
+        (-6.0, theme.guide_gray),
+    ] {
+        chart.draw_series(LineSeries::new(
+            [(layout.range_x.start, cells), (layout.range_x.end, cells)],
+            color.mix(0.4),
+        ))?;
+    }
+
+    let datums = decreasing_x(
+        track
+            .datums
+            .iter()
+            .map(|d| Datum {
+                x: m_to_nm(d.x),
+                ..*d
+            })
+            .filter(|d| layout.range_x.contains(&d.x)),
+    );
+    chart.draw_series(datums.map(|d| {
+        Circle::new(
+            (d.x, d.ball),
+            layout.px(2),
+            ball_color(d.ball, theme).filled(),
+        )
+    }))?;
+
+    // mark the segments of the groove, with an arrow for the trend of the ball within each of
+    // them
+    let trends = track.trends();
+    let label_y = BALL_RANGE_Y.end;
+    for segment in Segment::ALL {
+        let (from, to) = segment.range();
+        chart.draw_series(LineSeries::new(
+            [(from, BALL_RANGE_Y.start), (from, BALL_RANGE_Y.end)],
+            theme.fg.mix(0.15),
+        ))?;
+        let label = ((from + to) / 2.0, label_y);
+        chart.draw_series(std::iter::once(Text::new(
+            segment.as_str(),
+            label,
+            text_style(theme, layout),
+        )))?;
+        let vertical = segment_trends(&trends, segment).find_map(|trend| match trend {
+            Trend::ComingUp => Some(true),
+            Trend::GoingDown => Some(false),
+            _ => None,
+        });
+        if let Some(up) = vertical {
+            chart.draw_series(std::iter::once(
+                EmptyElement::at(label)
+                    + PathElement::new(
+                        trend_arrow(up, layout),
+                        theme.fg.stroke_width(layout.px(2)),
+                    ),
+            ))?;
+        }
+    }
+
+    canvas.draw_text("Ball", &text_style(theme, layout), layout.point(16, 8))?;
+
+    Ok(())
+}
+
+/// Draw the sink rate and the closure speed over the distance.
+#[tracing::instrument(skip_all)]
+pub fn draw_speeds_view(
+    track: &TrackResult,
+    theme: &Theme,
+    layout: &Layout,
+    sink_rate_canvas: DrawingArea<BitMapBackend<'_, RGBPixel>, Shift>,
+    closure_canvas: DrawingArea<BitMapBackend<'_, RGBPixel>, Shift>,
+) -> Result<(), DrawError> {
+    // (x in nm, sink rate in ft/min, closure in kts)
+    let mut rates: Vec<(f64, f64, f64)> = Vec::with_capacity(track.datums.len());
+    let mut start = 0;
+    for (i, datum) in track.datums.iter().enumerate() {
+        while datum.time - track.datums[start].time > RATE_WINDOW {
+            start += 1;
+        }
+        let from = &track.datums[start];
+        let dt = datum.time - from.time;
+        let x = m_to_nm(datum.x);
+        // same as for the other panels, only keep datums with a continuously decreasing x
+        if dt <= 0.0 || !layout.range_x.contains(&x) || rates.last().is_some_and(|r| x >= r.0) {
+            continue;
+        }
+
+        let window = &track.datums[start..=i];
+        let vs = window.iter().map(|d| d.vs).sum::<f64>() / window.len() as f64;
+        rates.push((
+            x,
+            m_to_ft(vs.neg()) * 60.0,
+            ms_to_kts((from.x - datum.x) / dt),
+        ));
+    }
+
+    // sink rate
+    let mut chart = ChartBuilder::on(&sink_rate_canvas)
+        .margin(0u32)
+        .x_label_area_size(0u32)
+        .y_label_area_size(0u32)
+        .build_cartesian_2d(layout.range_x.clone(), SINK_RATE_RANGE_Y)?;
+    // place the labels 70px (before scaling) from the right edge
+    let label_x = layout.range_x.end
+        - 70.0 * (layout.range_x.end - layout.range_x.start) / f64::from(layout.width);
+    for sink_rate in [0.0, 500.0, 1000.0, 1500.0] {
+        chart.draw_series(LineSeries::new(
+            [
+                (layout.range_x.start, sink_rate),
+                (layout.range_x.end, sink_rate),
+            ],
+            theme.guide_gray.mix(0.4),
+        ))?;
+        chart.draw_series(std::iter::once(Text::new(
+            format!("{}", sink_rate),
+            (label_x, sink_rate + 300.0),
+            text_style(theme, layout),
+        )))?;
+    }
+    chart.draw_series(LineSeries::new(
+        rates.iter().map(|(x, sink_rate, _)| {
+            (
+                *x,
+                sink_rate.clamp(SINK_RATE_RANGE_Y.start, SINK_RATE_RANGE_Y.end),
+            )
+        }),
+        theme.fg.stroke_width(layout.px(2)),
+    ))?;
+    sink_rate_canvas.draw_text(
+        "Sink rate (ft/min)",
+        &text_style(theme, layout),
+        layout.point(16, 8),
+    )?;
+
+    // closure
+    let (min, max) = rates
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(min, max), (_, _, closure)| {
+            (min.min(*closure), max.max(*closure))
+        });
+    let range_y = if min <= max {
+        (min - 5.0)..(max + 5.0)
+    } else {
+        100.0..160.0
+    };
+    let mut chart = ChartBuilder::on(&closure_canvas)
+        .margin(0u32)
+        .x_label_area_size(0u32)
+        .y_label_area_size(0u32)
+        .build_cartesian_2d(layout.range_x.clone(), range_y.clone())?;
+    chart.draw_series(LineSeries::new(
+        rates.iter().map(|(x, _, closure)| (*x, *closure)),
+        theme.fg.stroke_width(layout.px(2)),
+    ))?;
+    closure_canvas.draw_text(
+        &format!(
+            "Closure ({:.0}-{:.0} kts)",
+            range_y.start + 5.0,
+            range_y.end - 5.0
+        ),
+        &text_style(theme, layout),
+        layout.point(16, 8),
+    )?;
+
+    Ok(())
+}
+
+#[tracing::instrument(skip_all)]
+pub fn draw_deck_view(
+    track: &TrackResult,
+    theme: &Theme,
+    layout: &Layout,
+    canvas: DrawingArea<BitMapBackend<'_, RGBPixel>, Shift>,
+) -> Result<(), DrawError> {
+    let mut chart = ChartBuilder::on(&canvas)
+        .margin(0u32)
+        .x_label_area_size(0u32)
+        .y_label_area_size(0u32)
+        .build_cartesian_2d(DECK_RANGE_X, DECK_RANGE_Y)?;
+
+    draw_landing_area(
+        &mut chart,
+        track.carrier_info,
+        &track.rigged_wires,
+        theme,
+        layout,
+    )?;
+
+    // draw the hook's path after touching down
+    chart.draw_series(LineSeries::new(
+        track
+            .touchdown
+            .iter()
+            .chain(&track.rollout)
+            .map(deck_point)
+            .filter(|(x, y)| DECK_RANGE_X.contains(x) && DECK_RANGE_Y.contains(y)),
+        theme.guide_yellow.mix(0.6).stroke_width(layout.px(2)),
+    ))?;
+
+    // draw touchdown point
+    if let Some(touchdown) = &track.touchdown {
+        chart.draw_series(std::iter::once(Circle::new(
+            deck_point(touchdown),
+            layout.px(6),
+            theme.guide_yellow.filled(),
+        )))?;
+    }
+
+    Ok(())
+}
+
+/// Draw the hook touchdown points of multiple passes onto a single landing area diagram (the
+/// classic LSO spotting chart). Touchdowns of traps are drawn in green, all others in red.
+#[tracing::instrument(skip_all)]
+pub fn draw_spotting_chart(
+    path: &std::path::Path,
+    title: &str,
+    carrier_info: &CarrierInfo,
+    touchdowns: &[(DeckPosition, bool)],
+    theme: &Theme,
+    layout: &Layout,
+) -> Result<(), DrawError> {
+    let deck_height = (((DECK_RANGE_Y.end - DECK_RANGE_Y.start)
+        / (DECK_RANGE_X.end - DECK_RANGE_X.start))
+        * f64::from(layout.width))
+    .floor() as u32;
+
+    let root_drawing_area = BitMapBackend::new(
+        path,
+        (
+            layout.px(layout.width),
+            layout.px(SPOTTING_TITLE_HEIGHT + deck_height),
+        ),
+    )
+    .into_drawing_area();
+    root_drawing_area.fill(&theme.bg)?;
+
+    let text_style = TextStyle::from(("sans-serif", layout.px(24)).into_font()).color(&theme.fg);
+    root_drawing_area.draw_text(title, &text_style, layout.point(16, 16))?;
+
+    let (_, deck) = root_drawing_area.split_vertically(layout.px(SPOTTING_TITLE_HEIGHT));
+    let mut chart = ChartBuilder::on(&deck)
+        .margin(0u32)
+        .x_label_area_size(0u32)
+        .y_label_area_size(0u32)
+        .build_cartesian_2d(DECK_RANGE_X, DECK_RANGE_Y)?;
+
+    draw_landing_area(&mut chart, carrier_info, &ALL_WIRES, theme, layout)?;
+
+    chart.draw_series(
+        touchdowns
+            .iter()
+            .map(|(touchdown, trapped)| (deck_point(touchdown), trapped))
+            .filter(|((x, y), _)| DECK_RANGE_X.contains(x) && DECK_RANGE_Y.contains(y))
+            .map(|(point, trapped)| {
+                let color = if *trapped {
+                    theme.guide_green
+                } else {
+                    theme.guide_red
+                };
+                Circle::new(point, layout.px(5), color.mix(0.7).filled())
+            }),
+    )?;
+
+    std::mem::drop(deck);
+    std::mem::drop(root_drawing_area);
+
+    Ok(())
+}
+
+/// Draw the GPA and boarding rate of a pilot per day, and how often they caught each wire.
+pub fn draw_pilot_stats(
+    path: &std::path::Path,
+    stats: &PilotStats,
+    theme: &Theme,
+    layout: &Layout,
+) -> Result<(), DrawError> {
+    let root_drawing_area = BitMapBackend::new(
+        path,
+        (
+            layout.px(layout.width),
+            layout.px(SPOTTING_TITLE_HEIGHT + 3 * STATS_PANEL_HEIGHT),
+        ),
+    )
+    .into_drawing_area();
+    root_drawing_area.fill(&theme.bg)?;
+
+    let title = format!(
+        "{}: {} passes, {} traps ({} at night){}",
+        stats.pilot,
+        stats.passes,
+        stats.traps,
+        stats.night_traps,
+        stats
+            .gpa
+            .map(|gpa| format!(", GPA {:.2}", gpa))
+            .unwrap_or_default()
+    );
+    let title_style = TextStyle::from(("sans-serif", layout.px(24)).into_font()).color(&theme.fg);
+    root_drawing_area.draw_text(&title, &title_style, layout.point(16, 16))?;
+
+    let (_, panels) = root_drawing_area.split_vertically(layout.px(SPOTTING_TITLE_HEIGHT));
+    let panels = panels.split_evenly((3, 1));
+    let range_x = -0.5..(stats.trend.len() as f64 - 0.5).max(0.5);
+
+    // GPA and boarding rate per day
+    for (canvas, label, range_y, guides, values) in [
+        (
+            &panels[0],
+            "GPA",
+            0.0..5.5,
+            vec![
+                (2.0, theme.guide_red),
+                (3.0, theme.guide_yellow),
+                (4.0, theme.guide_green),
+            ],
+            stats.trend.iter().map(|d| d.gpa).collect::<Vec<_>>(),
+        ),
+        (
+            &panels[1],
+            "Boarding rate",
+            0.0..1.1,
+            vec![(0.5, theme.guide_gray), (1.0, theme.guide_gray)],
+            stats
+                .trend
+                .iter()
+                .map(|d| d.boarding_rate)
+                .collect::<Vec<_>>(),
+        ),
+    ] {
+        let mut chart = ChartBuilder::on(canvas)
+            .margin(0u32)
+            .x_label_area_size(0u32)
+            .y_label_area_size(0u32)
+            .build_cartesian_2d(range_x.clone(), range_y)?;
+        for (y, color) in guides {
+            chart.draw_series(LineSeries::new(
+                [(range_x.start, y), (range_x.end, y)],
+                color.mix(0.4),
+            ))?;
+        }
+        let points = values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| Some((i as f64, (*v)?)))
+            .collect::<Vec<_>>();
+        chart.draw_series(LineSeries::new(
+            points.iter().copied(),
+            theme.fg.stroke_width(layout.px(2)),
+        ))?;
+        chart.draw_series(
+            points
+                .iter()
+                .map(|p| Circle::new(*p, layout.px(4), theme.fg.filled())),
+        )?;
+        canvas.draw_text(label, &text_style(theme, layout), layout.point(16, 8))?;
+    }
+
+    // label the first and the last day
+    let (_, height) = panels[1].dim_in_pixel();
+    let date_style = text_style(theme, layout);
+    if let Some(first) = stats.trend.first() {
+        panels[1].draw_text(
+            &first.date,
+            &date_style,
+            (layout.px(16) as i32, height as i32 - layout.px(28) as i32),
+        )?;
+    }
+    if let Some(last) = stats.trend.last().filter(|_| stats.trend.len() > 1) {
+        panels[1].draw_text(
+            &last.date,
+            &date_style,
+            (
+                layout.px(layout.width) as i32 - layout.px(130) as i32,
+                height as i32 - layout.px(28) as i32,
+            ),
+        )?;
+    }
+
+    // wire distribution
+    let max = stats.wires.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let mut chart = ChartBuilder::on(&panels[2])
+        .margin(layout.px(8))
+        .x_label_area_size(0u32)
+        .y_label_area_size(0u32)
+        .build_cartesian_2d(0.5..4.5, 0.0..(max * 1.3))?;
+    chart.draw_series(stats.wires.iter().enumerate().map(|(i, count)| {
+        let wire = i as f64 + 1.0;
+        let color = if wire == 3.0 {
+            theme.guide_green
+        } else if wire == 1.0 {
+            theme.guide_red
+        } else {
+            theme.guide_yellow
+        };
+        Rectangle::new(
+            [(wire - 0.3, 0.0), (wire + 0.3, *count as f64)],
+            color.mix(0.7).filled(),
+        )
+    }))?;
+    chart.draw_series(stats.wires.iter().enumerate().map(|(i, count)| {
+        Text::new(
+            format!("#{}: {}", i + 1, count),
+            (i as f64 + 0.75, max * 1.2),
+            text_style(theme, layout),
+        )
+    }))?;
+    panels[2].draw_text("Wires", &text_style(theme, layout), layout.point(16, 8))?;
+
+    std::mem::drop(panels);
+    std::mem::drop(root_drawing_area);
+
+    Ok(())
+}
+
+/// Draw the landing area's centerline, its wires and the target touchdown point.
+fn draw_landing_area(
+    chart: &mut ChartContext<'_, BitMapBackend<'_>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    carrier_info: &CarrierInfo,
+    rigged_wires: &[u8],
+    theme: &Theme,
+    layout: &Layout,
+) -> Result<(), DrawError> {
+    // draw centerline
+    chart.draw_series(LineSeries::new(
+        [(DECK_RANGE_X.start, 0.0), (DECK_RANGE_X.end, 0.0)],
+        theme.guide_gray.mix(0.4),
+    ))?;
+
+    // draw wires
+    for nr in ALL_WIRES {
+        let Some(pendants) = carrier_info.cable(nr) else {
+            continue;
+        };
+        let left = deck_point(&DeckPosition::new(carrier_info, rigged_wires, pendants.0));
+        let right = deck_point(&DeckPosition::new(carrier_info, rigged_wires, pendants.1));
+        let color = if rigged_wires.contains(&nr) {
+            theme.fg.mix(1.0)
+        } else {
+            theme.guide_gray.mix(0.4)
+        };
+        chart.draw_series(LineSeries::new(
+            [left, right],
+            color.stroke_width(layout.px(2)),
+        ))?;
+        chart.draw_series(std::iter::once(Text::new(
+            format!("#{}", nr),
+            (left.0 - 4.0, DECK_RANGE_Y.start + 16.0),
+            text_style(theme, layout),
+        )))?;
+    }
+
+    // draw target touchdown point
+    chart.draw_series(std::iter::once(Cross::new(
+        (0.0, 0.0),
+        layout.px(6),
+        theme.guide_green.stroke_width(layout.px(2)),
+    )))?;
+
+    Ok(())
+}
+
+/// The datums to mark on the approach. These are either the datums at which the plane passed the
+/// labeled distances and the ramp, or (if an interval is given) the datums every `interval_s`
+/// seconds, counted back from the end of the pass.
+fn tick_marks(track: &TrackResult, layout: &Layout, interval_s: Option<f64>) -> Vec<Datum> {
+    let datums = decreasing_x(track.datums.iter().copied())
+        .filter(|d| layout.range_x.contains(&m_to_nm(d.x)))
+        .collect::<Vec<_>>();
+
+    if let Some(interval_s) = interval_s.filter(|i| *i > 0.0) {
+        let Some(end) = datums.last().map(|d| d.time) else {
+            return Vec::new();
+        };
+        let mut ticks = Vec::new();
+        let mut next = end;
+        for datum in datums.iter().rev() {
+            if datum.time <= next {
+                ticks.push(*datum);
+                next = datum.time - interval_s;
+            }
+        }
+        return ticks;
+    }
+
+    let mut distances = layout
+        .key_points()
+        .into_iter()
+        .map(nm_to_m)
+        .collect::<Vec<_>>();
+    distances.push(track.carrier_info.ramp_distance(&track.rigged_wires));
+
+    distances
+        .into_iter()
+        .filter_map(|distance| {
+            datums
+                .windows(2)
+                .find(|w| w[0].x > distance && w[1].x <= distance)
+                .map(|w| w[1])
+        })
+        .collect()
+}
+
+/// The chart coordinates of a position on the landing area.
+fn deck_point(pos: &DeckPosition) -> (f64, f64) {
+    (pos.long.neg(), pos.right)
+}
+
+/// Filter out datums with an x that is not continuously getting smaller (as drawing the series
+/// will explode otherwise).
+fn decreasing_x(
+    mut datums: impl Iterator<Item = Datum> + Clone,
+) -> impl Iterator<Item = Datum> + Clone {
+    let mut x_before = f64::MAX;
+    std::iter::from_fn(move || {
+        for datum in &mut datums {
+            if datum.x < x_before {
+                x_before = datum.x;
+                return Some(datum);
+            }
+        }
+
+        None
+    })
+}
+
+/// The trends of the given segment of the groove (see [TrackResult::trends]).
+fn segment_trends(
+    trends: &[(Segment, Vec<Trend>)],
+    segment: Segment,
+) -> impl Iterator<Item = Trend> + '_ {
+    trends
+        .iter()
+        .filter(move |(s, _)| *s == segment)
+        .flat_map(|(_, trends)| trends.iter().copied())
+}
+
+/// The path (in px, relative to a segment's label) of an arrow pointing up or down next to the
+/// label.
+fn trend_arrow(up: bool, layout: &Layout) -> Vec<(i32, i32)> {
+    let dir = if up { -1 } else { 1 };
+    [(0, -8), (0, 8), (-5, 3), (0, 8), (5, 3)]
+        .into_iter()
+        .map(|(x, y)| layout.point(36 + x, 12 + y * dir))
+        .collect()
+}
+
+fn ball_color(ball: f64, theme: &Theme) -> RGBColor {
+    if ball.abs() <= 1.0 {
+        theme.guide_green
+    } else if ball < -3.0 {
+        theme.guide_red
+    } else {
+        theme.guide_yellow
+    }
+}
+
+/// The ramp clearance (in ft) is safe above 10ft, and dangerously low below 6ft.
+fn ramp_color(ramp_clearance: f64, theme: &Theme) -> RGBColor {
+    if ramp_clearance >= 10.0 {
+        theme.guide_green
+    } else if ramp_clearance < 6.0 {
+        theme.guide_red
+    } else {
+        theme.guide_yellow
+    }
+}
+
+fn text_style<'a>(theme: &'a Theme, layout: &Layout) -> TextStyle<'a> {
+    TextStyle::from(("sans-serif", layout.px(20)).into_font()).color(&theme.fg)
 }
 
-fn aoa_color(aoa: f64, plane_info: &'static AirplaneInfo) -> RGBColor {
-    match (plane_info.aoa_rating)(aoa) {
-        Aoa::Fast => THEME_AOA_FAST,
-        Aoa::SlightlyFast => THEME_AOA_SLIGHTLY_FAST,
-        Aoa::OnSpeed => THEME_AOA_ON_SPEED,
-        Aoa::SlightlySlow => THEME_AOA_SLIGHTLY_SLOW,
-        Aoa::Slow => THEME_AOA_SLOW,
+fn aoa_color(aoa: f64, plane_info: &'static AirplaneInfo, theme: &Theme) -> RGBColor {
+    match plane_info.aoa_rating(aoa) {
+        Aoa::Fast => theme.aoa_fast,
+        Aoa::SlightlyFast => theme.aoa_slightly_fast,
+        Aoa::OnSpeed => theme.aoa_on_speed,
+        Aoa::SlightlySlow => theme.aoa_slightly_slow,
+        Aoa::Slow => theme.aoa_slow,
     }
 
     /*
@@ -1,19 +1,83 @@
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
+    // the large errors are boxed to keep the results small
+    #[cfg(feature = "grpc")]
     #[error(transparent)]
-    Grpc(#[from] tonic::Status),
+    Grpc(Box<tonic::Status>),
+    #[cfg(feature = "grpc")]
     #[error(transparent)]
     Transport(#[from] tonic::transport::Error),
     #[error(transparent)]
     Fmt(#[from] std::fmt::Error),
     #[error("failed to open file")]
     File(#[from] std::io::Error),
+    #[cfg(feature = "charts")]
     #[error("failed to draw chart")]
     Draw(#[from] crate::draw::DrawError),
     #[error("failed to parse ACMI (Tacview) file")]
     Tracview(#[from] tacview::ParseError),
+    #[error("failed to parse record {record} of the ACMI (Tacview) file")]
+    AcmiRecord {
+        /// The number of the record (starting at 1), which roughly corresponds to the line.
+        record: usize,
+        #[source]
+        source: tacview::ParseError,
+    },
+    #[cfg(feature = "discord")]
     #[error("failed to send Discord message")]
-    Discord(#[from] serenity::prelude::SerenityError),
+    Discord(#[source] Box<serenity::prelude::SerenityError>),
+    #[cfg(feature = "notify")]
+    #[error("failed to send notification")]
+    Http(#[from] reqwest::Error),
+    #[error("failed to send notification: {0}")]
+    Notify(String),
+    #[cfg(feature = "cli")]
+    #[error("invalid glob pattern")]
+    Glob(#[from] glob::PatternError),
+    #[error("{0}")]
+    InvalidConfig(String),
+    #[error("{0} check(s) failed")]
+    Doctor(usize),
+    #[error("incompatible DCS-gRPC: {0}")]
+    IncompatibleVersion(String),
+    #[error("invalid recording task: {0}")]
+    Task(String),
+    #[error("the {0} task keeps panicking")]
+    Panic(&'static str),
+    #[error("reconnect requested")]
+    Reconnect,
+    #[error("the units stream ended unexpectedly")]
+    StreamEnded,
     #[error("failed to deserialize JSON")]
     Serde(#[from] serde_json::Error),
+    #[cfg(windows)]
+    #[error("Windows service error")]
+    Service(#[source] std::io::Error),
+}
+
+#[cfg(feature = "grpc")]
+impl From<tonic::Status> for Error {
+    fn from(status: tonic::Status) -> Self {
+        Error::Grpc(Box::new(status))
+    }
+}
+
+#[cfg(feature = "discord")]
+impl From<serenity::prelude::SerenityError> for Error {
+    fn from(err: serenity::prelude::SerenityError) -> Self {
+        Error::Discord(Box::new(err))
+    }
+}
+
+/// The error with all its causes (e.g. `failed to open file: No such file or directory`), for
+/// reporting it to the user.
+pub fn report(err: &dyn std::error::Error) -> String {
+    let mut report = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        report.push_str(": ");
+        report.push_str(&err.to_string());
+        source = err.source();
+    }
+    report
 }
@@ -0,0 +1,133 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::config::MqttConfig;
+use crate::mqtt::MqttPublisher;
+use crate::store::PassRecord;
+
+/// A lifecycle event written to the event log.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LifecycleEvent<'a> {
+    /// Started observing a server for recovery attempts.
+    DetectionStarted,
+    RecordingStarted {
+        carrier: &'a str,
+        plane: &'a str,
+        pilot: &'a str,
+    },
+    PassCompleted {
+        pass: &'a PassRecord,
+    },
+    /// A helicopter set down on the landing area of a carrier (see
+    /// [crate::config::HelicopterMode::DeckLandings]).
+    DeckLanding {
+        carrier: &'a str,
+        helicopter: &'a str,
+        pilot: &'a str,
+        /// The distance (in m) from the ramp along the landing area's centerline.
+        from_ramp_m: f64,
+        /// The distance (in m) to the right of the centerline (negative if left of it).
+        from_centerline_m: f64,
+    },
+    /// A post (`pass` or `session_report`) was sent to a Discord webhook.
+    WebhookPosted {
+        post: &'a str,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    /// When the event happened (RFC 3339).
+    time: String,
+    server: Option<&'a str>,
+    #[serde(flatten)]
+    event: &'a LifecycleEvent<'a>,
+}
+
+impl LifecycleEvent<'_> {
+    /// The type of the event (as serialized into `event`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            LifecycleEvent::DetectionStarted => "detection_started",
+            LifecycleEvent::RecordingStarted { .. } => "recording_started",
+            LifecycleEvent::PassCompleted { .. } => "pass_completed",
+            LifecycleEvent::DeckLanding { .. } => "deck_landing",
+            LifecycleEvent::WebhookPosted { .. } => "webhook_posted",
+            LifecycleEvent::Error { .. } => "error",
+        }
+    }
+}
+
+/// Appends lifecycle events as JSON lines to a file and/or publishes them to an MQTT broker, for
+/// external tools to follow the LSO. Does nothing if neither is set.
+#[derive(Clone, Default)]
+pub struct EventLog {
+    file: Option<Arc<Mutex<File>>>,
+    mqtt: Option<(MqttPublisher, String)>,
+}
+
+impl EventLog {
+    /// Open the event log at the given path (appending to it if it already exists), and connect
+    /// to the MQTT broker.
+    pub fn new(
+        path: Option<&Path>,
+        mqtt: Option<&MqttConfig>,
+    ) -> Result<Self, crate::error::Error> {
+        let file = match path {
+            Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+            None => None,
+        };
+        Ok(Self {
+            file: file.map(|file| Arc::new(Mutex::new(file))),
+            mqtt: mqtt.map(|config| (MqttPublisher::spawn(config.clone()), config.topic.clone())),
+        })
+    }
+
+    /// Append the event. Failures are only logged, as the event log must not affect the
+    /// recordings.
+    pub fn log(&self, server: Option<&str>, event: LifecycleEvent<'_>) {
+        if self.file.is_none() && self.mqtt.is_none() {
+            return;
+        }
+
+        let line = Line {
+            time: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or_default(),
+            server,
+            event: &event,
+        };
+        let mut buf = match serde_json::to_vec(&line) {
+            Ok(buf) => buf,
+            Err(err) => {
+                tracing::warn!(%err, "failed to serialize event");
+                return;
+            }
+        };
+
+        if let Some((mqtt, topic)) = &self.mqtt {
+            let topic = topic
+                .replace("{server}", server.unwrap_or("default"))
+                .replace("{event}", event.name());
+            mqtt.publish(topic, buf.clone());
+        }
+
+        if let Some(file) = &self.file {
+            buf.push(b'\n');
+            // write the whole line at once to not interleave concurrent events
+            if let Err(err) = file.lock().unwrap().write_all(&buf) {
+                tracing::warn!(%err, "failed to write to the event log");
+            }
+        }
+    }
+}
@@ -0,0 +1,762 @@
+//! Extracting and grading the carrier recoveries of ACMI recordings (made by the LSO or by
+//! Tacview).
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::Read;
+use std::ops::Neg;
+use std::str::FromStr;
+
+use tacview::record::{Coords, Event, EventKind, GlobalProperty, Property, Record, Tag, Update};
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime, UtcOffset};
+use ultraviolet::{DRotor3, DVec3};
+
+use crate::config::{AoaSource, Config, DetectionEnvelope, Resampling, Smoothing};
+use crate::data::{AirplaneInfo, CarrierInfo};
+use crate::track::{is_recovery_attempt, ki_pilot_name, Incident, Track, TrackResult};
+use crate::transform::Transform;
+use crate::utils::TurnRate;
+
+/// Extract and grade the carrier recoveries of the ACMI recording read from `rd` (with the default
+/// config).
+pub fn extract_recoveries(rd: &mut impl Read) -> Result<Vec<TrackResult>, crate::error::Error> {
+    extract_recoveries_with_config(rd, &Config::default())
+}
+
+/// Like [extract_recoveries], but with the given config.
+pub fn extract_recoveries_with_config(
+    rd: &mut impl Read,
+    config: &Config,
+) -> Result<Vec<TrackResult>, crate::error::Error> {
+    let tracks = extract_tracks(rd, config)?;
+    Ok(tracks
+        .into_iter()
+        .filter(|t| t.is_recovery_attempt)
+        .map(|t| t.datums.finish())
+        .collect())
+}
+
+/// Extract the carriers and planes of the ACMI recording read from `rd` that were paired with each
+/// other, with their passes (if any).
+pub fn extract_tracks(
+    rd: &mut impl Read,
+    config: &Config,
+) -> Result<Vec<CarrierPlanePair>, crate::error::Error> {
+    let parser = crate::acmi::parse(rd)?;
+
+    let mut recording_time =
+        OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+    let mut mission_name = None;
+    let mut server_name = None;
+    // Recordings directly from Tacview (instead of the LSO) have no land events, and the AoA and
+    // flat-world coordinates are not necessarily included.
+    let mut lso_recording = false;
+    let mut geographic_coords = None;
+    let mut reference_latitude = 0.0;
+    let mut reference_longitude = 0.0;
+    let mut reference_time = None;
+    // the carriers with their rigged wires, unit name and type
+    let mut carriers: HashMap<u64, (&'static CarrierInfo, Option<&[u8]>, String, String)> =
+        HashMap::new();
+    let mut planes: HashMap<u64, (String, String, &'static AirplaneInfo)> = HashMap::new();
+    let mut tracks: Vec<CarrierPlanePair> = Vec::new();
+
+    let mut time = 0.0;
+    for (i, record) in parser.enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(source) => {
+                let err = crate::error::Error::AcmiRecord {
+                    record: i + 1,
+                    source,
+                };
+                if !tracks.iter().any(|track| track.is_recovery_attempt) {
+                    return Err(err);
+                }
+                // still process the passes recorded before the corrupted part
+                tracing::warn!(
+                    "{} (only processing the passes before it)",
+                    crate::error::report(&err)
+                );
+                break;
+            }
+        };
+        match record {
+            Record::GlobalProperty(GlobalProperty::RecordingTime(time)) => {
+                if let Ok(time) = OffsetDateTime::parse(&time, &Rfc3339) {
+                    recording_time = if let Ok(offset) = UtcOffset::current_local_offset() {
+                        time.to_offset(offset)
+                    } else {
+                        time
+                    };
+                }
+            }
+
+            Record::GlobalProperty(GlobalProperty::Title(title)) => {
+                // recordings of Tacview are titled after the mission
+                mission_name = Some(
+                    title
+                        .strip_prefix("Carrier Recovery during ")
+                        .unwrap_or(&title)
+                        .to_string(),
+                );
+            }
+
+            Record::GlobalProperty(GlobalProperty::DataSource(source)) => {
+                server_name = source
+                    .strip_prefix(crate::acmi::SERVER_PREFIX)
+                    .map(String::from);
+            }
+
+            Record::GlobalProperty(GlobalProperty::Author(author)) => {
+                lso_recording = author.starts_with("dcs-grpc-lso");
+            }
+
+            Record::GlobalProperty(GlobalProperty::ReferenceLatitude(lat)) => {
+                reference_latitude = lat;
+            }
+
+            Record::GlobalProperty(GlobalProperty::ReferenceLongitude(lon)) => {
+                reference_longitude = lon;
+            }
+
+            Record::GlobalProperty(GlobalProperty::ReferenceTime(time)) => {
+                reference_time = OffsetDateTime::parse(&time, &Rfc3339).ok();
+            }
+
+            Record::Frame(secs) => {
+                for track in &mut tracks {
+                    track.process_frame();
+                }
+
+                time = secs;
+            }
+
+            Record::Update(mut update) => {
+                for prop in &mut update.props {
+                    if let Property::T(coords) = prop {
+                        // whether the recording only has geographic coordinates is decided by the
+                        // first (complete) coordinates
+                        let geographic_coords = *geographic_coords
+                            .get_or_insert(coords.u.is_none() && coords.v.is_none());
+                        if geographic_coords {
+                            approximate_flat_coords(coords, reference_latitude);
+                        }
+                    }
+                }
+
+                if !carriers.contains_key(&update.id) && !planes.contains_key(&update.id) {
+                    let pilot_name = update
+                        .props
+                        .iter()
+                        .find_map(|p| {
+                            if let Property::Pilot(pilot_name) = p {
+                                Some(pilot_name.as_str())
+                            } else {
+                                None
+                            }
+                        })
+                        .map(crate::privacy::recorded_pilot_name)
+                        .unwrap_or_else(|| {
+                            ki_pilot_name(update.props.iter().find_map(|p| match p {
+                                Property::Group(group_name) => Some(group_name.as_str()),
+                                _ => None,
+                            }))
+                        });
+                    let name = update.props.iter().find_map(|p| {
+                        if let Property::Name(name) = p {
+                            Some(name)
+                        } else {
+                            None
+                        }
+                    });
+                    let tags = update.props.iter().find_map(|p| {
+                        if let Property::Type(tags) = p {
+                            Some(tags)
+                        } else {
+                            None
+                        }
+                    });
+
+                    if let Some((name, tags)) = name.zip(tags) {
+                        if tags.contains(&Tag::AircraftCarrier) {
+                            match CarrierInfo::by_type(name) {
+                                Some(carrier_info) => {
+                                    let group_name = update
+                                        .props
+                                        .iter()
+                                        .find_map(|p| match p {
+                                            Property::Group(group_name) => {
+                                                Some(group_name.as_str())
+                                            }
+                                            _ => None,
+                                        })
+                                        .unwrap_or_default();
+                                    let rigged_wires = config.rigged_wires(
+                                        mission_name.as_deref(),
+                                        group_name,
+                                        name,
+                                    );
+                                    // recorded by `lso run` (and by Tacview for named units),
+                                    // otherwise the carriers can only be told apart by their type
+                                    let carrier_name = update
+                                        .props
+                                        .iter()
+                                        .find_map(|p| match p {
+                                            Property::CallSign(callsign) => Some(callsign.as_str()),
+                                            _ => None,
+                                        })
+                                        .unwrap_or(name);
+                                    for (plane_id, (pilot_name, plane_type, plane_info)) in &planes
+                                    {
+                                        tracks.push(
+                                            CarrierPlanePair::new(
+                                                recording_time + Duration::seconds_f64(time),
+                                                update.id,
+                                                carrier_info,
+                                                *plane_id,
+                                                pilot_name,
+                                                plane_info,
+                                                config.detection,
+                                            )
+                                            .with_rigged_wires(rigged_wires)
+                                            .with_smoothing(config.smoothing)
+                                            .with_resampling(config.resampling)
+                                            .with_aoa_source(config.aoa_source)
+                                            .with_names(
+                                                mission_name.as_deref(),
+                                                carrier_name,
+                                                name,
+                                                plane_type,
+                                            )
+                                            .with_server_name(server_name.as_deref())
+                                            .with_landing_detection(!lso_recording),
+                                        );
+                                    }
+
+                                    carriers.insert(
+                                        update.id,
+                                        (
+                                            carrier_info,
+                                            rigged_wires,
+                                            carrier_name.to_string(),
+                                            name.clone(),
+                                        ),
+                                    );
+                                }
+                                None => tracing::trace!(name, "unsupported aircraft carrier"),
+                            }
+                        } else if tags.contains(&Tag::FixedWing) {
+                            match AirplaneInfo::by_type(name) {
+                                Some(plane_info) => {
+                                    for (
+                                        carrier_id,
+                                        (carrier_info, rigged_wires, carrier_name, carrier_type),
+                                    ) in &carriers
+                                    {
+                                        tracks.push(
+                                            CarrierPlanePair::new(
+                                                recording_time + Duration::seconds_f64(time),
+                                                *carrier_id,
+                                                carrier_info,
+                                                update.id,
+                                                &pilot_name,
+                                                plane_info,
+                                                config.detection,
+                                            )
+                                            .with_rigged_wires(*rigged_wires)
+                                            .with_smoothing(config.smoothing)
+                                            .with_resampling(config.resampling)
+                                            .with_aoa_source(config.aoa_source)
+                                            .with_names(
+                                                mission_name.as_deref(),
+                                                carrier_name,
+                                                carrier_type,
+                                                name,
+                                            )
+                                            .with_server_name(server_name.as_deref())
+                                            .with_landing_detection(!lso_recording),
+                                        );
+                                    }
+
+                                    planes.insert(
+                                        update.id,
+                                        (pilot_name.to_string(), name.clone(), plane_info),
+                                    );
+                                }
+                                None => tracing::trace!(name, "unsupported fixed wing aircraft"),
+                            }
+                        } else if tags.contains(&Tag::Rotorcraft) {
+                            // deck landings of helicopters are only detected when recording live
+                            tracing::trace!(name, "ignore helicopter");
+                        }
+                    }
+                }
+
+                for track in &mut tracks {
+                    track.update(time, &update);
+                }
+            }
+
+            Record::Event(Event {
+                kind: EventKind::Landed,
+                mut params,
+                ..
+            }) => {
+                tracing::trace!(?params, "landed event");
+                if let Some((carrier_id, plane_id)) = params
+                    .pop()
+                    .and_then(|id| parse_object_id(&id))
+                    .zip(params.pop().and_then(|id| parse_object_id(&id)))
+                {
+                    tracing::trace!(carrier_id, plane_id, "landed event");
+                    for track in &mut tracks {
+                        track.landed(carrier_id, plane_id);
+                    }
+                }
+            }
+
+            Record::Event(Event {
+                kind: EventKind::Message,
+                mut params,
+                text: Some(dcs_grading),
+            }) => {
+                if let Some((carrier_id, plane_id)) = params
+                    .pop()
+                    .and_then(|id| parse_object_id(&id))
+                    .zip(params.pop().and_then(|id| parse_object_id(&id)))
+                {
+                    tracing::trace!(carrier_id, plane_id, dcs_grading, "dcs lso grading");
+                    for track in &mut tracks {
+                        track.dcs_grading(carrier_id, plane_id, &dcs_grading);
+                    }
+                }
+            }
+
+            Record::Event(Event {
+                kind: EventKind::Destroyed,
+                params,
+                text,
+            }) => {
+                let incident = text
+                    .as_deref()
+                    .and_then(|t| Incident::from_str(t).ok())
+                    .unwrap_or(Incident::Crash);
+                for plane_id in params.iter().filter_map(|id| parse_object_id(id)) {
+                    tracing::trace!(plane_id, ?incident, "incident");
+                    for track in &mut tracks {
+                        track.incident(plane_id, incident);
+                    }
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    for track in &mut tracks {
+        track.process_frame();
+        if let Some(reference_time) = reference_time {
+            track
+                .datums
+                .set_reference(reference_time, reference_latitude, reference_longitude);
+        }
+    }
+
+    Ok(tracks)
+}
+
+/// Parse the ID of an object referenced in an event (hexadecimal like all object IDs in ACMI).
+fn parse_object_id(id: &str) -> Option<u64> {
+    u64::from_str_radix(id, 16).ok()
+}
+
+/// Over how many frames the velocity is averaged to derive the AoA (the positions are rounded, so
+/// the difference of two consecutive frames is noisy).
+const AOA_FRAMES: usize = 5;
+
+/// The AoA (in degrees) of the plane flying with the given velocity, i.e. the angle between the
+/// velocity and the plane's longitudinal axis, in the plane's plane of symmetry (positive when
+/// the velocity points below the nose).
+fn aoa(plane: &Transform, velocity: DVec3) -> f64 {
+    let forward = DVec3::unit_z().rotated_by(plane.rotation);
+    let up = DVec3::unit_y().rotated_by(plane.rotation);
+    (-velocity.dot(up))
+        .atan2(velocity.dot(forward))
+        .to_degrees()
+}
+
+/// The mean radius of the earth (in m).
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// Approximate the flat-world coordinates (`u` and `v`) and heading for recordings that only
+/// contain geographic coordinates, by projecting them around the reference point of the recording
+/// (which is accurate enough in the vicinity of a carrier).
+fn approximate_flat_coords(coords: &mut Coords, reference_latitude: f64) {
+    if let Some(lon) = coords.longitude {
+        coords.u = Some(lon.to_radians() * EARTH_RADIUS * reference_latitude.to_radians().cos());
+    }
+    if let Some(lat) = coords.latitude {
+        coords.v = Some(lat.to_radians() * EARTH_RADIUS);
+    }
+    coords.heading = coords.yaw;
+}
+
+/// The minimal deceleration (in m/s²) relative to the carrier that is considered a trap, when
+/// detecting them in recordings without land events.
+const TRAP_DECELERATION: f64 = 15.0;
+/// How far above the deck (in m) the plane's origin is considered on deck.
+const ON_DECK_HEIGHT: f64 = 6.0;
+
+/// A plane paired with a carrier in a recording, and its current pass.
+pub struct CarrierPlanePair {
+    /// When the pair was first seen in the recording.
+    pub recording_time: OffsetDateTime,
+    /// The time of the recording the pair was first seen at.
+    first_seen: Option<f64>,
+    pub mission_name: Option<String>,
+    /// The name of the DCS server the recording was made on (if recorded by `lso run`).
+    pub server_name: Option<String>,
+    pub pilot_name: String,
+    /// The unit name of the carrier (its type if the recording doesn't contain it).
+    pub carrier_name: String,
+    pub carrier_type: String,
+    pub plane_type: String,
+    carrier_id: u64,
+    carrier: Transform,
+    carrier_info: &'static CarrierInfo,
+    pub plane_id: u64,
+    plane: Transform,
+    plane_info: &'static AirplaneInfo,
+    envelope: DetectionEnvelope,
+    is_recovery_attempt: bool,
+    is_dirty: bool,
+    is_done: bool,
+    datums: Track,
+    landed: bool,
+    /// The plane's time of the landing.
+    landed_at: Option<f64>,
+    carrier_turn_rate: TurnRate,
+    /// Whether the AoA of the plane is part of the recording (otherwise it is derived from its
+    /// velocity).
+    has_aoa: bool,
+    /// Ignore the AoA that is part of the recording.
+    geometric_aoa: bool,
+    /// The plane's positions of the last frames (for the velocity).
+    previous_positions: VecDeque<(f64, DVec3)>,
+    /// Detect the landing by the deceleration of the plane (for recordings without land events).
+    detect_landing: bool,
+    /// The time, the plane's horizontal offset to the carrier and (if known) its speed relative
+    /// to the carrier at the previous frame (to detect landings).
+    previous_offset: Option<(f64, DVec3, Option<f64>)>,
+}
+
+impl fmt::Display for CarrierPlanePair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}, object {:x}) on {} (object {:x})",
+            self.pilot_name, self.plane_type, self.plane_id, self.carrier_type, self.carrier_id
+        )
+    }
+}
+
+impl CarrierPlanePair {
+    fn new(
+        recording_time: OffsetDateTime,
+        carrier_id: u64,
+        carrier_info: &'static CarrierInfo,
+        plane_id: u64,
+        pilot_name: &str,
+        plane_info: &'static AirplaneInfo,
+        envelope: DetectionEnvelope,
+    ) -> Self {
+        let mut datums = Track::new(pilot_name, carrier_info, plane_info);
+        datums.set_max_turn_rate(envelope.max_turn_rate);
+
+        Self {
+            recording_time,
+            first_seen: None,
+            mission_name: None,
+            server_name: None,
+            pilot_name: pilot_name.to_string(),
+            carrier_name: String::new(),
+            carrier_type: String::new(),
+            plane_type: String::new(),
+            carrier_id,
+            carrier: Default::default(),
+            carrier_info,
+            plane_id,
+            plane: Default::default(),
+            plane_info,
+            envelope,
+            is_recovery_attempt: false,
+            is_dirty: false,
+            is_done: false,
+            datums,
+            landed: false,
+            landed_at: None,
+            carrier_turn_rate: TurnRate::default(),
+            has_aoa: false,
+            geometric_aoa: false,
+            previous_positions: VecDeque::with_capacity(AOA_FRAMES),
+            detect_landing: false,
+            previous_offset: None,
+        }
+    }
+
+    fn with_rigged_wires(mut self, rigged_wires: Option<&[u8]>) -> Self {
+        if let Some(rigged_wires) = rigged_wires {
+            self.datums.set_rigged_wires(rigged_wires.to_vec());
+        }
+        self
+    }
+
+    fn with_smoothing(mut self, smoothing: Option<Smoothing>) -> Self {
+        self.datums.set_smoothing(smoothing);
+        self
+    }
+
+    fn with_resampling(mut self, resampling: Option<Resampling>) -> Self {
+        self.datums.set_resampling(resampling);
+        self
+    }
+
+    fn with_aoa_source(mut self, aoa_source: AoaSource) -> Self {
+        self.geometric_aoa = aoa_source == AoaSource::Geometric;
+        self
+    }
+
+    /// Set the mission name, carrier name, carrier and plane type (used for the filenames and
+    /// results of the passes).
+    fn with_names(
+        mut self,
+        mission_name: Option<&str>,
+        carrier_name: &str,
+        carrier_type: &str,
+        plane_type: &str,
+    ) -> Self {
+        self.mission_name = mission_name.map(String::from);
+        self.carrier_name = carrier_name.to_string();
+        self.carrier_type = carrier_type.to_string();
+        self.plane_type = plane_type.to_string();
+        self
+    }
+
+    fn with_server_name(mut self, server_name: Option<&str>) -> Self {
+        self.server_name = server_name.map(String::from);
+        self
+    }
+
+    fn with_landing_detection(mut self, detect_landing: bool) -> Self {
+        self.detect_landing = detect_landing;
+        self
+    }
+
+    fn update(&mut self, time: f64, update: &Update) {
+        // the pair is always updated right after it got created
+        self.first_seen.get_or_insert(time);
+        let (transform, is_plane) = if update.id == self.carrier_id {
+            (&mut self.carrier, false)
+        } else if update.id == self.plane_id {
+            (&mut self.plane, true)
+        } else {
+            return;
+        };
+
+        for p in &update.props {
+            match p {
+                Property::T(coords) => {
+                    let mut orientation_changed = false;
+
+                    if let Some(roll) = coords.roll {
+                        transform.roll = roll;
+                        orientation_changed = true;
+                    }
+                    if let Some(pitch) = coords.pitch {
+                        transform.pitch = pitch;
+                        orientation_changed = true;
+                    }
+                    if let Some(yaw) = coords.yaw {
+                        transform.yaw = yaw;
+                        orientation_changed = true;
+                    }
+                    if let Some(heading) = coords.heading {
+                        transform.heading = heading;
+                        orientation_changed = true;
+                    }
+
+                    if orientation_changed {
+                        transform.forward = DVec3::new(
+                            transform.yaw.to_radians().sin() * transform.pitch.to_radians().cos(),
+                            transform.pitch.to_radians().sin(),
+                            transform.yaw.to_radians().cos() * transform.pitch.to_radians().cos(),
+                        );
+                        transform.rotation = DRotor3::from_euler_angles(
+                            transform.roll.neg().to_radians(),
+                            transform.pitch.neg().to_radians(),
+                            transform.heading.neg().to_radians(),
+                        );
+                    }
+
+                    let mut new_pos = transform.position;
+
+                    if let Some(altitude) = coords.altitude {
+                        new_pos.y = altitude;
+                        transform.alt = altitude;
+                    }
+                    if let Some(u) = coords.u {
+                        new_pos.x = u;
+                    }
+                    if let Some(v) = coords.v {
+                        new_pos.z = v;
+                    }
+
+                    transform.position = new_pos;
+                    transform.time = time;
+
+                    if is_plane {
+                        self.is_dirty = true;
+                        if !self.has_aoa {
+                            if let Some(&(previous_time, previous)) =
+                                self.previous_positions.front()
+                            {
+                                if time > previous_time {
+                                    let velocity = (new_pos - previous) / (time - previous_time);
+                                    transform.aoa = aoa(transform, velocity);
+                                }
+                            }
+                            if self.previous_positions.len() == AOA_FRAMES {
+                                self.previous_positions.pop_front();
+                            }
+                            self.previous_positions.push_back((time, new_pos));
+                        }
+                    } else {
+                        self.carrier_turn_rate.update(time, transform.heading);
+                    }
+                }
+                Property::Pilot(pilot_name) => {
+                    self.pilot_name = crate::privacy::recorded_pilot_name(pilot_name);
+                }
+                Property::AOA(_) if is_plane && self.geometric_aoa => {}
+                Property::AOA(aoa) => {
+                    transform.aoa = *aoa;
+                    if is_plane {
+                        self.has_aoa = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn landed(&mut self, carrier_id: u64, plane_id: u64) {
+        if self.carrier_id == carrier_id && self.plane_id == plane_id && !self.landed {
+            self.landed = true;
+            self.is_dirty = true;
+        }
+    }
+
+    fn incident(&mut self, plane_id: u64, incident: Incident) {
+        if self.plane_id == plane_id && self.is_recovery_attempt && !self.is_done {
+            self.datums.set_incident(incident);
+            self.is_done = true;
+        }
+    }
+
+    fn dcs_grading(&mut self, carrier_id: u64, plane_id: u64, dcs_grading: &str) {
+        if self.carrier_id == carrier_id && self.plane_id == plane_id {
+            self.datums.set_dcs_grading(dcs_grading.to_string());
+        }
+    }
+
+    fn process_frame(&mut self) {
+        if !self.is_dirty || self.is_done {
+            return;
+        }
+
+        self.is_dirty = false;
+
+        if self.carrier.time == 0.0 || self.plane.time == 0.0 {
+            return;
+        }
+
+        if self.is_recovery_attempt {
+            if self.detect_landing && !self.landed {
+                self.landed = self.detect_trap();
+            }
+            let mut should_continue = self.datums.next(&self.carrier, &self.plane);
+            if self.landed && self.landed_at.is_none() {
+                self.datums.landed(&self.carrier, &self.plane);
+                self.landed_at = Some(self.plane.time);
+            }
+            // keep following the hook for a moment after landing to record its rollout
+            if self
+                .landed_at
+                .is_some_and(|landed_at| self.plane.time - landed_at > ROLLOUT_DURATION)
+            {
+                should_continue = false;
+            }
+            if !should_continue {
+                self.is_done = true;
+            }
+        } else if self.carrier_turn_rate.rate().abs() <= self.envelope.max_turn_rate
+            && is_recovery_attempt(&self.carrier, &self.plane, &self.envelope)
+        {
+            // same as when recording live, passes aren't started while the carrier is turning
+            self.is_recovery_attempt = true;
+        }
+    }
+
+    /// Whether the plane is on deck and decelerates as strongly (relative to the carrier) as only a
+    /// trap does.
+    fn detect_trap(&mut self) -> bool {
+        let time = self.plane.time;
+        let offset = self.plane.position - self.carrier.position;
+        let offset = DVec3::new(offset.x, 0.0, offset.z);
+        let Some((previous_time, previous_offset, previous_speed)) = self.previous_offset else {
+            self.previous_offset = Some((time, offset, None));
+            return false;
+        };
+        if time <= previous_time {
+            return false;
+        }
+
+        let speed = (offset - previous_offset).mag() / (time - previous_time);
+        self.previous_offset = Some((time, offset, Some(speed)));
+        let on_deck = self.plane.alt < self.carrier_info.deck_altitude + ON_DECK_HEIGHT;
+        on_deck
+            && previous_speed.is_some_and(|previous| {
+                (previous - speed) / (time - previous_time) > TRAP_DECELERATION
+            })
+    }
+
+    /// When the pass ended (or, if it is still in progress, the time of the last update of the
+    /// plane).
+    pub fn ended(&self) -> OffsetDateTime {
+        self.recording_time
+            + Duration::seconds_f64(self.plane.time - self.first_seen.unwrap_or_default())
+    }
+
+    /// Finish the pass (if the plane is in a recovery attempt) and reset the pair for the plane's
+    /// next pass.
+    pub fn finish_pass(&mut self) -> Option<TrackResult> {
+        if !self.is_recovery_attempt {
+            return None;
+        }
+
+        let track = std::mem::replace(
+            &mut self.datums,
+            Track::new(&self.pilot_name, self.carrier_info, self.plane_info),
+        )
+        .finish();
+        self.is_recovery_attempt = false;
+        self.landed = false;
+        self.landed_at = None;
+        Some(track)
+    }
+}
+
+/// How long (in s) to keep following the hook after landing.
+const ROLLOUT_DURATION: f64 = 3.0;
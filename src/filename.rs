@@ -0,0 +1,228 @@
+use std::path::{Path, PathBuf};
+
+use time::format_description::BorrowedFormatItem;
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+use crate::config::OutputConfig;
+use crate::track::{Grading, TrackResult};
+
+/// The format of the `{datetime}` placeholder (and of the timestamps in other filenames).
+pub const FILENAME_DATETIME_FORMAT: &[BorrowedFormatItem<'_>] =
+    format_description!("[year][month][day]-[hour][minute][second]");
+
+/// The format of the dates and times shown to people (in reports, titles and the site).
+pub const DATETIME_FORMAT: &[BorrowedFormatItem<'_>] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]");
+
+/// Like [DATETIME_FORMAT], but with seconds.
+pub const DATETIME_SECONDS_FORMAT: &[BorrowedFormatItem<'_>] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
+
+/// The format of the times shown to people, when the date is clear from the context.
+pub const TIME_FORMAT: &[BorrowedFormatItem<'_>] = format_description!("[hour]:[minute]");
+
+/// Like [TIME_FORMAT], but with seconds.
+pub const TIME_SECONDS_FORMAT: &[BorrowedFormatItem<'_>] =
+    format_description!("[hour]:[minute]:[second]");
+
+/// The name of the index page written by `lso site` (in the output directory).
+pub const INDEX_FILENAME: &str = "index.html";
+
+/// The start of the filenames of the session reports.
+pub const SESSION_REPORT_PREFIX: &str = "LSO-Session-";
+
+/// The default template for the filenames of the charts and recordings.
+pub const DEFAULT_TEMPLATE: &str = "LSO-{datetime}-{server}-{pilot}-{callsign}-{id}";
+
+/// The values the placeholders of a filename template are replaced with.
+#[derive(Debug, Default)]
+pub struct FilenameParts<'a> {
+    /// When the recording started (`{datetime}`).
+    pub datetime: Option<OffsetDateTime>,
+    pub server: Option<&'a str>,
+    pub pilot: Option<&'a str>,
+    /// The callsign of a KI plane (none for players).
+    pub callsign: Option<&'a str>,
+    /// The unit name of the carrier.
+    pub carrier: Option<&'a str>,
+    /// The type of the plane.
+    pub plane: Option<&'a str>,
+    pub mission: Option<&'a str>,
+    /// The ID of the pass (see [crate::store::PassRecord::id]).
+    pub id: Option<&'a str>,
+    /// The pass the wire and grade are taken from.
+    pub track: Option<&'a TrackResult>,
+}
+
+/// Render the filename `template` (without an extension). Placeholders are written as `{name}`
+/// (see [FilenameParts]). Unknown placeholders are kept as they are. Placeholders without a value
+/// (e.g. `{server}` when monitoring a single unnamed server, or `{wire}` for a bolter) are removed
+/// together with the separator in front of them.
+pub fn render(template: &str, parts: &FilenameParts<'_>) -> String {
+    let filename = render_part(template, parts);
+    if filename.is_empty() {
+        "LSO".to_string()
+    } else {
+        filename
+    }
+}
+
+/// The directory (below `out_dir`, created if necessary) and the filename (without extension) the
+/// outputs of a pass are saved to.
+pub fn output_location(
+    out_dir: &Path,
+    config: &OutputConfig,
+    parts: &FilenameParts<'_>,
+) -> Result<(PathBuf, String), std::io::Error> {
+    let mut dir = out_dir.to_path_buf();
+    for template in &config.subdirectories {
+        // levels without a value (e.g. `{server}` when monitoring a single server) are skipped
+        let name = render_part(template, parts);
+        if !name.is_empty() {
+            dir.push(name);
+        }
+    }
+    std::fs::create_dir_all(&dir)?;
+
+    Ok((dir, render(&config.filename, parts)))
+}
+
+/// The placeholders in `template` that aren't known (e.g. misspelled ones), which are kept as they
+/// are when rendering it.
+pub fn unknown_placeholders(template: &str) -> Vec<&str> {
+    let mut unknown = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 1..start + len];
+        if value(name, &FilenameParts::default()).is_none() {
+            unknown.push(name);
+        }
+        rest = &rest[start + len + 1..];
+    }
+    unknown
+}
+
+fn render_part(template: &str, parts: &FilenameParts<'_>) -> String {
+    let mut filename = String::with_capacity(template.len() + 32);
+    let mut rest = template;
+    // whether the previous placeholder was empty at the start of the filename, in which case the
+    // separator after it is removed instead
+    let mut skip_separator = false;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        push_literal(&mut filename, &rest[..start], skip_separator);
+        let name = &rest[start + 1..start + len];
+        match value(name, parts) {
+            Some(value) if !value.is_empty() => {
+                filename.push_str(&value);
+                skip_separator = false;
+            }
+            Some(_) => {
+                if filename.ends_with(is_separator) {
+                    filename.pop();
+                } else {
+                    skip_separator = filename.is_empty();
+                }
+            }
+            None => {
+                filename.push_str(&rest[start..=start + len]);
+                skip_separator = false;
+            }
+        }
+        rest = &rest[start + len + 1..];
+    }
+    push_literal(&mut filename, rest, skip_separator);
+    filename
+}
+
+fn push_literal(filename: &mut String, literal: &str, skip_separator: bool) {
+    // path separators are not allowed to not write outside of the output directory
+    let literal = literal.replace(['/', '\\'], "");
+    if skip_separator {
+        filename.push_str(literal.strip_prefix(is_separator).unwrap_or(&literal));
+    } else {
+        filename.push_str(&literal);
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '-' | '_' | '.' | ' ')
+}
+
+/// The value of the placeholder `name` (empty if not known for this pass), or `None` for an
+/// unknown placeholder.
+fn value(name: &str, parts: &FilenameParts<'_>) -> Option<String> {
+    Some(match name {
+        "datetime" => parts
+            .datetime
+            .and_then(|datetime| datetime.format(FILENAME_DATETIME_FORMAT).ok())
+            .unwrap_or_default(),
+        "server" => parts.server.map(sanitize).unwrap_or_default(),
+        "pilot" => parts.pilot.map(sanitize).unwrap_or_default(),
+        "callsign" => parts.callsign.map(sanitize).unwrap_or_default(),
+        "carrier" => parts.carrier.map(sanitize).unwrap_or_default(),
+        "plane" => parts.plane.map(sanitize).unwrap_or_default(),
+        "mission" => parts.mission.map(sanitize).unwrap_or_default(),
+        "id" => parts.id.map(sanitize).unwrap_or_default(),
+        "wire" => parts
+            .track
+            .and_then(|track| match track.grading {
+                Grading::Recovered {
+                    cable,
+                    cable_estimated,
+                } => cable.or(cable_estimated),
+                _ => None,
+            })
+            .map(|wire| wire.to_string())
+            .unwrap_or_default(),
+        "grade" => parts.track.map(grade).unwrap_or_default(),
+        _ => return None,
+    })
+}
+
+/// The grade of the DCS LSO (e.g. `OK` or `(OK)`), or the grading of the LSO itself if DCS didn't
+/// grade the pass.
+fn grade(track: &TrackResult) -> String {
+    if let Some(dcs_grading) = &track.dcs_grading {
+        let grade = dcs_grading
+            .grade
+            .as_str()
+            .chars()
+            .filter(|c| c.is_alphanumeric() || matches!(c, '_' | '(' | ')' | '-'))
+            .collect::<String>();
+        if !grade.is_empty() {
+            return grade;
+        }
+    }
+    match &track.grading {
+        Grading::Unknown => "unknown".to_string(),
+        Grading::TouchAndGo => "touch-and-go".to_string(),
+        Grading::Bolter if track.hook_up => "touch-and-go".to_string(),
+        Grading::Bolter => "bolter".to_string(),
+        Grading::Recovered { .. } => "trap".to_string(),
+        Grading::Incident(incident) => sanitize(incident.as_str()),
+        Grading::Spin => "spin".to_string(),
+        Grading::OwnWaveOff => "own-waveoff".to_string(),
+    }
+}
+
+/// Strip everything from `s` that isn't safe to use as part of a filename. Letters and digits of
+/// all scripts are kept. If nothing is left (e.g. for a name consisting only of symbols), a hash
+/// of `s` is used instead, so that different names still result in different filenames.
+pub fn sanitize(s: &str) -> String {
+    let part = s
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>();
+    if part.is_empty() && !s.is_empty() {
+        format!("x{:08x}", crate::utils::fnv1a(s.bytes()))
+    } else {
+        part
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[cfg(feature = "notify")]
+use crate::notify::Notification;
+use crate::stats;
+use crate::store::PassRecord;
+use crate::track::Grading;
+
+/// The standing of a pilot on the leaderboard.
+#[derive(Debug)]
+pub struct Standing<'a> {
+    pub pilot: &'a str,
+    pub passes: usize,
+    pub traps: usize,
+    pub night_traps: usize,
+    pub boarding_rate: f64,
+    pub gpa: Option<f64>,
+}
+
+/// Rank all pilots with at least `min_passes` passes by their GPA (and their boarding rate for
+/// pilots with the same GPA). Touch-and-goes don't count as passes.
+pub fn standings(records: &[PassRecord], min_passes: usize) -> Vec<Standing<'_>> {
+    let mut pilots: BTreeMap<&str, Vec<&PassRecord>> = BTreeMap::new();
+    for record in records.iter().filter(|r| !r.is_touch_and_go()) {
+        pilots
+            .entry(record.pilot.as_str())
+            .or_default()
+            .push(record);
+    }
+
+    let mut standings = pilots
+        .into_iter()
+        .filter(|(_, records)| records.len() >= min_passes)
+        .map(|(pilot, records)| {
+            let traps = records
+                .iter()
+                .filter(|r| matches!(r.grading, Grading::Recovered { .. }))
+                .collect::<Vec<_>>();
+            Standing {
+                pilot,
+                passes: records.len(),
+                traps: traps.len(),
+                night_traps: traps.iter().filter(|r| r.night).count(),
+                boarding_rate: traps.len() as f64 / records.len() as f64,
+                gpa: stats::gpa(records.iter().copied()),
+            }
+        })
+        .collect::<Vec<_>>();
+    standings.sort_by(|a, b| {
+        b.gpa
+            .unwrap_or(-1.0)
+            .total_cmp(&a.gpa.unwrap_or(-1.0))
+            .then_with(|| b.boarding_rate.total_cmp(&a.boarding_rate))
+    });
+    standings
+}
+
+/// Format the standings as one line per pilot (e.g. `1. Pilot: GPA 3.75, 90% boarding rate (9/10
+/// traps, 2 at night)`).
+pub fn format(standings: &[Standing<'_>]) -> String {
+    let mut text = String::new();
+    for (i, standing) in standings.iter().enumerate() {
+        let _ = writeln!(
+            text,
+            "{}. {}: GPA {}, {:.0}% boarding rate ({}/{} traps{})",
+            i + 1,
+            standing.pilot,
+            standing
+                .gpa
+                .map(|gpa| format!("{:.2}", gpa))
+                .unwrap_or_else(|| "-".to_string()),
+            standing.boarding_rate * 100.0,
+            standing.traps,
+            standing.passes,
+            if standing.night_traps > 0 {
+                format!(", {} at night", standing.night_traps)
+            } else {
+                String::new()
+            },
+        );
+    }
+    text
+}
+
+/// The standings as a notification to post to chat services.
+#[cfg(feature = "notify")]
+pub fn notification(title: &str, standings: &[Standing<'_>]) -> Notification {
+    Notification {
+        title: Some(title.to_string()),
+        text: Some(if standings.is_empty() {
+            "No passes".to_string()
+        } else {
+            format(standings)
+        }),
+        ..Default::default()
+    }
+}
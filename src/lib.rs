@@ -0,0 +1,170 @@
+//! Grade carrier recoveries in DCS World.
+//!
+//! Besides the `lso` binary, the crate can be used as a library to grade passes in other Rust
+//! programs. The stable entry points are re-exported at the crate root:
+//!
+//! - [extract_recoveries] extracts and grades the carrier recoveries of an ACMI recording (made by
+//!   the LSO or by Tacview),
+//! - [Track] grades a pass from the carrier's and the plane's transforms (e.g. received via
+//!   DCS-gRPC), one frame at a time,
+//! - [TrackResult] is the graded pass, which can be drawn as a chart with [draw::draw_chart],
+//! - [RecoveryTaskBuilder] records a pass live from a DCS-gRPC connection, like `lso run` does
+//!   once it detected a recovery attempt,
+//! - [DataRegistry] adds carriers and airplanes (or overrides the built-in ones) at runtime,
+//! - [Error] is the error of all fallible functions.
+//!
+//! ```no_run
+//! let mut file = std::fs::File::open("recording.zip.acmi")?;
+//! for pass in lso::extract_recoveries(&mut file)? {
+//!     println!("{}: {}", pass.pilot_name, pass.debriefing());
+//! }
+//! # Ok::<(), lso::Error>(())
+//! ```
+//!
+//! The other modules are public for the binary and the benchmarks, but are not covered by the
+//! semver guarantees of the crate. [TrackResult] and [Grading] are `#[non_exhaustive]`, as they
+//! get new fields and variants in minor versions.
+//!
+//! The chart rendering (`charts`), the commands of the binary (`cli`), Discord (`discord`),
+//! DCS-gRPC (`grpc`) and the chat services and MQTT (`notify`) are optional cargo features, enabled
+//! by default. Without them, the crate only grades passes (e.g. ACMI in, [TrackResult] out).
+
+pub mod acmi;
+#[cfg(feature = "grpc")]
+pub mod client;
+#[cfg(feature = "cli")]
+pub mod commands;
+pub mod config;
+pub mod cq;
+pub mod data;
+pub mod dcs_grading;
+pub mod debrief;
+#[cfg(feature = "charts")]
+pub mod draw;
+pub mod error;
+#[cfg(feature = "notify")]
+pub mod event_log;
+pub mod extract;
+pub mod filename;
+pub mod leaderboard;
+#[cfg(feature = "cli")]
+pub mod logging;
+#[cfg(feature = "notify")]
+pub mod mqtt;
+#[cfg(feature = "notify")]
+pub mod notify;
+pub mod privacy;
+pub mod realtime;
+pub mod recovery;
+pub mod report;
+pub mod retention;
+#[cfg(feature = "cli")]
+pub mod site;
+pub mod stats;
+#[cfg(feature = "grpc")]
+pub mod status;
+pub mod store;
+// synthesized passes for the tests and benchmarks
+#[doc(hidden)]
+pub mod synthetic;
+#[cfg(feature = "grpc")]
+pub mod tasks;
+#[cfg(test)]
+mod tests;
+pub mod track;
+pub mod transform;
+pub mod utils;
+
+pub use data::DataRegistry;
+pub use error::Error;
+pub use extract::extract_recoveries;
+#[cfg(feature = "grpc")]
+pub use tasks::RecoveryTaskBuilder;
+pub use track::{Grading, Track, TrackResult};
+
+#[cfg(feature = "cli")]
+use std::path::PathBuf;
+
+#[cfg(feature = "cli")]
+use clap::ArgAction;
+
+#[cfg(feature = "cli")]
+#[derive(clap::Parser)]
+#[clap(version = env!("CARGO_PKG_VERSION"))]
+pub struct Opts {
+    /// A level of verbosity, and can be used multiple times
+    #[clap(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+    /// Enable colorized output
+    #[clap(long)]
+    pub color: bool,
+    /// The format of the log output
+    #[clap(long, value_enum, default_value = "text", global = true)]
+    pub log_format: logging::LogFormat,
+    /// Write the log to this file instead of stdout (rotated once it exceeds --log-max-size)
+    #[clap(long, global = true)]
+    pub log_file: Option<PathBuf>,
+    /// The size (in MB) at which the log file is rotated
+    #[clap(long, default_value = "10", global = true)]
+    pub log_max_size: u64,
+    /// How many rotated log files are kept
+    #[clap(long, default_value = "5", global = true)]
+    pub log_keep: usize,
+    /// A JSON config file to fine-tune the LSO (see README for the available settings)
+    #[clap(long, global = true)]
+    pub config: Option<PathBuf>,
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+#[cfg(feature = "cli")]
+#[derive(clap::Parser)]
+pub enum Command {
+    /// Connect to DCS-gRPC to track carrier recoveries.
+    #[cfg(feature = "grpc")]
+    Run(commands::run::Opts),
+
+    /// Extract carrier recoveries from ACMI recordings (created by the LSO or by Tacview).
+    File(commands::file::Opts),
+
+    /// Watch a directory for new ACMI recordings (e.g. the Tacview directory of a server) and
+    /// extract the carrier recoveries of each one as it appears.
+    Watch(commands::watch::Opts),
+
+    /// Regrade the ACMI recordings in a directory and compare the results (wire and grade) with
+    /// the stored ones, e.g. to validate changes to the grading.
+    Regrade(commands::regrade::Opts),
+
+    /// Draw the hook touchdown points of all recorded passes onto a single landing area diagram.
+    #[cfg(feature = "charts")]
+    Spotting(commands::spotting::Opts),
+
+    /// Summarize the recovery windows of each carrier (traps and the intervals between them).
+    Recoveries(commands::recoveries::Opts),
+
+    /// Write a summary of all passes (e.g. of a mission) as an HTML file.
+    Report(commands::report::Opts),
+
+    /// Write an index of all passes (with their charts, debriefs and recordings) to an output
+    /// directory, to publish it as a static website.
+    Site(commands::site::Opts),
+
+    /// Rank the pilots by their GPA and boarding rate.
+    Leaderboard(commands::leaderboard::Opts),
+
+    /// Statistics of the recorded passes.
+    #[cfg(feature = "charts")]
+    Stats(commands::stats::Opts),
+
+    /// Validate the config file.
+    Config(commands::config::Opts),
+
+    /// Check the setup (config file, output directory, connection to DCS-gRPC and its version,
+    /// Discord webhooks) and print hints on how to fix the problems found.
+    #[cfg(feature = "grpc")]
+    Doctor(commands::doctor::Opts),
+
+    /// Install, uninstall or run the LSO as a Windows service.
+    #[cfg(all(windows, feature = "grpc"))]
+    Service(commands::service::Opts),
+}
@@ -1,42 +1,21 @@
-mod client;
-mod commands;
-mod data;
-mod draw;
-mod error;
-mod tasks;
-#[cfg(test)]
-mod tests;
-mod track;
-mod transform;
-mod utils;
+use std::sync::{Arc, Mutex};
 
-use clap::{ArgAction, Parser};
+use clap::Parser;
+use lso::utils::shutdown::Shutdown;
+use lso::{commands, config, error, logging, privacy, Command, Opts};
+use tokio::sync::Notify;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
-use tracing_subscriber::{filter, fmt};
-use utils::shutdown::Shutdown;
+use tracing_subscriber::{filter, fmt, Layer, Registry};
 
-#[derive(clap::Parser)]
-#[clap(version = env!("CARGO_PKG_VERSION"))]
-struct Opts {
-    /// A level of verbosity, and can be used multiple times
-    #[clap(short, long, action = ArgAction::Count)]
-    verbose: u8,
-    /// Enable colorized output
-    #[clap(long)]
-    color: bool,
-    #[clap(subcommand)]
-    command: Command,
-}
-
-#[derive(clap::Parser)]
-enum Command {
-    /// Connect to DCS-gRPC to track carrier recoveries.
-    Run(commands::run::Opts),
-
-    /// Extract carrier recoveries from ACMI recordings (must be recordings created by the LSO;
-    /// recordings directly from TacView will not work).
-    File(commands::file::Opts),
+/// Writes one JSON object per line, with the fields of the event and of the spans it happened in.
+fn json_layer<S>() -> fmt::Layer<S, fmt::format::JsonFields, fmt::format::Format<fmt::format::Json>>
+{
+    fmt::layer()
+        .json()
+        .flatten_event(true)
+        .with_current_span(true)
+        .with_span_list(true)
 }
 
 #[tokio::main]
@@ -47,24 +26,102 @@ async fn main() {
         1 => tracing::Level::DEBUG,
         _ => tracing::Level::TRACE,
     };
+    #[cfg(windows)]
+    if let Command::Service(service) = &opts.command {
+        if let Some(dir) = service.working_dir() {
+            if let Err(err) = std::env::set_current_dir(dir) {
+                eprintln!(
+                    "Error: failed to change to the working directory `{}`: {err}",
+                    dir.display()
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let log_file = opts.log_file.as_ref().map(|path| {
+        logging::RotatingFile::open(path, opts.log_max_size * 1024 * 1024, opts.log_keep)
+            .map(Mutex::new)
+            .unwrap_or_else(|err| {
+                eprintln!("Error: failed to open log file `{}`: {err}", path.display());
+                std::process::exit(1);
+            })
+    });
+    let log_layer: Box<dyn Layer<Registry> + Send + Sync> = match (opts.log_format, log_file) {
+        (logging::LogFormat::Text, None) => fmt::layer().with_ansi(opts.color).boxed(),
+        (logging::LogFormat::Text, Some(file)) => {
+            fmt::layer().with_ansi(false).with_writer(file).boxed()
+        }
+        (logging::LogFormat::Json, None) => json_layer().boxed(),
+        (logging::LogFormat::Json, Some(file)) => json_layer().with_writer(file).boxed(),
+    };
+    #[cfg_attr(not(windows), allow(unused_mut))]
+    let mut layers = vec![log_layer];
+    #[cfg(windows)]
+    if let Command::Service(service) = &opts.command {
+        layers.extend(service.event_log_layer().map(Layer::boxed));
+    }
     tracing_subscriber::registry()
+        .with(layers)
         .with(filter::filter_fn(move |m| {
             m.target().starts_with("lso") && m.level() <= &max_level
         }))
-        .with(fmt::layer().with_ansi(opts.color))
         .init();
 
-    // shutdown gracefully on CTRL+C
+    // shutdown gracefully on CTRL+C (or when stopped as a Windows service)
     let shutdown = Shutdown::new();
     let shutdown_handle = shutdown.handle();
-    tokio::task::spawn(async {
-        tokio::signal::ctrl_c().await.unwrap();
-        shutdown.shutdown().await;
+    let stop = Arc::new(Notify::new());
+    tokio::task::spawn({
+        let stop = stop.clone();
+        async move {
+            tokio::select! {
+                result = tokio::signal::ctrl_c() => result.unwrap(),
+                _ = stop.notified() => {}
+            }
+            shutdown.shutdown().await;
+        }
     });
 
-    match opts.command {
-        Command::Run(opts) => commands::run::execute(opts, shutdown_handle).await.unwrap(),
-        // TODO: better error report than unwrap?
-        Command::File(opts) => commands::file::execute(opts).unwrap(),
+    let config = match &opts.command {
+        // these report the problems of the config file themselves
+        Command::Config(_) | Command::Doctor(_) => Default::default(),
+        _ => match opts.config.as_deref().map(config::Config::load).transpose() {
+            Ok(config) => config.unwrap_or_default(),
+            Err(err) => {
+                eprintln!("Error: {}", error::report(&err));
+                std::process::exit(1);
+            }
+        },
+    };
+    config.register_data();
+    if let Err(err) = privacy::init(&config.privacy) {
+        eprintln!("Error: {}", error::report(&err));
+        std::process::exit(1);
+    }
+
+    let result = match opts.command {
+        Command::Run(opts) => commands::run::execute(opts, config, shutdown_handle).await,
+        Command::File(opts) => commands::file::execute(opts, config),
+        Command::Watch(opts) => commands::watch::execute(opts, config, shutdown_handle).await,
+        Command::Regrade(opts) => commands::regrade::execute(opts, config),
+        Command::Spotting(opts) => commands::spotting::execute(opts, config),
+        Command::Recoveries(opts) => commands::recoveries::execute(opts, config),
+        Command::Report(opts) => commands::report::execute(opts, config).await,
+        Command::Site(opts) => commands::site::execute(opts),
+        Command::Leaderboard(opts) => commands::leaderboard::execute(opts).await,
+        Command::Stats(opts) => commands::stats::execute(opts, config),
+        Command::Config(command) => commands::config::execute(command, opts.config.as_deref()),
+        Command::Doctor(command) => {
+            commands::doctor::execute(command, opts.config.as_deref()).await
+        }
+        #[cfg(windows)]
+        Command::Service(opts) => {
+            commands::service::execute(opts, config, shutdown_handle, stop).await
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("Error: {}", error::report(&err));
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,196 @@
+//! A minimal MQTT 3.1.1 client that only publishes messages (QoS 0), which is all that is needed
+//! to forward events to a broker.
+
+use std::io;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+use crate::config::MqttConfig;
+
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+/// How long to wait for the TCP connection and the broker's CONNACK.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// The connection is considered lost if the broker didn't answer a ping within this time.
+const READ_TIMEOUT: Duration = Duration::from_secs(KEEP_ALIVE.as_secs() * 3 / 2);
+/// How long to wait before reconnecting after the connection to the broker got lost.
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+/// How many messages are queued while the broker is slow or not connected.
+const QUEUE_SIZE: usize = 256;
+/// The largest remaining length a packet can have (encoded in four bytes).
+const MAX_REMAINING_LENGTH: usize = 268_435_455;
+
+/// Publishes messages to an MQTT broker from a background task. Messages are queued while not
+/// connected to the broker, and dropped once the queue is full.
+#[derive(Clone)]
+pub struct MqttPublisher {
+    tx: mpsc::Sender<(String, Vec<u8>)>,
+}
+
+impl MqttPublisher {
+    /// Spawn the background task connecting to the broker.
+    pub fn spawn(config: MqttConfig) -> Self {
+        let (tx, mut rx) = mpsc::channel(QUEUE_SIZE);
+        tokio::spawn(async move {
+            loop {
+                let connect = tokio::time::timeout(CONNECT_TIMEOUT, connect(&config));
+                match connect
+                    .await
+                    .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()))
+                {
+                    Ok(stream) => {
+                        tracing::info!(host = %config.host, "connected to MQTT broker");
+                        match publish_all(stream, &mut rx).await {
+                            Ok(()) => return,
+                            Err(err) => tracing::warn!(%err, "lost connection to MQTT broker"),
+                        }
+                    }
+                    Err(err) => tracing::warn!(%err, "failed to connect to MQTT broker"),
+                }
+
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+
+        Self { tx }
+    }
+
+    pub fn publish(&self, topic: String, payload: Vec<u8>) {
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.tx.try_send((topic, payload)) {
+            tracing::debug!("MQTT queue is full, dropping message");
+        }
+    }
+}
+
+async fn connect(config: &MqttConfig) -> Result<TcpStream, io::Error> {
+    let connect = connect_packet(config)?;
+    let mut stream = TcpStream::connect((config.host.as_str(), config.port)).await?;
+    stream.write_all(&connect).await?;
+
+    let mut connack = [0; 4];
+    stream.read_exact(&mut connack).await?;
+    if connack[0] != 0x20 || connack[3] != 0 {
+        return Err(io::Error::other(format!(
+            "connection refused by broker (return code {})",
+            connack[3]
+        )));
+    }
+
+    Ok(stream)
+}
+
+/// Create the CONNECT packet (with a clean session and the credentials of the config, if any).
+pub fn connect_packet(config: &MqttConfig) -> Result<Vec<u8>, io::Error> {
+    let mut flags = 0x02; // clean session
+    let mut payload = Vec::new();
+    write_string(&mut payload, &config.client_id)?;
+    if let Some(username) = &config.username {
+        flags |= 0x80;
+        write_string(&mut payload, username)?;
+    }
+    // a password without a username is not allowed (reported by `Config::problems`)
+    if let Some(password) = config
+        .password
+        .as_ref()
+        .filter(|_| config.username.is_some())
+    {
+        flags |= 0x40;
+        write_string(&mut payload, password)?;
+    }
+    let mut body = Vec::new();
+    write_string(&mut body, "MQTT")?;
+    body.push(4); // protocol level 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+    body.extend_from_slice(&payload);
+    packet(0x10, &body)
+}
+
+/// Create a PUBLISH packet (QoS 0).
+pub fn publish_packet(topic: &str, payload: &[u8]) -> Result<Vec<u8>, io::Error> {
+    let mut body = Vec::with_capacity(topic.len() + payload.len() + 2);
+    write_string(&mut body, topic)?;
+    body.extend_from_slice(payload);
+    packet(0x30, &body)
+}
+
+/// Publish all messages received on `rx` until the channel is closed or the connection fails.
+async fn publish_all(
+    stream: TcpStream,
+    rx: &mut mpsc::Receiver<(String, Vec<u8>)>,
+) -> Result<(), io::Error> {
+    let (mut read, mut write) = stream.into_split();
+    let mut ping =
+        tokio::time::interval_at(tokio::time::Instant::now() + KEEP_ALIVE / 2, KEEP_ALIVE / 2);
+    let read_timeout = tokio::time::sleep(READ_TIMEOUT);
+    tokio::pin!(read_timeout);
+    let mut buf = [0; 64];
+    loop {
+        tokio::select! {
+            message = rx.recv() => {
+                let Some((topic, payload)) = message else {
+                    write.write_all(&packet(0xe0, &[])?).await?;
+                    return Ok(());
+                };
+                match publish_packet(&topic, &payload) {
+                    Ok(packet) => write.write_all(&packet).await?,
+                    Err(err) => tracing::warn!(%err, %topic, "failed to publish MQTT message"),
+                }
+            }
+            _ = ping.tick() => {
+                write.write_all(&packet(0xc0, &[])?).await?;
+            }
+            // only ping responses are expected, which are ignored
+            read = read.read(&mut buf) => {
+                if read? == 0 {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                read_timeout.as_mut().reset(tokio::time::Instant::now() + READ_TIMEOUT);
+            }
+            _ = &mut read_timeout => {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "no ping response from broker"));
+            }
+        }
+    }
+}
+
+/// Create a packet of the given type with its remaining length encoded as variable byte integer.
+pub fn packet(header: u8, body: &[u8]) -> Result<Vec<u8>, io::Error> {
+    if body.len() > MAX_REMAINING_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("packet of {} bytes is too large", body.len()),
+        ));
+    }
+
+    let mut packet = vec![header];
+    let mut len = body.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    Ok(packet)
+}
+
+/// Write a string prefixed with its length (which is limited to 65535 bytes).
+fn write_string(buf: &mut Vec<u8>, s: &str) -> Result<(), io::Error> {
+    let len = u16::try_from(s.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("string of {} bytes is too long", s.len()),
+        )
+    })?;
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(s.as_bytes());
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{Notification, Notifiers};
+
+/// How long the same error is not posted again.
+const REPEAT_AFTER: Duration = Duration::from_secs(15 * 60);
+
+/// Posts errors of the LSO itself (lost connections, failed notifications, crashed tasks) to
+/// separate chat services, so that server operators notice when passes stop being recorded. Does
+/// nothing if no chat service is set.
+#[derive(Clone, Default)]
+pub struct AdminAlerts {
+    inner: Option<Arc<Inner>>,
+    /// The server the errors are about.
+    server: Option<String>,
+}
+
+struct Inner {
+    notifiers: Notifiers,
+    /// When each error was last posted.
+    sent: Mutex<HashMap<String, Instant>>,
+    /// The servers the connection to is currently lost.
+    disconnected: Mutex<HashSet<Option<String>>>,
+}
+
+impl AdminAlerts {
+    pub fn new(notifiers: Notifiers) -> Self {
+        Self {
+            inner: (!notifiers.is_empty()).then(|| {
+                Arc::new(Inner {
+                    notifiers,
+                    sent: Default::default(),
+                    disconnected: Default::default(),
+                })
+            }),
+            server: None,
+        }
+    }
+
+    /// The alerts about the given server.
+    pub fn for_server(&self, server: Option<&str>) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            server: server.map(String::from),
+        }
+    }
+
+    /// Post the error, unless the same one was already posted recently.
+    pub fn alert(&self, message: impl Into<String>) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+
+        let message = message.into();
+        {
+            let mut sent = inner.sent.lock().unwrap();
+            let now = Instant::now();
+            sent.retain(|_, at| now.duration_since(*at) < REPEAT_AFTER);
+            if sent.contains_key(&message) {
+                return;
+            }
+            sent.insert(message.clone(), now);
+        }
+
+        self.post("LSO error", message);
+    }
+
+    /// Post that the connection to the server failed (only once until it is re-established).
+    pub fn connection_lost(&self, err: impl Display) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        if inner
+            .disconnected
+            .lock()
+            .unwrap()
+            .insert(self.server.clone())
+        {
+            self.post(
+                "LSO error",
+                format!("Lost the connection to DCS-gRPC, retrying ({})", err),
+            );
+        }
+    }
+
+    /// Post that the connection to the server is re-established (if it got lost).
+    pub fn connected(&self) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        if inner.disconnected.lock().unwrap().remove(&self.server) {
+            self.post("LSO recovered", "Connected to DCS-gRPC again".to_string());
+        }
+    }
+
+    fn post(&self, title: &str, message: String) {
+        let Some(inner) = self.inner.clone() else {
+            return;
+        };
+        let mut notification = Notification {
+            title: Some(title.to_string()),
+            text: Some(message),
+            ..Default::default()
+        };
+        if let Some(server) = &self.server {
+            notification = notification.field("Server", server, true);
+        }
+        tokio::spawn(async move {
+            inner.notifiers.notify(&notification).await;
+        });
+    }
+}
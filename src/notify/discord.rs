@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use serenity::builder::{CreateAttachment, CreateEmbed, CreateEmbedFooter, ExecuteWebhook};
+use serenity::http::Http;
+use serenity::model::id::UserId;
+use serenity::model::mention::Mention;
+
+use super::{DiscordUsers, Notification, Notifier};
+
+/// Posts to a Discord webhook, with the fields as an embed.
+pub struct DiscordNotifier {
+    webhook: String,
+    /// Discord user IDs by player name, to mention the pilots.
+    users: DiscordUsers,
+}
+
+impl DiscordNotifier {
+    pub fn new(webhook: &str, users: DiscordUsers) -> Self {
+        Self {
+            webhook: webhook.to_string(),
+            users,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &'static str {
+        "Discord"
+    }
+
+    async fn notify(&self, notification: &Notification) -> Result<(), crate::error::Error> {
+        let http = Http::new("token");
+        let webhook = http.get_webhook_from_url(&self.webhook).await?;
+
+        let mut message = ExecuteWebhook::new();
+        if notification.fields.is_empty()
+            && notification.pilot.is_none()
+            && notification.text.is_none()
+        {
+            // a title for the attached files only, which doesn't need an embed
+            if let Some(title) = &notification.title {
+                message = message.content(title);
+            }
+        } else {
+            let mut embed = CreateEmbed::new();
+            if let Some(title) = &notification.title {
+                embed = embed.title(title);
+            }
+            if let Some(pilot) = &notification.pilot {
+                let pilot = notification
+                    .ucid
+                    .as_ref()
+                    .and_then(|ucid| self.users.get(ucid))
+                    .or_else(|| self.users.get(pilot))
+                    .map(|id| Mention::from(UserId::new(id)).to_string())
+                    .unwrap_or_else(|| pilot.clone());
+                embed = embed.field("Pilot", pilot, true);
+            }
+            for field in &notification.fields {
+                embed = embed.field(&field.name, &field.value, field.inline);
+            }
+            if let Some(text) = &notification.text {
+                embed = embed.description(text);
+            }
+            if let Some(footer) = &notification.footer {
+                embed = embed.footer(CreateEmbedFooter::new(footer));
+            }
+            message = message.embeds(vec![embed]);
+        }
+        for file in &notification.files {
+            message = message.add_file(CreateAttachment::bytes(file.content().await?, file.name()));
+        }
+        webhook.execute(&http, false, message).await?;
+
+        Ok(())
+    }
+}
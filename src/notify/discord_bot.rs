@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use serenity::all::{Command, Interaction, Ready};
+use serenity::builder::{
+    CreateCommand, CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+use serenity::client::{Client, Context, EventHandler};
+use serenity::model::gateway::GatewayIntents;
+
+use super::{DiscordUsers, LINK_CODE_TTL, LINK_COMMAND};
+use crate::utils::shutdown::ShutdownHandle;
+
+/// The name of the slash command that issues a link code.
+const COMMAND_NAME: &str = "lso-link";
+
+/// A Discord bot with a `/lso-link` command, which gives the user a code to type in the DCS chat
+/// to link their player to their Discord account (instead of an admin adding them to the
+/// `--discord-users` file).
+struct Handler {
+    users: DiscordUsers,
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        let command = CreateCommand::new(COMMAND_NAME)
+            .description("Get a code to link your DCS player to your Discord account");
+        match Command::create_global_command(&ctx.http, command).await {
+            Ok(_) => tracing::info!(bot = %ready.user.name, "Discord bot ready"),
+            Err(err) => tracing::warn!(%err, "failed to register the Discord command"),
+        }
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let Interaction::Command(command) = interaction else {
+            return;
+        };
+        if command.data.name != COMMAND_NAME {
+            return;
+        }
+
+        let code = self.users.issue_code(command.user.id.get());
+        tracing::debug!(user = %command.user.name, "issued link code");
+        let message = CreateInteractionResponseMessage::new()
+            .content(format!(
+                "Type `{} {}` in the DCS chat within {} minutes to link your player to your \
+                 Discord account.",
+                LINK_COMMAND,
+                code,
+                LINK_CODE_TTL.as_secs() / 60
+            ))
+            .ephemeral(true);
+        if let Err(err) = command
+            .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+            .await
+        {
+            tracing::warn!(%err, "failed to respond to the Discord command");
+        }
+    }
+}
+
+/// Run the Discord bot until shutdown.
+pub async fn run(
+    token: &str,
+    users: DiscordUsers,
+    shutdown_handle: ShutdownHandle,
+) -> Result<(), crate::error::Error> {
+    let mut client = Client::builder(token, GatewayIntents::empty())
+        .event_handler(Handler { users })
+        .await?;
+    let shard_manager = client.shard_manager.clone();
+    tokio::spawn(async move {
+        shutdown_handle.signal().await;
+        shard_manager.shutdown_all().await;
+    });
+    client.start().await?;
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::{Notification, Notifier};
+
+/// Sends the notification to a Matrix room (with the access token of a user that joined the
+/// room), followed by the attached files.
+pub struct MatrixNotifier {
+    homeserver: String,
+    access_token: String,
+    room_id: String,
+    client: reqwest::Client,
+    /// Counter for the transaction IDs of the sent events.
+    txn: AtomicU64,
+}
+
+impl MatrixNotifier {
+    pub fn new(homeserver: &str, access_token: &str, room_id: &str) -> Self {
+        Self {
+            homeserver: homeserver.trim_end_matches('/').to_string(),
+            access_token: access_token.to_string(),
+            room_id: room_id.to_string(),
+            client: reqwest::Client::new(),
+            txn: AtomicU64::new(0),
+        }
+    }
+
+    async fn send(&self, content: serde_json::Value) -> Result<(), crate::error::Error> {
+        let txn_id = format!(
+            "lso-{}-{}",
+            time::OffsetDateTime::now_utc().unix_timestamp(),
+            self.txn.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut url = reqwest::Url::parse(&self.homeserver)
+            .map_err(|err| crate::error::Error::Notify(err.to_string()))?;
+        url.path_segments_mut()
+            .map_err(|_| crate::error::Error::Notify("invalid homeserver URL".to_string()))?
+            .extend([
+                "_matrix",
+                "client",
+                "v3",
+                "rooms",
+                &self.room_id,
+                "send",
+                "m.room.message",
+                &txn_id,
+            ]);
+        self.client
+            .put(url)
+            .bearer_auth(&self.access_token)
+            .json(&content)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for MatrixNotifier {
+    fn name(&self) -> &'static str {
+        "Matrix"
+    }
+
+    async fn notify(&self, notification: &Notification) -> Result<(), crate::error::Error> {
+        self.send(json!({ "msgtype": "m.text", "body": notification.to_text() }))
+            .await?;
+
+        for file in &notification.files {
+            let content = file.content().await?;
+            let size = content.len();
+            let res: serde_json::Value = self
+                .client
+                .post(format!("{}/_matrix/media/v3/upload", self.homeserver))
+                .query(&[("filename", file.name())])
+                .bearer_auth(&self.access_token)
+                .header(reqwest::header::CONTENT_TYPE, file.mime_type())
+                .body(content)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let Some(content_uri) = res["content_uri"].as_str() else {
+                return Err(crate::error::Error::Notify(
+                    "missing content URI of upload".to_string(),
+                ));
+            };
+            let msgtype = if file.mime_type() == "image/png" {
+                "m.image"
+            } else {
+                "m.file"
+            };
+            self.send(json!({
+                "msgtype": msgtype,
+                "body": file.name(),
+                "url": content_uri,
+                "info": { "mimetype": file.mime_type(), "size": size },
+            }))
+            .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::config::NotifierConfig;
+
+mod admin;
+#[cfg(feature = "discord")]
+mod discord;
+#[cfg(feature = "discord")]
+pub mod discord_bot;
+mod matrix;
+mod slack;
+mod telegram;
+mod users;
+mod webhook;
+
+pub use admin::AdminAlerts;
+#[cfg(feature = "discord")]
+pub use discord::DiscordNotifier;
+pub use matrix::MatrixNotifier;
+pub use slack::SlackNotifier;
+pub use telegram::TelegramNotifier;
+pub use users::{DiscordUsers, LINK_CODE_TTL, LINK_COMMAND};
+pub use webhook::WebhookNotifier;
+
+/// A message about a pass (or a summary of many) that is sent to all configured chat services.
+#[derive(Debug, Default)]
+pub struct Notification {
+    pub title: Option<String>,
+    /// The pilot the message is about (mentioned where the service supports it).
+    pub pilot: Option<String>,
+    /// The UCID of the pilot (if known), which users to mention are looked up by before their
+    /// name.
+    pub ucid: Option<String>,
+    pub fields: Vec<Field>,
+    pub text: Option<String>,
+    /// A small print at the end of the message (e.g. the ID of the pass).
+    pub footer: Option<String>,
+    /// Files (charts, recordings or reports) attached to the message.
+    pub files: Vec<Attachment>,
+}
+
+/// A file attached to a notification, either saved to disk or only kept in memory (e.g. a chart
+/// that isn't saved, see [crate::config::OutputConfig::save_charts]).
+#[derive(Debug, Clone)]
+pub enum Attachment {
+    Path(PathBuf),
+    Memory { name: String, content: Vec<u8> },
+}
+
+impl From<PathBuf> for Attachment {
+    fn from(path: PathBuf) -> Self {
+        Attachment::Path(path)
+    }
+}
+
+impl Attachment {
+    /// The filename (with extension) the file is attached as.
+    pub fn name(&self) -> String {
+        match self {
+            Attachment::Path(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            Attachment::Memory { name, .. } => name.clone(),
+        }
+    }
+
+    /// The MIME type of the file (by its extension).
+    pub fn mime_type(&self) -> &'static str {
+        match Path::new(&self.name())
+            .extension()
+            .and_then(|ext| ext.to_str())
+        {
+            Some("png") => "image/png",
+            Some("html") => "text/html",
+            _ => "application/octet-stream",
+        }
+    }
+
+    pub async fn content(&self) -> Result<Vec<u8>, crate::error::Error> {
+        match self {
+            Attachment::Path(path) => Ok(tokio::fs::read(path).await?),
+            Attachment::Memory { content, .. } => Ok(content.clone()),
+        }
+    }
+
+    /// The file as a multipart part.
+    async fn part(&self) -> Result<reqwest::multipart::Part, crate::error::Error> {
+        Ok(reqwest::multipart::Part::bytes(self.content().await?)
+            .file_name(self.name())
+            .mime_str(self.mime_type())?)
+    }
+}
+
+#[derive(Debug)]
+pub struct Field {
+    pub name: String,
+    pub value: String,
+    /// Whether the field can be shown next to other fields.
+    pub inline: bool,
+}
+
+impl Notification {
+    pub fn field(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+        inline: bool,
+    ) -> Self {
+        self.fields.push(Field {
+            name: name.into(),
+            value: value.into(),
+            inline,
+        });
+        self
+    }
+
+    /// The message as plain text (one line per field) for services without rich formatting.
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(title) = &self.title {
+            lines.push(title.clone());
+        }
+        if let Some(pilot) = &self.pilot {
+            lines.push(format!("Pilot: {}", pilot));
+        }
+        for field in &self.fields {
+            lines.push(format!("{}: {}", field.name, field.value));
+        }
+        if let Some(text) = &self.text {
+            lines.push(text.clone());
+        }
+        if let Some(footer) = &self.footer {
+            lines.push(footer.clone());
+        }
+        lines.join("\n")
+    }
+}
+
+/// A chat service notifications are sent to.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// The name of the service (for logging).
+    fn name(&self) -> &'static str;
+
+    async fn notify(&self, notification: &Notification) -> Result<(), crate::error::Error>;
+}
+
+/// All notifiers notifications are sent to.
+#[derive(Clone, Default)]
+pub struct Notifiers {
+    notifiers: Arc<Vec<Box<dyn Notifier>>>,
+    /// Where failures to send a notification are reported to.
+    admin: AdminAlerts,
+}
+
+impl Notifiers {
+    /// Create the notifiers for the Discord webhook given on the command line (with the Discord
+    /// users to mention) and the ones in the config file.
+    #[cfg_attr(not(feature = "discord"), allow(unused_variables))]
+    pub fn new(
+        discord_webhook: Option<&str>,
+        users: DiscordUsers,
+        config: &[NotifierConfig],
+    ) -> Self {
+        let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+        #[cfg(not(feature = "discord"))]
+        if discord_webhook.is_some() {
+            tracing::warn!("ignoring the Discord webhook, as the LSO is built without Discord");
+        }
+        #[cfg(feature = "discord")]
+        if let Some(discord_webhook) = discord_webhook {
+            notifiers.push(Box::new(DiscordNotifier::new(
+                discord_webhook,
+                users.clone(),
+            )));
+        }
+        for config in config {
+            notifiers.push(match config {
+                #[cfg(feature = "discord")]
+                NotifierConfig::Discord { url } => {
+                    Box::new(DiscordNotifier::new(url, users.clone()))
+                }
+                #[cfg(not(feature = "discord"))]
+                NotifierConfig::Discord { .. } => {
+                    tracing::warn!(
+                        "ignoring a Discord notifier, as the LSO is built without Discord"
+                    );
+                    continue;
+                }
+                NotifierConfig::Webhook { url } => Box::new(WebhookNotifier::new(url)),
+                NotifierConfig::Slack { token, channel } => {
+                    Box::new(SlackNotifier::new(token, channel))
+                }
+                NotifierConfig::Telegram { token, chat_id } => {
+                    Box::new(TelegramNotifier::new(token, chat_id))
+                }
+                NotifierConfig::Matrix {
+                    homeserver,
+                    access_token,
+                    room_id,
+                } => Box::new(MatrixNotifier::new(homeserver, access_token, room_id)),
+            });
+        }
+        Self {
+            notifiers: Arc::new(notifiers),
+            admin: AdminAlerts::default(),
+        }
+    }
+
+    pub fn single(notifier: impl Notifier + 'static) -> Self {
+        Self {
+            notifiers: Arc::new(vec![Box::new(notifier)]),
+            admin: AdminAlerts::default(),
+        }
+    }
+
+    /// Report failures to send a notification to the admins.
+    pub fn with_admin_alerts(mut self, admin: AdminAlerts) -> Self {
+        self.admin = admin;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+
+    /// Send the notification to all services. Failures are only logged, so that a failing
+    /// service doesn't affect the others. Returns whether it was sent to at least one service.
+    pub async fn notify(&self, notification: &Notification) -> bool {
+        let mut sent = false;
+        for notifier in self.notifiers.iter() {
+            match notifier.notify(notification).await {
+                Ok(()) => sent = true,
+                Err(err) => {
+                    tracing::warn!(%err, service = notifier.name(), "failed to send notification");
+                    self.admin.alert(format!(
+                        "Failed to send a notification to {}: {}",
+                        notifier.name(),
+                        crate::error::report(&err)
+                    ));
+                }
+            }
+        }
+        sent
+    }
+}
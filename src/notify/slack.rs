@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::{Notification, Notifier};
+
+/// Posts to a Slack channel via a bot token (which needs the `chat:write` and `files:write`
+/// scopes). Files are uploaded with the message as their comment.
+pub struct SlackNotifier {
+    token: String,
+    channel: String,
+    client: reqwest::Client,
+}
+
+impl SlackNotifier {
+    pub fn new(token: &str, channel: &str) -> Self {
+        Self {
+            token: token.to_string(),
+            channel: channel.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn call(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<serde_json::Value, crate::error::Error> {
+        let res: serde_json::Value = request
+            .bearer_auth(&self.token)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if res["ok"].as_bool() != Some(true) {
+            return Err(crate::error::Error::Notify(
+                res["error"].as_str().unwrap_or("unknown error").to_string(),
+            ));
+        }
+        Ok(res)
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    fn name(&self) -> &'static str {
+        "Slack"
+    }
+
+    async fn notify(&self, notification: &Notification) -> Result<(), crate::error::Error> {
+        let text = notification.to_text();
+        if notification.files.is_empty() {
+            self.call(
+                self.client
+                    .post("https://slack.com/api/chat.postMessage")
+                    .json(&json!({ "channel": self.channel, "text": text })),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        let mut files = Vec::new();
+        for file in &notification.files {
+            let content = file.content().await?;
+            let upload = self
+                .call(
+                    self.client
+                        .post("https://slack.com/api/files.getUploadURLExternal")
+                        .form(&[
+                            ("filename", file.name()),
+                            ("length", content.len().to_string()),
+                        ]),
+                )
+                .await?;
+            let (Some(upload_url), Some(file_id)) =
+                (upload["upload_url"].as_str(), upload["file_id"].as_str())
+            else {
+                return Err(crate::error::Error::Notify(
+                    "missing upload URL".to_string(),
+                ));
+            };
+            self.client
+                .post(upload_url)
+                .body(content)
+                .send()
+                .await?
+                .error_for_status()?;
+            files.push(json!({ "id": file_id, "title": file.name() }));
+        }
+        self.call(
+            self.client
+                .post("https://slack.com/api/files.completeUploadExternal")
+                .json(&json!({
+                    "files": files,
+                    "channel_id": self.channel,
+                    "initial_comment": text,
+                })),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
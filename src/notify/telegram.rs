@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+
+use super::{Notification, Notifier};
+
+/// Sends the notification via a Telegram bot to a chat, followed by the attached files (charts as
+/// photos, everything else as documents).
+pub struct TelegramNotifier {
+    token: String,
+    chat_id: String,
+    client: reqwest::Client,
+}
+
+impl TelegramNotifier {
+    pub fn new(token: &str, chat_id: &str) -> Self {
+        Self {
+            token: token.to_string(),
+            chat_id: chat_id.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn call(
+        &self,
+        method: &str,
+        form: reqwest::multipart::Form,
+    ) -> Result<(), crate::error::Error> {
+        let res: serde_json::Value = self
+            .client
+            .post(format!(
+                "https://api.telegram.org/bot{}/{}",
+                self.token, method
+            ))
+            .multipart(form.text("chat_id", self.chat_id.clone()))
+            .send()
+            .await
+            .map_err(without_token)?
+            .json()
+            .await
+            .map_err(without_token)?;
+        if res["ok"].as_bool() != Some(true) {
+            return Err(crate::error::Error::Notify(
+                res["description"]
+                    .as_str()
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Removes the request URL from the error, as it contains the bot token (and the error ends up in
+/// the logs and the admin notifications).
+fn without_token(err: reqwest::Error) -> reqwest::Error {
+    err.without_url()
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &'static str {
+        "Telegram"
+    }
+
+    async fn notify(&self, notification: &Notification) -> Result<(), crate::error::Error> {
+        self.call(
+            "sendMessage",
+            reqwest::multipart::Form::new().text("text", notification.to_text()),
+        )
+        .await?;
+        for file in &notification.files {
+            let (method, field) = if file.mime_type() == "image/png" {
+                ("sendPhoto", "photo")
+            } else {
+                ("sendDocument", "document")
+            };
+            self.call(
+                method,
+                reqwest::multipart::Form::new().part(field, file.part().await?),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
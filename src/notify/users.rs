@@ -0,0 +1,114 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use ulid::Ulid;
+
+/// The chat command pilots link themselves with in DCS (followed by the code).
+pub const LINK_COMMAND: &str = "-link";
+
+/// How long a link code can be used after it was issued.
+pub const LINK_CODE_TTL: Duration = Duration::from_secs(600);
+
+/// The characters of link codes (without the ones that are easily confused, like `0` and `O`).
+const LINK_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const LINK_CODE_LEN: usize = 6;
+
+/// The Discord user IDs by player UCID or name, to mention the pilots. Loaded from the
+/// `--discord-users` file, to which the links pilots make themselves (see [Self::issue_code]) are
+/// added.
+#[derive(Debug, Clone, Default)]
+pub struct DiscordUsers {
+    path: Option<PathBuf>,
+    users: Arc<RwLock<BTreeMap<String, u64>>>,
+    /// The Discord user IDs and when the codes were issued, by link code.
+    codes: Arc<Mutex<HashMap<String, (u64, Instant)>>>,
+    /// Held while saving the users file, so that concurrent links are saved one after another.
+    saving: Arc<Mutex<()>>,
+}
+
+impl DiscordUsers {
+    /// Load the users from the given file (if any). A file that doesn't exist yet is created once
+    /// the first pilot links themselves.
+    pub fn load(path: Option<&Path>) -> Result<Self, crate::error::Error> {
+        let users = match path.map(std::fs::read) {
+            Some(Ok(content)) => serde_json::from_slice(&content)?,
+            Some(Err(err)) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+            _ => BTreeMap::new(),
+        };
+        Ok(DiscordUsers {
+            path: path.map(Path::to_path_buf),
+            users: Arc::new(RwLock::new(users)),
+            codes: Default::default(),
+            saving: Default::default(),
+        })
+    }
+
+    /// The Discord user ID of the given UCID or player name.
+    pub fn get(&self, key: &str) -> Option<u64> {
+        self.users.read().unwrap().get(key).copied()
+    }
+
+    /// Issue a code the given Discord user can type in the DCS chat to link their player to their
+    /// Discord account (see [Self::link]). Issuing a new code invalidates the previous one.
+    pub fn issue_code(&self, discord_id: u64) -> String {
+        let mut codes = self.codes.lock().unwrap();
+        codes.retain(|_, (id, issued)| *id != discord_id && issued.elapsed() < LINK_CODE_TTL);
+        let code = loop {
+            let mut random = Ulid::new().random();
+            let code = (0..LINK_CODE_LEN)
+                .map(|_| {
+                    let c =
+                        LINK_CODE_ALPHABET[(random % LINK_CODE_ALPHABET.len() as u128) as usize];
+                    random /= LINK_CODE_ALPHABET.len() as u128;
+                    char::from(c)
+                })
+                .collect::<String>();
+            if !codes.contains_key(&code) {
+                break code;
+            }
+        };
+        codes.insert(code.clone(), (discord_id, Instant::now()));
+        code
+    }
+
+    /// Link the player (by UCID, or by name if the UCID isn't known) to the Discord user the given
+    /// code was issued to and save the users file. Returns the Discord user ID, or `None` if the
+    /// code is unknown or expired.
+    pub fn link(&self, code: &str, key: &str) -> Option<u64> {
+        let (discord_id, issued) = self
+            .codes
+            .lock()
+            .unwrap()
+            .remove(&code.trim().to_uppercase())?;
+        if issued.elapsed() >= LINK_CODE_TTL {
+            return None;
+        }
+
+        self.users
+            .write()
+            .unwrap()
+            .insert(key.to_string(), discord_id);
+        if let Some(path) = &self.path {
+            // save a snapshot taken while holding the save lock, so that the last save contains
+            // all links, without blocking the readers during the file I/O
+            let _saving = self.saving.lock().unwrap();
+            let users = self.users.read().unwrap().clone();
+            if let Err(err) = save(path, &users) {
+                tracing::warn!(%err, path = %path.display(), "failed to save Discord users");
+            }
+        }
+        Some(discord_id)
+    }
+}
+
+/// Write the users to a temporary file next to the users file and rename it, so that the users
+/// file is never left half-written.
+fn save(path: &Path, users: &BTreeMap<String, u64>) -> Result<(), crate::error::Error> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(users)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::{Notification, Notifier};
+
+/// Posts the notification as `multipart/form-data` to a URL, with the notification as JSON in
+/// the `payload` part and the attached files in the `file0`, `file1`, ... parts.
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn notify(&self, notification: &Notification) -> Result<(), crate::error::Error> {
+        let payload = json!({
+            "title": notification.title,
+            "pilot": notification.pilot,
+            "fields": notification
+                .fields
+                .iter()
+                .map(|field| json!({ "name": field.name, "value": field.value }))
+                .collect::<Vec<_>>(),
+            "text": notification.text,
+            "footer": notification.footer,
+        });
+        let mut form = reqwest::multipart::Form::new().part(
+            "payload",
+            reqwest::multipart::Part::text(payload.to_string()).mime_str("application/json")?,
+        );
+        for (i, file) in notification.files.iter().enumerate() {
+            form = form.part(format!("file{}", i), file.part().await?);
+        }
+        self.client
+            .post(&self.url)
+            .multipart(form)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
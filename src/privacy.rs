@@ -0,0 +1,161 @@
+//! Anonymization of pilot names (see [crate::config::PrivacyConfig]). The names of players are
+//! replaced as soon as they are read (from DCS-gRPC or a recording), so that the charts,
+//! recordings, results, exports and posts only ever contain the anonymized name. The real names
+//! are kept in a mapping file for the admins.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use crate::config::PrivacyConfig;
+use crate::utils::fnv1a;
+
+static ANONYMIZER: Lazy<Mutex<Option<Anonymizer>>> = Lazy::new(|| Mutex::new(None));
+static SAVING: Mutex<()> = Mutex::new(());
+
+/// How the names of players are anonymized.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PilotNames {
+    /// Keep the names as they are.
+    #[default]
+    Keep,
+    /// Replace each name with a hash of it (e.g. `Pilot-3f9a1c2e`), which is the same on every
+    /// server using the same salt.
+    Hash,
+    /// Replace each name with a sequential alias (e.g. `Pilot 7`) in the order the pilots are
+    /// first seen.
+    Alias,
+}
+
+/// Replaces pilot names according to the privacy settings and keeps track of the replacements in
+/// the mapping file (anonymized name to real name).
+#[derive(Debug)]
+pub struct Anonymizer {
+    mode: PilotNames,
+    salt: String,
+    mapping_file: PathBuf,
+    /// Anonymized name to real name.
+    names: BTreeMap<String, String>,
+    /// Real name to anonymized name.
+    anonymized: HashMap<String, String>,
+}
+
+impl Anonymizer {
+    /// Create an anonymizer with the replacements of the mapping file (if it exists yet).
+    pub fn load(config: &PrivacyConfig) -> Result<Self, crate::error::Error> {
+        let names: BTreeMap<String, String> = match std::fs::read(&config.mapping_file) {
+            Ok(content) => serde_json::from_slice(&content)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Anonymizer {
+            mode: config.pilot_names,
+            salt: config.salt.clone(),
+            mapping_file: config.mapping_file.clone(),
+            anonymized: names
+                .iter()
+                .map(|(anonymized, real)| (real.clone(), anonymized.clone()))
+                .collect(),
+            names,
+        })
+    }
+
+    /// Anonymize the given pilot name as read from DCS. Returns whether the name is new, and thus
+    /// the mapping file has to be saved again.
+    pub fn pilot_name(&mut self, name: &str) -> (String, bool) {
+        if let Some(anonymized) = self.anonymized.get(name) {
+            return (anonymized.clone(), false);
+        }
+
+        let anonymized = match self.mode {
+            PilotNames::Keep => return (name.to_string(), false),
+            PilotNames::Hash => {
+                format!("Pilot-{:08x}", fnv1a(self.salt.bytes().chain(name.bytes())))
+            }
+            PilotNames::Alias => (self.names.len() + 1..)
+                .map(|n| format!("Pilot {}", n))
+                .find(|alias| !self.names.contains_key(alias))
+                .unwrap_or_default(),
+        };
+        self.names.insert(anonymized.clone(), name.to_string());
+        self.anonymized.insert(name.to_string(), anonymized.clone());
+        (anonymized, true)
+    }
+
+    /// Anonymize the given pilot name as read from a recording. Names that already are anonymized
+    /// (i.e. the recording was made with anonymization enabled) are returned as they are.
+    pub fn recorded_pilot_name(&mut self, name: &str) -> (String, bool) {
+        if self.names.contains_key(name) {
+            return (name.to_string(), false);
+        }
+        self.pilot_name(name)
+    }
+
+    /// Write the mapping to a temporary file next to the mapping file and rename it, so that the
+    /// mapping file is never left half-written.
+    pub fn save(&self) -> Result<(), crate::error::Error> {
+        save(&self.mapping_file, &self.names)
+    }
+}
+
+fn save(path: &Path, names: &BTreeMap<String, String>) -> Result<(), crate::error::Error> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(names)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Save the current mapping. Saves are serialized and each writes the latest mapping, so that a
+/// save that finishes last never writes an outdated one.
+fn save_mapping() {
+    let _saving = SAVING.lock().unwrap();
+    let Some((path, names)) = ANONYMIZER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|anonymizer| (anonymizer.mapping_file.clone(), anonymizer.names.clone()))
+    else {
+        return;
+    };
+    if let Err(err) = save(&path, &names) {
+        tracing::warn!(%err, path = %path.display(), "failed to save pilot names");
+    }
+}
+
+/// Enable the anonymization of pilot names (if configured), once at startup.
+pub fn init(config: &PrivacyConfig) -> Result<(), crate::error::Error> {
+    if config.pilot_names != PilotNames::Keep {
+        *ANONYMIZER.lock().unwrap() = Some(Anonymizer::load(config)?);
+    }
+    Ok(())
+}
+
+/// The name the given player is recorded under (anonymized if enabled, see [init]).
+pub fn pilot_name(name: &str) -> String {
+    anonymize(name, Anonymizer::pilot_name)
+}
+
+/// The name the pilot read from a recording is recorded under (see [pilot_name]).
+pub fn recorded_pilot_name(name: &str) -> String {
+    anonymize(name, Anonymizer::recorded_pilot_name)
+}
+
+fn anonymize(name: &str, f: impl FnOnce(&mut Anonymizer, &str) -> (String, bool)) -> String {
+    let (anonymized, is_new) = match ANONYMIZER.lock().unwrap().as_mut() {
+        Some(anonymizer) => f(anonymizer, name),
+        None => return name.to_string(),
+    };
+    if is_new {
+        // don't block the async runtime with file I/O
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => drop(handle.spawn_blocking(save_mapping)),
+            Err(_) => save_mapping(),
+        }
+    }
+    anonymized
+}
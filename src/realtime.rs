@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::mem::discriminant;
+#[cfg(feature = "cli")]
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "cli")]
+use std::time::Duration;
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use tacview::record::{Coords, Event, GlobalProperty, Property, Record, Update};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+#[cfg(feature = "cli")]
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+#[cfg(feature = "cli")]
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+
+#[cfg(feature = "cli")]
+use crate::utils::shutdown::ShutdownHandle;
+
+/// The range of object IDs each recording gets in the shared stream (as all recordings use the
+/// same IDs for the carrier and the plane). Recordings must only use IDs below it.
+pub const IDS_PER_RECORDING: u64 = 0x100;
+
+/// How long to wait for the handshake of a connecting client.
+#[cfg(feature = "cli")]
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The length of the file header every [tacview::Writer] starts with.
+static WRITER_HEADER_LEN: Lazy<usize> = Lazy::new(|| {
+    tacview::Writer::new(Vec::new())
+        .map(|writer| writer.into_inner().len())
+        .unwrap_or_default()
+});
+
+/// Serves all recordings (while they are being recorded) to Tacview clients via its real-time
+/// telemetry protocol, so that the approaches can be watched live.
+///
+/// The recordings (possibly of different servers and missions) share the stream, so they are
+/// timed by a single clock: the time since the stream started, with the stream's start as the
+/// reference time. Each recording keeps its own timing, shifted onto that clock when it starts.
+#[derive(Clone)]
+pub struct RealtimeTelemetry {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// The name Tacview shows for the stream.
+    #[cfg_attr(not(feature = "cli"), allow(dead_code))]
+    name: String,
+    /// When the stream started (its time `0`).
+    started: Instant,
+    /// The start of the stream (RFC 3339).
+    reference_time: String,
+    tx: broadcast::Sender<Arc<[u8]>>,
+    state: Mutex<State>,
+}
+
+/// What a client needs to catch up when connecting in the middle of recordings.
+#[derive(Default)]
+struct State {
+    /// The time of the last frame sent (never decreases).
+    time: f64,
+    /// All objects currently shown (with their properties merged).
+    objects: HashMap<u64, Vec<Property>>,
+    next_base: u64,
+}
+
+impl RealtimeTelemetry {
+    /// Create the stream without serving it (see [Self::spawn]).
+    pub fn new(name: String) -> Self {
+        let (tx, _) = broadcast::channel(4096);
+        RealtimeTelemetry {
+            inner: Arc::new(Inner {
+                name,
+                started: Instant::now(),
+                reference_time: OffsetDateTime::now_utc()
+                    .replace_nanosecond(0)
+                    .ok()
+                    .and_then(|time| time.format(&Rfc3339).ok())
+                    .unwrap_or_default(),
+                tx,
+                state: Mutex::new(State {
+                    next_base: IDS_PER_RECORDING,
+                    ..Default::default()
+                }),
+            }),
+        }
+    }
+
+    /// Receive everything sent to the clients from now on (without the header and snapshot of
+    /// [Self::snapshot]).
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<[u8]>> {
+        self.inner.tx.subscribe()
+    }
+
+    /// An ACMI file of the current state of all recordings, which a client needs to catch up when
+    /// connecting in the middle of them.
+    pub fn snapshot(&self) -> Result<Vec<u8>, std::io::Error> {
+        let state = self.inner.state.lock().unwrap();
+        let mut writer = tacview::Writer::new(Vec::new())?;
+        writer.write(GlobalProperty::ReferenceLongitude(0.0))?;
+        writer.write(GlobalProperty::ReferenceLatitude(0.0))?;
+        writer.write(GlobalProperty::ReferenceTime(
+            self.inner.reference_time.clone(),
+        ))?;
+        writer.write(Record::Frame(state.time))?;
+        for (id, props) in &state.objects {
+            writer.write(Update {
+                id: *id,
+                props: props.clone(),
+            })?;
+        }
+        Ok(writer.into_inner())
+    }
+
+    /// Start streaming a new recording.
+    pub fn recording(&self) -> LiveRecording {
+        let mut state = self.inner.state.lock().unwrap();
+        let base = state.next_base;
+        state.next_base += IDS_PER_RECORDING;
+        LiveRecording {
+            telemetry: self.clone(),
+            base,
+            reference: (0.0, 0.0),
+            offset: None,
+            time: 0.0,
+            objects: Vec::new(),
+        }
+    }
+
+    fn send(&self, time: f64, record: Record) {
+        let mut state = self.inner.state.lock().unwrap();
+        match &record {
+            Record::Update(update) => {
+                let props = state.objects.entry(update.id).or_default();
+                for prop in &update.props {
+                    merge(props, prop);
+                }
+            }
+            Record::Remove(id) => {
+                state.objects.remove(id);
+            }
+            _ => {}
+        }
+
+        // recordings that started in between might already be ahead of this one
+        let time = time.max(state.time);
+        let mut data = Vec::new();
+        if time != state.time {
+            state.time = time;
+            let _ = writeln!(data, "#{}", time);
+        }
+        // the header of the writer is not needed, only the record
+        let Ok(mut writer) = tacview::Writer::new(Vec::new()) else {
+            return;
+        };
+        if writer.write(record).is_err() {
+            return;
+        }
+        data.extend_from_slice(&writer.into_inner()[*WRITER_HEADER_LEN..]);
+        // fails if there is no client connected, which is fine
+        let _ = self.inner.tx.send(data.into());
+    }
+}
+
+// serving the stream to Tacview clients is only needed by `lso run`
+#[cfg(feature = "cli")]
+impl RealtimeTelemetry {
+    /// Listen for Tacview clients on `addr` until shutdown.
+    pub async fn spawn(
+        addr: SocketAddr,
+        name: String,
+        shutdown_handle: ShutdownHandle,
+    ) -> Result<Self, std::io::Error> {
+        let listener = TcpListener::bind(addr).await?;
+        tracing::info!(%addr, "serving Tacview real-time telemetry");
+        let telemetry = RealtimeTelemetry::new(name);
+
+        let server = telemetry.clone();
+        tokio::spawn(async move {
+            loop {
+                let accepted = tokio::select! {
+                    accepted = listener.accept() => accepted,
+                    _ = shutdown_handle.signal() => return,
+                };
+                match accepted {
+                    Ok((stream, addr)) => {
+                        let server = server.clone();
+                        tokio::spawn(async move {
+                            tracing::info!(%addr, "Tacview client connected");
+                            if let Err(err) = server.serve(stream).await {
+                                tracing::debug!(%addr, %err, "Tacview client disconnected");
+                            }
+                        });
+                    }
+                    Err(err) => tracing::warn!(%err, "failed to accept Tacview client"),
+                }
+            }
+        });
+
+        Ok(telemetry)
+    }
+
+    async fn serve(&self, stream: TcpStream) -> Result<(), std::io::Error> {
+        let (rd, mut wr) = stream.into_split();
+        wr.write_all(
+            format!(
+                "XtraLib.Stream.0\nTacview.RealTimeTelemetry.0\n{}\n\0",
+                self.inner.name
+            )
+            .as_bytes(),
+        )
+        .await?;
+
+        // The client answers with the same protocol lines, its name and a password hash
+        // (terminated by a null byte), which isn't checked.
+        let mut handshake = Vec::new();
+        tokio::time::timeout(
+            HANDSHAKE_TIMEOUT,
+            BufReader::new(rd).read_until(0, &mut handshake),
+        )
+        .await
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+        if !handshake.starts_with(b"XtraLib.Stream.0\n") {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "invalid handshake",
+            ));
+        }
+
+        // subscribe before taking the snapshot to not miss anything in between
+        let mut rx = self.subscribe();
+        let snapshot = self.snapshot()?;
+        wr.write_all(&snapshot).await?;
+
+        loop {
+            match rx.recv().await {
+                Ok(data) => wr.write_all(&data).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!(skipped, "Tacview client is lagging behind");
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Merge a property into the known properties of an object (coordinates field by field).
+pub fn merge(props: &mut Vec<Property>, prop: &Property) {
+    match (
+        props
+            .iter_mut()
+            .find(|p| discriminant(*p) == discriminant(prop)),
+        prop,
+    ) {
+        (Some(Property::T(known)), Property::T(coords)) => {
+            let merge = |known: &mut Option<f64>, value: Option<f64>| {
+                if value.is_some() {
+                    *known = value;
+                }
+            };
+            merge(&mut known.longitude, coords.longitude);
+            merge(&mut known.latitude, coords.latitude);
+            merge(&mut known.altitude, coords.altitude);
+            merge(&mut known.roll, coords.roll);
+            merge(&mut known.pitch, coords.pitch);
+            merge(&mut known.yaw, coords.yaw);
+            merge(&mut known.u, coords.u);
+            merge(&mut known.v, coords.v);
+            merge(&mut known.heading, coords.heading);
+        }
+        (Some(known), prop) => *known = prop.clone(),
+        (None, prop) => props.push(prop.clone()),
+    }
+}
+
+/// A recording as streamed to the Tacview clients. Its objects are removed from the stream when
+/// it is dropped.
+pub struct LiveRecording {
+    telemetry: RealtimeTelemetry,
+    /// The offset of the object IDs of this recording in the stream.
+    base: u64,
+    /// The reference longitude and latitude of the recording (the stream uses absolute
+    /// coordinates, as each recording has its own reference).
+    reference: (f64, f64),
+    /// What is added to the times of the recording to get the time of the stream (set with the
+    /// first frame).
+    offset: Option<f64>,
+    /// The time of the current frame (in the stream).
+    time: f64,
+    objects: Vec<u64>,
+}
+
+impl LiveRecording {
+    pub fn write(&mut self, record: &Record) {
+        let id = match record {
+            Record::Update(Update { id, .. }) | Record::Remove(id) => Some(*id),
+            _ => None,
+        };
+        if let Some(id) = id {
+            debug_assert!(id < IDS_PER_RECORDING, "object ID {:x} out of range", id);
+            // would overwrite the objects of another recording
+            if id >= IDS_PER_RECORDING {
+                tracing::warn!(id, "object ID out of the recording's range, not streamed");
+                return;
+            }
+        }
+
+        let record = match record {
+            Record::Frame(time) => {
+                let started = self.telemetry.inner.started;
+                let offset = *self
+                    .offset
+                    .get_or_insert_with(|| started.elapsed().as_secs_f64() - time);
+                self.time = time + offset;
+                return;
+            }
+            Record::GlobalProperty(GlobalProperty::ReferenceLongitude(lon)) => {
+                self.reference.0 = *lon;
+                return;
+            }
+            Record::GlobalProperty(GlobalProperty::ReferenceLatitude(lat)) => {
+                self.reference.1 = *lat;
+                return;
+            }
+            // the other global properties (reference time, title, ...) describe a single recording
+            Record::GlobalProperty(_) => return,
+            Record::Update(update) => {
+                let id = self.base + update.id;
+                if !self.objects.contains(&id) {
+                    self.objects.push(id);
+                }
+                Record::Update(Update {
+                    id,
+                    props: update
+                        .props
+                        .iter()
+                        .map(|prop| match prop {
+                            Property::T(coords) => Property::T(Coords {
+                                longitude: coords.longitude.map(|lon| lon + self.reference.0),
+                                latitude: coords.latitude.map(|lat| lat + self.reference.1),
+                                ..coords.clone()
+                            }),
+                            prop => prop.clone(),
+                        })
+                        .collect(),
+                })
+            }
+            Record::Remove(id) => {
+                let id = self.base + id;
+                self.objects.retain(|o| *o != id);
+                Record::Remove(id)
+            }
+            Record::Event(event) => Record::Event(Event {
+                kind: event.kind.clone(),
+                params: event
+                    .params
+                    .iter()
+                    .map(|param| match u64::from_str_radix(param, 16) {
+                        // only the params that are objects of this recording are IDs
+                        Ok(id) if self.objects.contains(&(self.base + id)) => {
+                            format!("{:x}", self.base + id)
+                        }
+                        _ => param.clone(),
+                    })
+                    .collect(),
+                text: event.text.clone(),
+            }),
+        };
+        self.telemetry.send(self.time, record);
+    }
+}
+
+impl Drop for LiveRecording {
+    fn drop(&mut self) {
+        for id in std::mem::take(&mut self.objects) {
+            self.telemetry.send(self.time, Record::Remove(id));
+        }
+    }
+}
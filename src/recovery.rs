@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+
+use crate::store::{PassRecord, WindowPass};
+use crate::track::Grading;
+
+/// The passes flown to a single carrier without a longer break in between (see
+/// [crate::config::RecoveryConfig::max_gap_s]).
+pub struct RecoveryWindow<'a> {
+    pub server: Option<&'a str>,
+    /// The unit name of the carrier (or its type for results recorded before the name was stored).
+    pub carrier: &'a str,
+    /// The passes of the window (ordered by time).
+    pub passes: Vec<(OffsetDateTime, &'a PassRecord)>,
+}
+
+impl<'a> RecoveryWindow<'a> {
+    pub fn start(&self) -> OffsetDateTime {
+        self.passes.first().map(|(time, _)| *time).unwrap()
+    }
+
+    pub fn end(&self) -> OffsetDateTime {
+        self.passes.last().map(|(time, _)| *time).unwrap()
+    }
+
+    /// The passes of the window with their number within the window.
+    pub fn numbered_passes(
+        &self,
+    ) -> impl Iterator<Item = (u32, OffsetDateTime, &'a PassRecord)> + '_ {
+        self.passes.iter().enumerate().map(|(i, (time, record))| {
+            let number = record.window.as_ref().map_or(i as u32 + 1, |w| w.pass);
+            (number, *time, *record)
+        })
+    }
+
+    /// The times of all traps of the window.
+    pub fn traps(&self) -> impl Iterator<Item = OffsetDateTime> + '_ {
+        self.passes
+            .iter()
+            .filter(|(_, record)| matches!(record.grading, Grading::Recovered { .. }))
+            .map(|(time, _)| *time)
+    }
+
+    /// The time between each two consecutive traps.
+    pub fn trap_intervals(&self) -> Vec<Duration> {
+        let traps = self.traps().collect::<Vec<_>>();
+        traps.windows(2).map(|w| w[1] - w[0]).collect()
+    }
+
+    /// The average time between two consecutive traps (if there were at least two traps).
+    pub fn average_trap_interval(&self) -> Option<Duration> {
+        let intervals = self.trap_intervals();
+        if intervals.is_empty() {
+            return None;
+        }
+        Some(intervals.iter().sum::<Duration>() / intervals.len() as u32)
+    }
+}
+
+/// Group the given passes into recovery windows per server and carrier. Passes are grouped by the
+/// window they were assigned to while recording. For passes recorded before windows were stored, a
+/// new window starts after a break of more than `max_gap`. Passes without a valid time are
+/// ignored. The windows are ordered by their start.
+pub fn recovery_windows(records: &[PassRecord], max_gap: Duration) -> Vec<RecoveryWindow<'_>> {
+    let mut passes = records
+        .iter()
+        .filter_map(|record| Some((record.time()?, record)))
+        .collect::<Vec<_>>();
+    passes.sort_by_key(|(time, record)| (record.server.as_deref(), carrier(record), *time));
+
+    let mut windows: Vec<RecoveryWindow<'_>> = Vec::new();
+    for (time, record) in passes {
+        match windows.last_mut() {
+            Some(window)
+                if window.server == record.server.as_deref()
+                    && window.carrier == carrier(record)
+                    && match (&window.passes.last().unwrap().1.window, &record.window) {
+                        (Some(a), Some(b)) => a.start == b.start,
+                        _ => time - window.end() <= max_gap,
+                    } =>
+            {
+                window.passes.push((time, record));
+            }
+            _ => windows.push(RecoveryWindow {
+                server: record.server.as_deref(),
+                carrier: carrier(record),
+                passes: vec![(time, record)],
+            }),
+        }
+    }
+
+    windows.sort_by_key(|window| window.start());
+    windows
+}
+
+/// Assigns passes to the recovery windows of the carriers of a server while recording.
+#[derive(Clone)]
+pub struct RecoveryWindows {
+    max_gap: Duration,
+    /// The latest window by carrier name.
+    windows: Arc<Mutex<HashMap<String, OpenWindow>>>,
+}
+
+struct OpenWindow {
+    start: OffsetDateTime,
+    end: OffsetDateTime,
+    passes: u32,
+}
+
+impl OpenWindow {
+    fn new(start: OffsetDateTime) -> Self {
+        Self {
+            start,
+            end: start,
+            passes: 0,
+        }
+    }
+}
+
+impl RecoveryWindows {
+    /// Continue the windows of the given passes already recorded on the server (e.g. after a
+    /// restart).
+    pub fn new(max_gap: Duration, server: Option<&str>, records: &[PassRecord]) -> Self {
+        let records = records
+            .iter()
+            .filter(|r| r.server.as_deref() == server)
+            .cloned()
+            .collect::<Vec<_>>();
+        let windows = recovery_windows(&records, max_gap)
+            .into_iter()
+            .map(|window| {
+                let passes = window
+                    .numbered_passes()
+                    .last()
+                    .map_or(0, |(number, _, _)| number);
+                (
+                    window.carrier.to_string(),
+                    OpenWindow {
+                        start: window.start(),
+                        end: window.end(),
+                        passes,
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            max_gap,
+            windows: Arc::new(Mutex::new(windows)),
+        }
+    }
+
+    /// Assign a pass that ended at the given time to the carrier's current window, or start a new
+    /// one if there was a break of more than `max_gap`.
+    pub fn assign(&self, carrier: &str, time: OffsetDateTime) -> WindowPass {
+        let mut windows = self.windows.lock().unwrap();
+        let window = windows
+            .entry(carrier.to_string())
+            .or_insert_with(|| OpenWindow::new(time));
+        if time - window.end > self.max_gap {
+            *window = OpenWindow::new(time);
+        }
+        window.end = window.end.max(time);
+        window.passes += 1;
+
+        WindowPass {
+            start: window.start.format(&Rfc3339).unwrap_or_default(),
+            pass: window.passes,
+        }
+    }
+}
+
+fn carrier(record: &PassRecord) -> &str {
+    record.carrier.as_deref().unwrap_or(&record.carrier_type)
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use time::Duration;
+
+use crate::dcs_grading::DcsGrading;
+use crate::filename::TIME_SECONDS_FORMAT;
+use crate::recovery::recovery_windows;
+use crate::stats;
+use crate::store::PassRecord;
+use crate::track::Grading;
+
+/// Write a summary of the given passes (e.g. of a mission) as a standalone HTML file. It lists
+/// every pass (grouped by recovery window) and the boarding rate of each pilot.
+pub fn write_html(
+    path: &Path,
+    title: &str,
+    records: &[PassRecord],
+    max_gap: Duration,
+) -> Result<(), crate::error::Error> {
+    let mut html = String::new();
+    writeln!(html, "{}", html_head(title, ""))?;
+    writeln!(html, "<h1>{}</h1>", escape(title))?;
+
+    for window in recovery_windows(records, max_gap) {
+        writeln!(
+            html,
+            "<h2>{}{}, {}</h2>",
+            window
+                .server
+                .map(|s| format!("[{}] ", escape(s)))
+                .unwrap_or_default(),
+            escape(window.carrier),
+            window
+                .start()
+                .format(TIME_SECONDS_FORMAT)
+                .unwrap_or_default()
+        )?;
+        writeln!(
+            html,
+            "<table>\n<tr><th>#</th><th>Time</th><th>Pilot</th><th>Aircraft</th><th>Result</th>\
+             <th>DCS LSO</th><th>Points</th><th>Groove</th><th>Notes</th></tr>"
+        )?;
+        for (number, time, record) in window.numbered_passes() {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                number,
+                time.format(TIME_SECONDS_FORMAT).unwrap_or_default(),
+                escape(&record.pilot),
+                escape(&record.plane_type),
+                escape(&record.grading.summary(record.hook_up)),
+                record
+                    .dcs_comment
+                    .as_deref()
+                    .map(|comment| escape(DcsGrading::parse(comment).grade.as_str()))
+                    .unwrap_or_default(),
+                stats::points(record)
+                    .map(|p| format!("{:.1}", p))
+                    .unwrap_or_default(),
+                record
+                    .groove_time
+                    .map(|t| format!("{:.0}s", t))
+                    .unwrap_or_default(),
+                escape(&notes(record).join(", ")),
+            )?;
+        }
+        writeln!(html, "</table>")?;
+    }
+
+    writeln!(html, "<h2>Boarding rates</h2>")?;
+    writeln!(
+        html,
+        "<table>\n<tr><th>Pilot</th><th>Passes</th><th>Traps</th><th>Boarding rate</th><th>GPA</th></tr>"
+    )?;
+    for (pilot, (passes, traps)) in boarding_rates(records) {
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.0}%</td><td>{}</td></tr>",
+            escape(pilot),
+            passes,
+            traps,
+            traps as f64 / passes as f64 * 100.0,
+            stats::gpa(records.iter().filter(|r| r.pilot == pilot))
+                .map(|gpa| format!("{:.2}", gpa))
+                .unwrap_or_default(),
+        )?;
+    }
+    writeln!(html, "</table>\n</body>\n</html>")?;
+
+    std::fs::write(path, html)?;
+
+    Ok(())
+}
+
+/// The number of passes and traps of each pilot. Touch-and-goes don't count as passes.
+fn boarding_rates(records: &[PassRecord]) -> BTreeMap<&str, (usize, usize)> {
+    let mut rates: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for record in records.iter().filter(|r| !r.is_touch_and_go()) {
+        let (passes, traps) = rates.entry(record.pilot.as_str()).or_default();
+        *passes += 1;
+        if matches!(record.grading, Grading::Recovered { .. }) {
+            *traps += 1;
+        }
+    }
+    rates
+}
+
+pub fn notes(record: &PassRecord) -> Vec<String> {
+    let mut notes = Vec::new();
+    if let Some(unit_name) = &record.foul_deck {
+        notes.push(format!("foul deck ({})", unit_name));
+    }
+    if record.carrier_turning {
+        notes.push("carrier turning".to_string());
+    }
+    if let Some(deck_motion) = record.deck_motion.filter(|m| !m.is_steady()) {
+        notes.push(format!("{} deck", deck_motion.rating()));
+    }
+    if record.interrupted {
+        notes.push("recording interrupted".to_string());
+    }
+    notes
+}
+
+/// The start of a standalone HTML page (up to and including `<body>`) with the given title, styled
+/// like all pages of the LSO plus the page's own `extra_css`.
+pub fn html_head(title: &str, extra_css: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}\n{}</style>\n</head>\n<body>",
+        escape(title),
+        STYLE,
+        extra_css
+    )
+}
+
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The style shared by all pages.
+const STYLE: &str =
+    "body { background: #111; color: #eee; font-family: sans-serif; margin: 2em; } \
+a { color: #94a3b8; } \
+table { border-collapse: collapse; margin-bottom: 2em; } \
+th, td { border-bottom: 1px solid #444; padding: 0.3em 0.8em; text-align: left; }";
@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use futures_util::future::{select, Either};
+use ulid::Ulid;
+
+use crate::config::RetentionConfig;
+use crate::filename::{INDEX_FILENAME, SESSION_REPORT_PREFIX};
+use crate::utils::shutdown::ShutdownHandle;
+
+/// How often the output directory is cleaned up.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The extensions of the outputs (charts, recordings, debriefs and session reports).
+const EXTENSIONS: &[&str] = &["png", "acmi", "html"];
+
+/// A chart, recording or report in the output directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputFile {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+    pub size: u64,
+}
+
+/// Clean up the output directory according to the retention settings once per hour until
+/// shutdown.
+pub async fn cleanup_periodically(
+    out_dir: PathBuf,
+    config: RetentionConfig,
+    shutdown_handle: ShutdownHandle,
+) {
+    loop {
+        let dir = out_dir.clone();
+        match tokio::task::spawn_blocking(move || cleanup(&dir, &config, SystemTime::now())).await {
+            Ok(Ok(removed)) if !removed.is_empty() => {
+                tracing::info!(
+                    files = removed.len(),
+                    mb = removed.iter().map(|f| f.size).sum::<u64>() / 1_000_000,
+                    "removed old outputs"
+                );
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => tracing::warn!(%err, "failed to clean up outputs"),
+            Err(err) => tracing::warn!(%err, "failed to clean up outputs"),
+        }
+
+        let sleep = Box::pin(tokio::time::sleep(CLEANUP_INTERVAL));
+        if let Either::Right(_) = select(sleep, Box::pin(shutdown_handle.signal())).await {
+            return;
+        }
+    }
+}
+
+/// Remove the outputs in `dir` (and its subdirectories) that exceed the retention settings.
+/// Returns the removed files.
+///
+/// Only files written by the LSO are removed: the charts, recordings and debriefs of the passes in
+/// the results file in `dir` (matched by the pass ID in their filenames, see [pass_id]) and the
+/// session reports. Everything else (e.g. the index written by `lso site`) is kept.
+pub fn cleanup(
+    dir: &Path,
+    config: &RetentionConfig,
+    now: SystemTime,
+) -> Result<Vec<OutputFile>, crate::error::Error> {
+    let results_path = dir.join(crate::store::RESULTS_FILENAME);
+    let ids = if results_path.exists() {
+        crate::store::load_as_recorded(&results_path)?
+            .into_iter()
+            .filter_map(|record| record.id?.parse::<Ulid>().ok())
+            .collect()
+    } else {
+        HashSet::new()
+    };
+
+    let mut files = Vec::new();
+    collect(dir, &mut files)?;
+    files.retain(|file| is_output(&file.path, &ids));
+
+    let mut removed = Vec::new();
+    for file in expired(files, config, now) {
+        match std::fs::remove_file(&file.path) {
+            Ok(()) => {
+                tracing::debug!(path = %file.path.display(), "removed old output");
+                remove_empty_parents(dir, &file.path);
+                removed.push(file);
+            }
+            Err(err) => {
+                tracing::warn!(%err, path = %file.path.display(), "failed to remove old output");
+            }
+        }
+    }
+    Ok(removed)
+}
+
+/// Whether the file at `path` was written by the LSO, for one of the passes with the given IDs.
+fn is_output(path: &Path, ids: &HashSet<Ulid>) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    if name == INDEX_FILENAME {
+        return false;
+    }
+    (name.starts_with(SESSION_REPORT_PREFIX) && name.ends_with(".html"))
+        || pass_id(name).is_some_and(|id| ids.contains(&id))
+}
+
+/// Remove the directories below `dir` the removed file at `path` left empty (e.g. those of a
+/// single mission or pilot).
+fn remove_empty_parents(dir: &Path, path: &Path) {
+    let mut parent = path.parent();
+    while let Some(subdir) = parent.filter(|subdir| *subdir != dir && subdir.starts_with(dir)) {
+        // fails once a directory isn't empty
+        if std::fs::remove_dir(subdir).is_err() {
+            break;
+        }
+        parent = subdir.parent();
+    }
+}
+
+/// The ID of the pass in the given filename (see [crate::filename::DEFAULT_TEMPLATE]), if any.
+pub fn pass_id(filename: &str) -> Option<Ulid> {
+    filename
+        .split(['-', '.', '_'])
+        .filter(|part| part.len() == ulid::ULID_LEN)
+        .find_map(|part| Ulid::from_string(part).ok())
+}
+
+/// The files that are older than the max age, plus the oldest of the remaining files until they
+/// fit into the max size.
+pub fn expired(
+    mut files: Vec<OutputFile>,
+    config: &RetentionConfig,
+    now: SystemTime,
+) -> Vec<OutputFile> {
+    files.sort_by_key(|f| f.modified);
+    let max_age = config
+        .max_age_days
+        .map(|days| Duration::from_secs_f64(days.max(0.0) * 24.0 * 60.0 * 60.0));
+    let mut total_size = files.iter().map(|f| f.size).sum::<u64>();
+    let max_size = config
+        .max_size_mb
+        .map(|mb| (mb.max(0.0) * 1_000_000.0) as u64);
+
+    files
+        .into_iter()
+        .filter(|file| {
+            let too_old = max_age.is_some_and(|max_age| {
+                now.duration_since(file.modified)
+                    .is_ok_and(|age| age > max_age)
+            });
+            let too_big = max_size.is_some_and(|max_size| total_size > max_size);
+            if too_old || too_big {
+                total_size -= file.size;
+                true
+            } else {
+                false
+            }
+        })
+        .collect()
+}
+
+/// Add the charts, recordings and reports in `dir` (and its subdirectories) to `files`, whether
+/// the LSO wrote them or not.
+pub fn collect(dir: &Path, files: &mut Vec<OutputFile>) -> Result<(), std::io::Error> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let path = entry.path();
+        if metadata.is_dir() {
+            collect(&path, files)?;
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| EXTENSIONS.contains(&ext))
+        {
+            files.push(OutputFile {
+                path,
+                modified: metadata.modified()?,
+                size: metadata.len(),
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use ulid::Ulid;
+
+use crate::dcs_grading::DcsGrading;
+use crate::filename::DATETIME_FORMAT;
+use crate::report::{escape, html_head};
+use crate::retention::{pass_id, OutputFile};
+use crate::stats;
+use crate::store::PassRecord;
+use crate::track::Grading;
+
+pub use crate::filename::INDEX_FILENAME;
+
+/// The chart, debrief and recording of a pass (if they still exist).
+#[derive(Debug, Default)]
+struct PassFiles {
+    chart: Option<PathBuf>,
+    debrief: Option<PathBuf>,
+    acmi: Option<PathBuf>,
+}
+
+/// Write an index of all passes to `out_dir`, with filterable columns, thumbnails of the charts
+/// and links to the debriefs and recordings in it (or its subdirectories), so that the output
+/// directory can be published as is by a static web host. The files are matched with the passes
+/// by the pass ID in their filenames. Returns the path of the index.
+pub fn write_index(
+    out_dir: &Path,
+    title: &str,
+    records: &[PassRecord],
+) -> Result<PathBuf, crate::error::Error> {
+    let mut outputs = Vec::new();
+    crate::retention::collect(out_dir, &mut outputs)?;
+    let mut files: HashMap<Ulid, PassFiles> = HashMap::new();
+    for OutputFile { path, .. } in outputs {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let Some(id) = pass_id(name) else {
+            continue;
+        };
+        let entry = files.entry(id).or_default();
+        if name.ends_with(".png") {
+            entry.chart = Some(path);
+        } else if name.ends_with(".html") {
+            entry.debrief = Some(path);
+        } else if name.ends_with(".acmi") {
+            entry.acmi = Some(path);
+        }
+    }
+
+    let mut html = String::new();
+    writeln!(html, "{}", html_head(title, STYLE))?;
+    writeln!(html, "<h1>{}</h1>", escape(title))?;
+    writeln!(
+        html,
+        "<table>\n<thead>\n<tr><th>Date</th><th>Server</th><th>Carrier</th><th>Pilot</th>\
+         <th>Aircraft</th><th>Result</th><th>Wire</th><th>DCS LSO</th><th>Points</th>\
+         <th>Chart</th><th></th></tr>\n\
+         <tr class=\"filters\"><td><input data-column=\"0\" placeholder=\"Date\"></td><td></td>\
+         <td></td><td><input data-column=\"3\" placeholder=\"Pilot\"></td><td></td>\
+         <td><input data-column=\"5\" placeholder=\"Result\"></td>\
+         <td><input data-column=\"6\" placeholder=\"Wire\"></td>\
+         <td><input data-column=\"7\" placeholder=\"Grade\"></td><td></td><td></td><td></td></tr>\n\
+         </thead>\n<tbody>"
+    )?;
+
+    // newest first
+    for record in records.iter().rev() {
+        let pass_files = record
+            .id
+            .as_deref()
+            .and_then(|id| Ulid::from_string(id).ok())
+            .and_then(|id| files.get(&id));
+        let link = |path: &Option<PathBuf>| {
+            path.as_deref()
+                .map(|path| escape(&crate::debrief::relative_link(out_dir, path)))
+        };
+        let chart = pass_files.and_then(|f| link(&f.chart));
+        let debrief = pass_files.and_then(|f| link(&f.debrief));
+        let acmi = pass_files.and_then(|f| link(&f.acmi));
+
+        let (result, wire) = match &record.grading {
+            Grading::Recovered { cable, .. } => (
+                "Trap".into(),
+                cable.map(|c| c.to_string()).unwrap_or_default(),
+            ),
+            // neither a trap nor a bolter, most likely a wave-off
+            Grading::Unknown => ("Wave-off".into(), String::new()),
+            grading => (grading.summary(record.hook_up), String::new()),
+        };
+        let mut links = Vec::new();
+        if let Some(debrief) = debrief {
+            links.push(format!("<a href=\"{}\">Debrief</a>", debrief));
+        }
+        if let Some(acmi) = acmi {
+            links.push(format!("<a href=\"{}\">ACMI</a>", acmi));
+        }
+
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            record
+                .time()
+                .and_then(|time| time.format(DATETIME_FORMAT).ok())
+                .unwrap_or_default(),
+            escape(record.server.as_deref().unwrap_or_default()),
+            escape(record.carrier.as_deref().unwrap_or(&record.carrier_type)),
+            escape(&record.pilot),
+            escape(&record.plane_type),
+            escape(&result),
+            wire,
+            record
+                .dcs_comment
+                .as_deref()
+                .map(|comment| escape(DcsGrading::parse(comment).grade.as_str()))
+                .unwrap_or_default(),
+            stats::points(record)
+                .map(|p| format!("{:.1}", p))
+                .unwrap_or_default(),
+            chart
+                .map(|chart| format!(
+                    "<a href=\"{0}\"><img src=\"{0}\" loading=\"lazy\" alt=\"chart\"></a>",
+                    chart
+                ))
+                .unwrap_or_default(),
+            links.join(" "),
+        )?;
+    }
+    writeln!(
+        html,
+        "</tbody>\n</table>\n<script>{}</script>\n</body>\n</html>",
+        SCRIPT
+    )?;
+
+    let path = out_dir.join(INDEX_FILENAME);
+    std::fs::write(&path, html)?;
+
+    Ok(path)
+}
+
+/// The style of the index on top of the shared one (see [html_head]).
+const STYLE: &str = "img { width: 160px; display: block; } \
+input { width: 8em; background: #222; color: #eee; border: 1px solid #444; }";
+
+/// Hides the rows that don't contain the text of every filter (case-insensitive) in its column.
+const SCRIPT: &str = r#"
+const filters = [...document.querySelectorAll('.filters input')];
+const rows = [...document.querySelectorAll('tbody tr')];
+function filter() {
+  for (const row of rows) {
+    row.hidden = !filters.every(input => row.cells[input.dataset.column].textContent.toLowerCase()
+      .includes(input.value.trim().toLowerCase()));
+  }
+}
+filters.forEach(input => input.addEventListener('input', filter));
+"#;
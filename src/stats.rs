@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use time::Date;
+
+use crate::cq::CqStatus;
+use crate::dcs_grading::{DcsGrading, LsoGrade};
+use crate::store::PassRecord;
+use crate::track::Grading;
+
+/// The statistics of a single pilot across all their passes.
+#[derive(Debug, Serialize)]
+pub struct PilotStats {
+    pub pilot: String,
+    /// The number of passes (touch-and-goes with the hook up don't count).
+    pub passes: usize,
+    pub traps: usize,
+    /// The number of traps at night (see [crate::track::TrackResult::is_night]).
+    pub night_traps: usize,
+    /// Traps per pass.
+    pub boarding_rate: Option<f64>,
+    /// The average points of all graded passes.
+    pub gpa: Option<f64>,
+    /// The number of traps per wire (1 to 4).
+    pub wires: [usize; 4],
+    /// The deviations called by the LSO (most common first).
+    pub deviations: Vec<DeviationCount>,
+    /// The statistics per day the pilot flew (ordered by date).
+    pub trend: Vec<DayStats>,
+    /// The progress towards the carrier qualification (if configured).
+    pub cq: Option<CqStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviationCount {
+    /// The deviation code (e.g. `_LUL_`).
+    pub code: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayStats {
+    /// The date (`YYYY-MM-DD`).
+    pub date: String,
+    pub passes: usize,
+    pub traps: usize,
+    pub boarding_rate: Option<f64>,
+    pub gpa: Option<f64>,
+}
+
+impl PilotStats {
+    /// Calculate the statistics of the given pilot from all their passes in `records`. Returns
+    /// `None` if the pilot didn't fly any pass.
+    pub fn new(pilot: &str, records: &[PassRecord]) -> Option<Self> {
+        let records = records
+            .iter()
+            .filter(|r| r.pilot == pilot && !r.is_touch_and_go())
+            .collect::<Vec<_>>();
+        if records.is_empty() {
+            return None;
+        }
+
+        let mut wires = [0; 4];
+        let mut deviations: HashMap<String, usize> = HashMap::new();
+        for record in &records {
+            if let Grading::Recovered {
+                cable: Some(cable @ 1..=4),
+                ..
+            } = record.grading
+            {
+                wires[usize::from(cable) - 1] += 1;
+            }
+            if let Some(grading) = record.dcs_comment.as_deref().map(DcsGrading::parse) {
+                for deviation in grading.deviations {
+                    *deviations.entry(deviation).or_default() += 1;
+                }
+            }
+        }
+        let mut deviations = deviations
+            .into_iter()
+            .map(|(code, count)| DeviationCount { code, count })
+            .collect::<Vec<_>>();
+        deviations.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.code.cmp(&b.code)));
+
+        let mut days: BTreeMap<Date, Vec<&PassRecord>> = BTreeMap::new();
+        for record in &records {
+            if let Some(time) = record.time() {
+                days.entry(time.date()).or_default().push(record);
+            }
+        }
+        let trend = days
+            .into_iter()
+            .map(|(date, records)| DayStats {
+                date: date.to_string(),
+                passes: records.len(),
+                traps: traps(&records),
+                boarding_rate: boarding_rate(&records),
+                gpa: gpa(records.iter().copied()),
+            })
+            .collect();
+
+        Some(PilotStats {
+            pilot: pilot.to_string(),
+            passes: records.len(),
+            traps: traps(&records),
+            night_traps: records
+                .iter()
+                .filter(|r| r.night && matches!(r.grading, Grading::Recovered { .. }))
+                .count(),
+            boarding_rate: boarding_rate(&records),
+            gpa: gpa(records.iter().copied()),
+            wires,
+            deviations,
+            trend,
+            cq: None,
+        })
+    }
+}
+
+/// The points of the pass (see [LsoGrade::points]), if it counts towards the GPA. Touch-and-goes
+/// and passes to a fouled deck don't count. Bolters the DCS LSO didn't grade still count as such.
+pub fn points(record: &PassRecord) -> Option<f64> {
+    if record.is_touch_and_go() || record.foul_deck.is_some() {
+        return None;
+    }
+    match record.dcs_comment.as_deref() {
+        Some(comment) => DcsGrading::parse(comment).grade.points(),
+        None if record.grading == Grading::Bolter => LsoGrade::Bolter.points(),
+        None if record.grading == Grading::OwnWaveOff => LsoGrade::OwnWaveOff.points(),
+        None => None,
+    }
+}
+
+/// The grade point average of the given passes.
+pub fn gpa<'a>(records: impl IntoIterator<Item = &'a PassRecord>) -> Option<f64> {
+    let points = records.into_iter().filter_map(points).collect::<Vec<_>>();
+    (!points.is_empty()).then(|| points.iter().sum::<f64>() / points.len() as f64)
+}
+
+fn traps(records: &[&PassRecord]) -> usize {
+    records
+        .iter()
+        .filter(|r| matches!(r.grading, Grading::Recovered { .. }))
+        .count()
+}
+
+fn boarding_rate(records: &[&PassRecord]) -> Option<f64> {
+    (!records.is_empty()).then(|| traps(records) as f64 / records.len() as f64)
+}
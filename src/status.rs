@@ -0,0 +1,447 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::routing::{get, post};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio::sync::Notify;
+
+use crate::tasks::supervisor::Supervisor;
+use crate::utils::shutdown::ShutdownHandle;
+
+/// How long a connected server may not send any unit updates before it is considered unhealthy
+/// (the carrier alone already causes updates while it is moving).
+const STALE_AFTER: Duration = Duration::from_secs(300);
+
+/// The IDs of the active recordings (unique across all servers).
+static NEXT_RECORDING_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The state of the connections to all servers, served via HTTP (`/healthz` and `/status`) so
+/// that monitoring can detect when the LSO stopped recording (e.g. it is connected, but doesn't
+/// receive any units anymore). Optionally, the same endpoint accepts requests to control the
+/// running LSO (e.g. pause the detection or force a reconnect).
+#[derive(Clone, Default)]
+pub struct Status {
+    servers: Arc<Mutex<Vec<ServerStatus>>>,
+}
+
+/// The state of the connection to a single server, updated by its tasks.
+#[derive(Clone)]
+pub struct ServerStatus {
+    name: Option<String>,
+    supervisor: Supervisor,
+    state: Arc<Mutex<ServerState>>,
+    reconnect: Arc<Notify>,
+}
+
+#[derive(Default)]
+struct ServerState {
+    connected_since: Option<(Instant, OffsetDateTime)>,
+    last_error: Option<String>,
+    carriers: usize,
+    planes: usize,
+    last_update: Option<(Instant, OffsetDateTime)>,
+    /// Whether no new recordings are started.
+    paused: bool,
+    recordings: BTreeMap<u64, ActiveRecording>,
+}
+
+struct ActiveRecording {
+    carrier_id: u32,
+    plane_id: u32,
+    report: RecordingReport,
+    finish: Arc<Notify>,
+}
+
+/// Registers a recording as active until dropped.
+pub struct RecordingGuard {
+    status: ServerStatus,
+    id: u64,
+    finish: Arc<Notify>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    healthy: bool,
+    servers: Vec<ServerReport>,
+}
+
+#[derive(Serialize)]
+struct ServerReport {
+    name: Option<String>,
+    healthy: bool,
+    connected: bool,
+    /// When the connection was established (RFC 3339).
+    connected_since: Option<String>,
+    last_error: Option<String>,
+    tracked_carriers: usize,
+    tracked_planes: usize,
+    active_recordings: usize,
+    /// When the last unit update was received (RFC 3339).
+    last_update: Option<String>,
+    seconds_since_last_update: Option<u64>,
+    paused: bool,
+    recordings: Vec<RecordingReport>,
+    /// Why the server is considered unhealthy.
+    problem: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct RecordingReport {
+    id: u64,
+    carrier: String,
+    plane: String,
+    pilot: String,
+    /// When the recording started (RFC 3339).
+    started: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ServerQuery {
+    /// The name of the server to control (all servers if not set).
+    server: Option<String>,
+}
+
+impl Status {
+    /// Start tracking the state of a server.
+    pub fn server(&self, name: Option<&str>, supervisor: Supervisor) -> ServerStatus {
+        let server = ServerStatus {
+            name: name.map(String::from),
+            supervisor,
+            state: Default::default(),
+            reconnect: Default::default(),
+        };
+        self.servers.lock().unwrap().push(server.clone());
+        server
+    }
+
+    /// Serve `/healthz` and `/status` on `addr` until shutdown, and the control endpoints if
+    /// `control` is set.
+    pub fn spawn(
+        &self,
+        addr: SocketAddr,
+        control: bool,
+        shutdown_handle: ShutdownHandle,
+    ) -> Result<(), std::io::Error> {
+        let listener = std::net::TcpListener::bind(addr)?;
+        let app = self.router(control);
+        let server = axum::Server::from_tcp(listener)
+            .map_err(std::io::Error::other)?
+            .serve(app.into_make_service())
+            .with_graceful_shutdown(async move { shutdown_handle.signal().await });
+        tracing::info!(%addr, "serving status endpoint");
+
+        tokio::spawn(async move {
+            if let Err(err) = server.await {
+                tracing::error!(%err, "status endpoint failed");
+            }
+        });
+        Ok(())
+    }
+
+    /// The routes of `/healthz`, `/status` and `/recordings`, and of the control endpoints if
+    /// `control` is set.
+    pub fn router(&self, control: bool) -> Router {
+        let mut app = Router::new()
+            .route("/healthz", get(healthz))
+            .route("/status", get(status))
+            .route("/recordings", get(recordings));
+        if control {
+            app = app
+                .route("/recordings/:id/finish", post(finish_recording))
+                .route("/detection/pause", post(pause))
+                .route("/detection/resume", post(resume))
+                .route("/reconnect", post(reconnect));
+        }
+        app.with_state(self.clone())
+    }
+
+    fn report(&self) -> Report {
+        let servers = self
+            .servers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(ServerStatus::report)
+            .collect::<Vec<_>>();
+        Report {
+            healthy: servers.iter().all(|s| s.healthy),
+            servers,
+        }
+    }
+
+    /// The servers selected by `?server=NAME` (all servers if not set).
+    fn select(&self, query: &ServerQuery) -> Result<Vec<ServerStatus>, (StatusCode, String)> {
+        let servers = self
+            .servers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|s| query.server.is_none() || s.name == query.server)
+            .cloned()
+            .collect::<Vec<_>>();
+        if servers.is_empty() {
+            return Err((StatusCode::NOT_FOUND, "unknown server\n".to_string()));
+        }
+        Ok(servers)
+    }
+}
+
+impl ServerStatus {
+    pub fn connected(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.connected_since = Some((Instant::now(), OffsetDateTime::now_utc()));
+        state.last_update = None;
+    }
+
+    pub fn disconnected(&self, err: String) {
+        let mut state = self.state.lock().unwrap();
+        state.connected_since = None;
+        state.last_error = Some(err);
+    }
+
+    /// Stop (or continue) starting new recordings. Active recordings are not affected.
+    pub fn set_paused(&self, paused: bool) {
+        self.state.lock().unwrap().paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().unwrap().paused
+    }
+
+    /// Whether the plane is currently recorded while recovering on the carrier.
+    pub fn is_recording(&self, carrier_id: u32, plane_id: u32) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .recordings
+            .values()
+            .any(|r| r.carrier_id == carrier_id && r.plane_id == plane_id)
+    }
+
+    /// Register a recording as active (until the returned guard is dropped).
+    pub fn start_recording(
+        &self,
+        (carrier_id, carrier): (u32, &str),
+        (plane_id, plane): (u32, &str),
+        pilot: &str,
+    ) -> RecordingGuard {
+        let id = NEXT_RECORDING_ID.fetch_add(1, Ordering::Relaxed);
+        let finish = Arc::new(Notify::new());
+        self.state.lock().unwrap().recordings.insert(
+            id,
+            ActiveRecording {
+                carrier_id,
+                plane_id,
+                report: RecordingReport {
+                    id,
+                    carrier: carrier.to_string(),
+                    plane: plane.to_string(),
+                    pilot: pilot.to_string(),
+                    started: OffsetDateTime::now_utc().format(&Rfc3339).ok(),
+                },
+                finish: finish.clone(),
+            },
+        );
+        RecordingGuard {
+            status: self.clone(),
+            id,
+            finish,
+        }
+    }
+
+    /// Resolves once a reconnect to the server got requested.
+    pub async fn reconnect_requested(&self) {
+        self.reconnect.notified().await
+    }
+
+    /// A unit update was received from the server.
+    pub fn unit_update(&self) {
+        self.state.lock().unwrap().last_update = Some((Instant::now(), OffsetDateTime::now_utc()));
+    }
+
+    /// Pretend the last unit update was received at the given time (for the tests).
+    #[cfg(test)]
+    pub(crate) fn set_last_update(&self, at: Instant) {
+        let received = OffsetDateTime::now_utc() - at.elapsed();
+        self.state.lock().unwrap().last_update = Some((at, received));
+    }
+
+    /// The number of carriers and planes that are currently observed.
+    pub fn tracking(&self, carriers: usize, planes: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.carriers = carriers;
+        state.planes = planes;
+    }
+
+    fn report(&self) -> ServerReport {
+        let state = self.state.lock().unwrap();
+        let since_update = state.last_update.map(|(at, _)| at.elapsed());
+        let problem = match state.connected_since {
+            None => Some(match &state.last_error {
+                Some(err) => format!("not connected ({})", err),
+                None => "not connected".to_string(),
+            }),
+            Some((connected, _)) => {
+                let silent = since_update.unwrap_or_else(|| connected.elapsed());
+                (silent > STALE_AFTER).then(|| format!("no unit updates for {}s", silent.as_secs()))
+            }
+        };
+
+        ServerReport {
+            name: self.name.clone(),
+            healthy: problem.is_none(),
+            connected: state.connected_since.is_some(),
+            connected_since: state
+                .connected_since
+                .and_then(|(_, at)| at.format(&Rfc3339).ok()),
+            last_error: state.last_error.clone(),
+            tracked_carriers: state.carriers,
+            tracked_planes: state.planes,
+            active_recordings: self.supervisor.active_recordings(),
+            last_update: state
+                .last_update
+                .and_then(|(_, at)| at.format(&Rfc3339).ok()),
+            seconds_since_last_update: since_update.map(|d| d.as_secs()),
+            paused: state.paused,
+            recordings: state
+                .recordings
+                .values()
+                .map(|r| r.report.clone())
+                .collect(),
+            problem,
+        }
+    }
+}
+
+impl RecordingGuard {
+    /// Notified when finishing the recording got requested.
+    pub fn finish(&self) -> Arc<Notify> {
+        self.finish.clone()
+    }
+}
+
+impl Drop for RecordingGuard {
+    fn drop(&mut self) {
+        self.status
+            .state
+            .lock()
+            .unwrap()
+            .recordings
+            .remove(&self.id);
+    }
+}
+
+/// `200 ok` if all servers are connected and send unit updates, `503` with the problems
+/// otherwise.
+async fn healthz(State(status): State<Status>) -> (StatusCode, String) {
+    let report = status.report();
+    if report.healthy {
+        return (StatusCode::OK, "ok\n".to_string());
+    }
+
+    let problems = report
+        .servers
+        .iter()
+        .filter_map(|s| {
+            let problem = s.problem.as_deref()?;
+            Some(match &s.name {
+                Some(name) => format!("{}: {}\n", name, problem),
+                None => format!("{}\n", problem),
+            })
+        })
+        .collect::<String>();
+    (StatusCode::SERVICE_UNAVAILABLE, problems)
+}
+
+async fn status(State(status): State<Status>) -> ([(header::HeaderName, &'static str); 1], String) {
+    let report = serde_json::to_string_pretty(&status.report()).unwrap_or_default();
+    ([(header::CONTENT_TYPE, "application/json")], report)
+}
+
+/// The active recordings of all servers (as JSON).
+async fn recordings(
+    State(status): State<Status>,
+) -> ([(header::HeaderName, &'static str); 1], String) {
+    let recordings = status
+        .report()
+        .servers
+        .into_iter()
+        .flat_map(|s| s.recordings)
+        .collect::<Vec<_>>();
+    let recordings = serde_json::to_string_pretty(&recordings).unwrap_or_default();
+    ([(header::CONTENT_TYPE, "application/json")], recordings)
+}
+
+/// Stop an active recording and save it (as if the plane left), e.g. when it got stuck.
+async fn finish_recording(
+    State(status): State<Status>,
+    Path(id): Path<u64>,
+) -> (StatusCode, String) {
+    let finish = status.servers.lock().unwrap().iter().find_map(|s| {
+        let state = s.state.lock().unwrap();
+        state.recordings.get(&id).map(|r| r.finish.clone())
+    });
+    match finish {
+        Some(finish) => {
+            tracing::info!(id, "finishing recording as requested");
+            finish.notify_one();
+            (StatusCode::ACCEPTED, "finishing recording\n".to_string())
+        }
+        None => (StatusCode::NOT_FOUND, "unknown recording\n".to_string()),
+    }
+}
+
+async fn pause(
+    State(status): State<Status>,
+    Query(query): Query<ServerQuery>,
+) -> (StatusCode, String) {
+    set_paused(&status, &query, true)
+}
+
+async fn resume(
+    State(status): State<Status>,
+    Query(query): Query<ServerQuery>,
+) -> (StatusCode, String) {
+    set_paused(&status, &query, false)
+}
+
+fn set_paused(status: &Status, query: &ServerQuery, paused: bool) -> (StatusCode, String) {
+    let servers = match status.select(query) {
+        Ok(servers) => servers,
+        Err(err) => return err,
+    };
+    for server in servers {
+        tracing::info!(
+            server = server.name.as_deref(),
+            paused,
+            "detection paused/resumed as requested"
+        );
+        server.set_paused(paused);
+    }
+    let state = if paused { "paused" } else { "resumed" };
+    (StatusCode::OK, format!("detection {}\n", state))
+}
+
+/// Drop the connection to the server(s) and connect again. Active recordings are continued. A
+/// request while a server is not connected applies once it is connected again.
+async fn reconnect(
+    State(status): State<Status>,
+    Query(query): Query<ServerQuery>,
+) -> (StatusCode, String) {
+    let servers = match status.select(&query) {
+        Ok(servers) => servers,
+        Err(err) => return err,
+    };
+    for server in servers {
+        server.reconnect.notify_one();
+    }
+    (StatusCode::ACCEPTED, "reconnecting\n".to_string())
+}
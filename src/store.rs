@@ -0,0 +1,236 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::track::{DeckMotion, DeckPosition, Grading, TrackResult};
+
+/// The name of the results file (in the output directory) every pass is appended to.
+pub const RESULTS_FILENAME: &str = "lso-results.jsonl";
+
+/// The result of a single pass as persisted in the results file (one JSON object per line).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PassRecord {
+    /// The unique ID (ULID) of the pass, which is also part of the filenames of its chart and
+    /// recording and of the chat posts about it.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// When the pass ended (RFC 3339).
+    pub time: String,
+    pub server: Option<String>,
+    /// The unit name of the carrier.
+    #[serde(default)]
+    pub carrier: Option<String>,
+    pub pilot: String,
+    /// The UCID of the player (if known), which identifies them across name changes.
+    #[serde(default)]
+    pub ucid: Option<String>,
+    pub plane_type: String,
+    pub carrier_type: String,
+    pub grading: Grading,
+    /// The raw comment of the DCS LSO (see [crate::dcs_grading::DcsGrading::parse]).
+    pub dcs_comment: Option<String>,
+    pub touchdown: Option<DeckPosition>,
+    /// The time in the groove (in seconds).
+    #[serde(default)]
+    pub groove_time: Option<f64>,
+    /// The average AoA in the groove (in degrees).
+    #[serde(default)]
+    pub groove_aoa: Option<f64>,
+    /// The same in units, for planes whose AoA is flown in units (see
+    /// [crate::data::AirplaneInfo::aoa_scale]).
+    #[serde(default)]
+    pub groove_aoa_units: Option<f64>,
+    /// The height (in ft) of the hook above the ramp when crossing it.
+    #[serde(default)]
+    pub ramp_clearance: Option<f64>,
+    /// The peak G around touching down.
+    #[serde(default)]
+    pub touchdown_g: Option<f64>,
+    /// Whether the pass was flown at night.
+    #[serde(default)]
+    pub night: bool,
+    pub hook_up: bool,
+    pub interrupted: bool,
+    #[serde(default)]
+    pub carrier_turning: bool,
+    #[serde(default)]
+    pub deck_motion: Option<DeckMotion>,
+    #[serde(default)]
+    pub foul_deck: Option<String>,
+    /// The recovery window (see [crate::recovery]) the pass belongs to.
+    #[serde(default)]
+    pub window: Option<WindowPass>,
+}
+
+/// The position of a pass within its recovery window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowPass {
+    /// When the window started (RFC 3339), which identifies it together with the server and the
+    /// carrier.
+    pub start: String,
+    /// The number of the pass within the window (starting at 1).
+    pub pass: u32,
+}
+
+impl PassRecord {
+    pub fn new(
+        time: OffsetDateTime,
+        server: Option<&str>,
+        carrier_name: &str,
+        plane_type: &str,
+        carrier_type: &str,
+        track: &TrackResult,
+    ) -> Self {
+        PassRecord {
+            id: None,
+            time: time.format(&Rfc3339).unwrap_or_default(),
+            server: server.map(str::to_string),
+            carrier: Some(carrier_name.to_string()),
+            pilot: track.pilot_name.clone(),
+            ucid: None,
+            plane_type: plane_type.to_string(),
+            carrier_type: carrier_type.to_string(),
+            grading: track.grading.clone(),
+            dcs_comment: track.dcs_grading.as_ref().map(|g| g.comment.clone()),
+            touchdown: track.touchdown,
+            groove_time: track.groove_time(),
+            groove_aoa: track.groove_aoa(),
+            groove_aoa_units: track
+                .groove_aoa()
+                .and_then(|aoa| track.plane_info.aoa_units(aoa)),
+            ramp_clearance: track.ramp_clearance,
+            touchdown_g: track.touchdown_g,
+            night: track.is_night(),
+            hook_up: track.hook_up,
+            interrupted: track.interrupted,
+            carrier_turning: track.carrier_turning,
+            deck_motion: track.deck_motion,
+            foul_deck: track.foul_deck.clone(),
+            window: None,
+        }
+    }
+
+    /// When the pass ended.
+    pub fn time(&self) -> Option<OffsetDateTime> {
+        OffsetDateTime::parse(&self.time, &Rfc3339).ok()
+    }
+
+    /// Whether the pass was a practice touch-and-go (flown with the hook up or during a declared
+    /// touch-and-go window), which doesn't count towards the statistics.
+    pub fn is_touch_and_go(&self) -> bool {
+        self.hook_up || self.grading == Grading::TouchAndGo
+    }
+}
+
+/// Append the given passes to the results file (creating it if it doesn't exist yet).
+pub fn append(path: &Path, records: &[PassRecord]) -> Result<(), crate::error::Error> {
+    // serialize all records first to append them with a single write
+    let mut buf = Vec::new();
+    for record in records {
+        serde_json::to_writer(&mut buf, record)?;
+        buf.push(b'\n');
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&buf)?;
+
+    Ok(())
+}
+
+/// Write the given passes as CSV (one row per pass, with a header).
+pub fn write_csv(mut wr: impl Write, records: &[PassRecord]) -> Result<(), std::io::Error> {
+    writeln!(
+        wr,
+        "id,time,server,carrier,pilot,plane_type,carrier_type,grade,dcs_comment,\
+         touchdown_long_ft,touchdown_right_ft,groove_time_s,hook_up,interrupted,groove_aoa_deg,groove_aoa_units,ramp_clearance_ft,touchdown_g,night,ucid"
+    )?;
+    let optional = |value: Option<f64>| value.map(|v| format!("{:.1}", v)).unwrap_or_default();
+    for record in records {
+        let row = [
+            record.id.clone().unwrap_or_default(),
+            record.time.clone(),
+            record.server.clone().unwrap_or_default(),
+            record.carrier.clone().unwrap_or_default(),
+            record.pilot.clone(),
+            record.plane_type.clone(),
+            record.carrier_type.clone(),
+            record.grading.summary(record.hook_up).into_owned(),
+            record.dcs_comment.clone().unwrap_or_default(),
+            optional(record.touchdown.map(|t| t.long)),
+            optional(record.touchdown.map(|t| t.right)),
+            optional(record.groove_time),
+            record.hook_up.to_string(),
+            record.interrupted.to_string(),
+            optional(record.groove_aoa),
+            optional(record.groove_aoa_units),
+            optional(record.ramp_clearance),
+            optional(record.touchdown_g),
+            record.night.to_string(),
+            record.ucid.clone().unwrap_or_default(),
+        ];
+        let row = row.iter().map(|field| csv_field(field)).collect::<Vec<_>>();
+        writeln!(wr, "{}", row.join(","))?;
+    }
+    wr.flush()
+}
+
+/// Quote a CSV field if necessary.
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Load all passes from the results file, with the passes of each player known by their UCID
+/// listed under the name of their latest pass (see [merge_by_ucid]).
+pub fn load(path: &Path) -> Result<Vec<PassRecord>, crate::error::Error> {
+    let mut records = load_as_recorded(path)?;
+    merge_by_ucid(&mut records);
+    Ok(records)
+}
+
+/// Rename the passes of each player known by their UCID to the name of their latest pass, so that
+/// the statistics (which are by name) stay together when a player renames themselves.
+pub fn merge_by_ucid(records: &mut [PassRecord]) {
+    let mut names = HashMap::new();
+    for record in records.iter() {
+        if let Some(ucid) = &record.ucid {
+            // the passes are appended in the order they ended
+            names.insert(ucid.clone(), record.pilot.clone());
+        }
+    }
+    for record in records.iter_mut() {
+        if let Some(name) = record.ucid.as_ref().and_then(|ucid| names.get(ucid)) {
+            record.pilot.clone_from(name);
+        }
+    }
+}
+
+/// Load all passes from the results file with the names they were recorded with. Lines that
+/// cannot be parsed are skipped.
+pub fn load_as_recorded(path: &Path) -> Result<Vec<PassRecord>, crate::error::Error> {
+    let mut records = Vec::new();
+    for (i, line) in BufReader::new(std::fs::File::open(path)?)
+        .lines()
+        .enumerate()
+    {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(err) => tracing::warn!(line = i + 1, %err, "skipping invalid result"),
+        }
+    }
+
+    Ok(records)
+}
@@ -0,0 +1,207 @@
+//! Synthesized carrier and plane transforms of typical passes, to test the grading, the wire
+//! estimation and the charts without adding more ACMI recordings to the repository.
+
+use std::ops::Neg;
+
+use ultraviolet::{DRotor3, DVec3};
+
+use crate::data::{AirplaneInfo, CarrierInfo, ALL_WIRES};
+use crate::track::{Track, TrackResult};
+use crate::transform::Transform;
+
+/// The time (in s) between two frames.
+const FRAME_INTERVAL: f64 = 0.1;
+/// How long (in s) the plane is moved on after the hook touched down (long enough to detect a
+/// bolter).
+const ROLLOUT_DURATION: f64 = 6.0;
+/// The deceleration (in m/s²) of a trap.
+const TRAP_DECELERATION: f64 = 25.0;
+const GRAVITY: f64 = 9.81;
+
+/// How a pass ends once the hook touched down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum End {
+    /// The hook catches a wire.
+    Trap,
+    /// The hook skips all wires and the plane flies on.
+    Bolter,
+}
+
+/// A pass flown along the glide slope towards the target touchdown point, with the deviations
+/// given as functions of the distance (in m, along the landing area) to that point.
+pub struct Pass {
+    pub carrier: &'static CarrierInfo,
+    pub plane: &'static AirplaneInfo,
+    /// The carrier's heading in degrees.
+    pub brc: f64,
+    /// The carrier's speed in m/s.
+    pub carrier_speed: f64,
+    /// The plane's speed relative to the carrier in m/s.
+    pub speed: f64,
+    pub aoa: f64,
+    /// The distance (in m) to the target touchdown point the pass starts at.
+    pub start: f64,
+    /// The offset (in m) right of the landing area's centerline.
+    pub lineup: fn(f64) -> f64,
+    /// The offset (in m) of the hook above the glide slope.
+    pub glide_slope: fn(f64) -> f64,
+    pub end: End,
+}
+
+impl Pass {
+    /// An on-speed pass that stays on the glide slope and the centerline all the way.
+    pub fn ideal() -> Self {
+        Pass {
+            carrier: CarrierInfo::by_type("CVN_71").unwrap(),
+            plane: AirplaneInfo::by_type("FA-18C_hornet").unwrap(),
+            brc: 30.0,
+            carrier_speed: 12.0,
+            speed: 60.0,
+            aoa: 8.1,
+            start: 1_500.0,
+            lineup: |_| 0.0,
+            glide_slope: |_| 0.0,
+            end: End::Trap,
+        }
+    }
+
+    /// A pass that starts far right of the centerline (after overshooting it in the approach
+    /// turn) and is corrected back onto it by about half way.
+    pub fn overshooting_start() -> Self {
+        Pass {
+            lineup: |distance| {
+                let remaining = ((distance - 750.0) / 750.0).max(0.0);
+                60.0 * remaining.powi(2)
+            },
+            ..Pass::ideal()
+        }
+    }
+
+    /// A pass that settles below the glide slope in close and touches down short.
+    pub fn settle_at_ramp() -> Self {
+        Pass {
+            glide_slope: |distance| -(1.0 - distance / 300.0).max(0.0) * 3.0,
+            ..Pass::ideal()
+        }
+    }
+
+    /// A pass that floats above the glide slope in close, touches down past all wires and flies
+    /// on.
+    pub fn bolter() -> Self {
+        Pass {
+            glide_slope: |distance| (1.0 - distance / 300.0).max(0.0) * 3.0,
+            end: End::Bolter,
+            ..Pass::ideal()
+        }
+    }
+
+    /// The carrier's and the plane's transforms of each frame, and the index of the frame the
+    /// plane touched down at.
+    pub fn frames(&self) -> (Vec<(Transform, Transform)>, usize) {
+        let target = self.carrier.target_touchdown(&ALL_WIRES);
+        let landing_dir = self.carrier.landing_direction();
+        let landing_right = self.carrier.landing_right();
+        let glide_slope = self.carrier.lens_basic_angle.to_radians().tan();
+        let fb = (self.brc - self.carrier.deck_angle).rem_euclid(360.0);
+
+        let mut frames = Vec::new();
+        let mut touchdown = None;
+        let mut distance = self.start;
+        let mut speed = self.speed;
+        let mut time = 0.0;
+        loop {
+            let carrier = self.carrier_transform(time);
+            let (height, bank) = match touchdown {
+                None => {
+                    let height = distance * glide_slope + (self.glide_slope)(distance);
+                    // bank into the lineup corrections (centripetal acceleration of the curve)
+                    let curvature = (self.lineup)(distance + 1.0) - 2.0 * (self.lineup)(distance)
+                        + (self.lineup)(distance - 1.0);
+                    let bank = (self.speed.powi(2) * curvature / GRAVITY)
+                        .atan()
+                        .to_degrees();
+                    (height.max(0.0), bank)
+                }
+                Some(_) => (0.0, 0.0),
+            };
+            let pitch = if touchdown.is_some() {
+                0.0
+            } else {
+                self.aoa - self.carrier.lens_basic_angle
+            };
+            let mut plane = transform(fb, pitch, bank, time);
+            plane.aoa = self.aoa;
+
+            let hook = target - landing_dir * distance
+                + landing_right * (self.lineup)(distance)
+                + DVec3::unit_y() * height;
+            plane.position = carrier.position + hook.rotated_by(carrier.rotation)
+                - self.plane.hook.rotated_by(plane.rotation);
+            plane.alt = plane.position.y;
+            frames.push((carrier, plane));
+
+            if touchdown.is_none() && height <= 0.0 {
+                touchdown = Some(frames.len() - 1);
+            }
+            if let Some(at) = touchdown {
+                if (frames.len() - 1 - at) as f64 * FRAME_INTERVAL > ROLLOUT_DURATION {
+                    break;
+                }
+                if self.end == End::Trap {
+                    speed = (speed - TRAP_DECELERATION * FRAME_INTERVAL).max(0.0);
+                }
+            }
+
+            distance -= speed * FRAME_INTERVAL;
+            time += FRAME_INTERVAL;
+        }
+
+        let touchdown = touchdown.unwrap_or(frames.len());
+        (frames, touchdown)
+    }
+
+    /// Track the pass the same way passes are tracked in recordings (with a land event once the
+    /// hook touched down).
+    pub fn track(&self) -> TrackResult {
+        let (frames, touchdown) = self.frames();
+        let mut track = Track::new("Synthetic", self.carrier, self.plane);
+        for (i, (carrier, plane)) in frames.iter().enumerate() {
+            let should_continue = track.next(carrier, plane);
+            if i == touchdown {
+                track.landed(carrier, plane);
+            }
+            if !should_continue {
+                break;
+            }
+        }
+        track.finish()
+    }
+
+    fn carrier_transform(&self, time: f64) -> Transform {
+        let mut carrier = transform(self.brc, 0.0, 0.0, time);
+        carrier.position = carrier.forward * self.carrier_speed * time;
+        carrier
+    }
+}
+
+/// A transform with the given orientation (in degrees) at the origin.
+fn transform(heading: f64, pitch: f64, roll: f64, time: f64) -> Transform {
+    Transform {
+        forward: DVec3::new(
+            heading.to_radians().sin() * pitch.to_radians().cos(),
+            pitch.to_radians().sin(),
+            heading.to_radians().cos() * pitch.to_radians().cos(),
+        ),
+        heading,
+        yaw: heading,
+        pitch,
+        roll,
+        rotation: DRotor3::from_euler_angles(
+            roll.neg().to_radians(),
+            pitch.neg().to_radians(),
+            heading.neg().to_radians(),
+        ),
+        time,
+        ..Default::default()
+    }
+}
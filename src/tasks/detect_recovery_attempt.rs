@@ -1,104 +1,540 @@
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
+use futures_util::future::{ready, Either};
+use futures_util::stream::{once, select};
 use futures_util::StreamExt;
-use tonic::Code;
+use stubs::common::v0::{Coalition, GroupCategory, Unit};
+use stubs::mission::v0::stream_units_response::{UnitGone, Update};
+use tonic::Status;
 
-use crate::client::UnitClient;
+use crate::client::{MissionClient, NetClient, UnitClient};
+use crate::config::{Config, DetectionEnvelope, HelicopterMode};
+use crate::data::{AirplaneInfo, CarrierInfo};
+use crate::event_log::LifecycleEvent;
+use crate::notify::Notification;
+use crate::privacy::PilotNames;
+use crate::track::{broad_phase_distance, is_recovery_attempt, ki_pilot_name};
 use crate::transform::Transform;
-use crate::utils::{m_to_ft, m_to_nm};
+use crate::utils::TurnRate;
 
-use super::TaskParams;
+use super::{DetectionParams, TaskParams, Traffic, TrafficUnit};
 
-#[tracing::instrument(
-    skip_all,
-    fields(carrier_name = params.carrier_name, plane_name = params.plane_name)
-)]
-pub async fn detect_recovery_attempt(params: TaskParams<'_>) -> Result<(), crate::error::Error> {
+/// The rate (in seconds) in which DCS-gRPC polls for unit changes.
+const UNITS_POLL_RATE: u32 = 1;
+
+/// The maximum height (in m) of a helicopter's origin above the deck for it to be on deck.
+const HELICOPTER_ON_DECK_HEIGHT: f64 = 4.0;
+
+/// How long a recovery attempt that couldn't be recorded (as the maximum of concurrent recordings
+/// was reached) is ignored before it is considered again.
+const REFUSED_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+struct Carrier {
+    name: String,
+    r#type: String,
+    info: &'static CarrierInfo,
+    transform: Transform,
+    turn_rate: TurnRate,
+}
+
+struct Plane {
+    name: String,
+    r#type: String,
+    pilot_name: String,
+    /// The UCID of a player.
+    ucid: Option<String>,
+    /// The callsign of a KI unit.
+    callsign: Option<String>,
+    info: &'static AirplaneInfo,
+    transform: Transform,
+}
+
+/// Observe all carriers and planes via a single units stream and start recording a recovery
+/// whenever a plane starts a recovery attempt on one of the carriers.
+#[tracing::instrument(skip_all)]
+pub async fn detect_recovery_attempts(params: DetectionParams) -> Result<(), crate::error::Error> {
     tracing::debug!("started observing for possible recovery attempts");
+    params.event_log.log(
+        params.server_name.as_deref(),
+        LifecycleEvent::DetectionStarted,
+    );
 
-    let mut client1 = UnitClient::new(params.ch.clone());
-    let mut client2 = UnitClient::new(params.ch.clone());
-    let mut interval =
+    let mut mission = MissionClient::new(params.ch.clone());
+    let mut client = UnitClient::new(params.ch.clone());
+    let mut net = NetClient::new(params.ch.clone());
+    let units = select(
+        select(
+            mission
+                .stream_units(GroupCategory::Airplane, UNITS_POLL_RATE)
+                .await?,
+            mission
+                .stream_units(GroupCategory::Helicopter, UNITS_POLL_RATE)
+                .await?,
+        ),
+        mission
+            .stream_units(GroupCategory::Ship, UNITS_POLL_RATE)
+            .await?,
+    );
+    let interval =
         crate::utils::interval::interval(Duration::from_secs(2), params.shutdown.clone());
+    // the end of the units stream is marked with `None`, as the interval keeps the combined stream
+    // going
+    let mut stream = select(
+        interval.map(Either::Left),
+        params
+            .shutdown
+            .wrap_stream(units.map(Some).chain(once(ready(None))))
+            .map(Either::Right),
+    );
+
+    let mut carriers: HashMap<u32, Carrier> = HashMap::new();
+    let mut planes: HashMap<u32, Plane> = HashMap::new();
+    // units that were already checked and are neither a supported carrier nor plane
+    let mut ignored: HashSet<u32> = HashSet::new();
+    let traffic: Traffic = Default::default();
+    // helicopters currently on the deck of a carrier (by helicopter id)
+    let mut on_deck: HashSet<u32> = HashSet::new();
+    // pairs (carrier id, plane id) that couldn't be recorded, and when that happened
+    let mut refused: HashMap<(u32, u32), Instant> = HashMap::new();
+    // the anonymized player names of the units, so that each is only anonymized once when the unit
+    // is added
+    let mut pilot_names: HashMap<u32, Option<String>> = HashMap::new();
+
+    while let Some(next) = stream.next().await {
+        if let Either::Right(Some(Ok(_))) = next {
+            params.status.unit_update();
+        }
 
-    while interval.next().await.is_some() {
-        let result = futures_util::future::try_join(
-            client1.get_transform(params.carrier_name),
-            client2.get_transform(params.plane_name),
-        )
-        .await;
-
-        match result {
-            Ok((carrier, plane)) => {
-                if is_recovery_attempt(&carrier, &plane) {
-                    super::record_recovery::record_recovery(params.clone()).await?;
+        match next {
+            // next interval
+            Either::Left(_) => {
+                params.status.tracking(carriers.len(), planes.len());
+                if params.status.is_paused() {
+                    continue;
+                }
+
+                if params.config.helicopters == HelicopterMode::DeckLandings {
+                    detect_deck_landings(&params, &carriers, &traffic, &mut on_deck);
+                }
+
+                refused.retain(|_, at| at.elapsed() < REFUSED_RETRY_DELAY);
+
+                for (plane_id, plane) in &planes {
+                    // Only pair the plane with its nearest carrier, and only if it is close
+                    // enough to that carrier to possibly be in the pattern.
+                    let Some((carrier_id, carrier)) =
+                        nearest_carrier(&carriers, plane, &params.config.detection)
+                    else {
+                        continue;
+                    };
+                    if params.status.is_recording(*carrier_id, *plane_id)
+                        || refused.contains_key(&(*carrier_id, *plane_id))
+                    {
+                        continue;
+                    }
+
+                    let span = tracing::debug_span!(
+                        "pair",
+                        carrier_name = %carrier.name,
+                        plane_name = %plane.name
+                    );
+                    // grades of passes to a turning deck are meaningless, so postpone starting a
+                    // recording until the carrier steadied
+                    let turn_rate = carrier.turn_rate.rate();
+                    if turn_rate.abs() > params.config.detection.max_turn_rate {
+                        tracing::trace!(
+                            carrier_name = %carrier.name,
+                            turn_rate,
+                            "postpone recovery attempts while the carrier is turning"
+                        );
+                        continue;
+                    }
+
+                    if !span.in_scope(|| {
+                        is_recovery_attempt(
+                            &carrier.transform,
+                            &plane.transform,
+                            &params.config.detection,
+                        )
+                    }) {
+                        continue;
+                    }
+
+                    if !params.supervisor.can_record() {
+                        refused.insert((*carrier_id, *plane_id), Instant::now());
+                        tracing::debug!(
+                            carrier_name = %carrier.name,
+                            plane_name = %plane.name,
+                            "ignore recovery attempt as the maximum of concurrent recordings is reached"
+                        );
+                        continue;
+                    }
+
+                    let (gear_down, hook_down) =
+                        match gear_and_hook_down(&mut client, &plane.name, plane.info).await {
+                            Ok(state) => state,
+                            Err(err) => {
+                                // assume down, e.g. for DCS-gRPC versions without draw argument
+                                // support
+                                tracing::debug!(
+                                    plane_name = %plane.name,
+                                    %err,
+                                    "failed to get gear and hook state",
+                                );
+                                (true, true)
+                            }
+                        };
+                    if !gear_down {
+                        tracing::debug!(
+                            carrier_name = %carrier.name,
+                            plane_name = %plane.name,
+                            "ignore recovery attempt flown gear up"
+                        );
+                        continue;
+                    }
+
+                    // removes the pair from the currently recorded pairs once the recording is
+                    // done (or didn't start in the first place)
+                    let guard = params.status.start_recording(
+                        (*carrier_id, &carrier.name),
+                        (*plane_id, &plane.name),
+                        &plane.pilot_name,
+                    );
+                    params.event_log.log(
+                        params.server_name.as_deref(),
+                        LifecycleEvent::RecordingStarted {
+                            carrier: &carrier.name,
+                            plane: &plane.name,
+                            pilot: &plane.pilot_name,
+                        },
+                    );
+
+                    let task = TaskParams {
+                        out_dir: params.out_dir.clone(),
+                        server_name: params.server_name.clone(),
+                        notifiers: if params.discord_posts.passes() {
+                            params.notifiers.clone()
+                        } else {
+                            Default::default()
+                        },
+                        acmi_retention: params.acmi_retention,
+                        ch: params.ch.clone(),
+                        carrier_id: *carrier_id,
+                        carrier_name: carrier.name.clone(),
+                        carrier_type: carrier.r#type.clone(),
+                        plane_id: *plane_id,
+                        plane_name: plane.name.clone(),
+                        plane_type: plane.r#type.clone(),
+                        pilot_name: plane.pilot_name.clone(),
+                        ucid: plane.ucid.clone(),
+                        callsign: plane.callsign.clone(),
+                        carrier_info: carrier.info,
+                        plane_info: plane.info,
+                        config: params.config.clone(),
+                        traffic: traffic.clone(),
+                        recovery_windows: params.recovery_windows.clone(),
+                        event_log: params.event_log.clone(),
+                        realtime: params.realtime.clone(),
+                        hook_up: !hook_down,
+                        finish: guard.finish(),
+                        shutdown: params.shutdown.clone(),
+                        drain_timeout: params.drain_timeout,
+                    };
+                    let params = params.clone();
+                    let spawned = params.supervisor.spawn_recording(async move {
+                        let _guard = guard;
+                        let result = super::record_recovery::record_recovery(task).await;
+                        if let Err(err) = &result {
+                            params.event_log.log(
+                                params.server_name.as_deref(),
+                                LifecycleEvent::Error {
+                                    message: format!("recording failed: {}", err),
+                                },
+                            );
+                        }
+                        result
+                    });
+                    if !spawned {
+                        refused.insert((*carrier_id, *plane_id), Instant::now());
+                    }
                 }
             }
-            Err(status) if status.code() == Code::NotFound => {
-                tracing::debug!("stop tracking as either carrier or plane doesn't exist anymore");
-                return Ok(());
+
+            // unit changed or got added
+            Either::Right(Some(Ok((time, Update::Unit(mut unit))))) => {
+                // the name as known to DCS is still needed to look up the player's UCID
+                let real_player_name = unit.player_name.take();
+                unit.player_name = pilot_names
+                    .entry(unit.id)
+                    .or_insert_with(|| real_player_name.as_deref().map(crate::privacy::pilot_name))
+                    .clone();
+                let transform = Transform::from((
+                    time,
+                    unit.position.clone().unwrap_or_default(),
+                    unit.orientation.clone().unwrap_or_default(),
+                    unit.velocity.clone().unwrap_or_default(),
+                ));
+
+                let category = unit.group.as_ref().map(|g| g.category).unwrap_or(-1);
+                if category != i32::from(GroupCategory::Ship) {
+                    traffic.lock().unwrap().insert(
+                        unit.id,
+                        TrafficUnit {
+                            name: unit.name.clone(),
+                            r#type: unit.r#type.clone(),
+                            pilot_name: unit.player_name.clone(),
+                            coalition: Coalition::try_from(unit.coalition)
+                                .unwrap_or(Coalition::Neutral),
+                            is_helicopter: category == i32::from(GroupCategory::Helicopter),
+                            transform: transform.clone(),
+                        },
+                    );
+                }
+
+                if let Some(carrier) = carriers.get_mut(&unit.id) {
+                    carrier.turn_rate.update(transform.time, transform.heading);
+                    carrier.transform = transform;
+                } else if let Some(plane) = planes.get_mut(&unit.id) {
+                    plane.transform = transform;
+                } else if !ignored.contains(&unit.id) {
+                    match check_candidate(&mut client, &unit, params.include_ki, &params.config)
+                        .await
+                    {
+                        Ok(Some(Candidate::Carrier(info))) => {
+                            tracing::debug!(carrier_name = %unit.name, "observing carrier");
+                            carriers.insert(
+                                unit.id,
+                                Carrier {
+                                    name: unit.name,
+                                    r#type: unit.r#type,
+                                    info,
+                                    transform,
+                                    turn_rate: TurnRate::default(),
+                                },
+                            );
+                        }
+                        Ok(Some(Candidate::Plane(info))) => {
+                            tracing::debug!(plane_name = %unit.name, "observing plane");
+                            // not stored for anonymized pilots, as it would identify them
+                            let ucid = match &real_player_name {
+                                Some(name)
+                                    if params.config.privacy.pilot_names == PilotNames::Keep =>
+                                {
+                                    net.get_ucid(name).await.unwrap_or_else(|err| {
+                                    tracing::debug!(%err, "failed to get the UCID of the player");
+                                    None
+                                })
+                                }
+                                _ => None,
+                            };
+                            planes.insert(
+                                unit.id,
+                                Plane {
+                                    name: unit.name,
+                                    r#type: unit.r#type,
+                                    callsign: unit
+                                        .player_name
+                                        .is_none()
+                                        .then(|| unit.callsign.clone())
+                                        .filter(|callsign| !callsign.is_empty()),
+                                    pilot_name: unit.player_name.unwrap_or_else(|| {
+                                        ki_pilot_name(unit.group.as_ref().map(|g| g.name.as_str()))
+                                    }),
+                                    ucid,
+                                    info,
+                                    transform,
+                                },
+                            );
+                        }
+                        Ok(None) => {
+                            ignored.insert(unit.id);
+                        }
+                        Err(err) => {
+                            tracing::error!(
+                                unit_name = %unit.name,
+                                %err,
+                                "ignoring unit due to an error while checking its eligibility",
+                            );
+                            ignored.insert(unit.id);
+                        }
+                    }
+                }
             }
-            Err(err) => {
-                return Err(err.into());
+
+            // unit does not exist anymore
+            Either::Right(Some(Ok((_, Update::Gone(UnitGone { id, .. }))))) => {
+                carriers.remove(&id);
+                planes.remove(&id);
+                traffic.lock().unwrap().remove(&id);
+                on_deck.remove(&id);
+                ignored.remove(&id);
+                pilot_names.remove(&id);
             }
+
+            Either::Right(Some(Err(err))) => return Err(err.into()),
+
+            // the stream only ends by itself if DCS-gRPC stopped sending units without an error,
+            // so reconnect instead of silently not detecting anything anymore
+            Either::Right(None) if params.shutdown.is_shutdown() => break,
+            Either::Right(None) => return Err(crate::error::Error::StreamEnded),
         }
     }
 
     Ok(())
 }
 
-pub fn is_recovery_attempt(carrier: &Transform, plane: &Transform) -> bool {
-    // ignore planes above 500ft
-    if m_to_ft(plane.alt) > 500.0 {
-        tracing::trace!(alt_in_ft = m_to_ft(plane.alt), "ignore planes above 500ft");
-        return false;
-    }
+/// Report helicopters that set down on the landing area of a carrier since the last check.
+fn detect_deck_landings(
+    params: &DetectionParams,
+    carriers: &HashMap<u32, Carrier>,
+    traffic: &Traffic,
+    on_deck: &mut HashSet<u32>,
+) {
+    let traffic = traffic.lock().unwrap();
+    for (id, unit) in traffic.iter().filter(|(_, unit)| unit.is_helicopter) {
+        if unit.pilot_name.is_none() && !params.include_ki {
+            continue;
+        }
 
-    let ray_from_plane_to_carrier = carrier.position - plane.position;
-    let distance = ray_from_plane_to_carrier.mag();
+        let landed_on = carriers.values().find_map(|carrier| {
+            let position = (unit.transform.position - carrier.transform.position)
+                .rotated_by(carrier.transform.rotation.reversed());
+            (carrier.info.in_landing_area(position)
+                && position.y < carrier.info.deck_altitude + HELICOPTER_ON_DECK_HEIGHT)
+                .then(|| (carrier, carrier.info.landing_area_offset(position)))
+        });
+        let Some((carrier, (from_ramp, from_centerline))) = landed_on else {
+            on_deck.remove(id);
+            continue;
+        };
+        if !on_deck.insert(*id) {
+            continue;
+        }
 
-    // ignore planes farther away than 1.5nm
-    if m_to_nm(distance) > 1.5 {
-        tracing::trace!(
-            distance_in_nm = m_to_nm(distance),
-            "ignore planes farther away than 1.5nm"
+        let pilot_name = unit.pilot_name.as_deref().unwrap_or("KI");
+        tracing::info!(
+            carrier_name = %carrier.name,
+            helicopter_name = %unit.name,
+            pilot_name,
+            from_ramp,
+            from_centerline,
+            "deck landing"
+        );
+        params.event_log.log(
+            params.server_name.as_deref(),
+            LifecycleEvent::DeckLanding {
+                carrier: &carrier.name,
+                helicopter: &unit.name,
+                pilot: pilot_name,
+                from_ramp_m: from_ramp,
+                from_centerline_m: from_centerline,
+            },
         );
-        return false;
-    }
 
-    // ignore takeoffs
-    if distance < 200.0 {
-        tracing::trace!(distance_in_m = distance, "ignore takeoffs");
-        return false;
+        if params.discord_posts.passes() && !params.notifiers.is_empty() {
+            let mut notification = Notification {
+                title: Some("Deck landing".to_string()),
+                pilot: Some(pilot_name.to_string()),
+                ..Default::default()
+            };
+            if let Some(server_name) = &params.server_name {
+                notification = notification.field("Server", server_name, true);
+            }
+            notification = notification
+                .field("Helicopter", &unit.r#type, true)
+                .field("Carrier", &carrier.name, true)
+                .field(
+                    "Spot",
+                    format!(
+                        "{:.0} m past the ramp, {:.1} m {} of the centerline",
+                        from_ramp,
+                        from_centerline.abs(),
+                        if from_centerline < 0.0 {
+                            "left"
+                        } else {
+                            "right"
+                        }
+                    ),
+                    false,
+                );
+            let notifiers = params.notifiers.clone();
+            tokio::spawn(async move { notifiers.notify(&notification).await });
+        }
     }
+}
 
-    // is the plane behind the carrier
-    let dot = carrier
-        .forward
-        .normalized()
-        .dot(ray_from_plane_to_carrier.normalized());
-    if dot < 0.0 {
-        tracing::trace!(dot, "ignore not behind the carrier");
-        return false;
-    }
+/// Cheap broad-phase check that returns the carrier nearest to the plane, but only if the plane is
+/// within the altitude and distance limits of the detection envelope (see
+/// [broad_phase_distance]).
+fn nearest_carrier<'a>(
+    carriers: &'a HashMap<u32, Carrier>,
+    plane: &Plane,
+    envelope: &DetectionEnvelope,
+) -> Option<(&'a u32, &'a Carrier)> {
+    carriers
+        .iter()
+        .filter_map(|(id, carrier)| {
+            broad_phase_distance(&carrier.transform, &plane.transform, envelope)
+                .map(|distance| (id, carrier, distance))
+        })
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+        .map(|(id, carrier, _)| (id, carrier))
+}
 
-    // Does the nose of the plane roughly point towards the carrier?
-    let dot = plane
-        .forward
-        .normalized()
-        .dot(ray_from_plane_to_carrier.normalized());
-    if dot < 0.65 {
-        tracing::trace!(dot, "ignore not roughly pointing towards the carrier");
-        return false;
-    }
+/// Query whether the plane's gear and hook are extended.
+async fn gear_and_hook_down(
+    client: &mut UnitClient,
+    plane_name: &str,
+    info: &AirplaneInfo,
+) -> Result<(bool, bool), Status> {
+    let gear = client
+        .get_draw_argument_value(plane_name, info.gear_draw_arg)
+        .await?;
+    let hook = client
+        .get_draw_argument_value(plane_name, info.hook_draw_arg)
+        .await?;
+    Ok((gear > 0.5, hook > 0.5))
+}
 
-    tracing::debug!(
-        at = plane.time,
-        dot,
-        distance_in_m = distance,
-        distance_in_nm = m_to_nm(distance),
-        "found recovery attempt",
-    );
+#[derive(Debug)]
+enum Candidate {
+    Carrier(&'static CarrierInfo),
+    Plane(&'static AirplaneInfo),
+}
+
+async fn check_candidate(
+    client: &mut UnitClient,
+    unit: &Unit,
+    include_ki: bool,
+    config: &Config,
+) -> Result<Option<Candidate>, Status> {
+    let group_name = unit
+        .group
+        .as_ref()
+        .map(|g| g.name.as_str())
+        .unwrap_or_default();
+    match GroupCategory::try_from(unit.group.as_ref().map(|g| g.category).unwrap_or(-1)) {
+        Ok(GroupCategory::Airplane)
+            if unit.player_name.is_some()
+                || (include_ki && config.records_ki_group(group_name)) =>
+        {
+            return Ok(AirplaneInfo::by_type(&unit.r#type).map(Candidate::Plane))
+        }
+        Ok(GroupCategory::Helicopter) => {
+            tracing::trace!(unit_name = %unit.name, "ignore helicopter for recovery attempts");
+        }
+        Ok(GroupCategory::Ship) => {
+            let attrs = client.get_descriptor(&unit.name).await?;
+            if attrs
+                .iter()
+                .any(|a| a.as_str() == "AircraftCarrier With Arresting Gear")
+            {
+                return Ok(CarrierInfo::by_type(&unit.r#type).map(Candidate::Carrier));
+            }
+        }
+        _ => {}
+    }
 
-    true
+    Ok(None)
 }
@@ -0,0 +1,75 @@
+use futures_util::StreamExt;
+use stubs::mission::v0::stream_events_response::{Event, PlayerSendChatEvent};
+
+use crate::client::{MissionClient, NetClient};
+use crate::notify::{DiscordUsers, LINK_COMMAND};
+use crate::privacy::PilotNames;
+
+use super::DetectionParams;
+
+/// Link the players that type a code issued by the Discord bot (see
+/// [crate::notify::discord_bot]) in the chat to the Discord user the code was issued to.
+#[tracing::instrument(skip_all)]
+pub async fn link_players(
+    params: DetectionParams,
+    users: DiscordUsers,
+) -> Result<(), crate::error::Error> {
+    let mut mission = MissionClient::new(params.ch.clone());
+    let mut net = NetClient::new(params.ch.clone());
+    let mut events = params.shutdown.wrap_stream(mission.stream_events().await?);
+
+    while let Some(event) = events.next().await {
+        let Event::PlayerSendChat(PlayerSendChatEvent { player_id, message }) = event?.1 else {
+            continue;
+        };
+        // require whitespace after the command, so that e.g. `-linked` isn't taken for a code
+        let Some(code) = message
+            .trim()
+            .strip_prefix(LINK_COMMAND)
+            .filter(|rest| rest.starts_with(char::is_whitespace))
+            .map(str::trim)
+            .filter(|code| !code.is_empty())
+        else {
+            continue;
+        };
+
+        let players = match net.get_players().await {
+            Ok(players) => players,
+            Err(err) => {
+                tracing::warn!(%err, "failed to get the players to link");
+                continue;
+            }
+        };
+        let Some(player) = players.into_iter().find(|player| player.id == player_id) else {
+            continue;
+        };
+        // link the UCID, unless the pilots are anonymized (and thus mentioned by their anonymized
+        // name)
+        let key =
+            if params.config.privacy.pilot_names == PilotNames::Keep && !player.ucid.is_empty() {
+                player.ucid
+            } else {
+                crate::privacy::pilot_name(&player.name)
+            };
+
+        let reply = match users.link(code, &key) {
+            Some(discord_id) => {
+                tracing::info!(player = player.name, discord_id, "linked player to Discord");
+                "Your player is now linked to your Discord account.".to_string()
+            }
+            None => {
+                tracing::debug!(player = player.name, "invalid link code");
+                format!(
+                    "Unknown or expired link code, get a new one from the LSO on Discord and \
+                     type `{} CODE`.",
+                    LINK_COMMAND
+                )
+            }
+        };
+        if let Err(err) = net.send_chat_to(player_id, reply).await {
+            tracing::warn!(%err, "failed to reply to the link command");
+        }
+    }
+
+    Ok(())
+}
@@ -1,27 +1,336 @@
 use std::collections::HashMap;
-use std::path::Path;
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use stubs::common::v0::Coalition;
+use tokio::sync::Notify;
 use tonic::transport::Channel;
 
+use crate::config::Config;
 use crate::data::{AirplaneInfo, CarrierInfo};
+use crate::error::Error;
+use crate::event_log::EventLog;
+use crate::notify::Notifiers;
+use crate::realtime::RealtimeTelemetry;
+use crate::recovery::RecoveryWindows;
+use crate::status::ServerStatus;
+use crate::transform::Transform;
 use crate::utils::shutdown::ShutdownHandle;
+use supervisor::Supervisor;
 
 pub mod detect_recovery_attempt;
+pub mod link_players;
 pub mod record_recovery;
+pub mod report_session;
+pub mod supervisor;
+
+/// Which ACMI recordings should be kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum AcmiRetention {
+    /// Keep the ACMI recording of every pass.
+    All,
+    /// Only keep ACMI recordings of notable passes (cuts, wave-offs, 1-wires and no-grades).
+    Notable,
+}
+
+/// Which posts are sent to Discord (and the other configured chat services).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum DiscordPosts {
+    /// Post every pass.
+    Passes,
+    /// Only post a summary of all passes when the mission ends.
+    Session,
+    /// Post every pass and the summary when the mission ends.
+    All,
+}
+
+impl DiscordPosts {
+    pub fn passes(self) -> bool {
+        matches!(self, DiscordPosts::Passes | DiscordPosts::All)
+    }
+
+    pub fn session(self) -> bool {
+        matches!(self, DiscordPosts::Session | DiscordPosts::All)
+    }
+}
+
+/// All airplanes and helicopters in the mission (by unit id), used to detect a fouled deck and to
+/// record the traffic around the carrier.
+pub type Traffic = Arc<Mutex<HashMap<u32, TrafficUnit>>>;
+
+#[derive(Debug, Clone)]
+pub struct TrafficUnit {
+    pub name: String,
+    pub r#type: String,
+    pub pilot_name: Option<String>,
+    pub coalition: Coalition,
+    pub is_helicopter: bool,
+    pub transform: Transform,
+}
 
 #[derive(Clone)]
-pub struct TaskParams<'a> {
-    pub out_dir: &'a Path,
-    pub discord_webhook: Option<String>,
-    pub users: Arc<HashMap<String, u64>>,
+pub struct DetectionParams {
+    pub out_dir: PathBuf,
+    pub server_name: Option<String>,
+    pub notifiers: Notifiers,
+    pub discord_posts: DiscordPosts,
+    /// Whether to write a summary of all passes when the mission ends.
+    pub session_report: bool,
+    pub acmi_retention: AcmiRetention,
+    pub ch: Channel,
+    pub include_ki: bool,
+    pub config: Arc<Config>,
+    pub recovery_windows: RecoveryWindows,
+    pub event_log: EventLog,
+    /// Where the recordings are streamed to live (if enabled).
+    pub realtime: Option<RealtimeTelemetry>,
+    /// Where the connection state and the tracked units are reported to.
+    pub status: ServerStatus,
+    pub supervisor: Supervisor,
+    pub shutdown: ShutdownHandle,
+    pub drain_timeout: Duration,
+}
+
+/// The parameters of the recording of a single recovery (see [RecoveryTaskBuilder]).
+#[derive(Clone)]
+pub struct TaskParams {
+    pub out_dir: PathBuf,
+    pub server_name: Option<String>,
+    /// Where the pass is posted to (empty if passes aren't posted).
+    pub notifiers: Notifiers,
+    pub acmi_retention: AcmiRetention,
     pub ch: Channel,
     pub carrier_id: u32,
-    pub carrier_name: &'a str,
+    pub carrier_name: String,
+    pub carrier_type: String,
     pub plane_id: u32,
-    pub plane_name: &'a str,
-    pub pilot_name: &'a str,
+    pub plane_name: String,
+    pub plane_type: String,
+    pub pilot_name: String,
+    /// The UCID of the player (if known), which identifies them regardless of their name.
+    pub ucid: Option<String>,
+    /// The callsign of a KI plane (shown in addition to the group it is recorded under).
+    pub callsign: Option<String>,
     pub carrier_info: &'static CarrierInfo,
     pub plane_info: &'static AirplaneInfo,
+    pub config: Arc<Config>,
+    pub traffic: Traffic,
+    pub recovery_windows: RecoveryWindows,
+    pub event_log: EventLog,
+    pub realtime: Option<RealtimeTelemetry>,
+    /// Whether the plane started the pass with the hook up.
+    pub hook_up: bool,
+    /// Notified when the recording should be finished right away (requested via the control
+    /// endpoint).
+    pub finish: Arc<Notify>,
     pub shutdown: ShutdownHandle,
+    /// How long an active recording is continued after a shutdown signal.
+    pub drain_timeout: Duration,
+}
+
+/// Builds the [TaskParams] of the recording of a single recovery, e.g. to record a pass from an
+/// application embedding the library without running the detection task.
+///
+/// Only the connection, the shutdown handle, the carrier and the plane are required, everything
+/// else defaults to what `lso run` uses without any options.
+pub struct RecoveryTaskBuilder {
+    ch: Channel,
+    shutdown: ShutdownHandle,
+    carrier: Option<(u32, String, String)>,
+    plane: Option<(u32, String, String, String)>,
+    ucid: Option<String>,
+    callsign: Option<String>,
+    out_dir: PathBuf,
+    server_name: Option<String>,
+    notifiers: Notifiers,
+    acmi_retention: AcmiRetention,
+    config: Arc<Config>,
+    traffic: Traffic,
+    recovery_windows: Option<RecoveryWindows>,
+    event_log: EventLog,
+    realtime: Option<RealtimeTelemetry>,
+    hook_up: bool,
+    finish: Arc<Notify>,
+    drain_timeout: Duration,
+}
+
+impl RecoveryTaskBuilder {
+    pub fn new(ch: Channel, shutdown: ShutdownHandle) -> Self {
+        Self {
+            ch,
+            shutdown,
+            carrier: None,
+            plane: None,
+            ucid: None,
+            callsign: None,
+            out_dir: PathBuf::from("."),
+            server_name: None,
+            notifiers: Default::default(),
+            acmi_retention: AcmiRetention::All,
+            config: Default::default(),
+            traffic: Default::default(),
+            recovery_windows: None,
+            event_log: Default::default(),
+            realtime: None,
+            hook_up: false,
+            finish: Default::default(),
+            drain_timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// The carrier the plane recovers to (by unit id, unit name and DCS type).
+    pub fn carrier(mut self, id: u32, name: impl Into<String>, r#type: impl Into<String>) -> Self {
+        self.carrier = Some((id, name.into(), r#type.into()));
+        self
+    }
+
+    /// The recovering plane (by unit id, unit name and DCS type) and the name of its pilot.
+    pub fn plane(
+        mut self,
+        id: u32,
+        name: impl Into<String>,
+        r#type: impl Into<String>,
+        pilot_name: impl Into<String>,
+    ) -> Self {
+        self.plane = Some((id, name.into(), r#type.into(), pilot_name.into()));
+        self
+    }
+
+    /// The UCID of the player, which the pass is stored under (besides the name).
+    pub fn ucid(mut self, ucid: Option<String>) -> Self {
+        self.ucid = ucid;
+        self
+    }
+
+    /// The callsign of a KI plane (which is recorded under its group as the pilot name).
+    pub fn callsign(mut self, callsign: Option<String>) -> Self {
+        self.callsign = callsign;
+        self
+    }
+
+    /// Where the recording, the chart and the results are written to (defaults to the current
+    /// directory).
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = out_dir.into();
+        self
+    }
+
+    pub fn server_name(mut self, server_name: Option<String>) -> Self {
+        self.server_name = server_name;
+        self
+    }
+
+    /// Where the pass is posted to (not posted anywhere by default).
+    pub fn notifiers(mut self, notifiers: Notifiers) -> Self {
+        self.notifiers = notifiers;
+        self
+    }
+
+    pub fn acmi_retention(mut self, acmi_retention: AcmiRetention) -> Self {
+        self.acmi_retention = acmi_retention;
+        self
+    }
+
+    pub fn config(mut self, config: Arc<Config>) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// The other units in the mission, used to detect a fouled deck (empty by default).
+    pub fn traffic(mut self, traffic: Traffic) -> Self {
+        self.traffic = traffic;
+        self
+    }
+
+    /// The recovery windows shared with other recordings of the same server (by default, the
+    /// pass starts a window of its own).
+    pub fn recovery_windows(mut self, recovery_windows: RecoveryWindows) -> Self {
+        self.recovery_windows = Some(recovery_windows);
+        self
+    }
+
+    pub fn event_log(mut self, event_log: EventLog) -> Self {
+        self.event_log = event_log;
+        self
+    }
+
+    pub fn realtime(mut self, realtime: Option<RealtimeTelemetry>) -> Self {
+        self.realtime = realtime;
+        self
+    }
+
+    /// Whether the plane starts the pass with the hook up.
+    pub fn hook_up(mut self, hook_up: bool) -> Self {
+        self.hook_up = hook_up;
+        self
+    }
+
+    /// Finish the recording right away once notified.
+    pub fn finish(mut self, finish: Arc<Notify>) -> Self {
+        self.finish = finish;
+        self
+    }
+
+    pub fn drain_timeout(mut self, drain_timeout: Duration) -> Self {
+        self.drain_timeout = drain_timeout;
+        self
+    }
+
+    /// Fails if the carrier or the plane is missing, or if their type isn't supported.
+    pub fn build(self) -> Result<TaskParams, Error> {
+        let (carrier_id, carrier_name, carrier_type) = self
+            .carrier
+            .ok_or_else(|| Error::Task("no carrier given".to_string()))?;
+        let (plane_id, plane_name, plane_type, pilot_name) = self
+            .plane
+            .ok_or_else(|| Error::Task("no plane given".to_string()))?;
+        let carrier_info = CarrierInfo::by_type(&carrier_type)
+            .ok_or_else(|| Error::Task(format!("unsupported carrier type `{}`", carrier_type)))?;
+        let plane_info = AirplaneInfo::by_type(&plane_type)
+            .ok_or_else(|| Error::Task(format!("unsupported plane type `{}`", plane_type)))?;
+        let recovery_windows = self.recovery_windows.unwrap_or_else(|| {
+            RecoveryWindows::new(
+                time::Duration::seconds_f64(self.config.recovery.max_gap_s),
+                self.server_name.as_deref(),
+                &[],
+            )
+        });
+
+        Ok(TaskParams {
+            out_dir: self.out_dir,
+            server_name: self.server_name,
+            notifiers: self.notifiers,
+            acmi_retention: self.acmi_retention,
+            ch: self.ch,
+            carrier_id,
+            carrier_name,
+            carrier_type,
+            plane_id,
+            plane_name,
+            plane_type,
+            pilot_name,
+            ucid: self.ucid,
+            callsign: self.callsign,
+            carrier_info,
+            plane_info,
+            config: self.config,
+            traffic: self.traffic,
+            recovery_windows,
+            event_log: self.event_log,
+            realtime: self.realtime,
+            hook_up: self.hook_up,
+            finish: self.finish,
+            shutdown: self.shutdown,
+            drain_timeout: self.drain_timeout,
+        })
+    }
+
+    /// Record the recovery until the plane traps, bolters, or leaves the approach, and write and
+    /// post its results.
+    pub async fn record(self) -> Result<(), Error> {
+        record_recovery::record_recovery(self.build()?).await
+    }
 }
@@ -1,73 +1,142 @@
-use std::borrow::Cow;
-use std::collections::HashSet;
-use std::io::Cursor;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 use futures_util::future::Either;
 use futures_util::stream::select;
 use futures_util::StreamExt;
-use once_cell::sync::Lazy;
-use serenity::builder::{CreateAttachment, CreateEmbed, ExecuteWebhook};
-use serenity::http::Http;
-use serenity::model::id::UserId;
-use serenity::model::mention::Mention;
 use stubs::common::v0::{initiator, Airbase, Coalition, Initiator};
 use stubs::mission::v0::stream_events_response::{
-    CrashEvent, DeadEvent, Event, LandingQualityMarkEvent, PlayerLeaveUnitEvent, RunwayTouchEvent,
-    UnitLostEvent,
+    CrashEvent, DeadEvent, EjectionEvent, Event, LandingQualityMarkEvent, PilotDeadEvent,
+    PlayerLeaveUnitEvent, RunwayTouchEvent, UnitLostEvent,
 };
 use tacview::record::{self, Color, Coords, GlobalProperty, Property, Record, Tag, Update};
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
-use tonic::Status;
+use tonic::{Code, Status};
+use ulid::Ulid;
+use ultraviolet::DVec3;
 
-use crate::client::{HookClient, MissionClient, UnitClient};
-use crate::track::{Grading, Track};
+use crate::acmi::{AcmiFile, Recording};
+use crate::client::{
+    AtmosphereClient, CoalitionClient, CustomClient, HookClient, MissionClient, UnitClient,
+};
+use crate::config::AoaSource;
+use crate::cq::CqStatus;
+use crate::event_log::LifecycleEvent;
+use crate::filename::{FilenameParts, FILENAME_DATETIME_FORMAT};
+use crate::notify::{Attachment, Notification};
+use crate::realtime::IDS_PER_RECORDING;
+use crate::stats;
+use crate::store::{self, PassRecord};
+use crate::track::{is_recovery_attempt, Incident, Track, TrackResult};
 use crate::transform::Transform;
+use crate::utils::m_to_nm;
+use crate::utils::shutdown::AbortableStream;
 
-use super::TaskParams;
+use super::{AcmiRetention, TaskParams, TrafficUnit};
 
-pub static FILENAME_DATETIME_FORMAT: Lazy<Vec<time::format_description::FormatItem<'_>>> =
-    Lazy::new(|| {
-        time::format_description::parse("[year][month][day]-[hour][minute][second]").unwrap()
-    });
+/// How long to wait for the next pass after a bolter or wave-off before ending the recording.
+const PATTERN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Stop waiting for the next pass once the plane is farther away from the carrier than this (in
+/// nm).
+const PATTERN_MAX_DISTANCE: f64 = 10.0;
+/// Check the landing area for other units once the plane is closer to the carrier than this (in
+/// nm).
+const FOUL_DECK_DISTANCE: f64 = 0.75;
+/// How often the wind at the plane's position is updated (for the AoA).
+const WIND_INTERVAL: Duration = Duration::from_secs(1);
+/// The extension of the recordings that are still being written.
+const PARTIAL_EXTENSION: &str = ".acmi.part";
+/// Partial recordings that weren't written to for this long are left over from a crash.
+const PARTIAL_STALE_AFTER: Duration = Duration::from_secs(60 * 60);
 
 #[tracing::instrument(
     skip_all,
-    fields(carrier_name = params.carrier_name, plane_name = params.plane_name)
+    fields(carrier_name = %params.carrier_name, plane_name = %params.plane_name)
 )]
-pub async fn record_recovery(params: TaskParams<'_>) -> Result<(), crate::error::Error> {
+pub async fn record_recovery(params: TaskParams) -> Result<(), crate::error::Error> {
     tracing::debug!("started recording");
 
-    // Tacview-20211111-143727-DCS-grpc-lso.zip
     let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
-    let filename = format!(
-        "LSO-{}-{}",
-        now.format(&FILENAME_DATETIME_FORMAT).unwrap_or_default(),
-        params
-            .pilot_name
-            .chars()
-            .filter(|c| c.is_ascii_alphanumeric())
-            .collect::<String>()
-    );
 
     let mut client1 = UnitClient::new(params.ch.clone());
     let mut client2 = UnitClient::new(params.ch.clone());
     let mut mission = MissionClient::new(params.ch.clone());
     let mut hook = HookClient::new(params.ch.clone());
-    let interval = crate::utils::interval::interval(Duration::from_millis(100), params.shutdown);
+    // unset once querying the weight failed (e.g. if the custom service is disabled)
+    let mut custom = Some(CustomClient::new(params.ch.clone()));
+    // Don't stop right away on shutdown, but give the pass a chance to complete first (unless
+    // finishing the recording got requested).
+    let interval = crate::utils::interval::ticks(Duration::from_millis(100));
+    let drain = futures_util::future::select(
+        Box::pin(params.shutdown.drain(params.drain_timeout)),
+        Box::pin(params.finish.notified()),
+    );
 
-    let mut acmi = Cursor::new(Vec::new());
-    let mut recording = tacview::Writer::new_compressed(&mut acmi)?;
-    let mut datums = Track::new(params.pilot_name, params.carrier_info, params.plane_info);
+    // Stream the recording to a temporary file, which is only moved to its final location (named
+    // after the pass) once the recording is complete.
+    // The ULID keeps the recordings of the same plane ID on different servers sharing the output
+    // directory apart.
+    let tmp = PartialFile::new(params.out_dir.join(format!(
+        "LSO-{}-{}-{}{}",
+        now.format(FILENAME_DATETIME_FORMAT).unwrap_or_default(),
+        params.plane_id,
+        Ulid::new(),
+        PARTIAL_EXTENSION
+    )));
+    let mut recording = Recording::new(
+        AcmiFile::create(&tmp.path, params.config.output.acmi_format)?,
+        params
+            .realtime
+            .as_ref()
+            .map(|realtime| realtime.recording()),
+    );
+    let mission_name = hook.get_mission_name().await?;
+    let rigged_wires = params.config.rigged_wires(
+        Some(&mission_name),
+        &params.carrier_name,
+        &params.carrier_type,
+    );
+    let touch_and_go = params.config.touch_and_go(
+        Some(&mission_name),
+        &params.carrier_name,
+        &params.carrier_type,
+    );
+    let new_track = || {
+        let mut track = Track::new(&params.pilot_name, params.carrier_info, params.plane_info);
+        if touch_and_go {
+            track.set_touch_and_go();
+        }
+        track.set_max_turn_rate(params.config.detection.max_turn_rate);
+        track.set_smoothing(params.config.smoothing);
+        track.set_resampling(params.config.resampling);
+        if let Some(rigged_wires) = rigged_wires {
+            track.set_rigged_wires(rigged_wires.to_vec());
+        }
+        track
+    };
+    let mut datums = new_track();
+    // the hook state at detection time only applies to the first pass, the one of each following
+    // pass is queried once it starts
+    if params.hook_up {
+        datums.set_hook_up();
+    }
+    // Static objects don't move, so they are only looked up once per recording.
+    let statics = get_statics(&mut CoalitionClient::new(params.ch.clone())).await;
+    // the previous passes of the session (after bolters and wave-offs), with the lowest altitude
+    // during each pass
+    let mut passes: Vec<(Track, f64)> = Vec::new();
+    // set while waiting for the plane to start its next pass
+    let mut waiting_since: Option<Instant> = None;
 
     let reference_time = mission.get_scenario_start_time().await?;
+    let mission_start = OffsetDateTime::parse(&reference_time, &Rfc3339).ok();
     recording.write(GlobalProperty::ReferenceTime(reference_time))?;
     recording.write(GlobalProperty::RecordingTime(
         OffsetDateTime::now_utc().format(&Rfc3339).unwrap(),
     ))?;
 
-    let mission_name = hook.get_mission_name().await?;
     recording.write(GlobalProperty::Title(format!(
         "Carrier Recovery during {}",
         mission_name
@@ -76,31 +145,79 @@ pub async fn record_recovery(params: TaskParams<'_>) -> Result<(), crate::error:
         "dcs-grpc-lso v{}",
         env!("CARGO_PKG_VERSION")
     )))?;
+    if let Some(server_name) = &params.server_name {
+        recording.write(GlobalProperty::DataSource(format!(
+            "{}{}",
+            crate::acmi::SERVER_PREFIX,
+            server_name
+        )))?;
+    }
     let mut ref_written = false;
     let mut lat_ref = 0.0;
     let mut lon_ref = 0.0;
 
-    recording.write(create_initial_update(&mut client1, 1, params.carrier_name).await?)?;
-    recording.write(create_initial_update(&mut client1, 2, params.plane_name).await?)?;
+    let mut carrier_update = create_initial_update(&mut client1, 1, &params.carrier_name).await?;
+    // the passes extracted from the recording are assigned to the carrier by its unit name
+    carrier_update
+        .props
+        .push(Property::CallSign(params.carrier_name.clone()));
+    recording.write(carrier_update)?;
+    recording.write(create_initial_update(&mut client1, 2, &params.plane_name).await?)?;
+
+    let mut wind = Wind::new(
+        (params.config.aoa_source != AoaSource::Geometric)
+            .then(|| AtmosphereClient::new(params.ch.clone())),
+    );
+    if let Ok(plane) = client2.get_transform(&params.plane_name).await {
+        wind.update(&plane).await;
+    }
 
     let events = mission.stream_events().await?;
 
     let mut known_carrier_coords = None;
     let mut known_plane_coords = None;
+    let mut recorded_traffic = RecordedTraffic::new();
     let mut track_stopped: Option<Instant> = None;
     let mut lowest_altitude = f64::MAX;
+    // whether the plane (or carrier) doesn't exist anymore
+    let mut unit_gone = false;
 
-    let mut stream = select(interval.map(Either::Left), events.map(Either::Right));
+    let mut stream = AbortableStream::new(
+        drain,
+        select(interval.map(Either::Left), events.map(Either::Right)),
+    );
+
+    loop {
+        let Some(next) = stream.next().await else {
+            tracing::info!("shutdown or finish requested, saving recording so far");
+            datums.set_interrupted();
+            break;
+        };
 
-    while let Some(next) = stream.next().await {
         match next {
             // next interval
             Either::Left(_) => {
-                let (carrier, plane) = futures_util::future::try_join(
-                    client1.get_transform(params.carrier_name),
-                    client2.get_transform(params.plane_name),
+                let (carrier, plane) = match futures_util::future::try_join(
+                    client1.get_transform(&params.carrier_name),
+                    client2.get_transform_in_wind(&params.plane_name, wind.velocity),
                 )
-                .await?;
+                .await
+                {
+                    Ok(transforms) => transforms,
+                    Err(status) if is_connection_lost(&status) => {
+                        tracing::warn!(%status, "connection lost, saving recording so far");
+                        datums.set_interrupted();
+                        break;
+                    }
+                    Err(status) if status.code() == Code::NotFound => {
+                        tracing::info!(%status, "unit gone");
+                        unit_gone = true;
+                        break;
+                    }
+                    Err(status) => return Err(status.into()),
+                };
+
+                wind.update(&plane).await;
 
                 if !ref_written {
                     lat_ref = carrier.lat;
@@ -152,10 +269,93 @@ pub async fn record_recovery(params: TaskParams<'_>) -> Result<(), crate::error:
                     recording.write(carrier_update)?;
                 }
 
+                if let Some(range_nm) = params.config.output.traffic_range_nm {
+                    recorded_traffic.update(
+                        &mut recording,
+                        &params.traffic.lock().unwrap(),
+                        params.plane_id,
+                        &carrier,
+                        (lat_ref, lon_ref),
+                        range_nm,
+                    )?;
+                }
+
+                if let Some(since) = waiting_since {
+                    if is_recovery_attempt(&carrier, &plane, &params.config.detection) {
+                        tracing::debug!("next pass started");
+                        waiting_since = None;
+                        match client1
+                            .get_draw_argument_value(
+                                &params.plane_name,
+                                params.plane_info.hook_draw_arg,
+                            )
+                            .await
+                        {
+                            Ok(hook) if hook <= 0.5 => datums.set_hook_up(),
+                            Ok(_) => {}
+                            Err(err) => tracing::debug!(%err, "failed to get hook state"),
+                        }
+                    } else if since.elapsed() > PATTERN_TIMEOUT
+                        || m_to_nm((carrier.position - plane.position).mag()) > PATTERN_MAX_DISTANCE
+                        || params.shutdown.is_shutdown()
+                    {
+                        tracing::debug!("stop waiting for next pass");
+                        break;
+                    } else {
+                        continue;
+                    }
+                }
+
                 lowest_altitude = lowest_altitude.min(plane.alt);
 
                 if !datums.next(&carrier, &plane) {
-                    break;
+                    if datums.is_recovered() {
+                        break;
+                    }
+
+                    // Follow the plane through the pattern after a bolter or wave-off and record
+                    // its next pass into the same session.
+                    tracing::debug!("pass done, waiting for next pass");
+                    recording.write(record::Event {
+                        kind: record::EventKind::Bookmark,
+                        params: vec!["2".to_string()],
+                        text: Some(datums.departure_summary().to_string()),
+                    })?;
+                    passes.push((std::mem::replace(&mut datums, new_track()), lowest_altitude));
+                    lowest_altitude = f64::MAX;
+                    track_stopped = None;
+                    waiting_since = Some(Instant::now());
+                    continue;
+                }
+
+                if let Some(client) = custom.as_mut().filter(|_| datums.needs_weight()) {
+                    match client.get_weight(&params.plane_name).await {
+                        Ok(weight) => datums.set_weight(weight),
+                        Err(status) => {
+                            tracing::warn!(%status, "failed to get the weight, not recording it");
+                            custom = None;
+                        }
+                    }
+                }
+
+                if datums.is_approaching()
+                    && m_to_nm((carrier.position - plane.position).mag()) < FOUL_DECK_DISTANCE
+                {
+                    let in_landing_area = |position: DVec3| {
+                        params.carrier_info.in_landing_area(
+                            (position - carrier.position).rotated_by(carrier.rotation.reversed()),
+                        )
+                    };
+                    let traffic = params.traffic.lock().unwrap();
+                    let fouled_by = traffic
+                        .iter()
+                        .filter(|(id, _)| **id != params.plane_id)
+                        .map(|(_, unit)| (&unit.name, unit.transform.position))
+                        .chain(statics.iter().map(|(name, position)| (name, *position)))
+                        .find(|(_, position)| in_landing_area(*position));
+                    if let Some((unit_name, _)) = fouled_by {
+                        datums.set_foul_deck(unit_name);
+                    }
                 }
 
                 if let Some(track_stopped) = track_stopped {
@@ -165,6 +365,12 @@ pub async fn record_recovery(params: TaskParams<'_>) -> Result<(), crate::error:
                 }
             }
 
+            Either::Right(Err(status)) if is_connection_lost(&status) => {
+                tracing::warn!(%status, "connection lost, saving recording so far");
+                datums.set_interrupted();
+                break;
+            }
+
             // DCS landing grade
             Either::Right(event) => match event? {
                 (
@@ -183,7 +389,13 @@ pub async fn record_recovery(params: TaskParams<'_>) -> Result<(), crate::error:
                     }),
                 ) if plane.id == params.plane_id && carrier.id == params.carrier_id => {
                     tracing::info!(%comment, "landing quality mark event");
-                    datums.set_dcs_grading(comment.clone());
+                    // While waiting for the next pass, the grading belongs to the previous one.
+                    match passes.last_mut() {
+                        Some((previous, _)) if waiting_since.is_some() => {
+                            previous.set_dcs_grading(comment.clone())
+                        }
+                        _ => datums.set_dcs_grading(comment.clone()),
+                    }
                     recording.write(Record::Frame(time))?;
 
                     let carrier = Transform::from((
@@ -204,12 +416,13 @@ pub async fn record_recovery(params: TaskParams<'_>) -> Result<(), crate::error:
                         ))],
                     })?;
 
-                    let plane = Transform::from((
+                    let plane = Transform::in_wind(
                         time,
                         plane.position.unwrap_or_default(),
                         plane.orientation.unwrap_or_default(),
                         plane.velocity.unwrap_or_default(),
-                    ));
+                        wind.velocity,
+                    );
                     recording.write(Update {
                         id: 2,
                         props: vec![
@@ -268,12 +481,13 @@ pub async fn record_recovery(params: TaskParams<'_>) -> Result<(), crate::error:
                         ))],
                     })?;
 
-                    let plane = Transform::from((
+                    let plane = Transform::in_wind(
                         time,
                         plane.position.unwrap_or_default(),
                         plane.orientation.unwrap_or_default(),
                         plane.velocity.unwrap_or_default(),
-                    ));
+                        wind.velocity,
+                    );
                     recording.write(Update {
                         id: 2,
                         props: vec![
@@ -295,6 +509,19 @@ pub async fn record_recovery(params: TaskParams<'_>) -> Result<(), crate::error:
                         text: None,
                     })?;
 
+                    // the hook might have been raised during the pass
+                    match client1
+                        .get_draw_argument_value(
+                            &params.plane_name,
+                            params.plane_info.hook_draw_arg,
+                        )
+                        .await
+                    {
+                        Ok(hook) if hook <= 0.5 => datums.set_hook_up(),
+                        Ok(_) => {}
+                        Err(err) => tracing::debug!(%err, "failed to get hook state"),
+                    }
+
                     datums.next(&carrier, &plane);
                     datums.landed(&carrier, &plane);
 
@@ -302,7 +529,33 @@ pub async fn record_recovery(params: TaskParams<'_>) -> Result<(), crate::error:
                     track_stopped = Some(Instant::now());
                 }
 
-                // Any event indicating that either the carrier or plane do not exist anymore
+                // The plane crashed, or its pilot ejected or died
+                (
+                    time,
+                    event @ (Event::Crash(_)
+                    | Event::Dead(_)
+                    | Event::Ejection(_)
+                    | Event::PilotDead(_)),
+                ) if incident_unit_id(&event) == Some(params.plane_id) => {
+                    let incident = match event {
+                        Event::Ejection(_) => Incident::Ejection,
+                        Event::PilotDead(_) => Incident::PilotDead,
+                        _ => Incident::Crash,
+                    };
+                    tracing::info!(?incident, "incident");
+                    datums.set_incident(incident);
+
+                    recording.write(Record::Frame(time))?;
+                    recording.write(record::Event {
+                        kind: record::EventKind::Destroyed,
+                        params: vec!["2".to_string()],
+                        text: Some(incident.as_str().to_string()),
+                    })?;
+
+                    break;
+                }
+
+                // Any event indicating that the carrier does not exist anymore
                 (
                     _,
                     Event::Crash(CrashEvent {
@@ -329,9 +582,38 @@ pub async fn record_recovery(params: TaskParams<'_>) -> Result<(), crate::error:
                                 initiator: Some(initiator::Initiator::Unit(unit)),
                             }),
                     }),
-                ) if unit.id == params.plane_id || unit.id == params.carrier_id => {
-                    tracing::info!("stop (either carrier or plane despawned)");
-                    return Ok(());
+                ) if unit.id == params.carrier_id => {
+                    // Keep whatever got recorded so far when the carrier despawns or sinks.
+                    tracing::info!("carrier gone, saving recording so far");
+                    datums.set_interrupted();
+                    break;
+                }
+
+                (_, Event::MissionEnd(_)) => {
+                    tracing::info!("mission ended, saving recording so far");
+                    datums.set_interrupted();
+                    break;
+                }
+
+                // Any event indicating that the plane does not exist anymore
+                (
+                    _,
+                    Event::PlayerLeaveUnit(PlayerLeaveUnitEvent {
+                        initiator:
+                            Some(Initiator {
+                                initiator: Some(initiator::Initiator::Unit(unit)),
+                            }),
+                    })
+                    | Event::UnitLost(UnitLostEvent {
+                        initiator:
+                            Some(Initiator {
+                                initiator: Some(initiator::Initiator::Unit(unit)),
+                            }),
+                    }),
+                ) if unit.id == params.plane_id => {
+                    tracing::info!("plane despawned");
+                    unit_gone = true;
+                    break;
                 }
 
                 _ => {}
@@ -339,61 +621,389 @@ pub async fn record_recovery(params: TaskParams<'_>) -> Result<(), crate::error:
         }
     }
 
-    // If the plane was never below 100ft, don't consider it a worthy recovery attempt and discard
-    // the record
-    if lowest_altitude > 100.0 {
+    // If the plane (or carrier) disappeared, the pass is saved up to that point if it got past its
+    // start, and discarded otherwise.
+    if unit_gone && !datums.is_started() {
+        tracing::debug!("discard pass that didn't get past its start");
+    } else {
+        if unit_gone {
+            datums.set_interrupted();
+        }
+        passes.push((datums, lowest_altitude));
+    }
+
+    // If the plane was never below 100ft during a pass, don't consider it a worthy recovery attempt
+    // and discard it
+    let tracks = passes
+        .into_iter()
+        .filter(|(track, lowest_altitude)| *lowest_altitude <= 100.0 || track.is_incident())
+        .map(|(mut track, _)| {
+            if let Some(mission_start) = mission_start {
+                track.set_reference(mission_start, lat_ref, lon_ref);
+            }
+            let mut track = track.finish();
+            track.server_name.clone_from(&params.server_name);
+            track
+        })
+        .collect::<Vec<_>>();
+    if tracks.is_empty() {
         tracing::debug!("discard as plane was never below 100ft");
         return Ok(());
     }
 
-    recording.into_inner();
-    let data = acmi.into_inner();
-    let acmi_path = params.out_dir.join(&filename).with_extension("zip.acmi");
-    tokio::fs::write(&acmi_path, &data).await?;
-    let track = datums.finish();
-    let chart_path = crate::draw::draw_chart(params.out_dir, &filename, &track)?;
-
-    if let Some(discord_webhook) = params.discord_webhook.as_deref() {
-        let http = Http::new("token");
-        let webhook = http.get_webhook_from_url(discord_webhook).await?;
-
-        let embed = CreateEmbed::new()
-            .field(
-                "Pilot",
-                params
-                    .users
-                    .get(params.pilot_name)
-                    .map(|id| Cow::Owned(Mention::from(UserId::new(*id)).to_string()))
-                    .unwrap_or(Cow::Borrowed(params.pilot_name)),
-                true,
-            )
-            .field(
-                "Grading",
-                match track.grading {
-                    Grading::Unknown => Cow::Borrowed("unknown"),
-                    Grading::Bolter => Cow::Borrowed("Bolter"),
-                    Grading::Recovered { cable, .. } => cable
-                        .map(|c| Cow::Owned(format!("#{}", c)))
-                        .unwrap_or(Cow::Borrowed("-")),
+    // The passes are timed by their end, relative to the start of the recording, and identified by
+    // a ULID (which is sortable by that time).
+    let recording_start = tracks
+        .first()
+        .and_then(|track| track.datums.first())
+        .map(|datum| datum.time);
+    let passes = tracks
+        .iter()
+        .map(|track| {
+            let ended = recording_start
+                .zip(track.datums.last())
+                .map(|(start, datum)| now + time::Duration::seconds_f64(datum.time - start))
+                .unwrap_or(now);
+            (ended, Ulid::from_datetime(ended.into()).to_string())
+        })
+        .collect::<Vec<_>>();
+
+    let mut comments = vec![format!(
+        "Pass {}",
+        passes
+            .iter()
+            .map(|(_, id)| id.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )];
+    if tracks.iter().any(|t| t.interrupted) {
+        comments.push("Recording got interrupted before the pass was complete".to_string());
+    }
+    recording.write(GlobalProperty::Comments(comments.join("; ")))?;
+    crate::acmi::write_debriefing(
+        &mut recording,
+        &tracks
+            .iter()
+            .map(|track| (crate::acmi::PLANE_ID, track))
+            .collect::<Vec<_>>(),
+    )?;
+    recording.into_inner().finish()?;
+
+    let output_location = |track: &TrackResult, id: &str| {
+        crate::filename::output_location(
+            &params.out_dir,
+            &params.config.output,
+            &FilenameParts {
+                datetime: Some(now),
+                server: params.server_name.as_deref(),
+                pilot: Some(params.pilot_name.as_str()),
+                callsign: params.callsign.as_deref(),
+                carrier: Some(params.carrier_name.as_str()),
+                plane: Some(params.plane_type.as_str()),
+                mission: Some(&mission_name),
+                id: Some(id),
+                track: Some(track),
+            },
+        )
+    };
+    let acmi_path = if params.acmi_retention == AcmiRetention::All
+        || tracks.iter().any(|t| t.is_notable())
+    {
+        // the recording is named after its last pass
+        let (dir, filename) = output_location(tracks.last().unwrap(), &passes.last().unwrap().1)?;
+        let acmi_path = dir.join(format!(
+            "{}.{}",
+            filename,
+            params.config.output.acmi_format.extension()
+        ));
+        tmp.persist(&acmi_path).await?;
+        Some(acmi_path)
+    } else {
+        tracing::debug!("discard ACMI recording of a pass that isn't notable");
+        drop(tmp);
+        None
+    };
+
+    // the chart and debrief of each pass are named after it
+    let mut locations = Vec::with_capacity(tracks.len());
+    for (i, (track, (_, id))) in tracks.iter().zip(&passes).enumerate() {
+        let (dir, mut filename) = output_location(track, id)?;
+        if tracks.len() > 1 {
+            filename = format!("{}-{}", filename, i + 1);
+        }
+        locations.push((dir, filename));
+    }
+
+    // one chart per pass (only kept in memory if they aren't saved, and skipped altogether if there
+    // is nothing to attach them to either)
+    #[cfg(feature = "charts")]
+    let (tracks, charts) = if !params.config.output.save_charts && params.notifiers.is_empty() {
+        (tracks, Vec::new())
+    } else {
+        let locations = locations.clone();
+
+        // drawing is CPU-heavy, so it is moved off the runtime to not hold up the other recordings
+        // when several planes trap at once
+        let config = params.config.clone();
+        let rendered = tokio::task::spawn_blocking(move || {
+            let charts = tracks
+                .iter()
+                .zip(locations)
+                .map(|(track, (dir, filename))| {
+                    if config.output.save_charts {
+                        crate::draw::draw_chart(&dir, &filename, track, &config.chart, None)
+                            .map(Attachment::from)
+                    } else {
+                        crate::draw::draw_chart_png(track, &config.chart, None).map(|content| {
+                            Attachment::Memory {
+                                name: format!("{}.png", filename),
+                                content,
+                            }
+                        })
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>();
+            (tracks, charts)
+        })
+        .await;
+        match rendered {
+            Ok((tracks, charts)) => (tracks, charts?),
+            // report the panic the same way as if it happened in the recording task itself
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    };
+    #[cfg(not(feature = "charts"))]
+    let charts = Vec::new();
+
+    let records = tracks
+        .iter()
+        .zip(&passes)
+        .map(|(track, (ended, id))| {
+            let mut record = PassRecord::new(
+                *ended,
+                params.server_name.as_deref(),
+                &params.carrier_name,
+                &params.plane_type,
+                &params.carrier_type,
+                track,
+            );
+            record.id = Some(id.clone());
+            record.ucid.clone_from(&params.ucid);
+            record.window = Some(params.recovery_windows.assign(&params.carrier_name, *ended));
+            record
+        })
+        .collect::<Vec<_>>();
+    for record in &records {
+        params.event_log.log(
+            params.server_name.as_deref(),
+            LifecycleEvent::PassCompleted { pass: record },
+        );
+    }
+    // appending is blocking file I/O, so it is moved off the runtime as well
+    let results_path = params.out_dir.join(store::RESULTS_FILENAME);
+    let appended = {
+        let (results_path, records) = (results_path.clone(), records.clone());
+        tokio::task::spawn_blocking(move || {
+            store::append(&results_path, &records).map_err(|err| crate::error::report(&err))
+        })
+        .await
+    };
+    match appended {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => tracing::warn!(%err, "failed to save results"),
+        Err(err) => tracing::warn!(%err, "failed to save results"),
+    }
+
+    let mut debriefs = Vec::new();
+    if params.config.output.debrief {
+        for ((track, record), (dir, filename)) in tracks.iter().zip(&records).zip(&locations) {
+            let path = dir.join(format!("{}.html", filename));
+            let acmi = acmi_path
+                .as_deref()
+                .map(|acmi| crate::debrief::relative_link(dir, acmi));
+            match crate::debrief::write_html(&path, track, record, acmi.as_deref()) {
+                Ok(()) => debriefs.push(Attachment::from(path)),
+                Err(err) => tracing::warn!(%err, "failed to save debrief"),
+            }
+        }
+    }
+
+    if !params.notifiers.is_empty() {
+        // the session is summarized by its last pass
+        let track = tracks.last().unwrap();
+        let mut notification = Notification {
+            pilot: Some(params.pilot_name.clone()),
+            ucid: params.ucid.clone(),
+            footer: Some(format!("Pass {}", passes.last().unwrap().1)),
+            ..Default::default()
+        };
+        if let Some(server_name) = &params.server_name {
+            notification = notification.field("Server", server_name, true);
+        }
+        if let Some(callsign) = &params.callsign {
+            notification = notification.field("Callsign", callsign, true);
+        }
+        let mut grading = track.grading.summary(track.hook_up).into_owned();
+        if track.is_night() {
+            grading.push_str(" (night)");
+        }
+        notification = notification.field("Grading", grading, true);
+
+        if let Some(dcs_grading) = &track.dcs_grading {
+            let mut grade = dcs_grading.grade.as_str().to_string();
+            for deviation in &dcs_grading.deviations {
+                grade.push(' ');
+                grade.push_str(deviation);
+            }
+            notification = notification.field("DCS LSO", grade, true);
+        }
+
+        // the results history is only needed for the GPA and the CQ status, and reading it is
+        // blocking file I/O as well
+        let points = records.last().and_then(stats::points);
+        let results = if points.is_some() || params.config.cq.is_some() {
+            let loaded = tokio::task::spawn_blocking(move || {
+                store::load(&results_path).map_err(|err| crate::error::report(&err))
+            })
+            .await;
+            match loaded {
+                Ok(Ok(results)) => Some(results),
+                Ok(Err(err)) => {
+                    tracing::warn!(%err, "failed to load results");
+                    None
+                }
+                Err(err) => {
+                    tracing::warn!(%err, "failed to load results");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        if let Some(points) = points {
+            let gpa = results.as_ref().and_then(|results| {
+                stats::gpa(results.iter().filter(|r| r.pilot == track.pilot_name))
+            });
+            notification = notification.field(
+                "Points",
+                match gpa {
+                    Some(gpa) => format!("{:.1} (GPA {:.2})", points, gpa),
+                    None => format!("{:.1}", points),
                 },
                 true,
             );
+        }
+
+        if let Some((cq, results)) = params.config.cq.zip(results.as_deref()) {
+            let status = CqStatus::new(cq, &track.pilot_name, results, OffsetDateTime::now_utc());
+            notification = notification.field("CQ", status.to_string(), false);
+        }
+
+        if let Some(touchdown) = &track.touchdown {
+            let mut value = touchdown.to_string();
+            if let Some(touchdown_g) = track.touchdown_g {
+                value.push_str(&format!(", {:.1}G", touchdown_g));
+                if track.is_overstress() {
+                    value.push_str(" (potential overstress)");
+                }
+            }
+            notification = notification.field("Touchdown", value, true);
+        }
+
+        if let Some(deck_motion) = track.deck_motion.filter(|m| !m.is_steady()) {
+            notification = notification.field("Deck", deck_motion.to_string(), true);
+        }
+
+        if let Some(weight) = &track.weight {
+            let mut value = weight.to_string();
+            if track.is_overweight_trap() {
+                value.push_str(&format!(
+                    ", over the max. trap weight of {:.0} lb",
+                    track.plane_info.max_trap_weight
+                ));
+            }
+            notification = notification.field("Weight", value, true);
+        }
+
+        if let Some((cable_estimated, cable)) = track.cable_mismatch() {
+            notification = notification.field(
+                "Wire mismatch",
+                format!("estimated #{}, DCS reported #{}", cable_estimated, cable),
+                false,
+            );
+        }
 
-        webhook
-            .execute(
-                &http,
+        if tracks.len() > 1 {
+            notification = notification.field(
+                "Passes",
+                tracks
+                    .iter()
+                    .enumerate()
+                    .map(|(i, t)| format!("{}. {}", i + 1, t.grading.summary(t.hook_up)))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
                 false,
-                ExecuteWebhook::new()
-                    .embeds(vec![embed])
-                    .add_file(CreateAttachment::path(&chart_path).await?)
-                    .add_file(CreateAttachment::path(&acmi_path).await?),
-            )
-            .await?;
+            );
+        }
+
+        if tracks.iter().any(|t| t.interrupted) {
+            notification = notification.field("Note", "Recording got interrupted", false);
+        }
+        if tracks.iter().any(|t| t.carrier_turning) {
+            notification = notification.field("Note", "Carrier turned during the pass", false);
+        }
+        if let Some(unit_name) = tracks.iter().find_map(|t| t.foul_deck.as_deref()) {
+            notification = notification.field("Note", format!("Foul deck ({})", unit_name), false);
+        }
+
+        notification.files.extend(charts);
+        notification.files.extend(debriefs);
+        notification.files.extend(acmi_path.map(Attachment::from));
+        if params.notifiers.notify(&notification).await {
+            params.event_log.log(
+                params.server_name.as_deref(),
+                LifecycleEvent::WebhookPosted { post: "pass" },
+            );
+        }
     }
 
     Ok(())
 }
 
+/// The id of the unit that caused a crash, dead, ejection or pilot dead event.
+fn incident_unit_id(event: &Event) -> Option<u32> {
+    let initiator = match event {
+        Event::Crash(CrashEvent { initiator })
+        | Event::Dead(DeadEvent { initiator })
+        | Event::Ejection(EjectionEvent { initiator, .. })
+        | Event::PilotDead(PilotDeadEvent { initiator }) => initiator.as_ref()?,
+        _ => return None,
+    };
+    match &initiator.initiator {
+        Some(initiator::Initiator::Unit(unit)) => Some(unit.id),
+        _ => None,
+    }
+}
+
+/// The names and positions of all static objects in the mission. Failures are only logged, as the
+/// statics are only used to detect a fouled deck.
+async fn get_statics(client: &mut CoalitionClient) -> Vec<(String, DVec3)> {
+    let mut statics = Vec::new();
+    for coalition in [Coalition::Neutral, Coalition::Red, Coalition::Blue] {
+        match client.get_static_objects(coalition).await {
+            Ok(objects) => statics.extend(objects.into_iter().filter_map(|object| {
+                let position = object.position?;
+                Some((
+                    object.name,
+                    DVec3::new(position.u, position.alt, position.v),
+                ))
+            })),
+            Err(status) => tracing::warn!(%status, "failed to get static objects"),
+        }
+    }
+    statics
+}
+
 async fn create_initial_update(
     client: &mut UnitClient,
     id: u64,
@@ -410,12 +1020,124 @@ async fn create_initial_update(
         Property::Color(color(coalition)),
     ];
     if let Some(player_name) = &unit.player_name {
-        props.push(Property::Pilot(player_name.to_string()))
+        props.push(Property::Pilot(crate::privacy::pilot_name(player_name)))
     }
 
     Ok(Update { id, props })
 }
 
+/// The wind at the plane's position, to calculate its AoA relative to the air.
+struct Wind {
+    /// `None` if the AoA isn't corrected for the wind.
+    client: Option<AtmosphereClient>,
+    velocity: DVec3,
+    updated: Option<Instant>,
+}
+
+impl Wind {
+    fn new(client: Option<AtmosphereClient>) -> Self {
+        Self {
+            client,
+            velocity: DVec3::zero(),
+            updated: None,
+        }
+    }
+
+    /// Update the wind at the position of the `plane` if it is older than [WIND_INTERVAL].
+    async fn update(&mut self, plane: &Transform) {
+        let Some(client) = &mut self.client else {
+            return;
+        };
+        if self
+            .updated
+            .is_some_and(|updated| updated.elapsed() < WIND_INTERVAL)
+        {
+            return;
+        }
+        match client.get_wind(plane.lat, plane.lon, plane.alt).await {
+            Ok(velocity) => {
+                self.velocity = velocity;
+                self.updated = Some(Instant::now());
+            }
+            Err(status) => {
+                tracing::warn!(%status, "failed to get the wind, not correcting the AoA for it");
+                self.client = None;
+            }
+        }
+    }
+}
+
+/// A recording that is still being written, which is deleted when dropped (e.g. when the recording
+/// fails or is discarded) unless it got moved to its final location.
+struct PartialFile {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl PartialFile {
+    fn new(path: PathBuf) -> Self {
+        PartialFile {
+            path,
+            persisted: false,
+        }
+    }
+
+    /// Move the file to its final location.
+    async fn persist(mut self, path: &Path) -> Result<(), std::io::Error> {
+        tokio::fs::rename(&self.path, path).await?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            if let Err(err) = std::fs::remove_file(&self.path) {
+                tracing::warn!(%err, path = %self.path.display(), "failed to remove partial recording");
+            }
+        }
+    }
+}
+
+/// Remove the partial recordings in `out_dir` that are left over from a crash (the ones that
+/// weren't written to for a while, to not touch the active recordings of another LSO sharing the
+/// directory).
+pub async fn remove_stale_recordings(out_dir: &Path) -> Result<(), std::io::Error> {
+    let mut entries = match tokio::fs::read_dir(out_dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if !path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(PARTIAL_EXTENSION))
+        {
+            continue;
+        }
+        let stale = entry
+            .metadata()
+            .await?
+            .modified()?
+            .elapsed()
+            .is_ok_and(|elapsed| elapsed >= PARTIAL_STALE_AFTER);
+        if stale {
+            tracing::info!(path = %path.display(), "removing partial recording left over from a crash");
+            tokio::fs::remove_file(&path).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether the status indicates that the connection to DCS-gRPC got lost. The recording is then
+/// saved up to that point (and marked as interrupted) instead of being resumed after a reconnect.
+fn is_connection_lost(status: &Status) -> bool {
+    matches!(status.code(), Code::Unavailable | Code::Cancelled)
+}
+
 fn tags<I: AsRef<str>>(attrs: impl IntoIterator<Item = I>) -> HashSet<Tag> {
     let mut tags = HashSet::with_capacity(2);
     for attr in attrs.into_iter() {
@@ -439,6 +1161,141 @@ fn tags<I: AsRef<str>>(attrs: impl IntoIterator<Item = I>) -> HashSet<Tag> {
     tags
 }
 
+/// The other aircraft recorded along with the carrier and the plane.
+struct RecordedTraffic {
+    objects: HashMap<u32, RecordedUnit>,
+    /// The last object ID used in the recording.
+    last_id: u64,
+    /// The object IDs of removed traffic, which are reused so that the recording stays within
+    /// [IDS_PER_RECORDING] objects.
+    free_ids: Vec<u64>,
+}
+
+struct RecordedUnit {
+    /// The object ID in the recording.
+    id: u64,
+    /// The time of the last recorded position.
+    time: f64,
+    known_coords: Option<Coords>,
+}
+
+impl RecordedTraffic {
+    fn new() -> Self {
+        Self {
+            objects: HashMap::new(),
+            // `1` and `2` are the carrier and the plane
+            last_id: 2,
+            free_ids: Vec::new(),
+        }
+    }
+
+    /// The object ID for the next traffic unit, if there are any left.
+    fn next_id(&mut self) -> Option<u64> {
+        self.free_ids.pop().or_else(|| {
+            (self.last_id + 1 < IDS_PER_RECORDING).then(|| {
+                self.last_id += 1;
+                self.last_id
+            })
+        })
+    }
+
+    /// Add the traffic within `range_nm` of the carrier to the recording (at its current time),
+    /// and remove the traffic that left that range.
+    fn update<W: std::io::Write>(
+        &mut self,
+        recording: &mut Recording<W>,
+        traffic: &HashMap<u32, TrafficUnit>,
+        plane_id: u32,
+        carrier: &Transform,
+        (lat_ref, lon_ref): (f64, f64),
+        range_nm: f64,
+    ) -> Result<(), std::io::Error> {
+        for (unit_id, unit) in traffic {
+            if *unit_id == plane_id {
+                continue;
+            }
+            let transform = &unit.transform;
+            let in_range = m_to_nm((transform.position - carrier.position).mag()) <= range_nm;
+            let coords = Coords::default()
+                .position(
+                    transform.lat - lat_ref,
+                    transform.lon - lon_ref,
+                    transform.alt,
+                )
+                .uv(transform.position.x, transform.position.z)
+                .orientation(transform.yaw, transform.pitch, transform.roll)
+                .heading(transform.heading);
+
+            match self.objects.get_mut(unit_id) {
+                Some(object) if !in_range => {
+                    recording.write(Record::Remove(object.id))?;
+                    self.free_ids.push(object.id);
+                    self.objects.remove(unit_id);
+                }
+                Some(object) if transform.time > object.time => {
+                    object.time = transform.time;
+                    recording.write(Update {
+                        id: object.id,
+                        props: vec![Property::T(remove_unchanged(
+                            coords,
+                            &mut object.known_coords,
+                        ))],
+                    })?;
+                }
+                Some(_) => {}
+                None if in_range => {
+                    // busy decks are only recorded up to the available IDs
+                    let Some(id) = self.next_id() else {
+                        continue;
+                    };
+                    let mut object = RecordedUnit {
+                        id,
+                        time: transform.time,
+                        known_coords: None,
+                    };
+                    let mut tags = HashSet::from([Tag::Air]);
+                    tags.insert(if unit.is_helicopter {
+                        Tag::Rotorcraft
+                    } else {
+                        Tag::FixedWing
+                    });
+                    let mut props = vec![
+                        Property::Type(tags),
+                        Property::Name(unit.r#type.clone()),
+                        Property::Color(color(unit.coalition)),
+                        Property::T(remove_unchanged(coords, &mut object.known_coords)),
+                    ];
+                    if let Some(pilot_name) = &unit.pilot_name {
+                        props.push(Property::Pilot(pilot_name.clone()));
+                    }
+                    recording.write(Update {
+                        id: object.id,
+                        props,
+                    })?;
+                    self.objects.insert(*unit_id, object);
+                }
+                None => {}
+            }
+        }
+
+        // remove the units that are gone (e.g. landed and despawned)
+        let gone = self
+            .objects
+            .keys()
+            .filter(|unit_id| !traffic.contains_key(unit_id))
+            .copied()
+            .collect::<Vec<_>>();
+        for unit_id in gone {
+            if let Some(object) = self.objects.remove(&unit_id) {
+                recording.write(Record::Remove(object.id))?;
+                self.free_ids.push(object.id);
+            }
+        }
+
+        Ok(())
+    }
+}
+
 fn color(coalition: Coalition) -> Color {
     match coalition {
         Coalition::All | Coalition::Neutral => Color::Grey,
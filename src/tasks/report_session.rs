@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use stubs::mission::v0::stream_events_response::Event;
+use time::OffsetDateTime;
+
+use crate::client::MissionClient;
+use crate::event_log::LifecycleEvent;
+use crate::notify::Notification;
+use crate::store;
+
+use super::DetectionParams;
+use crate::filename::{DATETIME_FORMAT, FILENAME_DATETIME_FORMAT, SESSION_REPORT_PREFIX};
+
+/// How long to wait for active recordings to be saved after the mission ended.
+const RECORDINGS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Write a summary of all passes of a mission once it ends, and post it to the chat services if
+/// enabled.
+#[tracing::instrument(skip_all)]
+pub async fn report_sessions(params: DetectionParams) -> Result<(), crate::error::Error> {
+    let mut mission = MissionClient::new(params.ch.clone());
+    let mut events = params.shutdown.wrap_stream(mission.stream_events().await?);
+
+    // the session starts with the mission, or when connecting to an already running mission
+    let mut session_start = OffsetDateTime::now_utc();
+    while let Some(event) = events.next().await {
+        match event?.1 {
+            Event::MissionStart(_) => {
+                session_start = OffsetDateTime::now_utc();
+            }
+            Event::MissionEnd(_) => {
+                tracing::debug!("mission ended, writing session report");
+                // wait for the recordings that got interrupted by the mission end to be saved
+                let _ =
+                    tokio::time::timeout(RECORDINGS_TIMEOUT, params.supervisor.recordings_done())
+                        .await;
+                if let Err(err) = report_session(&params, session_start).await {
+                    tracing::warn!(%err, "failed to write session report");
+                }
+                session_start = OffsetDateTime::now_utc();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+async fn report_session(
+    params: &DetectionParams,
+    since: OffsetDateTime,
+) -> Result<(), crate::error::Error> {
+    let results_path = params.out_dir.join(store::RESULTS_FILENAME);
+    if !results_path.exists() {
+        return Ok(());
+    }
+    let records = store::load(&results_path)?
+        .into_iter()
+        .filter(|r| r.server == params.server_name)
+        .filter(|r| r.time().is_some_and(|time| time >= since))
+        .collect::<Vec<_>>();
+    if records.is_empty() {
+        tracing::debug!("no passes during the mission, skipping session report");
+        return Ok(());
+    }
+
+    let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+    let server_part = params
+        .server_name
+        .as_deref()
+        .map(|server_name| format!("-{}", crate::filename::sanitize(server_name)))
+        .unwrap_or_default();
+    let path = params.out_dir.join(format!(
+        "{}{}{}.html",
+        SESSION_REPORT_PREFIX,
+        now.format(FILENAME_DATETIME_FORMAT).unwrap_or_default(),
+        server_part,
+    ));
+    let title = format!(
+        "Carrier recoveries{} ({})",
+        params
+            .server_name
+            .as_deref()
+            .map(|server_name| format!(" on {}", server_name))
+            .unwrap_or_default(),
+        now.format(DATETIME_FORMAT).unwrap_or_default(),
+    );
+    crate::report::write_html(
+        &path,
+        &title,
+        &records,
+        time::Duration::seconds_f64(params.config.recovery.max_gap_s),
+    )?;
+    tracing::info!(passes = records.len(), path = %path.display(), "saved session report");
+
+    if params.discord_posts.session()
+        && params
+            .notifiers
+            .notify(&Notification {
+                title: Some(title),
+                files: vec![path.into()],
+                ..Default::default()
+            })
+            .await
+    {
+        params.event_log.log(
+            params.server_name.as_deref(),
+            LifecycleEvent::WebhookPosted {
+                post: "session_report",
+            },
+        );
+    }
+
+    Ok(())
+}
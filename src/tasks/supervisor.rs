@@ -0,0 +1,164 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Semaphore;
+use tokio::task::{JoinError, JoinHandle};
+use tonic::Code;
+use tracing::Instrument;
+
+use crate::error::Error;
+use crate::notify::AdminAlerts;
+
+/// How often a task is restarted in a row before its error is considered permanent.
+const MAX_RESTARTS: u32 = 5;
+
+/// Spawns and supervises the tasks of a gRPC connection. It caps the number of concurrent
+/// recordings, restarts tasks on transient errors and reports panics.
+#[derive(Clone)]
+pub struct Supervisor {
+    max_recordings: usize,
+    recordings: Arc<Semaphore>,
+    /// Where failed recordings and panics are reported to.
+    admin: AdminAlerts,
+}
+
+impl Supervisor {
+    pub fn new(max_recordings: usize, admin: AdminAlerts) -> Self {
+        Self {
+            max_recordings,
+            recordings: Arc::new(Semaphore::new(max_recordings)),
+            admin,
+        }
+    }
+
+    /// Wait until all active recordings are done.
+    pub async fn recordings_done(&self) {
+        let _ = self
+            .recordings
+            .acquire_many(self.max_recordings as u32)
+            .await;
+    }
+
+    /// The number of recordings that are currently active.
+    pub fn active_recordings(&self) -> usize {
+        self.max_recordings - self.recordings.available_permits()
+    }
+
+    /// Whether there is capacity left to start another recording.
+    pub fn can_record(&self) -> bool {
+        self.recordings.available_permits() > 0
+    }
+
+    /// Spawn a recording task, unless the maximum of concurrent recordings is already reached, in
+    /// which case `false` is returned. Errors of the recording are only reported, as a failed
+    /// recording should not affect any other task.
+    pub fn spawn_recording(
+        &self,
+        recording: impl Future<Output = Result<(), Error>> + Send + 'static,
+    ) -> bool {
+        let Ok(permit) = self.recordings.clone().try_acquire_owned() else {
+            tracing::warn!(
+                "not starting recording as the maximum of concurrent recordings is reached"
+            );
+            return false;
+        };
+
+        let task = tokio::spawn(recording.in_current_span());
+        let admin = self.admin.clone();
+        tokio::spawn(
+            async move {
+                let result = task.await;
+                drop(permit);
+
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(err)) => {
+                        tracing::error!(%err, "recording failed");
+                        admin.alert(format!("Recording failed: {}", crate::error::report(&err)));
+                    }
+                    Err(err) => report_join_error(&admin, "recording", err),
+                }
+            }
+            .in_current_span(),
+        );
+
+        true
+    }
+
+    /// Run the task created by `new_task` and restart it if it fails with a transient error or
+    /// panics. Errors are returned if they are permanent (e.g. the connection got lost), or if the
+    /// task keeps failing right after it got restarted.
+    pub async fn supervise<F, Fut>(&self, name: &'static str, mut new_task: F) -> Result<(), Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        let mut restarts = 0;
+        loop {
+            let started = Instant::now();
+            match AbortOnDrop(tokio::spawn(new_task().in_current_span())).await {
+                Ok(Ok(())) => return Ok(()),
+                Ok(Err(err)) if !is_transient(&err) => return Err(err),
+                Ok(Err(err)) if restarts >= MAX_RESTARTS => return Err(err),
+                Ok(Err(err)) => tracing::warn!(task = name, %err, "restarting task after error"),
+                Err(err) if err.is_cancelled() => return Ok(()),
+                Err(err) => {
+                    report_join_error(&self.admin, name, err);
+                    if restarts >= MAX_RESTARTS {
+                        return Err(Error::Panic(name));
+                    }
+                }
+            }
+
+            // only consider the task to fail repeatedly if it fails right after it got restarted
+            if started.elapsed() > Duration::from_secs(60) {
+                restarts = 0;
+            }
+            restarts += 1;
+            tokio::time::sleep(Duration::from_secs(restarts.into())).await;
+        }
+    }
+}
+
+/// Aborts the task when dropped, so that the tasks of a connection don't outlive it (e.g. when a
+/// reconnect got requested).
+struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> Future for AbortOnDrop<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Whether the error is worth restarting an individual task for, as opposed to errors that indicate
+/// that the whole connection is gone and must be re-established.
+fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::Transport(_) => false,
+        Error::Grpc(status) => !matches!(
+            status.code(),
+            Code::Unavailable | Code::Unauthenticated | Code::Unimplemented
+        ),
+        _ => true,
+    }
+}
+
+fn report_join_error(admin: &AdminAlerts, task: &str, err: JoinError) {
+    if err.is_panic() {
+        tracing::error!(task, "task panicked");
+        admin.alert(format!("The {} task panicked", task));
+    } else {
+        tracing::debug!(task, "task got cancelled");
+    }
+}
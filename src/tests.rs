@@ -5,12 +5,15 @@ macro_rules! test_recording {
         fn $name() {
             use std::io::Cursor;
 
-            use crate::commands::file::extract_recoveries;
+            use crate::extract::extract_recoveries;
             use crate::track::{Grading, TrackResult};
 
             let acmi = include_bytes!($path);
             let recoveries = extract_recoveries(&mut Cursor::new(acmi)).unwrap();
             let [recovery]: [TrackResult; 1] = recoveries.try_into().unwrap();
+            assert!(recovery.datums.windows(2).all(|d| d[0].time < d[1].time));
+            // the recordings were all flown in the morning
+            assert!(recovery.sun_elevation.unwrap() > 20.0);
             assert_eq!(
                 recovery.grading,
                 Grading::Recovered {
@@ -55,3 +58,1003 @@ test_recording!(
     4,
     4
 );
+
+#[test]
+#[tracing_test::traced_test]
+fn raw_tacview_recording() {
+    use std::io::Cursor;
+
+    use tacview::record::{Event, EventKind, GlobalProperty, Property, Record};
+
+    use crate::extract::extract_recoveries;
+    use crate::track::Grading;
+
+    // turn a recording of the LSO into one like Tacview records it: without land events and AoA
+    let acmi = include_bytes!("../tests/recordings/wire_3_01_T45.zip.acmi");
+    let mut raw = tacview::Writer::new(Vec::new()).unwrap();
+    for record in crate::acmi::parse(Cursor::new(acmi)).unwrap() {
+        match record.unwrap() {
+            Record::GlobalProperty(GlobalProperty::Author(_))
+            | Record::Event(Event {
+                kind: EventKind::Landed,
+                ..
+            }) => {}
+            Record::Update(mut update) => {
+                update.props.retain(|p| !matches!(p, Property::AOA(_)));
+                raw.write(update).unwrap();
+            }
+            record => raw.write(record).unwrap(),
+        }
+    }
+
+    let [expected] = <[_; 1]>::try_from(extract_recoveries(&mut Cursor::new(acmi)).unwrap())
+        .ok()
+        .unwrap();
+    let [recovery] =
+        <[_; 1]>::try_from(extract_recoveries(&mut Cursor::new(raw.into_inner())).unwrap())
+            .ok()
+            .unwrap();
+    assert_eq!(
+        recovery.grading,
+        Grading::Recovered {
+            cable: Some(3),
+            cable_estimated: Some(3)
+        }
+    );
+    // the AoA derived from the velocity is close to the one reported by DCS
+    let aoa = |datums: &[crate::track::Datum]| {
+        datums.iter().map(|d| d.aoa).sum::<f64>() / datums.len() as f64
+    };
+    assert!((aoa(&recovery.datums) - aoa(&expected.datums)).abs() < 1.0);
+    // ... and not only on average
+    assert!(recovery
+        .datums
+        .iter()
+        .zip(&expected.datums)
+        .all(|(derived, reported)| (derived.aoa - reported.aoa).abs() < 1.5));
+}
+
+#[test]
+fn incident_on_hex_object_id() {
+    use std::io::Cursor;
+
+    use tacview::record::{Event, EventKind, Property, Record, Tag};
+
+    use crate::extract::extract_recoveries;
+    use crate::track::{Grading, Incident};
+
+    // offset all object IDs so that they contain hex digits, which must not be parsed as decimal
+    const OFFSET: u64 = 0xa0;
+    let acmi = include_bytes!("../tests/recordings/wire_1_01_FA18C.zip.acmi");
+    let records = crate::acmi::parse(Cursor::new(acmi))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let frames = records
+        .iter()
+        .filter(|record| matches!(record, Record::Frame(_)))
+        .count();
+
+    let mut raw = tacview::Writer::new(Vec::new()).unwrap();
+    let mut plane_id = None;
+    let mut frame = 0;
+    for record in records {
+        match record {
+            Record::Update(mut update) => {
+                update.id += OFFSET;
+                if update
+                    .props
+                    .iter()
+                    .any(|p| matches!(p, Property::Type(tags) if tags.contains(&Tag::FixedWing)))
+                {
+                    plane_id.get_or_insert(update.id);
+                }
+                raw.write(update).unwrap();
+            }
+            Record::Event(mut event) => {
+                for param in &mut event.params {
+                    if let Ok(id) = u64::from_str_radix(param, 16) {
+                        *param = format!("{:x}", id + OFFSET);
+                    }
+                }
+                raw.write(Record::Event(event)).unwrap();
+            }
+            Record::Frame(time) => {
+                raw.write(Record::Frame(time)).unwrap();
+                // the pilot ejects halfway through the approach
+                frame += 1;
+                if frame == frames / 2 {
+                    raw.write(Record::Event(Event {
+                        kind: EventKind::Destroyed,
+                        params: vec![format!("{:x}", plane_id.unwrap())],
+                        text: Some("Ejected".to_string()),
+                    }))
+                    .unwrap();
+                }
+            }
+            record => raw.write(record).unwrap(),
+        }
+    }
+    assert!(plane_id.unwrap() >= 0xa);
+
+    let [recovery] =
+        <[_; 1]>::try_from(extract_recoveries(&mut Cursor::new(raw.into_inner())).unwrap())
+            .ok()
+            .unwrap();
+    assert_eq!(recovery.grading, Grading::Incident(Incident::Ejection));
+}
+
+#[test]
+fn parse_dcs_grading() {
+    use crate::dcs_grading::{DcsGrading, LsoGrade};
+
+    let grading = DcsGrading::parse("LSO: GRADE:--- : _TMRDAR_  (EGTL)  WIRE# 1");
+    assert_eq!(grading.grade, LsoGrade::NoGrade);
+    assert_eq!(grading.deviations, vec!["_TMRDAR_", "(EGTL)"]);
+    assert_eq!(grading.wire, Some(1));
+
+    let grading = DcsGrading::parse("LSO: GRADE:OK  :  WIRE# 3");
+    assert_eq!(grading.grade, LsoGrade::Ok);
+    assert!(grading.deviations.is_empty());
+    assert_eq!(grading.wire, Some(3));
+
+    let grading = DcsGrading::parse("LSO: GRADE:WO  AFU TL");
+    assert_eq!(grading.grade, LsoGrade::WaveOff);
+    assert_eq!(grading.deviations, vec!["AFU", "TL"]);
+    assert_eq!(grading.wire, None);
+}
+
+#[test]
+fn lso_grade_points() {
+    use crate::dcs_grading::LsoGrade;
+
+    assert_eq!(LsoGrade::Ok.points(), Some(4.0));
+    assert_eq!(LsoGrade::Bolter.points(), Some(2.5));
+    assert_eq!(LsoGrade::Cut.points(), Some(0.0));
+    assert_eq!(LsoGrade::PatternWaveOff.points(), None);
+}
+
+#[test]
+fn expired_outputs() {
+    use std::time::{Duration, SystemTime};
+
+    use crate::config::RetentionConfig;
+    use crate::retention::{expired, OutputFile};
+
+    let now = SystemTime::now();
+    let day = Duration::from_secs(24 * 60 * 60);
+    let file = |name: &str, age_days: u32, size: u64| OutputFile {
+        path: name.into(),
+        modified: now - day * age_days,
+        size,
+    };
+    let files = vec![
+        file("new.png", 0, 400_000),
+        file("old.zip.acmi", 10, 300_000),
+        file("older.png", 20, 300_000),
+    ];
+    let names = |config: RetentionConfig| {
+        expired(files.clone(), &config, now)
+            .into_iter()
+            .map(|f| f.path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names(RetentionConfig {
+            max_age_days: Some(15.0),
+            max_size_mb: None
+        }),
+        vec!["older.png"]
+    );
+    assert_eq!(
+        names(RetentionConfig {
+            max_age_days: None,
+            max_size_mb: Some(0.5)
+        }),
+        vec!["older.png", "old.zip.acmi"]
+    );
+    assert!(names(RetentionConfig::default()).is_empty());
+}
+
+#[test]
+fn render_filename() {
+    use time::OffsetDateTime;
+
+    use crate::filename::{render, sanitize, FilenameParts, DEFAULT_TEMPLATE};
+
+    let parts = FilenameParts {
+        datetime: Some(OffsetDateTime::from_unix_timestamp(1636641447).unwrap()),
+        pilot: Some("Pilot Name"),
+        mission: Some("CQ Night"),
+        ..Default::default()
+    };
+    assert_eq!(
+        render(DEFAULT_TEMPLATE, &parts),
+        "LSO-20211111-143727-PilotName"
+    );
+    assert_eq!(
+        render("{server}_{mission}/{pilot}-{wire}", &parts),
+        "CQNightPilotName"
+    );
+    assert_eq!(render("{pilot}-{unknown}", &parts), "PilotName-{unknown}");
+    let ki = FilenameParts {
+        pilot: Some("KI Recovery Tanker"),
+        callsign: Some("Texaco11"),
+        ..parts
+    };
+    assert_eq!(
+        render(DEFAULT_TEMPLATE, &ki),
+        "LSO-20211111-143727-KIRecoveryTanker-Texaco11"
+    );
+
+    assert_eq!(sanitize("Пилот 1"), "Пилот1");
+    assert_ne!(sanitize("|||"), sanitize("///"));
+    assert!(sanitize("|||").starts_with('x'));
+}
+
+#[test]
+fn sun_elevation() {
+    use time::format_description::well_known::Rfc3339;
+    use time::OffsetDateTime;
+
+    use crate::utils::sun::{elevation, mission_time_to_utc};
+
+    let time = |time: &str| OffsetDateTime::parse(time, &Rfc3339).unwrap();
+    // the solstice at the tropic of cancer around noon
+    let noon = elevation(time("2024-06-20T12:00:00Z"), 23.44, 0.0);
+    assert!((88.0..=90.0).contains(&noon), "{}", noon);
+    // midnight in the Caucasus (UTC+3 by its longitude)
+    let midnight = mission_time_to_utc(time("2016-06-21T00:00:00Z"), 40.0);
+    assert!(elevation(midnight, 42.0, 40.0) < -20.0);
+}
+
+#[test]
+fn data_registry() {
+    use crate::data::{AirplaneInfo, DataRegistry};
+
+    assert!(AirplaneInfo::by_type("FA-18C_mod").is_none());
+    let hornet = AirplaneInfo::by_type("FA-18C_hornet").unwrap();
+    let registered = DataRegistry::register_airplane(
+        &["FA-18C_mod"],
+        AirplaneInfo {
+            hook_to_eye: 4.0,
+            ..hornet.clone()
+        },
+    );
+    assert_eq!(AirplaneInfo::by_type("FA-18C_mod"), Some(registered));
+    assert_eq!(registered.hook, hornet.hook);
+}
+
+#[test]
+fn config_problems() {
+    use crate::config::Config;
+
+    assert!(Config::default().problems().is_empty());
+
+    let config: Config = serde_json::from_str(
+        r#"{
+            "detection": { "max_turn_rate": 0 },
+            "rigged_wires": [{ "wires": [2, 5] }],
+            "output": { "filename": "{pilott}-{id}" },
+            "cq": { "min_boarding_rate": 80 },
+            "airplanes": {
+                "F-14B": { "hook": [0.0, -2.0, -6.6] },
+                "T-45": {
+                    "aoa_brackets": {
+                        "fast": 7.4, "slightly_fast": 7.4, "on_speed": 8.8, "slightly_slow": 9.3
+                    }
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.problems(),
+        vec![
+            "`detection.max_turn_rate` must be greater than 0 (is 0)",
+            "`cq.min_boarding_rate` must be between 0 and 1 (is 80)",
+            "`airplanes.T-45.aoa_brackets` must be ascending (fast < slightly_fast < on_speed < \
+             slightly_slow)",
+            "`rigged_wires[0].wires` contains wire 5, but only wires 1-4 exist",
+            "`output.filename` contains the unknown placeholder `{pilott}`",
+        ]
+    );
+
+    let config: Config = serde_json::from_str(
+        r#"{
+            "output": { "filename": "{datetime}-{pilot}" },
+            "retention": { "max_size_mb": 500 }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.problems(),
+        vec!["`output.filename` doesn't contain `{id}`, so `retention` never removes any outputs"]
+    );
+}
+
+#[cfg(feature = "grpc")]
+#[test]
+fn parse_dcs_grpc_version() {
+    use tonic::Status;
+
+    use crate::client::{check_version, parse_version};
+    use crate::error::Error;
+
+    assert_eq!(parse_version("0.8.1-beta"), Some((0, 8, 1)));
+    assert_eq!(parse_version("v1.2"), Some((1, 2, 0)));
+    assert_eq!(parse_version("unknown"), None);
+
+    assert_eq!(check_version(Ok("0.8.1".to_string())).unwrap(), "0.8.1");
+    assert_eq!(check_version(Ok("1.0.0".to_string())).unwrap(), "1.0.0");
+    // unknown formats are accepted (and only logged)
+    assert_eq!(check_version(Ok("dev".to_string())).unwrap(), "dev");
+    assert!(matches!(
+        check_version(Ok("0.7.1".to_string())),
+        Err(Error::IncompatibleVersion(_))
+    ));
+    // versions before 0.8.1 don't implement `GetVersion` at all
+    assert!(matches!(
+        check_version(Err(Status::unimplemented("GetVersion"))),
+        Err(Error::IncompatibleVersion(_))
+    ));
+    assert!(matches!(
+        check_version(Err(Status::unavailable("connection refused"))),
+        Err(Error::Grpc(_))
+    ));
+}
+
+#[test]
+fn synthetic_passes() {
+    use crate::track::{Grading, Segment, Trend};
+
+    use crate::synthetic::Pass;
+
+    let wire = |track: crate::track::TrackResult| match track.grading {
+        Grading::Recovered { cable, .. } => cable,
+        grading => panic!("expected a trap, got {:?}", grading),
+    };
+    let track = Pass::ideal().track();
+    assert_eq!(track.start().unwrap().to_string(), "good start");
+    let ideal_ramp_clearance = track.ramp_clearance.unwrap();
+    assert!((12.0..15.0).contains(&ideal_ramp_clearance));
+    assert_eq!(wire(track), Some(3));
+    let track = Pass::settle_at_ramp().track();
+    assert!(track.ramp_clearance.unwrap() < ideal_ramp_clearance);
+    assert!(track
+        .trends()
+        .contains(&(Segment::AtTheRamp, vec![Trend::GoingDown])));
+    assert_eq!(wire(track), Some(1));
+    assert_eq!(Pass::bolter().track().grading, Grading::Bolter);
+
+    let track = Pass::overshooting_start().track();
+    assert!(track.datums.first().unwrap().y.abs() > 50.0);
+    assert!(track.datums.last().unwrap().y.abs() < 2.0);
+    assert_eq!(track.start().unwrap().deviations(), ["overshooting"]);
+    assert_eq!(
+        track.trends()[0],
+        (Segment::Start, vec![Trend::DriftingLeft])
+    );
+    #[cfg(feature = "charts")]
+    {
+        let dir = std::env::temp_dir().join("lso-synthetic-passes");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path =
+            crate::draw::draw_chart(&dir, "overshoot", &track, &Default::default(), None).unwrap();
+        assert!(path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+#[test]
+fn smoothing() {
+    use crate::utils::smoothing::{ema, savitzky_golay};
+
+    let times = (0..50).map(|i| f64::from(i) * 0.1).collect::<Vec<_>>();
+    // a linear trend with alternating noise
+    let raw = times
+        .iter()
+        .enumerate()
+        .map(|(i, t)| 8.0 + t + if i % 2 == 0 { 0.5 } else { -0.5 })
+        .collect::<Vec<_>>();
+    let noise = |values: &[f64]| {
+        values
+            .windows(2)
+            .map(|w| (w[1] - w[0] - 0.1).abs())
+            .fold(0.0, f64::max)
+    };
+
+    let mut values = raw.clone();
+    ema(&times, &mut values, 0.3);
+    assert!(noise(&values) < 0.2);
+
+    let mut values = raw.clone();
+    savitzky_golay(&times, &mut values, 4);
+    // (the fit is less smooth at the ends, where the window is cut off)
+    assert!(noise(&values[4..46]) < 0.2);
+    // the trend is kept
+    assert!((values[25] - (8.0 + times[25])).abs() < 0.2);
+}
+
+#[test]
+fn resampling() {
+    use crate::config::Resampling;
+    use crate::synthetic::Pass;
+    use crate::track::Track;
+
+    let pass = Pass::ideal();
+    let (frames, touchdown) = pass.frames();
+    let mut track = Track::new("Synthetic", pass.carrier, pass.plane);
+    track.set_resampling(Some(Resampling::Distance { interval_m: 50.0 }));
+    for (carrier, plane) in &frames[..touchdown] {
+        track.next(carrier, plane);
+    }
+    let result = track.finish();
+
+    assert_eq!(result.datums.first(), result.raw_datums.first());
+    assert_eq!(result.datums.last(), result.raw_datums.last());
+    for pair in result.datums[..result.datums.len() - 1].windows(2) {
+        assert!((pair[0].x - pair[1].x - 50.0).abs() < 1e-6);
+        assert!(pair[0].time < pair[1].time);
+    }
+}
+
+#[test]
+fn debrief() {
+    use std::path::Path;
+
+    use time::OffsetDateTime;
+
+    use crate::debrief::{relative_link, write_html};
+    use crate::store::PassRecord;
+    use crate::synthetic::Pass;
+
+    assert_eq!(
+        relative_link(Path::new("out/a/b"), Path::new("out/a/c/pass.zip.acmi")),
+        "../c/pass.zip.acmi"
+    );
+    assert_eq!(
+        relative_link(Path::new("out"), Path::new("out/pass.zip.acmi")),
+        "pass.zip.acmi"
+    );
+
+    let track = Pass::ideal().track();
+    let record = PassRecord::new(
+        OffsetDateTime::UNIX_EPOCH,
+        None,
+        "CVN-71",
+        "FA-18C_hornet",
+        "CVN_71",
+        &track,
+    );
+    let path = std::env::temp_dir().join("lso-debrief.html");
+    write_html(&path, &track, &record, Some("pass.zip.acmi")).unwrap();
+    let html = std::fs::read_to_string(&path).unwrap();
+    assert!(html.contains("href=\"pass.zip.acmi\""));
+    assert!(html.contains("\"datums\":[["));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn anonymize_pilot_names() {
+    use crate::config::PrivacyConfig;
+    use crate::privacy::{Anonymizer, PilotNames};
+
+    let mapping_file = std::env::temp_dir().join("lso-pilot-names.json");
+    let _ = std::fs::remove_file(&mapping_file);
+    let config = PrivacyConfig {
+        pilot_names: PilotNames::Alias,
+        mapping_file: mapping_file.clone(),
+        ..Default::default()
+    };
+    let mut anonymizer = Anonymizer::load(&config).unwrap();
+    assert_eq!(
+        anonymizer.pilot_name("Maverick"),
+        ("Pilot 1".to_string(), true)
+    );
+    assert_eq!(anonymizer.pilot_name("Goose").0, "Pilot 2");
+    assert_eq!(
+        anonymizer.pilot_name("Maverick"),
+        ("Pilot 1".to_string(), false)
+    );
+    // already anonymized in a recording
+    assert_eq!(anonymizer.recorded_pilot_name("Pilot 2").0, "Pilot 2");
+    // a player that happens to be called like an alias is a different pilot
+    assert_eq!(anonymizer.pilot_name("Pilot 2").0, "Pilot 3");
+    anonymizer.save().unwrap();
+
+    // the aliases are kept across restarts
+    let mut anonymizer = Anonymizer::load(&config).unwrap();
+    assert_eq!(anonymizer.pilot_name("Goose").0, "Pilot 2");
+    assert_eq!(anonymizer.pilot_name("Pilot 2").0, "Pilot 3");
+    assert_eq!(anonymizer.pilot_name("Iceman").0, "Pilot 4");
+    std::fs::remove_file(&mapping_file).unwrap();
+}
+
+#[test]
+fn merge_passes_by_ucid() {
+    use crate::store::{merge_by_ucid, PassRecord};
+    use crate::synthetic::Pass;
+
+    let track = Pass::ideal().track();
+    let record = |pilot: &str, ucid: Option<&str>| PassRecord {
+        pilot: pilot.to_string(),
+        ucid: ucid.map(str::to_string),
+        ..PassRecord::new(
+            time::OffsetDateTime::UNIX_EPOCH,
+            None,
+            "CVN-71",
+            "FA-18C_hornet",
+            "CVN_71",
+            &track,
+        )
+    };
+    let mut records = vec![
+        record("Mav", Some("a1")),
+        record("Goose", None),
+        record("Maverick", Some("a1")),
+        record("Mav", None),
+    ];
+    merge_by_ucid(&mut records);
+    let pilots = records.iter().map(|r| r.pilot.as_str()).collect::<Vec<_>>();
+    assert_eq!(pilots, ["Maverick", "Goose", "Maverick", "Mav"]);
+}
+
+#[cfg(feature = "notify")]
+#[test]
+fn link_discord_users() {
+    use crate::notify::DiscordUsers;
+
+    let path = std::env::temp_dir().join("lso-discord-users.json");
+    let _ = std::fs::remove_file(&path);
+    let users = DiscordUsers::load(Some(&path)).unwrap();
+    let code = users.issue_code(12345);
+    assert_eq!(users.link("WRONG1", "a1"), None);
+    assert_eq!(users.link(&code.to_lowercase(), "a1"), Some(12345));
+    // codes can only be used once
+    assert_eq!(users.link(&code, "b2"), None);
+    assert_eq!(users.get("a1"), Some(12345));
+
+    // the links are saved to the users file
+    let users = DiscordUsers::load(Some(&path)).unwrap();
+    assert_eq!(users.get("a1"), Some(12345));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn realtime_merge_properties() {
+    use tacview::record::{Coords, Property};
+
+    let mut props = vec![
+        Property::T(Coords::default().position(1.0, 2.0, 3.0)),
+        Property::Name("FA-18C_hornet".to_string()),
+    ];
+    crate::realtime::merge(
+        &mut props,
+        &Property::T(Coords {
+            altitude: Some(4.0),
+            ..Default::default()
+        }),
+    );
+    crate::realtime::merge(&mut props, &Property::AOA(8.1));
+    assert_eq!(
+        props,
+        vec![
+            Property::T(Coords::default().position(1.0, 2.0, 4.0)),
+            Property::Name("FA-18C_hornet".to_string()),
+            Property::AOA(8.1),
+        ]
+    );
+}
+
+#[test]
+fn realtime_stream() {
+    use std::io::Cursor;
+
+    use tacview::record::{Coords, Event, EventKind, GlobalProperty, Property, Record, Update};
+
+    use crate::realtime::RealtimeTelemetry;
+
+    let parse = |data: &[u8]| {
+        crate::acmi::parse(Cursor::new(data.to_vec()))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    };
+    let updates = |records: &[Record]| {
+        let mut ids = records
+            .iter()
+            .filter_map(|record| match record {
+                Record::Update(update) => Some(update.id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+
+    let telemetry = RealtimeTelemetry::new("test".to_string());
+    let mut rx = telemetry.subscribe();
+    // two recordings of different missions, with the same object IDs
+    let mut first = telemetry.recording();
+    let mut second = telemetry.recording();
+    first.write(&Record::GlobalProperty(GlobalProperty::ReferenceLongitude(
+        20.0,
+    )));
+    first.write(&Record::GlobalProperty(GlobalProperty::ReferenceLatitude(
+        10.0,
+    )));
+    first.write(&Record::Frame(3600.0));
+    first.write(&Record::Update(Update {
+        id: 2,
+        props: vec![Property::T(Coords::default().position(1.0, 2.0, 50.0))],
+    }));
+    second.write(&Record::Frame(5.0));
+    second.write(&Record::Update(Update {
+        id: 2,
+        props: vec![Property::T(Coords::default().position(1.0, 2.0, 50.0))],
+    }));
+    first.write(&Record::Frame(3601.0));
+    first.write(&Record::Event(Event {
+        kind: EventKind::Bookmark,
+        params: vec!["2".to_string(), "ff".to_string()],
+        text: Some("bolter".to_string()),
+    }));
+
+    // the IDs of each recording are offset, its coordinates made absolute
+    let snapshot = parse(&telemetry.snapshot().unwrap());
+    assert_eq!(updates(&snapshot), [0x102, 0x202]);
+    let position = snapshot.iter().find_map(|record| match record {
+        Record::Update(Update { id: 0x102, props }) => props.iter().find_map(|p| match p {
+            Property::T(coords) => Some((coords.latitude, coords.longitude)),
+            _ => None,
+        }),
+        _ => None,
+    });
+    assert_eq!(position, Some((Some(11.0), Some(22.0))));
+
+    // the clock of the stream never goes backwards and only object IDs are offset
+    let mut sent = tacview::Writer::new(Vec::new()).unwrap().into_inner();
+    while let Ok(data) = rx.try_recv() {
+        sent.extend_from_slice(&data);
+    }
+    let sent = parse(&sent);
+    let frames = sent
+        .iter()
+        .filter_map(|record| match record {
+            Record::Frame(time) => Some(*time),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(frames.windows(2).all(|t| t[0] <= t[1]), "{:?}", frames);
+    assert!(frames.iter().all(|time| *time < 60.0), "{:?}", frames);
+    let event = sent.iter().find_map(|record| match record {
+        Record::Event(event) => Some(event.params.clone()),
+        _ => None,
+    });
+    assert_eq!(event, Some(vec!["102".to_string(), "ff".to_string()]));
+
+    // the objects of a finished recording are removed
+    drop(first);
+    assert_eq!(updates(&parse(&telemetry.snapshot().unwrap())), [0x202]);
+}
+
+#[cfg(feature = "notify")]
+#[test]
+fn mqtt_packets() {
+    use crate::config::MqttConfig;
+    use crate::mqtt::{connect_packet, packet};
+
+    // the remaining length is encoded as variable byte integer
+    for (len, encoded) in [
+        (0, vec![0x00]),
+        (127, vec![0x7f]),
+        (128, vec![0x80, 0x01]),
+        (16_383, vec![0xff, 0x7f]),
+        (16_384, vec![0x80, 0x80, 0x01]),
+        (2_097_152, vec![0x80, 0x80, 0x80, 0x01]),
+    ] {
+        let packet = packet(0x30, &vec![0; len]).unwrap();
+        assert_eq!(packet[0], 0x30);
+        assert_eq!(packet[1..=encoded.len()], encoded, "length {len}");
+        assert_eq!(packet.len(), 1 + encoded.len() + len);
+    }
+    assert!(packet(0x30, &vec![0; 268_435_456]).is_err());
+
+    let config = MqttConfig {
+        host: "localhost".to_string(),
+        port: 1883,
+        client_id: "lso".to_string(),
+        username: Some("user".to_string()),
+        password: None,
+        topic: "lso/{server}/{event}".to_string(),
+    };
+    #[rustfmt::skip]
+    assert_eq!(
+        connect_packet(&config).unwrap(),
+        [
+            0x10, 21,
+            0, 4, b'M', b'Q', b'T', b'T',
+            4, // protocol level
+            0x82, // username and clean session
+            0, 60, // keep alive
+            0, 3, b'l', b's', b'o',
+            0, 4, b'u', b's', b'e', b'r',
+        ]
+    );
+
+    // a password requires a username
+    let without_username = MqttConfig {
+        username: None,
+        password: Some("secret".to_string()),
+        ..config.clone()
+    };
+    let packet = connect_packet(&without_username).unwrap();
+    assert_eq!(packet[9], 0x02);
+    assert_eq!(packet.len(), 2 + 10 + 5);
+    assert_eq!(
+        crate::config::Config {
+            mqtt: Some(without_username),
+            ..Default::default()
+        }
+        .problems(),
+        vec![
+            "`mqtt.password` is ignored without `mqtt.username` (MQTT doesn't allow a password \
+             without a username)"
+        ]
+    );
+
+    // strings are prefixed with their length, so they cannot be longer than 65535 bytes
+    let config = MqttConfig {
+        client_id: "x".repeat(65_536),
+        ..config
+    };
+    assert!(connect_packet(&config).is_err());
+}
+
+#[test]
+fn enlarged_detection_envelope() {
+    use ultraviolet::DVec3;
+
+    use crate::config::DetectionEnvelope;
+    use crate::track::{broad_phase_distance, is_recovery_attempt};
+    use crate::transform::Transform;
+    use crate::utils::nm_to_m;
+
+    // a plane 6nm behind the carrier at 4000ft, heading towards it
+    let alt = 1219.2;
+    let carrier = Transform {
+        forward: DVec3::unit_z(),
+        ..Default::default()
+    };
+    let plane = Transform {
+        forward: DVec3::new(0.0, -alt, nm_to_m(6.0)).normalized(),
+        position: DVec3::new(0.0, alt, -nm_to_m(6.0)),
+        alt,
+        ..Default::default()
+    };
+
+    let envelope = DetectionEnvelope::default();
+    assert_eq!(broad_phase_distance(&carrier, &plane, &envelope), None);
+    assert!(!is_recovery_attempt(&carrier, &plane, &envelope));
+
+    // both the broad phase and the detection itself follow the configured envelope
+    let envelope = DetectionEnvelope {
+        max_altitude_ft: 5000.0,
+        max_distance_nm: 8.0,
+        ..Default::default()
+    };
+    let distance = broad_phase_distance(&carrier, &plane, &envelope).unwrap();
+    assert!((distance - nm_to_m(6.0)).abs() < 1e-6);
+    assert!(is_recovery_attempt(&carrier, &plane, &envelope));
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn parse_durations() {
+    use time::{Duration, OffsetDateTime};
+
+    use crate::commands::{parse_duration, parse_time};
+
+    assert_eq!(parse_duration("90s"), Ok(Duration::seconds(90)));
+    assert_eq!(parse_duration("1.5h"), Ok(Duration::minutes(90)));
+    assert_eq!(parse_duration("2w"), Ok(Duration::days(14)));
+    assert!(parse_duration("5y").is_err());
+    assert!(parse_duration("").is_err());
+    // rejected instead of panicking or going forward in time
+    assert!(parse_duration("nand").is_err());
+    assert!(parse_duration("1e20d").is_err());
+    assert!(parse_duration("-1d").is_err());
+
+    assert_eq!(
+        parse_time("2024-05-01T18:00:00Z"),
+        Ok(time::macros::datetime!(2024-05-01 18:00 UTC))
+    );
+    assert!(parse_time("30d").unwrap() < OffsetDateTime::now_utc() - Duration::days(29));
+    assert!(parse_time("1e10d").is_err());
+    assert!(parse_time("nand").is_err());
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn rotate_log_file() {
+    use std::io::Write;
+
+    use crate::logging::RotatingFile;
+
+    let dir = std::env::temp_dir().join("lso-rotate-log-file");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("lso.log");
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).ok();
+
+    let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+    for line in ["line 1\n", "line 2\n", "line 3\n", "line 4\n"] {
+        file.write_all(line.as_bytes()).unwrap();
+    }
+    assert_eq!(read("lso.log").as_deref(), Some("line 4\n"));
+    assert_eq!(read("lso.log.1").as_deref(), Some("line 3\n"));
+    assert_eq!(read("lso.log.2").as_deref(), Some("line 2\n"));
+    // only `keep` rotated files are kept
+    assert_eq!(read("lso.log.3"), None);
+
+    // a reopened file continues where it left off
+    let mut file = RotatingFile::open(&path, 10, 0).unwrap();
+    file.write_all(b"5\n").unwrap();
+    assert_eq!(read("lso.log").as_deref(), Some("line 4\n5\n"));
+    // without any rotated files to keep, the file is truncated
+    file.write_all(b"line 6\n").unwrap();
+    assert_eq!(read("lso.log").as_deref(), Some("line 6\n"));
+    assert_eq!(read("lso.log.1").as_deref(), Some("line 3\n"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "grpc")]
+#[tokio::test]
+async fn status_endpoints() {
+    use std::time::{Duration, Instant};
+
+    use axum::body::{Body, HttpBody};
+    use axum::http::{Request, StatusCode};
+    use axum::Router;
+    use tower::ServiceExt;
+
+    use crate::notify::AdminAlerts;
+    use crate::status::Status;
+    use crate::tasks::supervisor::Supervisor;
+
+    async fn call(app: &Router, method: &str, uri: &str) -> (StatusCode, String) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let code = response.status();
+        let mut body = response.into_body();
+        let mut content = Vec::new();
+        while let Some(chunk) = body.data().await {
+            content.extend_from_slice(&chunk.unwrap());
+        }
+        (code, String::from_utf8(content).unwrap())
+    }
+    async fn report(app: &Router) -> serde_json::Value {
+        let (code, body) = call(app, "GET", "/status").await;
+        assert_eq!(code, StatusCode::OK);
+        serde_json::from_str(&body).unwrap()
+    }
+
+    let status = Status::default();
+    let server = status.server(Some("test"), Supervisor::new(1, AdminAlerts::default()));
+    let app = status.router(true);
+
+    let (code, body) = call(&app, "GET", "/healthz").await;
+    assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body, "test: not connected\n");
+
+    server.connected();
+    server.unit_update();
+    server.tracking(1, 2);
+    let (code, body) = call(&app, "GET", "/healthz").await;
+    assert_eq!((code, body.as_str()), (StatusCode::OK, "ok\n"));
+    let report_ok = report(&app).await;
+    assert_eq!(report_ok["healthy"], true);
+    assert_eq!(report_ok["servers"][0]["name"], "test");
+    assert_eq!(report_ok["servers"][0]["connected"], true);
+    assert_eq!(report_ok["servers"][0]["tracked_planes"], 2);
+
+    // connected, but no unit updates received for too long
+    server.set_last_update(Instant::now() - Duration::from_secs(301));
+    let (code, body) = call(&app, "GET", "/healthz").await;
+    assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(body.starts_with("test: no unit updates for 30"), "{}", body);
+    let report_stale = report(&app).await;
+    assert_eq!(report_stale["healthy"], false);
+    assert_eq!(report_stale["servers"][0]["connected"], true);
+    assert!(report_stale["servers"][0]["problem"].is_string());
+
+    // a reconnect requested before the connection task waits for it isn't lost
+    let (code, _) = call(&app, "POST", "/reconnect?server=test").await;
+    assert_eq!(code, StatusCode::ACCEPTED);
+    tokio::time::timeout(Duration::from_secs(1), server.reconnect_requested())
+        .await
+        .unwrap();
+    let (code, _) = call(&app, "POST", "/reconnect?server=other").await;
+    assert_eq!(code, StatusCode::NOT_FOUND);
+
+    // the control endpoints are only served if enabled
+    let (code, _) = call(&status.router(false), "POST", "/reconnect").await;
+    assert_eq!(code, StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn cleanup_only_own_outputs() {
+    use std::time::SystemTime;
+
+    use ulid::Ulid;
+
+    use crate::config::RetentionConfig;
+    use crate::retention::cleanup;
+    use crate::store::{self, PassRecord};
+    use crate::synthetic::Pass;
+
+    let dir = std::env::temp_dir().join("lso-cleanup-only-own-outputs");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("Mission/Maverick")).unwrap();
+    std::fs::create_dir_all(dir.join("other")).unwrap();
+    std::fs::create_dir_all(dir.join("empty")).unwrap();
+
+    let id = Ulid::new().to_string();
+    let record = PassRecord {
+        id: Some(id.clone()),
+        ..PassRecord::new(
+            time::OffsetDateTime::UNIX_EPOCH,
+            None,
+            "CVN-71",
+            "FA-18C_hornet",
+            "CVN_71",
+            &Pass::ideal().track(),
+        )
+    };
+    store::append(&dir.join(store::RESULTS_FILENAME), &[record]).unwrap();
+    let files = [
+        format!("Mission/Maverick/LSO-{}.png", id),
+        format!("Mission/Maverick/LSO-{}.zip.acmi", id),
+        "LSO-Session-20240501-180000.html".to_string(),
+        // not written by the LSO, or of a pass that isn't in the results
+        format!("LSO-{}.png", Ulid::new()),
+        "other/photo.png".to_string(),
+        "index.html".to_string(),
+    ];
+    for file in &files {
+        std::fs::write(dir.join(file), "content").unwrap();
+    }
+
+    let config = RetentionConfig {
+        max_age_days: None,
+        max_size_mb: Some(0.0),
+    };
+    let mut removed = cleanup(&dir, &config, SystemTime::now())
+        .unwrap()
+        .into_iter()
+        .map(|file| file.path.strip_prefix(&dir).unwrap().to_path_buf())
+        .collect::<Vec<_>>();
+    removed.sort();
+    let mut expected = files[..3]
+        .iter()
+        .map(std::path::PathBuf::from)
+        .collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(removed, expected);
+    for file in &files[3..] {
+        assert!(dir.join(file).exists(), "{}", file);
+    }
+    // only the directories left empty by the cleanup are removed
+    assert!(!dir.join("Mission").exists());
+    assert!(dir.join("empty").exists());
+    assert!(dir.join(store::RESULTS_FILENAME).exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,46 +1,587 @@
+use std::borrow::Cow;
+use std::fmt;
 use std::ops::Neg;
 use std::str::FromStr;
 
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
 use ultraviolet::{DRotor3, DVec3};
 
-use crate::data::{AirplaneInfo, CarrierInfo};
+use crate::config::{DetectionEnvelope, Resampling, Smoothing};
+use crate::data::{AirplaneInfo, Aoa, CarrierInfo, ALL_WIRES};
+use crate::dcs_grading::{DcsGrading, LsoGrade};
 use crate::transform::Transform;
+use crate::utils::{heading_diff, m_to_ft, m_to_nm, nm_to_m, smoothing, sun, TurnRate};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Datum {
+    /// Time in seconds since the scenario started.
+    pub time: f64,
     pub x: f64,
     pub y: f64,
     pub aoa: f64,
     pub alt: f64,
+    /// Bank angle (roll) in degrees.
+    pub bank: f64,
+    /// Pitch in degrees.
+    pub pitch: f64,
+    /// Vertical speed in m/s (negative when descending).
+    pub vs: f64,
+    /// The simulated meatball's offset from the datum in cells (positive when high).
+    pub ball: f64,
+    /// The final bearing (heading of the landing area's centerline, in degrees) at the time of the
+    /// datum. `x` and `y` are relative to it, so that a carrier turning during the pass doesn't
+    /// distort the track.
+    pub fb: f64,
+}
+
+impl Datum {
+    /// The datum at `t` (0 to 1) of the way from this datum to `other`.
+    pub fn lerp(&self, other: &Datum, t: f64) -> Datum {
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        Datum {
+            time: lerp(self.time, other.time),
+            x: lerp(self.x, other.x),
+            y: lerp(self.y, other.y),
+            aoa: lerp(self.aoa, other.aoa),
+            alt: lerp(self.alt, other.alt),
+            bank: lerp(self.bank, other.bank),
+            pitch: lerp(self.pitch, other.pitch),
+            vs: lerp(self.vs, other.vs),
+            ball: lerp(self.ball, other.ball),
+            fb: (self.fb + heading_diff(self.fb, other.fb) * t).rem_euclid(360.0),
+        }
+    }
 }
 
 pub struct Track {
     pilot_name: String,
     previous_distance: f64,
+    /// The time and altitude of the previous datum.
+    previous_alt: Option<(f64, f64)>,
     datums: Vec<Datum>,
+    /// The most recent hook positions (time, position relative to the carrier) while airborne.
+    hook_path: Vec<(f64, DVec3)>,
     grading: Option<Grading>,
     dcs_grading: Option<String>,
+    touchdown: Option<DeckPosition>,
+    /// The time the plane touched down.
+    touchdown_time: Option<f64>,
+    /// The plane's most recent altitudes above the carrier (time, altitude) before and shortly
+    /// after touching down, to derive the G at touchdown.
+    heights: Vec<(f64, f64)>,
+    ramp_clearance: Option<f64>,
+    /// The hook positions (relative to the carrier) after touching down.
+    rollout: Vec<DVec3>,
+    interrupted: bool,
+    hook_up: bool,
+    touch_and_go: bool,
+    foul_deck: Option<String>,
+    weight: Option<Weight>,
+    /// The mission's reference time (the time of the scenario start) and the approximate position
+    /// (lat, lon) of the carrier.
+    reference: Option<(OffsetDateTime, f64, f64)>,
+    /// The range (min, max) of the carrier's pitch and roll during the pass.
+    deck_pitch: Option<(f64, f64)>,
+    deck_roll: Option<(f64, f64)>,
+    /// The carrier's rate of turn, derived from the final bearing of the datums.
+    turn_rate: TurnRate,
+    /// The rate of turn (in degrees per second) above which the carrier is considered turning.
+    max_turn_rate: f64,
+    carrier_turning: bool,
+    /// The wires that are rigged (all by default).
+    rigged_wires: Vec<u8>,
+    smoothing: Option<Smoothing>,
+    resampling: Option<Resampling>,
     carrier_info: &'static CarrierInfo,
     plane_info: &'static AirplaneInfo,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
 pub enum Grading {
+    #[default]
     Unknown,
     Bolter,
     Recovered {
         cable: Option<u8>,
         cable_estimated: Option<u8>,
     },
+    /// The plane touched down and flew on on purpose, with the hook up or during a declared
+    /// touch-and-go window (see [crate::config::Config::touch_and_go]).
+    TouchAndGo,
+    /// The pass ended due to a crash, an ejection or the death of the pilot.
+    Incident(Incident),
+    /// The pass was abandoned with a climbing turn away from the groove (e.g. after an
+    /// overshooting start).
+    Spin,
+    /// The pilot waved off before being in close (see [Segment::InClose]).
+    OwnWaveOff,
+}
+
+/// A position on the landing area relative to the target touchdown point (in the middle of the
+/// rigged wires).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DeckPosition {
+    /// Distance in ft along the landing area past the target point (negative if short).
+    pub long: f64,
+    /// Distance in ft right of the landing area's centerline (midway between the cable pendants;
+    /// negative if left).
+    pub right: f64,
+}
+
+impl DeckPosition {
+    /// Convert a position relative to the carrier into a position on its landing area.
+    pub fn new(carrier_info: &CarrierInfo, rigged_wires: &[u8], position: DVec3) -> Self {
+        let offset = position - carrier_info.target_touchdown(rigged_wires);
+        DeckPosition {
+            long: m_to_ft(offset.dot(carrier_info.landing_direction())),
+            right: m_to_ft(offset.dot(carrier_info.landing_right())),
+        }
+    }
+}
+
+/// How much the deck moved during a pass (peak-to-peak, in degrees).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeckMotion {
+    pub pitch: f64,
+    pub roll: f64,
+}
+
+impl DeckMotion {
+    fn sample(range: &mut Option<(f64, f64)>, value: f64) {
+        *range = Some(match *range {
+            Some((min, max)) => (min.min(value), max.max(value)),
+            None => (value, value),
+        });
+    }
+
+    /// A single severity metric (in degrees). Pitch is weighted higher than roll, as it moves the
+    /// ramp and the wires up and down.
+    pub fn severity(&self) -> f64 {
+        self.pitch + self.roll / 2.0
+    }
+
+    /// Whether the deck was steady enough to not be worth mentioning.
+    pub fn is_steady(&self) -> bool {
+        self.severity() < 0.5
+    }
+
+    pub fn rating(&self) -> &'static str {
+        if self.is_steady() {
+            "steady"
+        } else if self.severity() < 1.5 {
+            "moderate"
+        } else {
+            "heavy"
+        }
+    }
+}
+
+impl fmt::Display for DeckMotion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (pitch {:.1}°, roll {:.1}°)",
+            self.rating(),
+            self.pitch,
+            self.roll
+        )
+    }
+}
+
+/// The plane's weight at the ball call (in lb).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Weight {
+    /// The internal fuel.
+    pub fuel: f64,
+    /// The gross weight (without stores and external tanks).
+    pub gross: f64,
+}
+
+impl fmt::Display for Weight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.0} lb (fuel {:.0} lb)", self.gross, self.fuel)
+    }
+}
+
+/// The segments of the groove the LSO comments on (in the order they are flown).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment {
+    Start,
+    Middle,
+    InClose,
+    AtTheRamp,
+}
+
+impl Segment {
+    pub const ALL: [Segment; 4] = [
+        Segment::Start,
+        Segment::Middle,
+        Segment::InClose,
+        Segment::AtTheRamp,
+    ];
+
+    /// The LSO shorthand of the segment.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Segment::Start => "X",
+            Segment::Middle => "IM",
+            Segment::InClose => "IC",
+            Segment::AtTheRamp => "AR",
+        }
+    }
+
+    /// The distance (in nm from the optimal touchdown point) the segment starts and ends at.
+    pub fn range(&self) -> (f64, f64) {
+        match self {
+            Segment::Start => (START_DISTANCE, 0.5),
+            Segment::Middle => (0.5, 0.25),
+            Segment::InClose => (0.25, 0.1),
+            Segment::AtTheRamp => (0.1, 0.0),
+        }
+    }
+}
+
+/// How the plane's position relative to the glide slope and the centerline changed throughout a
+/// segment of the groove.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    ComingUp,
+    GoingDown,
+    DriftingLeft,
+    DriftingRight,
+}
+
+impl Trend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Trend::ComingUp => "coming up",
+            Trend::GoingDown => "going down",
+            Trend::DriftingLeft => "drifting left",
+            Trend::DriftingRight => "drifting right",
+        }
+    }
+}
+
+/// The plane's state at the nominal start of the pass (see [START_DISTANCE]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Start {
+    /// The ball in cells (positive when high).
+    pub ball: f64,
+    /// The lineup in degrees (positive when right of the centerline).
+    pub lineup: f64,
+    /// The AoA in degrees.
+    pub aoa: f64,
+    pub aoa_rating: Aoa,
+    /// Whether the plane rolled out into the groove long before the start.
+    pub long_in_groove: bool,
+}
+
+impl Start {
+    /// The deviations at the start (empty for a good start).
+    pub fn deviations(&self) -> Vec<&'static str> {
+        let mut deviations = Vec::new();
+        // The approach turn is a left turn, so being right of the centerline means the plane
+        // overshot it.
+        if self.lineup > START_MAX_LINEUP {
+            deviations.push("overshooting");
+        } else if self.lineup < -START_MAX_LINEUP {
+            deviations.push("lined up left");
+        }
+        if self.ball > START_MAX_BALL {
+            deviations.push("high");
+        } else if self.ball < -START_MAX_BALL {
+            deviations.push("low");
+        }
+        match self.aoa_rating {
+            Aoa::Fast => deviations.push("fast"),
+            Aoa::Slow => deviations.push("slow"),
+            _ => {}
+        }
+        if self.long_in_groove {
+            deviations.push("long in the groove");
+        }
+        deviations
+    }
+}
+
+impl fmt::Display for Start {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let deviations = self.deviations();
+        if deviations.is_empty() {
+            f.write_str("good start")
+        } else {
+            write!(f, "start: {}", deviations.join(", "))
+        }
+    }
+}
+
+impl fmt::Display for DeckPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.0}ft {}, {:.0}ft {}",
+            self.long.abs(),
+            if self.long < 0.0 { "short" } else { "long" },
+            self.right.abs(),
+            if self.right < 0.0 { "left" } else { "right" },
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Incident {
+    Crash,
+    Ejection,
+    PilotDead,
+}
+
+impl Incident {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Incident::Crash => "Crashed",
+            Incident::Ejection => "Ejected",
+            Incident::PilotDead => "Pilot dead",
+        }
+    }
+}
+
+impl FromStr for Incident {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Crashed" => Ok(Incident::Crash),
+            "Ejected" => Ok(Incident::Ejection),
+            "Pilot dead" => Ok(Incident::PilotDead),
+            _ => Err(()),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub struct TrackResult {
     pub pilot_name: String,
+    /// The name of the DCS server the pass was flown on (if known), shown in the chart header.
+    pub server_name: Option<String>,
     pub grading: Grading,
-    pub dcs_grading: Option<String>,
+    pub dcs_grading: Option<DcsGrading>,
+    /// The datums of the pass, smoothed and resampled if configured (see
+    /// [crate::config::Smoothing] and [crate::config::Resampling]).
     pub datums: Vec<Datum>,
+    /// The datums as recorded (before smoothing and resampling), e.g. for exports of the raw
+    /// data.
+    pub raw_datums: Vec<Datum>,
     pub plane_info: &'static AirplaneInfo,
+    pub carrier_info: &'static CarrierInfo,
+    pub rigged_wires: Vec<u8>,
+    /// Where the hook touched down on the deck (if it did).
+    pub touchdown: Option<DeckPosition>,
+    /// The height (in ft) of the hook above the ramp (the round-down) when crossing it (if it
+    /// did).
+    pub ramp_clearance: Option<f64>,
+    /// The peak G around touching down (if it did).
+    pub touchdown_g: Option<f64>,
+    /// The hook's path on the deck after touching down.
+    pub rollout: Vec<DeckPosition>,
+    /// Whether the recording got interrupted (e.g. due to a lost connection) before the pass was
+    /// complete.
+    pub interrupted: bool,
+    /// Whether the pass was flown with the hook up (e.g. touch-and-go practice).
+    pub hook_up: bool,
+    /// Whether the carrier turned during the pass (rendering the grade meaningless).
+    pub carrier_turning: bool,
+    /// The name of the unit that fouled the landing area during the approach (if any).
+    pub foul_deck: Option<String>,
+    /// How much the deck moved during the pass.
+    pub deck_motion: Option<DeckMotion>,
+    /// The plane's weight at the ball call (only known for passes recorded live).
+    pub weight: Option<Weight>,
+    /// The sun's elevation (in degrees) at the end of the pass (if the mission's time is known).
+    pub sun_elevation: Option<f64>,
+}
+
+impl TrackResult {
+    /// The estimated and the DCS-reported wire, if both are known but disagree.
+    pub fn cable_mismatch(&self) -> Option<(u8, u8)> {
+        match self.grading {
+            Grading::Recovered {
+                cable: Some(cable),
+                cable_estimated: Some(cable_estimated),
+            } if cable != cable_estimated => Some((cable_estimated, cable)),
+            _ => None,
+        }
+    }
+
+    /// The final bearing (in degrees) at the end of the pass and by how much (in degrees, positive
+    /// to the right) it changed during the pass.
+    pub fn fb(&self) -> Option<(f64, f64)> {
+        let first = self.datums.first()?;
+        let last = self.datums.last()?;
+        Some((last.fb, heading_diff(first.fb, last.fb)))
+    }
+
+    /// Whether the plane touched down hard enough to potentially overstress it (see
+    /// [OVERSTRESS_G]).
+    pub fn is_overstress(&self) -> bool {
+        self.touchdown_g.is_some_and(|g| g > OVERSTRESS_G)
+    }
+
+    /// Whether the pass was flown at night (see [sun::NIGHT_ELEVATION]).
+    pub fn is_night(&self) -> bool {
+        self.sun_elevation
+            .is_some_and(|elevation| elevation < sun::NIGHT_ELEVATION)
+    }
+
+    /// Whether the plane got trapped heavier than its maximum trap weight.
+    pub fn is_overweight_trap(&self) -> bool {
+        matches!(self.grading, Grading::Recovered { .. })
+            && self
+                .weight
+                .is_some_and(|weight| weight.gross > self.plane_info.max_trap_weight)
+    }
+
+    /// A one-line summary of the result of the pass (e.g.
+    /// `Trap #3, DCS LSO: OK LUL, groove 17.2s`).
+    pub fn debriefing(&self) -> String {
+        let mut parts = vec![match &self.grading {
+            Grading::Recovered { .. } => format!("Trap {}", self.grading.summary(self.hook_up)),
+            // neither a trap nor a bolter, most likely a wave-off
+            Grading::Unknown => "Wave-off".to_string(),
+            grading => grading.summary(self.hook_up).into_owned(),
+        }];
+        if let Some(dcs_grading) = &self.dcs_grading {
+            let mut grade = format!("DCS LSO: {}", dcs_grading.grade.as_str());
+            for deviation in &dcs_grading.deviations {
+                grade.push(' ');
+                grade.push_str(deviation);
+            }
+            parts.push(grade);
+        }
+        if let Some(groove_time) = self.groove_time() {
+            parts.push(format!("groove {:.1}s", groove_time));
+        }
+        if let Some(ramp_clearance) = self.ramp_clearance {
+            parts.push(format!("ramp {:.0}ft", ramp_clearance));
+        }
+        if let Some(touchdown_g) = self.touchdown_g {
+            parts.push(format!("{:.1}G", touchdown_g));
+        }
+        if self.is_night() {
+            parts.push("night".to_string());
+        }
+        if let Some(start) = self.start() {
+            parts.push(start.to_string());
+        }
+        if let Some(unit_name) = &self.foul_deck {
+            parts.push(format!("foul deck ({})", unit_name));
+        }
+        parts.join(", ")
+    }
+
+    /// The time (in seconds) spent in the groove, from rolling out of the approach turn (or the
+    /// start of the recording for straight-in approaches) to the end of the pass.
+    pub fn groove_time(&self) -> Option<f64> {
+        let groove = self.groove();
+        let (first, last) = (groove.first()?, groove.last()?);
+        Some(last.time - first.time).filter(|t| *t > 0.0)
+    }
+
+    /// The average AoA (in degrees) in the groove.
+    pub fn groove_aoa(&self) -> Option<f64> {
+        let groove = self.groove();
+        if groove.is_empty() {
+            return None;
+        }
+        Some(groove.iter().map(|d| d.aoa).sum::<f64>() / groove.len() as f64)
+    }
+
+    /// The plane's state at the nominal start of the pass, if it was already tracked before it.
+    pub fn start(&self) -> Option<Start> {
+        let start = nm_to_m(START_DISTANCE);
+        let i = self.datums.iter().position(|d| d.x <= start)?;
+        if i == 0 {
+            // the plane was first seen closer than the start
+            return None;
+        }
+        let (previous, next) = (&self.datums[i - 1], &self.datums[i]);
+        let datum = previous.lerp(next, (previous.x - start) / (previous.x - next.x));
+
+        let groove_start = self.groove().first()?;
+        Some(Start {
+            ball: datum.ball,
+            lineup: datum.y.atan2(datum.x).to_degrees(),
+            aoa: datum.aoa,
+            aoa_rating: self.plane_info.aoa_rating(datum.aoa),
+            long_in_groove: groove_start.x > nm_to_m(LONG_IN_GROOVE_DISTANCE),
+        })
+    }
+
+    /// The trends of each segment of the groove that was flown completely.
+    pub fn trends(&self) -> Vec<(Segment, Vec<Trend>)> {
+        Segment::ALL
+            .iter()
+            .filter_map(|segment| {
+                let (from, to) = segment.range();
+                let mut datums = self
+                    .datums
+                    .iter()
+                    .skip_while(|d| d.x > nm_to_m(from))
+                    .take_while(|d| d.x > nm_to_m(to));
+                let first = datums.next()?;
+                let last = datums.last()?;
+
+                let mut trends = Vec::new();
+                if last.ball - first.ball > TREND_MIN_BALL {
+                    trends.push(Trend::ComingUp);
+                } else if last.ball - first.ball < -TREND_MIN_BALL {
+                    trends.push(Trend::GoingDown);
+                }
+                if last.y - first.y > TREND_MIN_DRIFT {
+                    trends.push(Trend::DriftingRight);
+                } else if last.y - first.y < -TREND_MIN_DRIFT {
+                    trends.push(Trend::DriftingLeft);
+                }
+                Some((*segment, trends))
+            })
+            .collect()
+    }
+
+    /// The datums in the groove (see [TrackResult::groove_time]).
+    fn groove(&self) -> &[Datum] {
+        let start = self
+            .datums
+            .iter()
+            .rposition(|d| d.bank.abs() > GROOVE_MAX_BANK)
+            .unwrap_or(0);
+        &self.datums[start..]
+    }
+
+    /// Whether the pass is worth a closer look, i.e. a cut, a wave-off, a 1-wire or a no-grade.
+    pub fn is_notable(&self) -> bool {
+        match self.grading {
+            // neither a trap nor a bolter, most likely a wave-off
+            Grading::Unknown | Grading::Spin | Grading::OwnWaveOff => return true,
+            Grading::Recovered { cable: Some(1), .. } => return true,
+            Grading::Incident(_) => return true,
+            _ => {}
+        }
+
+        matches!(
+            self.dcs_grading.as_ref().map(|g| &g.grade),
+            Some(
+                LsoGrade::NoGrade
+                    | LsoGrade::Cut
+                    | LsoGrade::WaveOff
+                    | LsoGrade::PatternWaveOff
+                    | LsoGrade::OwnWaveOff
+            )
+        )
+    }
 }
 
 impl Track {
@@ -52,9 +593,30 @@ impl Track {
         Self {
             pilot_name: pilot_name.into(),
             previous_distance: f64::MAX,
+            previous_alt: None,
             datums: Default::default(),
+            hook_path: Default::default(),
             grading: None,
             dcs_grading: None,
+            touchdown: None,
+            touchdown_time: None,
+            heights: Vec::new(),
+            ramp_clearance: None,
+            rollout: Vec::new(),
+            interrupted: false,
+            hook_up: false,
+            touch_and_go: false,
+            foul_deck: None,
+            weight: None,
+            reference: None,
+            deck_pitch: None,
+            deck_roll: None,
+            turn_rate: TurnRate::default(),
+            max_turn_rate: DetectionEnvelope::default().max_turn_rate,
+            carrier_turning: false,
+            rigged_wires: ALL_WIRES.to_vec(),
+            smoothing: None,
+            resampling: None,
             carrier_info,
             plane_info,
         }
@@ -63,7 +625,7 @@ impl Track {
     pub fn next(&mut self, carrier: &Transform, plane: &Transform) -> bool {
         let landing_pos_offset = self
             .carrier_info
-            .optimal_landing_offset(self.plane_info)
+            .optimal_landing_offset(self.plane_info, &self.rigged_wires)
             .rotated_by(carrier.rotation);
         let landing_pos = carrier.position + landing_pos_offset;
 
@@ -79,9 +641,14 @@ impl Track {
         if distance < self.previous_distance {
             self.previous_distance = distance;
         } else if distance - self.previous_distance > 150.0 {
-            if self.grading.is_some() {
+            if self.grading.is_some() && (self.hook_up || self.touch_and_go) {
+                tracing::debug!(distance_in_m = distance, "touch-and-go detected");
+                self.grading = Some(Grading::TouchAndGo);
+            } else if self.grading.is_some() {
                 tracing::debug!(distance_in_m = distance, "bolter detected");
                 self.grading = Some(Grading::Bolter);
+            } else {
+                self.grading = self.departure(carrier, plane);
             }
 
             tracing::debug!(distance_in_m = distance, "stop tracking");
@@ -89,18 +656,51 @@ impl Track {
             return false;
         }
 
+        // Samples too close to the previous one (e.g. of events in between two frames) are skipped,
+        // as the precision of the altitude makes their vertical speed too noisy.
+        if self
+            .touchdown_time
+            .is_none_or(|time| plane.time - time <= TOUCHDOWN_G_AFTER)
+            && self
+                .heights
+                .last()
+                .is_none_or(|(time, _)| plane.time - time >= TOUCHDOWN_G_MIN_INTERVAL)
+        {
+            if self.touchdown_time.is_none() && self.heights.len() >= HEIGHTS_LEN {
+                self.heights.remove(0);
+            }
+            self.heights.push((plane.time, plane.alt - carrier.alt));
+        }
+
         // Already landed, no need to actually record any more datums, but keep going to detect
         // bolters.
         if self.grading.is_some() {
+            if self.is_recovered() && self.rollout.len() < ROLLOUT_LEN {
+                self.rollout.push(self.hook_position(carrier, plane));
+            }
             return true;
         }
 
-        // Construct the x axis, which is aligned to the angled deck.
-        let fb_rot = DRotor3::from_rotation_xz(
-            (carrier.heading - self.carrier_info.deck_angle)
-                .neg()
-                .to_radians(),
-        );
+        // Keep the last couple of hook positions to determine where it crossed the deck on landing.
+        if self.hook_path.len() >= HOOK_PATH_LEN {
+            self.hook_path.remove(0);
+        }
+        self.hook_path
+            .push((plane.time, self.hook_position(carrier, plane)));
+        if self.ramp_clearance.is_none() {
+            self.ramp_clearance = self.ramp_crossing();
+        }
+
+        // Construct the x axis, which is aligned to the angled deck at the time of this datum.
+        let fb_deg = (carrier.heading - self.carrier_info.deck_angle).rem_euclid(360.0);
+        let fb_rot = DRotor3::from_rotation_xz(fb_deg.neg().to_radians());
+        DeckMotion::sample(&mut self.deck_pitch, carrier.pitch);
+        DeckMotion::sample(&mut self.deck_roll, carrier.roll);
+        self.turn_rate.update(plane.time, fb_deg);
+        if !self.carrier_turning && self.turn_rate.rate().abs() > self.max_turn_rate {
+            tracing::debug!(turn_rate = self.turn_rate.rate(), "carrier is turning");
+            self.carrier_turning = true;
+        }
         let fb = DVec3::unit_z().rotated_by(fb_rot);
 
         let x = ray_from_plane_to_carrier.dot(fb);
@@ -114,17 +714,38 @@ impl Track {
 
         let hook_offset = self.plane_info.hook.rotated_by(plane.rotation);
         let alt = plane.alt - self.carrier_info.deck_altitude + hook_offset.y;
+        // Derive the vertical speed from the altitude change since the previous datum.
+        let vs = self
+            .previous_alt
+            .filter(|(time, _)| plane.time > *time)
+            .map(|(time, alt)| (plane.alt - alt) / (plane.time - time))
+            .unwrap_or(0.0);
+        self.previous_alt = Some((plane.time, plane.alt));
+
         self.datums.push(Datum {
+            time: plane.time,
             x,
             y,
             aoa: plane.aoa,
             alt: alt.max(0.0),
+            bank: plane.roll,
+            pitch: plane.pitch,
+            vs,
+            ball: self.ball(carrier, plane),
+            fb: fb_deg,
         });
 
         true
     }
 
     pub fn landed(&mut self, carrier: &Transform, plane: &Transform) {
+        self.touchdown_time = Some(plane.time);
+        self.touchdown = self
+            .estimate_touchdown(plane)
+            .map(|touchdown| DeckPosition::new(self.carrier_info, &self.rigged_wires, touchdown));
+        self.rollout.push(self.hook_position(carrier, plane));
+        tracing::debug!(touchdown = ?self.touchdown, "hook touchdown");
+
         let cable = self.estimate_cable(carrier, plane);
         self.grading = Some(Grading::Recovered {
             cable,
@@ -134,11 +755,20 @@ impl Track {
     }
 
     pub fn finish(self) -> TrackResult {
+        let touchdown_g = self.touchdown_g();
+        let sun_elevation =
+            self.reference
+                .zip(self.datums.last())
+                .map(|((reference_time, lat, lon), datum)| {
+                    let time = reference_time + Duration::seconds_f64(datum.time);
+                    sun::elevation(sun::mission_time_to_utc(time, lon), lat, lon)
+                });
+        let dcs_grading = self.dcs_grading.as_deref().map(DcsGrading::parse);
+
         // If DCS grading is set, use its reported wire instead of the estimated one.
-        let grading = if let Some(dcs_wire) = self.dcs_grading.as_ref().and_then(|s| {
-            s.split_once("WIRE# ")
-                .and_then(|(_, w)| u8::from_str(&w[0..1]).ok())
-        }) {
+        let grading = if let Some(Grading::Incident(incident)) = self.grading {
+            Grading::Incident(incident)
+        } else if let Some(dcs_wire) = dcs_grading.as_ref().and_then(|g| g.wire) {
             match self.grading {
                 Some(Grading::Recovered {
                     cable_estimated, ..
@@ -155,13 +785,58 @@ impl Track {
             self.grading.unwrap_or_default()
         };
 
-        TrackResult {
+        let mut datums = self.datums.clone();
+        if let Some(smoothing) = self.smoothing {
+            smooth(&mut datums, smoothing);
+        }
+        if let Some(resampling) = self.resampling {
+            datums = resample(&datums, resampling);
+        }
+
+        let rollout = self
+            .rollout
+            .iter()
+            .map(|hook| DeckPosition::new(self.carrier_info, &self.rigged_wires, *hook))
+            .collect();
+
+        let result = TrackResult {
             pilot_name: self.pilot_name,
+            server_name: None,
             grading,
-            dcs_grading: self.dcs_grading,
-            datums: self.datums,
+            dcs_grading,
+            datums,
+            raw_datums: self.datums,
             plane_info: self.plane_info,
+            carrier_info: self.carrier_info,
+            rigged_wires: self.rigged_wires,
+            touchdown: self.touchdown,
+            ramp_clearance: self.ramp_clearance,
+            touchdown_g,
+            rollout,
+            interrupted: self.interrupted,
+            hook_up: self.hook_up,
+            carrier_turning: self.carrier_turning,
+            foul_deck: self.foul_deck,
+            deck_motion: self
+                .deck_pitch
+                .zip(self.deck_roll)
+                .map(|(pitch, roll)| DeckMotion {
+                    pitch: pitch.1 - pitch.0,
+                    roll: roll.1 - roll.0,
+                }),
+            weight: self.weight,
+            sun_elevation,
+        };
+
+        if let Some((cable_estimated, cable)) = result.cable_mismatch() {
+            tracing::warn!(
+                cable_estimated,
+                cable,
+                "estimated wire disagrees with the wire reported by DCS"
+            );
         }
+
+        result
     }
 
     /// Set the track's dcs grading.
@@ -169,7 +844,266 @@ impl Track {
         self.dcs_grading = Some(dcs_grading);
     }
 
+    /// Whether the plane landed (and didn't bolter) during the pass.
+    pub fn is_recovered(&self) -> bool {
+        matches!(self.grading, Some(Grading::Recovered { .. }))
+    }
+
+    /// End the pass due to an incident.
+    pub fn set_incident(&mut self, incident: Incident) {
+        tracing::debug!(?incident, "incident, stop tracking");
+        self.grading = Some(Grading::Incident(incident));
+    }
+
+    /// Whether the plane boltered during the pass.
+    pub fn is_bolter(&self) -> bool {
+        matches!(self.grading, Some(Grading::Bolter))
+    }
+
+    /// Whether the pass ended due to an incident.
+    pub fn is_incident(&self) -> bool {
+        matches!(self.grading, Some(Grading::Incident(_)))
+    }
+
+    /// How a pass that didn't end with a trap ended (e.g. `Bolter` or `Spin`).
+    pub fn departure_summary(&self) -> &'static str {
+        match self.grading {
+            Some(Grading::TouchAndGo) => "Touch-and-go",
+            Some(Grading::Bolter) => "Bolter",
+            Some(Grading::Spin) => "Spin",
+            Some(Grading::OwnWaveOff) => "Own wave-off",
+            _ => "Wave-off",
+        }
+    }
+
+    /// Mark the track as interrupted before the pass was complete.
+    pub fn set_interrupted(&mut self) {
+        self.interrupted = true;
+    }
+
+    /// Set the wires that are rigged on the carrier.
+    pub fn set_rigged_wires(&mut self, mut rigged_wires: Vec<u8>) {
+        rigged_wires.sort_unstable();
+        rigged_wires.dedup();
+        self.rigged_wires = rigged_wires;
+    }
+
+    /// Flag the deck as fouled by the given unit (keeps the first one).
+    pub fn set_foul_deck(&mut self, unit_name: &str) {
+        if self.foul_deck.is_none() {
+            tracing::debug!(unit_name, "foul deck");
+            self.foul_deck = Some(unit_name.to_string());
+        }
+    }
+
+    /// Whether the plane is still in the approach (i.e. hasn't landed or passed the carrier yet).
+    pub fn is_approaching(&self) -> bool {
+        self.grading.is_none()
+    }
+
+    /// Whether the plane got past the nominal start of the pass (see [START_DISTANCE]) or already
+    /// landed.
+    pub fn is_started(&self) -> bool {
+        self.grading.is_some()
+            || self
+                .datums
+                .last()
+                .is_some_and(|datum| datum.x < nm_to_m(START_DISTANCE))
+    }
+
+    /// Whether the plane is at the ball call (see [START_DISTANCE]) and its weight isn't known
+    /// yet.
+    pub fn needs_weight(&self) -> bool {
+        self.is_approaching()
+            && self.weight.is_none()
+            && self
+                .datums
+                .last()
+                .is_some_and(|datum| datum.x < nm_to_m(START_DISTANCE))
+    }
+
+    /// Set the mission's reference time (the time of the scenario start) and the approximate
+    /// position of the carrier, to tell day and night passes apart.
+    pub fn set_reference(&mut self, reference_time: OffsetDateTime, lat: f64, lon: f64) {
+        self.reference = Some((reference_time, lat, lon));
+    }
+
+    /// Set the plane's weight at the ball call.
+    pub fn set_weight(&mut self, weight: Weight) {
+        tracing::debug!(?weight, "weight at the ball call");
+        self.weight = Some(weight);
+    }
+
+    /// Smooth the AoA and the position of the datums when the track is finished.
+    pub fn set_smoothing(&mut self, smoothing: Option<Smoothing>) {
+        self.smoothing = smoothing;
+    }
+
+    /// Resample the datums to a fixed grid when the track is finished.
+    pub fn set_resampling(&mut self, resampling: Option<Resampling>) {
+        self.resampling = resampling;
+    }
+
+    /// Set the rate of turn (in degrees per second) above which the carrier is considered turning.
+    pub fn set_max_turn_rate(&mut self, max_turn_rate: f64) {
+        self.max_turn_rate = max_turn_rate;
+    }
+
+    /// Mark the pass as flown with the hook up.
+    pub fn set_hook_up(&mut self) {
+        self.hook_up = true;
+    }
+
+    /// Mark the pass as flown during a declared touch-and-go window, so that touching down and
+    /// flying on isn't a bolter.
+    pub fn set_touch_and_go(&mut self) {
+        self.touch_and_go = true;
+    }
+
+    /// Simulate what the lens shows the pilot. The lens is line-stabilized, so the glide slope it
+    /// projects (at its basic angle) is relative to the horizon, but its position moves with the
+    /// carrier's pitch and roll. It is set for the plane's hook-to-eye distance, so the glide slope
+    /// of the pilot's eye passes that far above the target touchdown point.
+    fn ball(&self, carrier: &Transform, plane: &Transform) -> f64 {
+        let up = DVec3::unit_y() * self.plane_info.hook_to_eye;
+        let lens = carrier.position + self.carrier_info.lens.rotated_by(carrier.rotation);
+        let target = carrier.position
+            + self
+                .carrier_info
+                .target_touchdown(&self.rigged_wires)
+                .rotated_by(carrier.rotation)
+            + up;
+        let eye = plane.position + self.plane_info.hook.rotated_by(plane.rotation) + up;
+
+        let horizontal = |v: DVec3| DVec3::new(v.x, 0.0, v.z).mag();
+        let glide_slope = target.y
+            + horizontal(eye - target) * self.carrier_info.lens_basic_angle.to_radians().tan();
+        let angle = (eye.y - glide_slope)
+            .atan2(horizontal(eye - lens))
+            .to_degrees();
+        (angle / LENS_CELL_ANGLE).clamp(-LENS_CELLS, LENS_CELLS)
+    }
+
+    /// Classify a pass that ended without touching down: a spin (a climbing turn away from the
+    /// groove) or an early own wave-off. `None` for other wave-offs.
+    fn departure(&self, carrier: &Transform, plane: &Transform) -> Option<Grading> {
+        let closest = self.datums.iter().min_by(|a, b| a.x.total_cmp(&b.x))?;
+        let last = self.datums.last()?;
+        let fb = (carrier.heading - self.carrier_info.deck_angle).rem_euclid(360.0);
+        let turned = heading_diff(fb, plane.heading).abs();
+        let early = closest.x > nm_to_m(Segment::InClose.range().0);
+
+        if turned > SPIN_MIN_TURN && last.alt - closest.alt > SPIN_MIN_CLIMB {
+            tracing::debug!(turned, "spin detected");
+            Some(Grading::Spin)
+        } else if early {
+            tracing::debug!(distance_in_m = closest.x, "own wave-off detected");
+            Some(Grading::OwnWaveOff)
+        } else {
+            None
+        }
+    }
+
+    /// The peak normal acceleration (in G) from shortly before to shortly after touching down,
+    /// derived from the plane's vertical speed (ignoring its pitch).
+    fn touchdown_g(&self) -> Option<f64> {
+        let touchdown = self.touchdown_time?;
+        let vs = self
+            .heights
+            .windows(2)
+            .map(|w| {
+                (
+                    (w[0].0 + w[1].0) / 2.0,
+                    (w[1].1 - w[0].1) / (w[1].0 - w[0].0),
+                )
+            })
+            .collect::<Vec<_>>();
+        vs.windows(2)
+            .filter(|w| w[1].0 >= touchdown - TOUCHDOWN_G_BEFORE)
+            .map(|w| 1.0 + (w[1].1 - w[0].1) / (w[1].0 - w[0].0) / GRAVITY)
+            .max_by(|a, b| a.total_cmp(b))
+    }
+
+    /// The hook's height (in ft) above the ramp if it crossed the ramp between the last two hook
+    /// positions.
+    fn ramp_crossing(&self) -> Option<f64> {
+        let [.., (_, previous), (_, last)] = self.hook_path.as_slice() else {
+            return None;
+        };
+        let ramp = self.carrier_info.ramp;
+        let landing_dir = self.carrier_info.landing_direction();
+        let (before, after) = (
+            (*previous - ramp).dot(landing_dir),
+            (*last - ramp).dot(landing_dir),
+        );
+        if before >= 0.0 || after < 0.0 {
+            return None;
+        }
+        let crossing = *previous + (*last - *previous) * (before.neg() / (after - before));
+        Some(m_to_ft(crossing.y - ramp.y))
+    }
+
+    /// The hook position relative to the carrier (in the carrier's coordinate system, so the same
+    /// one the cable positions are in).
+    fn hook_position(&self, carrier: &Transform, plane: &Transform) -> DVec3 {
+        let hook = plane.position + self.plane_info.hook.rotated_by(plane.rotation);
+        (hook - carrier.position).rotated_by(carrier.rotation.reversed())
+    }
+
     fn estimate_cable(&self, carrier: &Transform, plane: &Transform) -> Option<u8> {
+        let Some(touchdown) = self.estimate_touchdown(plane) else {
+            return self.estimate_cable_from_land_event(carrier, plane);
+        };
+
+        // The touchdown is estimated along a straight path, which ignores any last-moment
+        // corrections, the flare and the hook bouncing. Thus, also consider a cable caught if the
+        // hook touched down shortly after it (up to roughly half the distance between two
+        // cables).
+        let landing_dir = self.carrier_info.landing_direction();
+        self.rigged_cables()
+            .find(|(nr, pendants)| {
+                let mid_cable = (pendants.0 + pendants.1) / 2.0;
+                let distance = (mid_cable - touchdown).dot(landing_dir);
+                tracing::trace!(cable = nr, distance, "cable candidate");
+                distance > -CABLE_CATCH_MARGIN
+            })
+            .map(|(nr, _)| nr)
+    }
+
+    /// Estimate where the hook touched down (relative to the carrier) by extending the hook's
+    /// path along its last two airborne positions until it crosses the deck.
+    fn estimate_touchdown(&self, plane: &Transform) -> Option<DVec3> {
+        let deck = self.carrier_info.deck_altitude;
+        let mut airborne = self
+            .hook_path
+            .iter()
+            .rev()
+            .filter(|(time, hook)| *time < plane.time && hook.y > deck + 0.5)
+            .map(|(_, hook)| *hook);
+        let (last, previous) = (airborne.next()?, airborne.next()?);
+
+        let path = last - previous;
+        if path.y >= 0.0 {
+            // not descending
+            return None;
+        }
+
+        let touchdown = last + path * ((last.y - deck) / path.y.neg());
+        tracing::trace!(?touchdown, "hook touchdown");
+
+        Some(touchdown)
+    }
+
+    /// The pendant positions of all rigged cables, ordered from aft to forward.
+    fn rigged_cables(&self) -> impl Iterator<Item = (u8, &(DVec3, DVec3))> + '_ {
+        self.rigged_wires
+            .iter()
+            .filter_map(|nr| self.carrier_info.cable(*nr).map(|c| (*nr, c)))
+    }
+
+    /// Fallback estimation if the hook's path isn't known, based on the hook's position at the
+    /// time of the land event.
+    fn estimate_cable_from_land_event(&self, carrier: &Transform, plane: &Transform) -> Option<u8> {
         let hook_offset = self.plane_info.hook.rotated_by(plane.rotation);
         let touchdown = plane.position + hook_offset;
         let forward = carrier
@@ -193,34 +1127,29 @@ impl Track {
         //     touchdown.x, touchdown.z, touchdown.y
         // );
 
-        let cables = [
-            (1, &self.carrier_info.cable1),
-            (2, &self.carrier_info.cable2),
-            (3, &self.carrier_info.cable3),
-            (4, &self.carrier_info.cable4),
-        ]
-        .into_iter()
-        .map(|(nr, pendants)| {
-            // Calculate the mid position between both cable pendants:
-            // o-----------o
-            //       ^
-            //       |
-            let mid_cable = (pendants.0 - pendants.1) / 2.0;
-            let mid_cable = pendants.0 - mid_cable;
-            let mid_cable = carrier.position + mid_cable.rotated_by(carrier.rotation);
-
-            // println!(
-            //     "cable_{};{};{};{}",
-            //     nr, mid_cable.x, mid_cable.z, mid_cable.y
-            // );
-            // let p0 = carrier.position + pendants.0.rotated_by(carrier.rotation);
-            // let p1 = carrier.position + pendants.1.rotated_by(carrier.rotation);
-            // println!("p0_{};{};{};{}", nr, p0.x, p0.z, p0.y);
-            // println!("p1_{};{};{};{}", nr, p1.x, p1.z, p1.y);
-
-            (nr, mid_cable)
-        })
-        .collect::<Vec<_>>();
+        let cables = self
+            .rigged_cables()
+            .map(|(nr, pendants)| {
+                // Calculate the mid position between both cable pendants:
+                // o-----------o
+                //       ^
+                //       |
+                let mid_cable = (pendants.0 - pendants.1) / 2.0;
+                let mid_cable = pendants.0 - mid_cable;
+                let mid_cable = carrier.position + mid_cable.rotated_by(carrier.rotation);
+
+                // println!(
+                //     "cable_{};{};{};{}",
+                //     nr, mid_cable.x, mid_cable.z, mid_cable.y
+                // );
+                // let p0 = carrier.position + pendants.0.rotated_by(carrier.rotation);
+                // let p1 = carrier.position + pendants.1.rotated_by(carrier.rotation);
+                // println!("p0_{};{};{};{}", nr, p0.x, p0.z, p0.y);
+                // println!("p1_{};{};{};{}", nr, p1.x, p1.z, p1.y);
+
+                (nr, mid_cable)
+            })
+            .collect::<Vec<_>>();
 
         for (nr, mid_cable) in cables {
             // If the cable is in front of the touchdown position, consider it the one the plane
@@ -241,8 +1170,218 @@ impl Track {
     }
 }
 
-impl Default for Grading {
-    fn default() -> Self {
-        Self::Unknown
+/// Apply the smoothing to the AoA and the position (`x`, `y` and `alt`) of the datums.
+fn smooth(datums: &mut [Datum], smoothing: Smoothing) {
+    let times = datums.iter().map(|d| d.time).collect::<Vec<_>>();
+    let fields: [fn(&mut Datum) -> &mut f64; 4] =
+        [|d| &mut d.aoa, |d| &mut d.x, |d| &mut d.y, |d| &mut d.alt];
+    for field in fields {
+        let mut values = datums.iter_mut().map(|d| *field(d)).collect::<Vec<_>>();
+        match smoothing {
+            Smoothing::Ema { time_constant_s } => {
+                smoothing::ema(&times, &mut values, time_constant_s)
+            }
+            Smoothing::SavitzkyGolay { window } => {
+                smoothing::savitzky_golay(&times, &mut values, window / 2)
+            }
+        }
+        for (datum, value) in datums.iter_mut().zip(values) {
+            *field(datum) = value;
+        }
+    }
+}
+
+/// Interpolate the datums at the points of the given grid, starting at the first datum. The last
+/// datum is always kept, so that the pass still ends at the same position.
+fn resample(datums: &[Datum], resampling: Resampling) -> Vec<Datum> {
+    let (Some(first), Some(last)) = (datums.first(), datums.last()) else {
+        return Vec::new();
+    };
+    // the position of a datum on the grid (increasing throughout the pass)
+    let (position, interval): (fn(&Datum) -> f64, f64) = match resampling {
+        Resampling::Time { interval_s } => (|d| d.time, interval_s),
+        // the distance to the carrier decreases during the pass
+        Resampling::Distance { interval_m } => (|d| d.x.neg(), interval_m),
+    };
+    if interval <= 0.0 || !interval.is_finite() {
+        return datums.to_vec();
+    }
+
+    let mut resampled = vec![*first];
+    let mut i = 0;
+    let mut at = position(first) + interval;
+    while at < position(last) {
+        // find the two datums around the grid point
+        while i + 1 < datums.len() && position(&datums[i + 1]) < at {
+            i += 1;
+        }
+        let Some(next) = datums.get(i + 1) else {
+            break;
+        };
+        let (from, to) = (position(&datums[i]), position(next));
+        if to > from && at >= from {
+            resampled.push(datums[i].lerp(next, (at - from) / (to - from)));
+        }
+        at += interval;
     }
+    if datums.len() > 1 {
+        resampled.push(*last);
+    }
+
+    resampled
+}
+
+/// The number of hook positions kept to estimate the hook's touchdown point.
+const HOOK_PATH_LEN: usize = 8;
+/// The number of altitudes kept before touching down to derive the G at touchdown.
+const HEIGHTS_LEN: usize = 10;
+/// How long (in s) before and after touching down the G at touchdown is derived from.
+const TOUCHDOWN_G_BEFORE: f64 = 0.5;
+const TOUCHDOWN_G_AFTER: f64 = 1.0;
+/// The minimum time (in s) between the altitudes the G at touchdown is derived from.
+const TOUCHDOWN_G_MIN_INTERVAL: f64 = 0.05;
+/// The G at touchdown above which a landing is flagged as a potential overstress.
+pub const OVERSTRESS_G: f64 = 5.0;
+const GRAVITY: f64 = 9.81;
+/// The bank angle (in degrees) beyond which the plane is considered to still be in the approach
+/// turn.
+const GROOVE_MAX_BANK: f64 = 20.0;
+/// The nominal start of the pass (in nm from the optimal touchdown point).
+pub const START_DISTANCE: f64 = 0.75;
+/// Rolling out into the groove farther away than this (in nm) is long in the groove.
+const LONG_IN_GROOVE_DISTANCE: f64 = 1.0;
+/// The lineup (in degrees) beyond which the start is not lined up.
+const START_MAX_LINEUP: f64 = 1.5;
+/// The ball (in cells) beyond which the start is high or low.
+const START_MAX_BALL: f64 = 2.0;
+/// The turn away from the final bearing (in degrees) beyond which a departure is a spin.
+const SPIN_MIN_TURN: f64 = 45.0;
+/// The climb (in m) since the closest point to the carrier beyond which a departure is a spin.
+const SPIN_MIN_CLIMB: f64 = 15.0;
+/// The change of the ball (in cells) throughout a segment of the groove that is a trend.
+const TREND_MIN_BALL: f64 = 1.0;
+/// The lateral drift (in m) throughout a segment of the groove that is a trend.
+const TREND_MIN_DRIFT: f64 = 3.0;
+/// The vertical angle (in degrees) covered by a single cell of the lens.
+const LENS_CELL_ANGLE: f64 = 0.1425;
+/// The number of cells of the lens above and below its center (the ball is off the lens beyond).
+const LENS_CELLS: f64 = 6.0;
+/// The number of hook positions recorded after touching down (about 5s).
+const ROLLOUT_LEN: usize = 50;
+/// How far (in m) the hook may touch down past a cable and still catch it.
+const CABLE_CATCH_MARGIN: f64 = 6.0;
+
+impl Grading {
+    /// A short summary of the grading (e.g. `#3` for a trap on the 3-wire).
+    pub fn summary(&self, hook_up: bool) -> Cow<'static, str> {
+        match self {
+            Grading::Unknown => Cow::Borrowed("unknown"),
+            // passes recorded before touch-and-goes got their own grading are bolters
+            Grading::TouchAndGo | Grading::Bolter if hook_up => {
+                Cow::Borrowed("Touch-and-go (hook up)")
+            }
+            Grading::TouchAndGo => Cow::Borrowed("Touch-and-go"),
+            Grading::Bolter => Cow::Borrowed("Bolter"),
+            Grading::Incident(incident) => Cow::Borrowed(incident.as_str()),
+            Grading::Spin => Cow::Borrowed("Spin"),
+            Grading::OwnWaveOff => Cow::Borrowed("Own wave-off"),
+            Grading::Recovered { cable, .. } => cable
+                .map(|c| Cow::Owned(format!("#{}", c)))
+                .unwrap_or(Cow::Borrowed("-")),
+        }
+    }
+}
+
+/// The name KI (AI) units are recorded under: their group (e.g. `KI Recovery Tanker`), so that
+/// the passes of all units of a group are aggregated.
+pub fn ki_pilot_name(group_name: Option<&str>) -> String {
+    match group_name.filter(|name| !name.is_empty()) {
+        Some(name) => format!("KI {}", name),
+        None => String::from("KI"),
+    }
+}
+
+/// The horizontal distance (in m) from the plane to the carrier, but only if the plane is within
+/// the altitude and distance limits of the detection envelope. A cheap broad-phase check to pair
+/// planes with carriers before [is_recovery_attempt].
+pub fn broad_phase_distance(
+    carrier: &Transform,
+    plane: &Transform,
+    envelope: &DetectionEnvelope,
+) -> Option<f64> {
+    if m_to_ft(plane.alt) > envelope.max_altitude_ft {
+        return None;
+    }
+
+    let mut ray = carrier.position - plane.position;
+    ray.y = 0.0; // ignore altitude
+    let distance = ray.mag();
+    (m_to_nm(distance) <= envelope.max_distance_nm).then_some(distance)
+}
+
+/// Whether the plane is within the detection envelope behind the carrier and heads towards it, i.e.
+/// starts a recovery attempt.
+pub fn is_recovery_attempt(
+    carrier: &Transform,
+    plane: &Transform,
+    envelope: &DetectionEnvelope,
+) -> bool {
+    // ignore planes above the envelope
+    if m_to_ft(plane.alt) > envelope.max_altitude_ft {
+        tracing::trace!(
+            alt_in_ft = m_to_ft(plane.alt),
+            max_alt_in_ft = envelope.max_altitude_ft,
+            "ignore planes above envelope"
+        );
+        return false;
+    }
+
+    let ray_from_plane_to_carrier = carrier.position - plane.position;
+    let distance = ray_from_plane_to_carrier.mag();
+
+    // ignore planes too far away
+    if m_to_nm(distance) > envelope.max_distance_nm {
+        tracing::trace!(
+            distance_in_nm = m_to_nm(distance),
+            max_distance_in_nm = envelope.max_distance_nm,
+            "ignore planes too far away"
+        );
+        return false;
+    }
+
+    // ignore takeoffs
+    if distance < envelope.min_distance_m {
+        tracing::trace!(distance_in_m = distance, "ignore takeoffs");
+        return false;
+    }
+
+    // is the plane behind the carrier
+    let dot = carrier
+        .forward
+        .normalized()
+        .dot(ray_from_plane_to_carrier.normalized());
+    if dot < 0.0 {
+        tracing::trace!(dot, "ignore not behind the carrier");
+        return false;
+    }
+
+    // Does the nose of the plane roughly point towards the carrier?
+    let dot = plane
+        .forward
+        .normalized()
+        .dot(ray_from_plane_to_carrier.normalized());
+    if dot < envelope.min_nose_alignment {
+        tracing::trace!(dot, "ignore not roughly pointing towards the carrier");
+        return false;
+    }
+
+    tracing::debug!(
+        at = plane.time,
+        dot,
+        distance_in_m = distance,
+        distance_in_nm = m_to_nm(distance),
+        "found recovery attempt",
+    );
+
+    true
 }
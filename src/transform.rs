@@ -1,11 +1,14 @@
+#[cfg(feature = "grpc")]
 use std::ops::Neg;
 
+#[cfg(feature = "grpc")]
 use stubs::common::v0::{Orientation, Position, Vector, Velocity};
 use ultraviolet::{DRotor3, DVec3};
 
+#[cfg(feature = "grpc")]
 use crate::utils::precision::Precision;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Transform {
     pub forward: DVec3,
     pub position: DVec3,
@@ -20,18 +23,37 @@ pub struct Transform {
     /// Roll in degrees.
     pub roll: f64,
     pub rotation: DRotor3,
+    /// AoA in degrees (derived from the forward vector and the velocity relative to the air, as
+    /// DCS-gRPC doesn't provide the true AoA).
     pub aoa: f64,
     /// Time in seconds since the scenario started.
     pub time: f64,
 }
 
+#[cfg(feature = "grpc")]
 impl From<(f64, Position, Orientation, Velocity)> for Transform {
     fn from(
         (time, position, orientation, velocity): (f64, Position, Orientation, Velocity),
+    ) -> Self {
+        Transform::in_wind(time, position, orientation, velocity, DVec3::zero())
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl Transform {
+    /// Like [Transform::from], but the AoA is calculated from the velocity relative to the air
+    /// instead of the ground, given the velocity of the `wind` (see
+    /// [crate::client::AtmosphereClient::get_wind]).
+    pub fn in_wind(
+        time: f64,
+        position: Position,
+        orientation: Orientation,
+        velocity: Velocity,
+        wind: DVec3,
     ) -> Self {
         // Since AOA is directly written to the TacView file, it can be calculated on the unrounded
         // data.
-        let velocity = fix_vector(velocity.velocity.unwrap_or_default());
+        let velocity = fix_vector(velocity.velocity.unwrap_or_default()) - wind;
         let forward = fix_vector(orientation.forward.unwrap_or_default());
         let aoa = forward.dot(velocity.normalized()).acos().to_degrees();
 
@@ -75,6 +97,7 @@ impl From<(f64, Position, Orientation, Velocity)> for Transform {
 
 /// Convert DCS' unusual right-hand coordinate system where +x points north to a more common
 /// left-hand coordinate system where +z points north (and +x points east).
+#[cfg(feature = "grpc")]
 fn fix_vector(v: Vector) -> DVec3 {
     DVec3::new(v.z, v.y, v.x)
 }
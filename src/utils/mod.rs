@@ -1,6 +1,8 @@
 pub mod interval;
 pub mod precision;
 pub mod shutdown;
+pub mod smoothing;
+pub mod sun;
 
 pub fn m_to_nm(m: f64) -> f64 {
     m / 1852.0
@@ -21,3 +23,54 @@ pub fn ft_to_nm(ft: f64) -> f64 {
 pub fn nm_to_ft(nm: f64) -> f64 {
     nm * 6076.118
 }
+
+pub fn ms_to_kts(ms: f64) -> f64 {
+    ms * 1.943844
+}
+
+pub fn kg_to_lb(kg: f64) -> f64 {
+    kg * 2.204623
+}
+
+/// The FNV-1a hash of `bytes`, which (unlike the std hasher) is stable across Rust versions.
+pub fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u32 {
+    bytes.into_iter().fold(0x811c9dc5, |hash, b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x01000193)
+    })
+}
+
+/// The signed difference (in degrees, positive to the right) between two headings.
+pub fn heading_diff(from: f64, to: f64) -> f64 {
+    (to - from + 540.0).rem_euclid(360.0) - 180.0
+}
+
+/// Derives the rate of turn (in degrees per second) from headings sampled a couple of seconds apart
+/// (as the recorded headings are rounded to a tenth of a degree).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TurnRate {
+    /// The time and heading of the last sample.
+    sample: Option<(f64, f64)>,
+    rate: f64,
+}
+
+impl TurnRate {
+    /// The minimum time (in seconds) between two samples.
+    const WINDOW: f64 = 3.0;
+
+    pub fn update(&mut self, time: f64, heading: f64) {
+        match self.sample {
+            Some((sample_time, sample_heading)) => {
+                if time - sample_time >= Self::WINDOW {
+                    self.rate = heading_diff(sample_heading, heading) / (time - sample_time);
+                    self.sample = Some((time, heading));
+                }
+            }
+            None => self.sample = Some((time, heading)),
+        }
+    }
+
+    /// The most recent rate of turn (in degrees per second, positive to the right).
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+}
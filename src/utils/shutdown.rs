@@ -1,6 +1,7 @@
 use std::future::{ready, Future};
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use futures_util::future::{Either, Shared, WeakShared};
 use futures_util::{FutureExt, Stream};
@@ -57,6 +58,12 @@ impl Shutdown {
     }
 }
 
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ShutdownHandle {
     pub fn signal(&self) -> impl Future<Output = ()> {
         match self.signal.as_ref() {
@@ -68,6 +75,21 @@ impl ShutdownHandle {
         }
     }
 
+    /// Whether the shutdown signal has already been sent.
+    pub fn is_shutdown(&self) -> bool {
+        self.signal().now_or_never().is_some()
+    }
+
+    /// A signal that resolves `timeout` after the shutdown signal, giving tasks the chance to
+    /// finish their work before they are aborted.
+    pub fn drain(&self, timeout: Duration) -> impl Future<Output = ()> {
+        let signal = self.signal();
+        async move {
+            signal.await;
+            tokio::time::sleep(timeout).await;
+        }
+    }
+
     pub fn wrap_stream<I, S: Stream<Item = I>>(&self, stream: S) -> impl Stream<Item = I> {
         AbortableStream::new(self.signal(), stream)
     }
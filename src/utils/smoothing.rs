@@ -0,0 +1,59 @@
+//! Low-pass filters for values sampled at (not necessarily regular) points in time.
+
+/// Smooth `values` (sampled at `times`, in seconds) with an exponential moving average with the
+/// given time constant (in seconds). The average is applied forwards and then backwards, so that
+/// the result doesn't lag behind.
+pub fn ema(times: &[f64], values: &mut [f64], time_constant: f64) {
+    let weight = |dt: f64| 1.0 - (-dt.abs() / time_constant).exp();
+    for i in 1..values.len() {
+        let a = weight(times[i] - times[i - 1]);
+        values[i] = values[i - 1] + a * (values[i] - values[i - 1]);
+    }
+    for i in (0..values.len().saturating_sub(1)).rev() {
+        let a = weight(times[i + 1] - times[i]);
+        values[i] = values[i + 1] + a * (values[i] - values[i + 1]);
+    }
+}
+
+/// Smooth `values` (sampled at `times`, in seconds) with a Savitzky–Golay filter: each value is
+/// replaced by a quadratic least-squares fit over itself and the `half_window` values before and
+/// after it (fewer at the ends). The fit is done over the actual times, so irregular sampling
+/// is fine.
+pub fn savitzky_golay(times: &[f64], values: &mut [f64], half_window: usize) {
+    let raw = values.to_vec();
+    for (i, value) in values.iter_mut().enumerate() {
+        let window = i.saturating_sub(half_window)..(i + half_window + 1).min(raw.len());
+        // sums of the powers of the time offsets (s) and of the values times these powers (t)
+        let mut s = [0.0; 5];
+        let mut t = [0.0; 3];
+        for j in window {
+            let dt = times[j] - times[i];
+            let mut p = 1.0;
+            for (k, s) in s.iter_mut().enumerate() {
+                *s += p;
+                if k < 3 {
+                    t[k] += p * raw[j];
+                }
+                p *= dt;
+            }
+        }
+
+        // solve the normal equations for the constant term (the fit at `times[i]`)
+        let det = |m: [[f64; 3]; 3]| {
+            m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+                - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+                + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+        };
+        let normal = [[s[0], s[1], s[2]], [s[1], s[2], s[3]], [s[2], s[3], s[4]]];
+        let d = det(normal);
+        if d.abs() < f64::EPSILON {
+            // too few (distinct) samples for a quadratic fit
+            continue;
+        }
+        let mut constant = normal;
+        for (row, t) in constant.iter_mut().zip(t) {
+            row[0] = t;
+        }
+        *value = det(constant) / d;
+    }
+}
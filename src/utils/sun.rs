@@ -0,0 +1,42 @@
+//! The position of the sun, to tell day and night passes apart.
+
+use std::f64::consts::PI;
+
+use time::{Duration, OffsetDateTime, UtcOffset};
+
+/// The sun's elevation (in degrees) below which it is night (the end of the civil twilight).
+pub const NIGHT_ELEVATION: f64 = -6.0;
+
+/// The sun's elevation (in degrees above the horizon) at the given time and position (using the
+/// approximation of the NOAA, which is accurate to a fraction of a degree).
+pub fn elevation(time: OffsetDateTime, lat: f64, lon: f64) -> f64 {
+    let time = time.to_offset(UtcOffset::UTC);
+    let hour = f64::from(time.hour())
+        + f64::from(time.minute()) / 60.0
+        + f64::from(time.second()) / 3600.0;
+    // the fractional year (in radians)
+    let g = 2.0 * PI / 365.0 * (f64::from(time.ordinal()) - 1.0 + (hour - 12.0) / 24.0);
+
+    // the equation of time (in minutes) and the sun's declination (in radians)
+    let eqtime = 229.18
+        * (0.000075 + 0.001868 * g.cos()
+            - 0.032077 * g.sin()
+            - 0.014615 * (2.0 * g).cos()
+            - 0.040849 * (2.0 * g).sin());
+    let decl = 0.006918 - 0.399912 * g.cos() + 0.070257 * g.sin() - 0.006758 * (2.0 * g).cos()
+        + 0.000907 * (2.0 * g).sin()
+        - 0.002697 * (3.0 * g).cos()
+        + 0.00148 * (3.0 * g).sin();
+
+    let solar_time = hour * 60.0 + eqtime + 4.0 * lon;
+    let hour_angle = (solar_time / 4.0 - 180.0).to_radians();
+    let lat = lat.to_radians();
+    let cos_zenith = lat.sin() * decl.sin() + lat.cos() * decl.cos() * hour_angle.cos();
+    90.0 - cos_zenith.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// The UTC time of the given mission time. DCS missions run in the local time of their map, which
+/// is approximated by the time zone of the given longitude.
+pub fn mission_time_to_utc(time: OffsetDateTime, lon: f64) -> OffsetDateTime {
+    time - Duration::hours((lon / 15.0).round() as i64)
+}